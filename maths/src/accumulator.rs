@@ -0,0 +1,395 @@
+use crate::internal_prelude::*;
+use radix_common::prelude::CheckedAdd;
+
+/// Accumulates weighted values to compute their weighted average, for instance prices weighted
+/// by the time during which they were valid for a time-weighted average price.
+///
+/// The products of the values and weights and the total weight are accumulated exactly, with
+/// 36 and 18 decimals respectively, so that the accumulator never overflows for less than 2^64
+/// terms. The weighted average is only rounded once, when it is computed, and can be computed as
+/// long as it fits in a [`Decimal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Accumulator {
+    /// Sum of the products of the values and weights, with 36 decimals.
+    weighted_sum: I512,
+    /// Sum of the weights, with 18 decimals.
+    total_weight: I256,
+}
+
+impl Accumulator {
+    /// Returns an empty accumulator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a value with the given weight.
+    /// Panics if the weight is negative.
+    ///
+    /// # Arguments
+    /// * `value`: value to add, such as a price.
+    /// * `weight`: weight of the value, such as the duration during which the price was valid.
+    pub fn add_weighted(&mut self, value: Decimal, weight: Decimal) {
+        if weight.is_negative() {
+            panic!("Weights cannot be negative");
+        }
+
+        let product = I512::from(value.0) * I512::from(weight.0);
+        self.weighted_sum = self.weighted_sum.checked_add(product).expect("Overflow");
+        self.total_weight = self
+            .total_weight
+            .checked_add(I256::from(weight.0))
+            .expect("Overflow");
+    }
+
+    /// Adds the values accumulated by another accumulator, as if they had been added to this one.
+    ///
+    /// # Arguments
+    /// * `other`: accumulator to merge into this one.
+    pub fn merge(&mut self, other: &Accumulator) {
+        self.weighted_sum = self
+            .weighted_sum
+            .checked_add(other.weighted_sum)
+            .expect("Overflow");
+        self.total_weight = self
+            .total_weight
+            .checked_add(other.total_weight)
+            .expect("Overflow");
+    }
+
+    /// Returns the sum of the weights, with 36 decimals so that it cannot overflow.
+    pub fn total_weight(&self) -> PreciseDecimal {
+        PreciseDecimal(self.total_weight * I256::from(Decimal::ONE.0))
+    }
+
+    /// Returns `true` if no weight has been accumulated.
+    pub fn is_empty(&self) -> bool {
+        self.total_weight == I256::ZERO
+    }
+
+    /// Returns the weighted average of the accumulated values, rounded to the nearest
+    /// [`Decimal`].
+    /// Panics if no weight has been accumulated or if the average overflows.
+    pub fn weighted_average(&self) -> Decimal {
+        if self.is_empty() {
+            panic!("Cannot compute the weighted average of an empty accumulator");
+        }
+        self.checked_weighted_average().expect("Overflow")
+    }
+
+    /// Returns the weighted average of the accumulated values, rounded to the nearest
+    /// [`Decimal`], or `None` if no weight has been accumulated or if the average overflows.
+    ///
+    /// The average is first truncated to a [`PreciseDecimal`], as by a [`PreciseDecimal`]
+    /// division, and then rounded to 18 decimals, which gives the same result as rounding the
+    /// exact average. The division is made on the 512 bits representation of the weighted sum,
+    /// which can exceed the range of a [`PreciseDecimal`] while the average does not.
+    pub fn checked_weighted_average(&self) -> Option<Decimal> {
+        if self.is_empty() {
+            return None;
+        }
+        let quotient = self.weighted_sum.checked_mul(I512::from(Decimal::ONE.0))?
+            / I512::from(self.total_weight);
+        let average = PreciseDecimal(I256::try_from(quotient).ok()?);
+        average.checked_truncate(RoundingMode::ToNearestMidpointAwayFromZero)
+    }
+}
+
+/// Converts a [`Decimal`], with 18 decimals, to a [`PreciseDecimal`], with 36 decimals. The
+/// conversion is exact.
+///
+/// # Arguments
+/// * `value`: value to convert.
+pub fn wad_to_ray(value: Decimal) -> PreciseDecimal {
+    PreciseDecimal::from(value)
+}
+
+/// Converts a [`PreciseDecimal`], with 36 decimals, to a [`Decimal`], with 18 decimals, rounding
+/// with the given rounding mode: `ToNegativeInfinity` to floor, `ToPositiveInfinity` to ceil and
+/// `ToNearestMidpointToEven` for banker's rounding.
+/// Panics if the result overflows.
+///
+/// # Arguments
+/// * `value`: value to convert.
+/// * `rounding`: rounding mode of the dropped decimals.
+pub fn ray_to_wad(value: PreciseDecimal, rounding: RoundingMode) -> Decimal {
+    checked_ray_to_wad(value, rounding).expect("Overflow")
+}
+
+/// Converts a [`PreciseDecimal`], with 36 decimals, to a [`Decimal`], with 18 decimals, rounding
+/// with the given rounding mode, or returns `None` if the result overflows.
+///
+/// # Arguments
+/// * `value`: value to convert.
+/// * `rounding`: rounding mode of the dropped decimals.
+pub fn checked_ray_to_wad(value: PreciseDecimal, rounding: RoundingMode) -> Option<Decimal> {
+    value.checked_truncate(rounding)
+}
+
+/// Conversions of a [`PreciseDecimal`], with 36 decimals, to a [`Decimal`], with 18 decimals,
+/// naming the rounding of the dropped decimals. Intermediate computations can be made in
+/// [`PreciseDecimal`] and only rounded once, in the direction favouring the protocol.
+pub trait ToDecimal {
+    /// Converts to the biggest [`Decimal`] smaller or equal to the number.
+    /// Panics if the result overflows.
+    fn to_decimal_floor(self) -> Decimal;
+
+    /// Converts to the smallest [`Decimal`] greater or equal to the number.
+    /// Panics if the result overflows.
+    fn to_decimal_ceil(self) -> Decimal;
+
+    /// Converts to the nearest [`Decimal`], midpoints being rounded away from zero.
+    /// Panics if the result overflows.
+    fn to_decimal_round(self) -> Decimal;
+}
+
+impl ToDecimal for PreciseDecimal {
+    fn to_decimal_floor(self) -> Decimal {
+        ray_to_wad(self, RoundingMode::ToNegativeInfinity)
+    }
+
+    fn to_decimal_ceil(self) -> Decimal {
+        ray_to_wad(self, RoundingMode::ToPositiveInfinity)
+    }
+
+    fn to_decimal_round(self) -> Decimal {
+        ray_to_wad(self, RoundingMode::ToNearestMidpointAwayFromZero)
+    }
+}
+
+#[cfg(test)]
+mod test_accumulator {
+    use crate::accumulator::*;
+    use radix_common_derive::{dec, pdec};
+
+    /// Returns pseudo-random numbers from a linear congruential generator, so that the tests are
+    /// reproducible.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            self.0 >> 11
+        }
+    }
+
+    /// Returns the weighted average of values and weights computed with exact integer
+    /// arithmetic, rounded to the nearest [`Decimal`].
+    fn reference_average(terms: &[(Decimal, Decimal)]) -> Decimal {
+        let mut weighted_sum = I512::ZERO;
+        let mut total_weight = I512::ZERO;
+        for (value, weight) in terms {
+            weighted_sum += I512::from(value.0) * I512::from(weight.0);
+            total_weight += I512::from(weight.0);
+        }
+        let two = I512::from(2);
+        let rounded = (weighted_sum.abs() * two + total_weight) / (total_weight * two);
+        Decimal(
+            I192::try_from(if weighted_sum.is_negative() {
+                -rounded
+            } else {
+                rounded
+            })
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_million_large_prices() {
+        let mut lcg = Lcg(42);
+        let terms: Vec<(Decimal, Decimal)> = (0..1_000_000)
+            .map(|_| {
+                // Prices between 10^31 and 2 * 10^31, valid for up to an hour.
+                let price = dec!("10000000000000000000000000000000")
+                    + Decimal::from(lcg.next()) * Decimal::from(lcg.next() % 1_000_000_000_000_000);
+                let duration = Decimal::from(lcg.next() % 3_600_000) / dec!(1000);
+                (price, duration)
+            })
+            .collect();
+
+        let mut accumulator = Accumulator::new();
+        let mut naive_sum = Some(Decimal::ZERO);
+        for (price, duration) in &terms {
+            accumulator.add_weighted(*price, *duration);
+            naive_sum = naive_sum.and_then(|sum| sum.checked_add(price.checked_mul(*duration)?));
+        }
+
+        assert_eq!(naive_sum, None);
+        assert_eq!(accumulator.weighted_average(), reference_average(&terms));
+    }
+
+    #[test]
+    fn test_negative_values() {
+        let terms = [
+            (dec!(-3), dec!(1)),
+            (dec!("-0.000000000000000001"), dec!(2)),
+            (dec!(1), dec!("0.5")),
+        ];
+        let mut accumulator = Accumulator::new();
+        for (value, weight) in terms {
+            accumulator.add_weighted(value, weight);
+        }
+        assert_eq!(accumulator.weighted_average(), reference_average(&terms));
+        assert_eq!(
+            accumulator.weighted_average(),
+            dec!("-0.714285714285714286")
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut lcg = Lcg(7);
+        let mut first = Accumulator::new();
+        let mut second = Accumulator::new();
+        let mut all = Accumulator::new();
+        for index in 0..1000 {
+            let value = Decimal::from(lcg.next()) / dec!(1000);
+            let weight = Decimal::from(lcg.next() % 1000);
+            all.add_weighted(value, weight);
+            if index % 3 == 0 {
+                first.add_weighted(value, weight);
+            } else {
+                second.add_weighted(value, weight);
+            }
+        }
+
+        first.merge(&second);
+        assert_eq!(first, all);
+        assert_eq!(first.weighted_average(), all.weighted_average());
+    }
+
+    #[test]
+    fn test_total_weight() {
+        let mut accumulator = Accumulator::new();
+        accumulator.add_weighted(dec!(1), Decimal::MAX);
+        accumulator.add_weighted(dec!(1), Decimal::MAX);
+        assert_eq!(
+            accumulator.total_weight(),
+            PreciseDecimal::from(Decimal::MAX) * pdec!(2)
+        );
+        assert_eq!(accumulator.weighted_average(), dec!(1));
+    }
+
+    #[test]
+    fn test_empty_accumulator() {
+        let mut accumulator = Accumulator::new();
+        assert!(accumulator.is_empty());
+        assert_eq!(accumulator.checked_weighted_average(), None);
+
+        accumulator.add_weighted(dec!(5), Decimal::ZERO);
+        assert!(accumulator.is_empty());
+        assert_eq!(accumulator.checked_weighted_average(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot compute the weighted average of an empty accumulator")]
+    fn test_empty_accumulator_panics() {
+        let _m = Accumulator::new().weighted_average();
+    }
+
+    #[test]
+    #[should_panic(expected = "Weights cannot be negative")]
+    fn test_negative_weight() {
+        Accumulator::new().add_weighted(dec!(1), dec!(-1));
+    }
+
+    #[test]
+    fn test_wad_to_ray() {
+        assert_eq!(
+            wad_to_ray(dec!("1.000000000000000001")),
+            pdec!("1.000000000000000001")
+        );
+        assert_eq!(wad_to_ray(Decimal::MIN), PreciseDecimal::from(Decimal::MIN));
+    }
+
+    #[test]
+    fn test_ray_to_wad_rounding() {
+        let one_and_a_half = pdec!("0.0000000000000000015");
+        let two_and_a_half = pdec!("0.0000000000000000025");
+        let small = dec!("0.000000000000000001");
+
+        assert_eq!(
+            ray_to_wad(one_and_a_half, RoundingMode::ToNegativeInfinity),
+            small
+        );
+        assert_eq!(
+            ray_to_wad(one_and_a_half, RoundingMode::ToPositiveInfinity),
+            small * 2
+        );
+        assert_eq!(
+            ray_to_wad(one_and_a_half, RoundingMode::ToNearestMidpointToEven),
+            small * 2
+        );
+        assert_eq!(
+            ray_to_wad(two_and_a_half, RoundingMode::ToNearestMidpointToEven),
+            small * 2
+        );
+        assert_eq!(
+            ray_to_wad(-two_and_a_half, RoundingMode::ToNearestMidpointToEven),
+            -small * 2
+        );
+        assert_eq!(
+            ray_to_wad(-one_and_a_half, RoundingMode::ToNegativeInfinity),
+            -small * 2
+        );
+        assert_eq!(
+            ray_to_wad(-one_and_a_half, RoundingMode::ToPositiveInfinity),
+            -small
+        );
+        assert_eq!(
+            ray_to_wad(pdec!("0.0000000000000000016"), RoundingMode::ToZero),
+            small
+        );
+    }
+
+    #[test]
+    fn test_to_decimal() {
+        let value = pdec!("1.0000000000000000015");
+        assert_eq!(value.to_decimal_floor(), dec!("1.000000000000000001"));
+        assert_eq!(value.to_decimal_ceil(), dec!("1.000000000000000002"));
+        assert_eq!(value.to_decimal_round(), dec!("1.000000000000000002"));
+        assert_eq!((-value).to_decimal_floor(), dec!("-1.000000000000000002"));
+        assert_eq!((-value).to_decimal_ceil(), dec!("-1.000000000000000001"));
+        assert_eq!((-value).to_decimal_round(), dec!("-1.000000000000000002"));
+        assert_eq!(pdec!(3).to_decimal_ceil(), dec!(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow")]
+    fn test_to_decimal_overflow() {
+        let _m = PreciseDecimal::MAX.to_decimal_floor();
+    }
+
+    #[test]
+    fn test_ray_to_wad_matches_truncate() {
+        let mut lcg = Lcg(3);
+        for _ in 0..1000 {
+            let value = PreciseDecimal(I256::from(lcg.next()) * I256::from(lcg.next()))
+                - PreciseDecimal(I256::from(lcg.next()) * I256::from(lcg.next()));
+            for rounding in [
+                RoundingMode::ToPositiveInfinity,
+                RoundingMode::ToNegativeInfinity,
+                RoundingMode::ToZero,
+                RoundingMode::AwayFromZero,
+                RoundingMode::ToNearestMidpointTowardZero,
+                RoundingMode::ToNearestMidpointAwayFromZero,
+                RoundingMode::ToNearestMidpointToEven,
+            ] {
+                assert_eq!(
+                    ray_to_wad(value, rounding),
+                    value.checked_truncate(rounding).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_ray_to_wad_overflow() {
+        assert_eq!(
+            checked_ray_to_wad(PreciseDecimal::MAX, RoundingMode::ToZero),
+            None
+        );
+    }
+}
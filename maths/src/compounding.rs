@@ -0,0 +1,347 @@
+use crate::exponential::scaled_exp;
+use crate::internal_prelude::*;
+
+/// Exponents of a continuous accrual above which the accrued amount of any non-zero principal
+/// overflows a [`Decimal`], and below whose opposite it is rounded to zero.
+///
+/// It is the logarithm of the ratio between [`Decimal::MAX`] and the smallest positive
+/// [`Decimal`], rounded up.
+pub const MAX_ACCRUAL_EXPONENT: Decimal = Decimal(I192::from_digits([4872791484033138688, 7, 0]));
+
+/// Returns the amount that a principal grows to when it accrues interest continuously at the
+/// given rate per second, that is principal * exp(rate_per_second * seconds).
+/// Panics if the result overflows.
+///
+/// # Arguments
+/// * `principal`: initial amount.
+/// * `rate_per_second`: interest rate per second, 1% being 0.01. A yearly rate is converted to a
+///   rate per second by dividing it by the number of seconds in a year.
+/// * `seconds`: duration of the accrual.
+pub fn continuous_accrual(principal: Decimal, rate_per_second: Decimal, seconds: u64) -> Decimal {
+    checked_continuous_accrual(principal, rate_per_second, seconds).expect("Overflow")
+}
+
+/// Returns the amount that a principal grows to when it accrues interest continuously at the
+/// given rate per second, or `None` if the result overflows.
+///
+/// The exponent rate_per_second * seconds is computed exactly, and its exponential is evaluated
+/// with 36 decimals, by range reduction, without ever being rounded to a [`Decimal`]. The result
+/// is therefore exact up to the rounding of its last decimal, even when the exponential of the
+/// exponent alone would overflow or be rounded to zero.
+///
+/// # Arguments
+/// * `principal`: initial amount.
+/// * `rate_per_second`: interest rate per second, 1% being 0.01.
+/// * `seconds`: duration of the accrual.
+pub fn checked_continuous_accrual(
+    principal: Decimal,
+    rate_per_second: Decimal,
+    seconds: u64,
+) -> Option<Decimal> {
+    if principal.is_zero() || rate_per_second.is_zero() || seconds == 0 {
+        return Some(principal);
+    }
+
+    let exponent = match rate_per_second.checked_mul(Decimal::from(seconds)) {
+        Some(exponent) => exponent,
+        None => return rate_per_second.is_negative().then_some(Decimal::ZERO),
+    };
+    if exponent > MAX_ACCRUAL_EXPONENT {
+        return None;
+    }
+    if exponent < -MAX_ACCRUAL_EXPONENT {
+        return Some(Decimal::ZERO);
+    }
+
+    let guard = I512::from(Decimal::ONE.0);
+    let one = guard * guard;
+    let principal = I512::from(principal.0) * guard;
+    let exponent = I512::from(exponent.0) * guard;
+
+    let accrued = if exponent.is_negative() {
+        principal * one / scaled_exp(-exponent, one)
+    } else {
+        principal.checked_mul(scaled_exp(exponent, one))? / one
+    };
+    round_to_decimal(accrued, guard)
+}
+
+/// Returns the amount that a principal grows to when interest is compounded at the end of each
+/// period, that is principal * (1 + rate_per_period)^periods.
+/// Panics if the result overflows.
+///
+/// # Arguments
+/// * `principal`: initial amount.
+/// * `rate_per_period`: interest rate per period, 1% being 0.01.
+/// * `periods`: number of compounding periods. The principal is returned for 0 periods.
+pub fn discrete_accrual(principal: Decimal, rate_per_period: Decimal, periods: u64) -> Decimal {
+    checked_discrete_accrual(principal, rate_per_period, periods).expect("Overflow")
+}
+
+/// Returns the amount that a principal grows to when interest is compounded at the end of each
+/// period, or `None` if the result overflows.
+///
+/// The power is computed by exponentiation by squaring with 36 decimals, so that the result is
+/// exact up to the rounding of its last decimal for any number of periods.
+///
+/// # Arguments
+/// * `principal`: initial amount.
+/// * `rate_per_period`: interest rate per period, 1% being 0.01.
+/// * `periods`: number of compounding periods. The principal is returned for 0 periods.
+pub fn checked_discrete_accrual(
+    principal: Decimal,
+    rate_per_period: Decimal,
+    periods: u64,
+) -> Option<Decimal> {
+    let guard = I512::from(Decimal::ONE.0);
+    let one = guard * guard;
+    let growth_factor = I512::from(rate_per_period.0) * guard + one;
+    let accrued = scaled_accrual(I512::from(principal.0) * guard, growth_factor, periods)?;
+    round_to_decimal(accrued, guard)
+}
+
+/// Returns the annual percentage yield corresponding to an annual percentage rate compounded a
+/// given number of times a year, that is (1 + rate / compounding_periods)^compounding_periods - 1.
+/// Panics if the result overflows.
+///
+/// # Arguments
+/// * `rate`: annual percentage rate, 1% being 0.01.
+/// * `compounding_periods`: number of times interest is compounded in a year. Interest is not
+///   compounded for 0 periods, and the yield is then the rate itself.
+pub fn apr_to_apy(rate: Decimal, compounding_periods: u64) -> Decimal {
+    checked_apr_to_apy(rate, compounding_periods).expect("Overflow")
+}
+
+/// Returns the annual percentage yield corresponding to an annual percentage rate compounded a
+/// given number of times a year, or `None` if the result overflows.
+///
+/// The rate per period is not rounded to a [`Decimal`], so that rates compounded every second of
+/// a year keep the precision of the library.
+///
+/// # Arguments
+/// * `rate`: annual percentage rate, 1% being 0.01.
+/// * `compounding_periods`: number of times interest is compounded in a year. Interest is not
+///   compounded for 0 periods, and the yield is then the rate itself.
+pub fn checked_apr_to_apy(rate: Decimal, compounding_periods: u64) -> Option<Decimal> {
+    if compounding_periods == 0 {
+        return Some(rate);
+    }
+
+    let guard = I512::from(Decimal::ONE.0);
+    let one = guard * guard;
+    let growth_factor = I512::from(rate.0) * guard / I512::from(compounding_periods) + one;
+    let growth = scaled_accrual(one, growth_factor, compounding_periods)?;
+    round_to_decimal(growth - one, guard)
+}
+
+/// Computes principal * growth_factor^periods by exponentiation by squaring, with both numbers
+/// given as their representation with 36 decimals.
+///
+/// The squarings stop as soon as the result is known to overflow a [`Decimal`] or to be rounded
+/// to zero, so that the computed numbers fit in an [`I512`] whatever the number of periods.
+fn scaled_accrual(principal: I512, mut growth_factor: I512, mut periods: u64) -> Option<I512> {
+    let guard = I512::from(Decimal::ONE.0);
+    let one = guard * guard;
+    // Bounds on |result| * |growth_factor|, which has 72 decimals.
+    let overflow = I512::from(Decimal::MAX.0) * guard * one;
+    let vanishing = guard * one / I512::from(2);
+
+    let mut result = principal;
+    while periods > 0 {
+        if periods & 1 == 1 {
+            result = result.checked_mul(growth_factor)? / one;
+        }
+        periods >>= 1;
+        if periods == 0 {
+            break;
+        }
+        if result == I512::ZERO {
+            return Some(I512::ZERO);
+        }
+
+        // A growth factor whose square overflows is growing, and so is the result.
+        growth_factor = growth_factor.checked_mul(growth_factor)? / one;
+        // The remaining factors are powers of the squared growth factor, so the final result is
+        // above |result| * |growth_factor| if it is at least one, and below otherwise.
+        let growing = growth_factor.abs() >= one;
+        match result.checked_mul(growth_factor).map(I512::abs) {
+            Some(bound) if growing && bound > overflow => return None,
+            Some(bound) if !growing && bound < vanishing => return Some(I512::ZERO),
+            Some(_) => {}
+            None => return None,
+        }
+    }
+    Some(result)
+}
+
+/// Rounds a number given by its representation with 18 extra decimals to the nearest
+/// [`Decimal`], or returns `None` if it overflows.
+fn round_to_decimal(value: I512, guard: I512) -> Option<Decimal> {
+    let rounded = (value.abs() + guard / I512::from(2)) / guard;
+    I192::try_from(if value.is_negative() {
+        -rounded
+    } else {
+        rounded
+    })
+    .ok()
+    .map(Decimal)
+}
+
+#[cfg(test)]
+mod test_compounding {
+    use crate::compounding::*;
+    use crate::exponential::Exponential;
+    use crate::RELATIVE_PRECISION;
+    use radix_common_derive::dec;
+
+    const SECONDS_PER_YEAR: u64 = 31_536_000;
+
+    fn assert_close(value: Decimal, true_val: Decimal) {
+        let rel_prec = (true_val - value).checked_abs().unwrap() / true_val.checked_abs().unwrap();
+        assert!(
+            rel_prec < RELATIVE_PRECISION,
+            "{} is not close to {}",
+            value,
+            true_val
+        );
+    }
+
+    #[test]
+    fn test_continuous_full_apr_over_ten_years() {
+        let rate = Decimal::ONE / Decimal::from(SECONDS_PER_YEAR);
+        let accrued = continuous_accrual(dec!(1000), rate, 10 * SECONDS_PER_YEAR);
+        assert_close(accrued, dec!("22026465.789495695085154402"));
+        assert_close(
+            accrued,
+            dec!(1000) * (rate * Decimal::from(10 * SECONDS_PER_YEAR)).exp(),
+        );
+    }
+
+    #[test]
+    fn test_continuous_tiny_rate_over_one_second() {
+        assert_eq!(
+            continuous_accrual(dec!(1000), dec!("0.00000001"), 1),
+            dec!("1000.000010000000050000")
+        );
+    }
+
+    #[test]
+    fn test_continuous_zero_duration() {
+        assert_eq!(continuous_accrual(dec!(1000), dec!(5), 0), dec!(1000));
+        assert_eq!(
+            continuous_accrual(dec!(1000), Decimal::ZERO, 1000),
+            dec!(1000)
+        );
+        assert_eq!(
+            continuous_accrual(Decimal::ZERO, dec!(5), 1000),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_continuous_exponent_above_exp_overflow() {
+        assert_eq!(dec!(95).checked_exp(), None);
+        assert_close(
+            continuous_accrual(dec!("0.0000000001"), dec!("0.95"), 100),
+            dec!("18112390828890232821937987580988.159250478971745441"),
+        );
+    }
+
+    #[test]
+    fn test_continuous_negative_rate() {
+        assert_eq!(
+            continuous_accrual(
+                dec!("1000000000000000000000000000000000000000"),
+                dec!(-1),
+                100
+            ),
+            dec!("0.000037200759760208")
+        );
+        assert_eq!(
+            continuous_accrual(dec!(1000), dec!(-1), u64::MAX),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_continuous_overflow() {
+        assert_eq!(checked_continuous_accrual(dec!(1000), dec!(1), 100), None);
+        assert_eq!(
+            checked_continuous_accrual(dec!(1000), dec!(1), u64::MAX),
+            None
+        );
+        assert_eq!(
+            checked_continuous_accrual(Decimal(I192::ONE), MAX_ACCRUAL_EXPONENT, 1),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow")]
+    fn test_continuous_overflow_panics() {
+        let _m = continuous_accrual(dec!(1000), dec!(1), 100);
+    }
+
+    #[test]
+    fn test_discrete_full_apr_over_ten_years() {
+        let rate = Decimal::ONE / Decimal::from(SECONDS_PER_YEAR);
+        assert_close(
+            discrete_accrual(dec!(1000), rate, 10 * SECONDS_PER_YEAR),
+            dec!("22026462.297222804315777315"),
+        );
+    }
+
+    #[test]
+    fn test_discrete_is_exact() {
+        assert_eq!(
+            discrete_accrual(dec!(100), dec!("0.05"), 10),
+            dec!("162.889462677744140625")
+        );
+        assert_eq!(
+            discrete_accrual(dec!(1000), dec!("0.00000001"), 1),
+            dec!("1000.00001")
+        );
+    }
+
+    #[test]
+    fn test_discrete_zero_periods() {
+        assert_eq!(discrete_accrual(dec!(1000), dec!(5), 0), dec!(1000));
+    }
+
+    #[test]
+    fn test_discrete_many_periods() {
+        assert_eq!(
+            discrete_accrual(dec!(1000), Decimal::ZERO, u64::MAX),
+            dec!(1000)
+        );
+        assert_eq!(
+            discrete_accrual(dec!(1000), dec!("-0.5"), u64::MAX),
+            Decimal::ZERO
+        );
+        assert_eq!(
+            checked_discrete_accrual(dec!(1000), dec!("0.01"), u64::MAX),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow")]
+    fn test_discrete_overflow_panics() {
+        let _m = discrete_accrual(dec!(1000), dec!(1), 200);
+    }
+
+    #[test]
+    fn test_apr_to_apy() {
+        assert_eq!(apr_to_apy(dec!("0.05"), 12), dec!("0.051161897881733190"));
+        assert_close(
+            apr_to_apy(Decimal::ONE, SECONDS_PER_YEAR),
+            dec!("1.718281785360970821"),
+        );
+    }
+
+    #[test]
+    fn test_apr_to_apy_without_compounding() {
+        assert_eq!(apr_to_apy(dec!("0.05"), 0), dec!("0.05"));
+        assert_eq!(apr_to_apy(dec!("0.05"), 1), dec!("0.05"));
+    }
+}
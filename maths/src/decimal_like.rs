@@ -0,0 +1,47 @@
+use crate::exponential::Exponential;
+use crate::internal_prelude::*;
+use crate::logarithm::Logarithm;
+use crate::power::Power;
+use crate::root::Root;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Numeric type behaving like a [`Decimal`].
+///
+/// Formulas written generically over [`DecimalLike`] can be evaluated with a [`Decimal`] to get
+/// their value, or with a [`crate::dual::Dual`] to also get their derivative.
+pub trait DecimalLike:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + Exponential
+    + Logarithm
+    + Power
+{
+    /// Returns the constant with the given value.
+    fn constant(value: Decimal) -> Self;
+
+    /// Returns the value of the number.
+    fn value(&self) -> Decimal;
+
+    /// Returns the square root of the number.
+    fn sqrt(self) -> Self;
+}
+
+impl DecimalLike for Decimal {
+    fn constant(value: Decimal) -> Self {
+        value
+    }
+
+    fn value(&self) -> Decimal {
+        *self
+    }
+
+    /// Returns the square root of a [`Decimal`].
+    /// Panics if the number is negative.
+    fn sqrt(self) -> Self {
+        Root::sqrt(self)
+    }
+}
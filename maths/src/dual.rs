@@ -0,0 +1,398 @@
+use crate::decimal_like::DecimalLike;
+use crate::exponential::Exponential;
+use crate::internal_prelude::*;
+use crate::logarithm::{Logarithm, LN_10, LN_2};
+use crate::power::Power;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// Dual number, holding the value of an expression and its derivative with respect to a chosen
+/// input.
+///
+/// Evaluating a formula written over [`DecimalLike`] with the input as [`Dual::variable`] and the
+/// other parameters as [`Dual::constant`] returns the value of the formula and its derivative,
+/// computed with the chain rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dual {
+    pub value: Decimal,
+    pub derivative: Decimal,
+}
+
+impl Dual {
+    /// Returns the input with respect to which derivatives are computed.
+    ///
+    /// # Arguments
+    /// * `value`: value of the input.
+    pub fn variable(value: Decimal) -> Self {
+        Self {
+            value,
+            derivative: Decimal::ONE,
+        }
+    }
+
+    /// Returns a constant, whose derivative is zero.
+    ///
+    /// # Arguments
+    /// * `value`: value of the constant.
+    pub fn constant(value: Decimal) -> Self {
+        Self {
+            value,
+            derivative: Decimal::ZERO,
+        }
+    }
+}
+
+impl Add for Dual {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            value: self.value + other.value,
+            derivative: self.derivative + other.derivative,
+        }
+    }
+}
+
+impl Sub for Dual {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self {
+            value: self.value - other.value,
+            derivative: self.derivative - other.derivative,
+        }
+    }
+}
+
+impl Mul for Dual {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            value: self.value * other.value,
+            derivative: self.derivative * other.value + self.value * other.derivative,
+        }
+    }
+}
+
+impl Div for Dual {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        Self {
+            value: self.value / other.value,
+            derivative: (self.derivative * other.value - self.value * other.derivative)
+                / (other.value * other.value),
+        }
+    }
+}
+
+impl Neg for Dual {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            value: -self.value,
+            derivative: -self.derivative,
+        }
+    }
+}
+
+impl Exponential for Dual {
+    /// Returns the exponential of a [`Dual`]: (exp(x), x' * exp(x)).
+    fn exp(self) -> Self {
+        self.checked_exp().expect("Overflow")
+    }
+
+    /// Returns the exponential of a [`Dual`], or `None` if its value or derivative overflows.
+    fn checked_exp(self) -> Option<Self> {
+        let exp = self.value.checked_exp()?;
+        Some(Self {
+            value: exp,
+            derivative: self.derivative.checked_mul(exp)?,
+        })
+    }
+}
+
+impl Logarithm for Dual {
+    /// Returns the natural logarithm of a [`Dual`]: (ln(x), x' / x).
+    fn ln(self) -> Self {
+        Self {
+            value: self.value.ln(),
+            derivative: self.derivative / self.value,
+        }
+    }
+
+    /// Returns the natural logarithm of a [`Dual`], or `None` if its value is not positive.
+    fn checked_ln(self) -> Option<Self> {
+        self.value.is_positive().then(|| self.ln())
+    }
+
+    /// Returns ln(1 + x) for a [`Dual`]: (ln(1 + x), x' / (1 + x)).
+    fn ln_1p(self) -> Self {
+        Self {
+            value: self.value.ln_1p(),
+            derivative: self.derivative / (Decimal::ONE + self.value),
+        }
+    }
+
+    /// Returns the binary logarithm of a [`Dual`].
+    fn log2(self) -> Self {
+        self.ln() / Dual::constant(LN_2)
+    }
+
+    /// Returns the decimal logarithm of a [`Dual`].
+    fn log10(self) -> Self {
+        self.ln() / Dual::constant(LN_10)
+    }
+
+    /// Returns the logarithm of a [`Dual`] in a given base, which can itself have a derivative.
+    fn log_base(self, base: Dual) -> Self {
+        self.ln() / base.ln()
+    }
+}
+
+impl Power for Dual {
+    /// Returns number to the exp, whose derivative is y * x^(y - 1) * x' for a constant exponent
+    /// and x^y * (y' * ln(x) + y * x' / x) otherwise.
+    /// Panics if the value is not defined, if the exponent has a derivative while the number is
+    /// not positive, or if the result overflows.
+    fn pow(self, exp: Self) -> Self {
+        if !exp.derivative.is_zero() && !self.value.is_positive() {
+            panic!("Only positive numbers can be raised to a power that has a derivative");
+        }
+        let value = self.value.pow(exp.value);
+        self.pow_derivative(exp, value).expect("Overflow")
+    }
+
+    /// Returns number to the exp, or `None` if its value or derivative overflows or is not
+    /// defined.
+    fn checked_pow(self, exp: Self) -> Option<Self> {
+        let value = self.value.checked_pow(exp.value)?;
+        self.pow_derivative(exp, value)
+    }
+}
+
+impl Dual {
+    /// Completes the value of number to the exp with its derivative. The logarithm of the number
+    /// is only needed, and the number only needs to be positive, when the exponent has a
+    /// derivative.
+    fn pow_derivative(self, exp: Self, value: Decimal) -> Option<Self> {
+        let derivative = if !exp.derivative.is_zero() {
+            if !self.value.is_positive() {
+                return None;
+            }
+            let ln_term = exp.derivative.checked_mul(self.value.checked_ln()?)?;
+            let base_term = exp
+                .value
+                .checked_mul(self.derivative)?
+                .checked_div(self.value)?;
+            value.checked_mul(ln_term.checked_add(base_term)?)?
+        } else if self.derivative.is_zero() {
+            Decimal::ZERO
+        } else {
+            let lower_power = self
+                .value
+                .checked_pow(exp.value.checked_sub(Decimal::ONE)?)?;
+            exp.value
+                .checked_mul(lower_power)?
+                .checked_mul(self.derivative)?
+        };
+        Some(Self { value, derivative })
+    }
+}
+
+impl DecimalLike for Dual {
+    fn constant(value: Decimal) -> Self {
+        Dual::constant(value)
+    }
+
+    fn value(&self) -> Decimal {
+        self.value
+    }
+
+    /// Returns the square root of a [`Dual`]: (sqrt(x), x' / (2 * sqrt(x))).
+    fn sqrt(self) -> Self {
+        let sqrt = self.value.sqrt();
+        Self {
+            value: sqrt,
+            derivative: self.derivative / (sqrt * 2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_dual {
+    use crate::decimal_like::DecimalLike;
+    use crate::dual::Dual;
+    use crate::exponential::Exponential;
+    use crate::internal_prelude::*;
+    use crate::logarithm::Logarithm;
+    use crate::power::Power;
+    use radix_common_derive::dec;
+
+    const FINITE_DIFFERENCE_STEP: Decimal = dec!("0.000001");
+    const TOLERANCE: Decimal = dec!("0.000000001");
+
+    /// Value of a call-like payoff under a log-normal model, with the volatility `sigma`.
+    fn pricing<T: DecimalLike>(spot: T, strike: T, rate: T, time: T, sigma: T) -> T {
+        let half = T::constant(dec!("0.5"));
+        let drift = (rate - half * sigma * sigma) * time;
+        let diffusion = sigma * time.sqrt();
+        spot * (drift + diffusion).exp() - strike * (-(rate * time)).exp() + (spot / strike).ln()
+    }
+
+    fn central_difference<F: Fn(Decimal) -> Decimal>(f: F, x: Decimal) -> Decimal {
+        (f(x + FINITE_DIFFERENCE_STEP) - f(x - FINITE_DIFFERENCE_STEP))
+            / (FINITE_DIFFERENCE_STEP * 2)
+    }
+
+    fn assert_close(actual: Decimal, expected: Decimal) {
+        let error = (actual - expected).checked_abs().unwrap();
+        assert!(
+            error <= TOLERANCE * expected.checked_abs().unwrap().max(Decimal::ONE),
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_exp_derivative() {
+        let x = dec!("1.7");
+        let dual = Dual::variable(x).exp();
+        assert_eq!(dual.value, x.exp());
+        assert_eq!(dual.derivative, x.exp());
+    }
+
+    #[test]
+    fn test_ln_derivative() {
+        let x = dec!(4);
+        let dual = Dual::variable(x).ln();
+        assert_eq!(dual.value, x.ln());
+        assert_eq!(dual.derivative, dec!("0.25"));
+    }
+
+    #[test]
+    fn test_log_base_derivative() {
+        let dual = Dual::variable(dec!(8)).log_base(Dual::constant(dec!(2)));
+        assert_close(dual.value, dec!(3));
+        assert_close(dual.derivative, dec!("0.180336880111120425"));
+
+        let dual = Dual::constant(dec!(8)).log_base(Dual::variable(dec!(2)));
+        assert_close(dual.value, dec!(3));
+        assert_close(dual.derivative, dec!("-2.164042561333445098"));
+    }
+
+    #[test]
+    fn test_pow_derivative() {
+        let x = dec!(3);
+        let dual = Dual::variable(x).pow(Dual::constant(dec!(2)));
+        assert_close(dual.value, dec!(9));
+        assert_close(dual.derivative, dec!(6));
+    }
+
+    #[test]
+    fn test_pow_negative_base() {
+        let dual = Dual::variable(dec!(-2))
+            .checked_pow(Dual::constant(dec!(3)))
+            .unwrap();
+        assert_eq!(dual.value, dec!(-8));
+        assert_eq!(dual.derivative, dec!(12));
+    }
+
+    #[test]
+    fn test_pow_zero_base() {
+        let dual = Dual::variable(dec!(0))
+            .checked_pow(Dual::constant(dec!(2)))
+            .unwrap();
+        assert_eq!(dual.value, dec!(0));
+        assert_eq!(dual.derivative, dec!(0));
+
+        let dual = Dual::variable(dec!(0)).pow(Dual::constant(dec!(1)));
+        assert_eq!(dual.value, dec!(0));
+        assert_eq!(dual.derivative, dec!(1));
+    }
+
+    #[test]
+    fn test_pow_non_positive_base_varying_exponent() {
+        assert_eq!(
+            Dual::constant(dec!(-2)).checked_pow(Dual::variable(dec!(3))),
+            None
+        );
+        assert_eq!(
+            Dual::constant(dec!(0)).checked_pow(Dual::variable(dec!(2))),
+            None
+        );
+        assert_eq!(
+            Dual::variable(dec!(0)).checked_pow(Dual::constant(dec!("0.5"))),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Only positive numbers can be raised to a power that has a derivative"
+    )]
+    fn test_pow_negative_base_varying_exponent_panics() {
+        Dual::constant(dec!(-2)).pow(Dual::variable(dec!(3)));
+    }
+
+    #[test]
+    fn test_pow_varying_exponent() {
+        let dual = Dual::constant(dec!(2)).pow(Dual::variable(dec!(3)));
+        assert_close(dual.value, dec!(8));
+        assert_close(dual.derivative, dec!(8) * dec!(2).ln());
+    }
+
+    #[test]
+    fn test_sqrt_derivative() {
+        let dual = Dual::variable(dec!(16)).sqrt();
+        assert_eq!(dual.value, dec!(4));
+        assert_eq!(dual.derivative, dec!("0.125"));
+    }
+
+    #[test]
+    fn test_constant_derivative() {
+        let dual = Dual::constant(dec!(5)) * Dual::constant(dec!(2)).exp();
+        assert_eq!(dual.derivative, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_delta() {
+        let (spot, strike, rate, time, sigma) =
+            (dec!(100), dec!(95), dec!("0.05"), dec!("0.5"), dec!("0.2"));
+
+        let delta = pricing(
+            Dual::variable(spot),
+            Dual::constant(strike),
+            Dual::constant(rate),
+            Dual::constant(time),
+            Dual::constant(sigma),
+        );
+
+        assert_eq!(delta.value, pricing(spot, strike, rate, time, sigma));
+        assert_close(
+            delta.derivative,
+            central_difference(|s| pricing(s, strike, rate, time, sigma), spot),
+        );
+    }
+
+    #[test]
+    fn test_vega() {
+        let (spot, strike, rate, time, sigma) =
+            (dec!(100), dec!(95), dec!("0.05"), dec!("0.5"), dec!("0.2"));
+
+        let vega = pricing(
+            Dual::constant(spot),
+            Dual::constant(strike),
+            Dual::constant(rate),
+            Dual::constant(time),
+            Dual::variable(sigma),
+        );
+
+        assert_close(
+            vega.derivative,
+            central_difference(|v| pricing(spot, strike, rate, time, v), sigma),
+        );
+    }
+}
@@ -6,44 +6,161 @@ pub const SMALLEST_NON_ZERO: Decimal = Decimal(I192::from_digits([
     18446744073709551615,
 ]));
 
-pub trait Exponential {
+/// Largest argument of [`Exponential::exp`] whose result does not overflow a [`Decimal`].
+pub const MAX_EXP_ARGUMENT: Decimal = Decimal(I192::from_digits([17157603518218525322, 4, 0]));
+
+/// Smallest argument whose exponential is not rounded to zero as a [`PreciseDecimal`].
+pub const PRECISE_SMALLEST_NON_ZERO: PreciseDecimal = PreciseDecimal(I256::from_digits([
+    12426883510167249175,
+    13915526521476400015,
+    18446744073709551615,
+    18446744073709551615,
+]));
+
+/// Biggest argument whose exponential does not overflow a [`PreciseDecimal`].
+pub const PRECISE_BIGGEST_NON_OVERFLOW: PreciseDecimal = PreciseDecimal(I256::from_digits([
+    16822948339361241385,
+    5088131939162623250,
+    0,
+    0,
+]));
+
+/// Arguments strictly above this threshold used to be evaluated with range reduction instead of a
+/// plain Taylor series.
+#[deprecated(note = "Every argument of `exp` is range reduced by the same kernel")]
+pub const EXP_RANGE_REDUCTION_THRESHOLD: Decimal = Decimal::ONE;
+
+/// Number of terms of the Taylor kernel that used to be evaluated on range reduced arguments.
+#[deprecated(note = "The Taylor kernel of `exp` is summed until its terms vanish")]
+pub const EXP_KERNEL_ORDER: u32 = 20;
+
+/// Range reduced arguments are smaller or equal to 1/2^EXP_REDUCED_ARGUMENT_LOG2.
+pub const EXP_REDUCED_ARGUMENT_LOG2: u32 = 3;
+
+pub trait Exponential: Sized {
     fn exp(self) -> Self;
+    fn checked_exp(self) -> Option<Self>;
 }
 
 impl Exponential for Decimal {
-    /// Returns the exponential of a [`Decimal`] using Taylor series.
+    /// Returns the exponential of a [`Decimal`].
+    /// Panics if the argument is above [`MAX_EXP_ARGUMENT`].
+    fn exp(self) -> Self {
+        self.checked_exp().expect("Overflow")
+    }
+
+    /// Returns the exponential of a [`Decimal`], or `None` if the argument is above
+    /// [`MAX_EXP_ARGUMENT`].
+    ///
+    /// The argument is reduced to at most 1/8 by halvings, and the exponential of the reduced
+    /// argument is evaluated by Taylor series and squared back, with 18 extra decimals. The result
+    /// is rounded to the nearest 18 decimals number.
+    fn checked_exp(self) -> Option<Self> {
+        if self.is_zero() {
+            Some(Decimal::ONE)
+        } else if self < SMALLEST_NON_ZERO {
+            Some(Decimal::ZERO)
+        } else if self > MAX_EXP_ARGUMENT {
+            None
+        } else {
+            let rounded = rounded_exp(I512::from(self.0), I512::from(Decimal::ONE.0));
+            I192::try_from(rounded).ok().map(Decimal)
+        }
+    }
+}
+
+impl Exponential for PreciseDecimal {
+    /// Returns the exponential of a [`PreciseDecimal`], rounded to the nearest 36 decimals number.
+    /// Panics if the argument is above [`PRECISE_BIGGEST_NON_OVERFLOW`].
     fn exp(self) -> Self {
+        self.checked_exp().expect("Overflow")
+    }
+
+    /// Returns the exponential of a [`PreciseDecimal`], rounded to the nearest 36 decimals number,
+    /// or `None` if the argument is above [`PRECISE_BIGGEST_NON_OVERFLOW`].
+    ///
+    /// The exponential is evaluated with the same reduced argument kernel as for a [`Decimal`],
+    /// with 18 extra decimals, so that the result is exact up to the rounding of its last decimal.
+    fn checked_exp(self) -> Option<Self> {
         if self.is_zero() {
-            Decimal::one()
-        } else if self.is_negative() {
-            if self < SMALLEST_NON_ZERO {
-                Decimal::zero()
-            } else {
-                Decimal::one() / ((-self).exp())
-            }
+            Some(PreciseDecimal::ONE)
+        } else if self < PRECISE_SMALLEST_NON_ZERO {
+            Some(PreciseDecimal::ZERO)
+        } else if self > PRECISE_BIGGEST_NON_OVERFLOW {
+            None
         } else {
-            let self_384 = I256::from(self.0);
-            let one_384 = I256::from(Decimal::ONE.0);
-            let mut result = one_384;
-            let mut added_term = self_384;
-            let mut counter = I256::ONE;
-            while added_term != I256::ZERO {
-                result += added_term;
-                counter += I256::ONE;
-                added_term *= self_384 / counter;
-                added_term /= one_384;
-            }
-            Decimal(I192::try_from(result).expect("Overflow"))
+            let rounded = rounded_exp(I512::from(self.0), I512::from(PreciseDecimal::ONE.0));
+            I256::try_from(rounded).ok().map(PreciseDecimal)
+        }
+    }
+}
+
+/// Computes the exponential of a fixed point number given by its integer representation and the
+/// representation of one, with 18 extra decimals, and rounds it to the nearest representable
+/// number.
+///
+/// The exponential of a negative argument is the inverse of the exponential of its opposite.
+fn rounded_exp(value: I512, one: I512) -> I512 {
+    let guard = I512::from(Decimal::ONE.0);
+    let one = one * guard;
+    let value = value * guard;
+
+    let result = if value.is_negative() {
+        one * one / scaled_exp(-value, one)
+    } else {
+        scaled_exp(value, one)
+    };
+    (result + guard / I512::from(2)) / guard
+}
+
+/// Computes the exponential of a non-negative fixed point number given by its integer
+/// representation and the representation of one, as exp(x/2^k)^(2^k) with a Taylor series
+/// evaluated on the reduced argument. The result is truncated.
+///
+/// This is the kernel of the exponentials of [`Decimal`] and [`PreciseDecimal`] and of the
+/// continuous accruals, which all evaluate it with 18 extra decimals. The reduced argument being
+/// at most 1/2^[`EXP_REDUCED_ARGUMENT_LOG2`], the series only needs a few dozen terms whatever the
+/// size of the argument.
+///
+/// The representation of one can have up to 54 decimals, and x must not be above the argument
+/// whose exponential overflows a [`PreciseDecimal`], so that the squarings fit in an [`I512`].
+pub(crate) fn scaled_exp(x: I512, one: I512) -> I512 {
+    let bound = one / I512::from(1u64 << EXP_REDUCED_ARGUMENT_LOG2);
+    let mut halvings = 0;
+    while x > bound * I512::from(1u128 << halvings) {
+        halvings += 1;
+    }
+    let reduced = x / I512::from(1u128 << halvings);
+
+    let mut result = one;
+    let mut term = one;
+    let mut n = 1u32;
+    loop {
+        term = term * reduced / (one * I512::from(n));
+        if term == I512::ZERO {
+            break;
         }
+        result += term;
+        n += 1;
     }
+
+    for _ in 0..halvings {
+        result = result * result / one;
+    }
+    result
 }
 
 #[cfg(test)]
 mod test_exp {
-    use crate::exponential::{Exponential, SMALLEST_NON_ZERO};
+    #[allow(deprecated)]
+    use crate::exponential::EXP_RANGE_REDUCTION_THRESHOLD;
+    use crate::exponential::{
+        Exponential, EXP_REDUCED_ARGUMENT_LOG2, MAX_EXP_ARGUMENT, PRECISE_BIGGEST_NON_OVERFLOW,
+        PRECISE_SMALLEST_NON_ZERO, SMALLEST_NON_ZERO,
+    };
     use crate::internal_prelude::*;
     use crate::RELATIVE_PRECISION;
-    use radix_common_derive::dec;
+    use radix_common_derive::{dec, pdec};
 
     #[test]
     fn test_zero() {
@@ -96,4 +213,196 @@ mod test_exp {
         let rel_prec = (true_val - dec!(57).exp()).checked_abs().unwrap() / true_val;
         assert!(rel_prec < RELATIVE_PRECISION)
     }
+
+    #[test]
+    fn test_89() {
+        let true_val = dec!("448961281917434524628424557964531627776.598058637185152605");
+        let rel_prec = (true_val - dec!(89).exp()).checked_abs().unwrap() / true_val;
+        assert!(rel_prec < RELATIVE_PRECISION)
+    }
+
+    #[test]
+    fn test_80() {
+        let true_val = dec!("55406223843935100525711733958316612.924856728832685323");
+        let rel_prec = (true_val - dec!(80).exp()).checked_abs().unwrap() / true_val;
+        assert!(rel_prec < RELATIVE_PRECISION)
+    }
+
+    #[test]
+    fn test_90_5() {
+        let true_val = dec!("2012104870174332967594465065174926757552.016826133823666238");
+        let rel_prec = (true_val - dec!("90.5").exp()).checked_abs().unwrap() / true_val;
+        assert!(rel_prec < RELATIVE_PRECISION)
+    }
+
+    #[test]
+    fn test_max_argument() {
+        let rel_prec = (Decimal::MAX - MAX_EXP_ARGUMENT.exp())
+            .checked_abs()
+            .unwrap()
+            / Decimal::MAX;
+        assert!(rel_prec < RELATIVE_PRECISION)
+    }
+
+    #[test]
+    fn test_rounding() {
+        assert_eq!(Decimal::ONE.exp(), dec!("2.718281828459045235"));
+        assert_eq!(dec!("0.5").exp(), dec!("1.648721270700128147"));
+        assert_eq!(
+            dec!("0.999999999999999999").exp(),
+            dec!("2.718281828459045233")
+        );
+    }
+
+    #[test]
+    fn test_reduction_continuity() {
+        // Arguments just above a power of two times the reduction bound need one more halving.
+        let mut bound = Decimal::ONE / Decimal::from(1u64 << EXP_REDUCED_ARGUMENT_LOG2);
+        while bound < dec!(90) {
+            let below = bound.exp();
+            let above = (bound + Decimal(I192::ONE)).exp();
+            let rel_prec = (above - below).checked_abs().unwrap() / below;
+            assert!(rel_prec < RELATIVE_PRECISION, "Discontinuity at {}", bound);
+            bound *= dec!(2);
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_switchover_continuity() {
+        // Arguments used to be range reduced above this threshold only.
+        let below = EXP_RANGE_REDUCTION_THRESHOLD.exp();
+        let above = (EXP_RANGE_REDUCTION_THRESHOLD + Decimal(I192::ONE)).exp();
+        let rel_prec = (above - below).checked_abs().unwrap() / below;
+        assert!(rel_prec < RELATIVE_PRECISION);
+        assert_eq!(below, dec!("2.718281828459045235"));
+    }
+
+    #[test]
+    fn test_strategies_agree() {
+        // The Decimal exponential agrees with the PreciseDecimal one on both sides of the former
+        // switchover.
+        let mut x = dec!("0.5");
+        while x < dec!(90) {
+            let precise = PreciseDecimal::from(x)
+                .exp()
+                .checked_truncate(RoundingMode::ToNearestMidpointAwayFromZero)
+                .unwrap();
+            let rel_prec = (x.exp() - precise).checked_abs().unwrap() / precise;
+            assert!(
+                rel_prec < RELATIVE_PRECISION,
+                "Exponentials differ at {}",
+                x
+            );
+            x += dec!("2.25");
+        }
+    }
+
+    #[test]
+    fn test_precise_zero() {
+        assert_eq!(PreciseDecimal::ZERO.exp(), PreciseDecimal::ONE);
+    }
+
+    #[test]
+    fn test_precise_one() {
+        assert_eq!(
+            PreciseDecimal::ONE.exp(),
+            pdec!("2.718281828459045235360287471352662498")
+        );
+    }
+
+    #[test]
+    fn test_precise_neg_one() {
+        assert_eq!(
+            (-PreciseDecimal::ONE).exp(),
+            pdec!("0.367879441171442321595523770161460867")
+        );
+    }
+
+    #[test]
+    fn test_precise_42() {
+        let true_val = pdec!("1739274941520501047.394681303611235226147984057725008401");
+        let rel_prec = (true_val - pdec!(42).exp()).checked_abs().unwrap() / true_val;
+        assert!(rel_prec < PreciseDecimal::from(RELATIVE_PRECISION));
+    }
+
+    #[test]
+    fn test_precise_smallest_non_zero() {
+        assert_eq!(PRECISE_SMALLEST_NON_ZERO.exp(), PreciseDecimal(I256::ONE));
+        assert_eq!(
+            (PRECISE_SMALLEST_NON_ZERO - PreciseDecimal(I256::ONE)).exp(),
+            PreciseDecimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_precise_biggest_non_overflow() {
+        let rel_prec = (PreciseDecimal::MAX - PRECISE_BIGGEST_NON_OVERFLOW.exp())
+            .checked_abs()
+            .unwrap()
+            / PreciseDecimal::MAX;
+        assert!(rel_prec < PreciseDecimal::from(RELATIVE_PRECISION));
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow")]
+    fn test_precise_overflow() {
+        let _m = (PRECISE_BIGGEST_NON_OVERFLOW + PreciseDecimal(I256::ONE)).exp();
+    }
+
+    #[test]
+    fn test_checked_exp_boundary() {
+        assert_eq!(MAX_EXP_ARGUMENT, dec!("90.944579813056731786"));
+        assert_eq!(
+            dec!("90.944579813056731786").checked_exp(),
+            Some(dec!("90.944579813056731786").exp())
+        );
+        assert_eq!(dec!("90.944579813056731787").checked_exp(), None);
+        assert_eq!(dec!(1000).checked_exp(), None);
+    }
+
+    #[test]
+    fn test_checked_exp_negative() {
+        assert_eq!(dec!(-1000).checked_exp(), Some(Decimal::ZERO));
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow")]
+    fn test_overflow() {
+        let _m = dec!("90.944579813056731787").exp();
+    }
+
+    #[test]
+    fn test_precise_checked_exp_boundary() {
+        assert_eq!(
+            PRECISE_BIGGEST_NON_OVERFLOW,
+            pdec!("93.859467695000409276746498603197913385")
+        );
+        assert_eq!(
+            PRECISE_SMALLEST_NON_ZERO,
+            pdec!("-83.586210528345589934064924490095288041")
+        );
+        assert!(PRECISE_BIGGEST_NON_OVERFLOW.checked_exp().is_some());
+        assert_eq!(
+            (PRECISE_BIGGEST_NON_OVERFLOW + PreciseDecimal(I256::ONE)).checked_exp(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_precise_agrees_with_decimal() {
+        // Small exponentials only have a few significant decimals as a Decimal, whose last
+        // decimal is then the tolerance.
+        let decimal_unit = PreciseDecimal::from(Decimal(I192::ONE));
+        let mut x = dec!(-40);
+        while x <= dec!(90) {
+            let precise = PreciseDecimal::from(x).exp();
+            let difference = (PreciseDecimal::from(x.exp()) - precise)
+                .checked_abs()
+                .unwrap();
+            let tolerance = (precise * PreciseDecimal::from(RELATIVE_PRECISION)).max(decimal_unit);
+            assert!(difference <= tolerance, "Exponentials differ at {}", x);
+            x += dec!("3.7");
+        }
+    }
 }
@@ -1 +1,4 @@
-pub use radix_common::prelude::{Decimal, I192, I256, U192};
+pub use radix_common::prelude::{
+    CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, CheckedTruncate, Decimal, PreciseDecimal,
+    RoundingMode, I192, I256, I512, U192,
+};
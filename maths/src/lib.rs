@@ -1,8 +1,14 @@
 use internal_prelude::*;
+pub mod accumulator;
+pub mod compounding;
+pub mod decimal_like;
+pub mod dual;
 pub mod exponential;
 pub(crate) mod internal_prelude;
+pub mod log_decimal;
 pub mod logarithm;
 pub mod power;
+pub mod root;
 
 // Relative precision of the library is 10^-16
 pub const RELATIVE_PRECISION: Decimal = Decimal(I192::from_digits([100, 0, 0]));
@@ -0,0 +1,297 @@
+use crate::exponential::{Exponential, MAX_EXP_ARGUMENT};
+use crate::internal_prelude::*;
+use crate::logarithm::Logarithm;
+use std::cmp::Ordering;
+use std::ops::{Div, DivAssign, Mul, MulAssign};
+
+/// Natural logarithm of [`Decimal::MAX`], rounded down. It is the largest argument of
+/// [`Exponential::exp`] that does not overflow.
+pub const LN_DECIMAL_MAX: Decimal = MAX_EXP_ARGUMENT;
+
+/// A non-negative quantity stored as its natural logarithm.
+///
+/// Products and quotients of [`LogDecimal`] are computed as sums and differences of logarithms,
+/// so that products of many factors neither underflow nor overflow before being converted back
+/// to a [`Decimal`]. Each factor brings an absolute error of at most 2.10^-18 on the logarithm,
+/// so the relative error of a product of n factors is at most n * 2.10^-18 plus the relative
+/// error of the final exponential.
+///
+/// Zero has no logarithm and is stored as the distinguished [`LogDecimal::Zero`] variant.
+/// Negative quantities cannot be represented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogDecimal {
+    /// The quantity zero.
+    Zero,
+    /// A positive quantity, stored as its natural logarithm.
+    Ln(Decimal),
+}
+
+impl LogDecimal {
+    pub const ZERO: Self = Self::Zero;
+    pub const ONE: Self = Self::Ln(Decimal::ZERO);
+
+    /// Returns the [`LogDecimal`] representing a non-negative [`Decimal`].
+    /// Panics if the given value is negative.
+    ///
+    /// # Arguments
+    /// * `value`: value to represent.
+    pub fn from_decimal(value: Decimal) -> Self {
+        assert!(
+            !value.is_negative(),
+            "LogDecimal can only represent non-negative numbers"
+        );
+        if value.is_zero() {
+            Self::Zero
+        } else if value == Decimal::ONE {
+            Self::ONE
+        } else {
+            Self::Ln(value.ln())
+        }
+    }
+
+    /// Returns the represented quantity as a [`Decimal`] or `None` if it is bigger than
+    /// [`Decimal::MAX`].
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        match self {
+            Self::Zero => Some(Decimal::ZERO),
+            Self::Ln(ln) if *ln > LN_DECIMAL_MAX => None,
+            Self::Ln(ln) => Some(ln.exp()),
+        }
+    }
+
+    /// Returns the natural logarithm of the represented quantity or `None` if it is zero.
+    pub fn ln(&self) -> Option<Decimal> {
+        match self {
+            Self::Zero => None,
+            Self::Ln(ln) => Some(*ln),
+        }
+    }
+
+    /// Returns whether the represented quantity is zero.
+    pub fn is_zero(&self) -> bool {
+        matches!(self, Self::Zero)
+    }
+
+    /// Returns the represented quantity to an integer power.
+    /// Panics if the quantity is zero and the exponent is negative, or if the logarithm of the
+    /// result overflows a [`Decimal`].
+    ///
+    /// # Arguments
+    /// * `exp`: exponent.
+    pub fn powi(self, exp: i64) -> Self {
+        if self.is_zero() && exp < 0 {
+            panic!("Zero cannot be raised to a negative power");
+        }
+        self.checked_powi(exp).expect("Overflow")
+    }
+
+    /// Returns the represented quantity to an integer power, or `None` if the quantity is zero
+    /// and the exponent is negative, or if the logarithm of the result overflows a [`Decimal`].
+    ///
+    /// # Arguments
+    /// * `exp`: exponent.
+    pub fn checked_powi(self, exp: i64) -> Option<Self> {
+        match self {
+            Self::Zero => match exp.cmp(&0) {
+                Ordering::Less => None,
+                Ordering::Equal => Some(Self::ONE),
+                Ordering::Greater => Some(Self::Zero),
+            },
+            Self::Ln(ln) => ln.checked_mul(Decimal::from(exp)).map(Self::Ln),
+        }
+    }
+
+    /// Returns the product of two represented quantities, or `None` if the sum of their
+    /// logarithms overflows a [`Decimal`].
+    ///
+    /// # Arguments
+    /// * `other`: other factor.
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Ln(a), Self::Ln(b)) => a.checked_add(b).map(Self::Ln),
+            _ => Some(Self::Zero),
+        }
+    }
+
+    /// Returns the quotient of two represented quantities, or `None` if the divisor is zero or
+    /// if the difference of their logarithms overflows a [`Decimal`].
+    ///
+    /// # Arguments
+    /// * `other`: divisor.
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        match (self, other) {
+            (_, Self::Zero) => None,
+            (Self::Zero, _) => Some(Self::Zero),
+            (Self::Ln(a), Self::Ln(b)) => a.checked_sub(b).map(Self::Ln),
+        }
+    }
+}
+
+impl From<Decimal> for LogDecimal {
+    fn from(value: Decimal) -> Self {
+        Self::from_decimal(value)
+    }
+}
+
+impl Mul for LogDecimal {
+    type Output = Self;
+
+    /// Panics if the sum of the logarithms overflows a [`Decimal`], see
+    /// [`LogDecimal::checked_mul`].
+    fn mul(self, other: Self) -> Self {
+        self.checked_mul(other).expect("Overflow")
+    }
+}
+
+impl Div for LogDecimal {
+    type Output = Self;
+
+    /// Panics if the divisor is zero or if the difference of the logarithms overflows a
+    /// [`Decimal`], see [`LogDecimal::checked_div`].
+    fn div(self, other: Self) -> Self {
+        if other.is_zero() {
+            panic!("Division by zero");
+        }
+        self.checked_div(other).expect("Overflow")
+    }
+}
+
+impl MulAssign for LogDecimal {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+impl DivAssign for LogDecimal {
+    fn div_assign(&mut self, other: Self) {
+        *self = *self / other;
+    }
+}
+
+impl PartialOrd for LogDecimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LogDecimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Zero, Self::Zero) => Ordering::Equal,
+            (Self::Zero, Self::Ln(_)) => Ordering::Less,
+            (Self::Ln(_), Self::Zero) => Ordering::Greater,
+            (Self::Ln(a), Self::Ln(b)) => a.cmp(b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_log_decimal {
+    use crate::internal_prelude::*;
+    use crate::log_decimal::{LogDecimal, LN_DECIMAL_MAX};
+    use radix_common_derive::dec;
+
+    #[test]
+    fn test_product_of_many_factors() {
+        let factor = LogDecimal::from_decimal(dec!("0.9999"));
+        let mut product = LogDecimal::ONE;
+        for _ in 0..10_000 {
+            product *= factor;
+        }
+
+        let expected = dec!("0.367861046432929924");
+        let rel_prec = (product.to_decimal().unwrap() - expected)
+            .checked_abs()
+            .unwrap()
+            / expected;
+        // Documented error: 10_000 factors * 2.10^-18
+        assert!(rel_prec < dec!("0.00000000000002"));
+        assert_eq!(product, factor.powi(10_000));
+    }
+
+    #[test]
+    fn test_factors_of_one() {
+        let one = LogDecimal::from_decimal(Decimal::ONE);
+        assert_eq!(one, LogDecimal::ONE);
+
+        let mut product = LogDecimal::from_decimal(dec!(3));
+        for _ in 0..1000 {
+            product *= one;
+        }
+        assert_eq!(product, LogDecimal::from_decimal(dec!(3)));
+        assert_eq!(LogDecimal::ONE.to_decimal(), Some(Decimal::ONE));
+    }
+
+    #[test]
+    fn test_zero() {
+        let zero = LogDecimal::from_decimal(Decimal::ZERO);
+        let two = LogDecimal::from_decimal(dec!(2));
+        assert_eq!(zero, LogDecimal::ZERO);
+        assert_eq!(zero * two, LogDecimal::ZERO);
+        assert_eq!(zero / two, LogDecimal::ZERO);
+        assert_eq!(zero.powi(0), LogDecimal::ONE);
+        assert_eq!(zero.to_decimal(), Some(Decimal::ZERO));
+        assert_eq!(zero.ln(), None);
+        assert!(zero < LogDecimal::from_decimal(Decimal(I192::ONE)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative() {
+        let _m = LogDecimal::from_decimal(dec!(-1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_division_by_zero() {
+        let _m = LogDecimal::ONE / LogDecimal::ZERO;
+    }
+
+    #[test]
+    fn test_comparison() {
+        let half = LogDecimal::from_decimal(dec!("0.5"));
+        let two = LogDecimal::from_decimal(dec!(2));
+        assert!(half < LogDecimal::ONE);
+        assert!(two > LogDecimal::ONE);
+        assert_eq!(half * two, LogDecimal::ONE);
+        assert_eq!(two / two, LogDecimal::ONE);
+    }
+
+    #[test]
+    fn test_overflow() {
+        assert_eq!(LN_DECIMAL_MAX, dec!("90.944579813056731786"));
+        let max = LogDecimal::Ln(LN_DECIMAL_MAX);
+        assert!(max.to_decimal().is_some());
+        assert_eq!(max.powi(2).to_decimal(), None);
+        assert_eq!((LogDecimal::from_decimal(dec!(2)) * max).to_decimal(), None);
+    }
+
+    #[test]
+    fn test_checked_operations() {
+        let max = LogDecimal::Ln(Decimal::MAX);
+        let two = LogDecimal::from_decimal(dec!(2));
+        assert_eq!(max.checked_mul(two), None);
+        assert_eq!(max.checked_powi(2), None);
+        assert_eq!(LogDecimal::Ln(Decimal::MIN).checked_div(two), None);
+        assert_eq!(two.checked_div(LogDecimal::ZERO), None);
+        assert_eq!(LogDecimal::ZERO.checked_powi(-1), None);
+        assert_eq!(two.checked_mul(LogDecimal::ZERO), Some(LogDecimal::ZERO));
+        assert_eq!(two.checked_powi(3), Some(two * two * two));
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow")]
+    fn test_mul_overflow() {
+        let _m = LogDecimal::Ln(Decimal::MAX) * LogDecimal::from_decimal(dec!(2));
+    }
+
+    #[test]
+    fn test_underflow() {
+        let smallest = LogDecimal::from_decimal(Decimal(I192::ONE));
+        assert_eq!(smallest.powi(2).to_decimal(), Some(Decimal::ZERO));
+        assert_eq!(
+            (smallest.powi(2) / smallest).to_decimal(),
+            Some(Decimal(I192::ONE))
+        );
+    }
+}
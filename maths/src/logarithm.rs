@@ -3,14 +3,37 @@ use crate::internal_prelude::*;
 
 pub const LN_2: Decimal = Decimal(I192::from_digits([693147180559945309, 0, 0]));
 pub const LN_10: Decimal = Decimal(I192::from_digits([2302585092994045684, 0, 0]));
+pub const PRECISE_LN_2: PreciseDecimal = PreciseDecimal(I256::from_digits([
+    9456716947207598648,
+    37575583950764745,
+    0,
+    0,
+]));
+pub const PRECISE_LN_10: PreciseDecimal = PreciseDecimal(I256::from_digits([
+    5541036900753882544,
+    124823388007844079,
+    0,
+    0,
+]));
 // Next power of two for the U192 representation of the Decimal 1
 pub const NEXT_POWER_OF_TWO_FOR_ONE: U192 = U192::from_digits([1152921504606846976, 0, 0]);
 
-pub trait Logarithm {
+/// Arguments of `ln_1p` whose absolute value is below or equal to this threshold are evaluated
+/// with a series instead of adding 1 first.
+pub const LN_1P_SERIES_THRESHOLD: Decimal = Decimal(I192::from_digits([500000000000000000, 0, 0]));
+
+pub trait Logarithm: Sized {
     fn ln(self) -> Self;
+    fn checked_ln(self) -> Option<Self>;
+    fn ln_1p(self) -> Self;
     fn log2(self) -> Self;
     fn log10(self) -> Self;
-    fn lob_base(self, base: Decimal) -> Self;
+    fn log_base(self, base: Self) -> Self;
+
+    #[deprecated(note = "Use `log_base` instead")]
+    fn lob_base(self, base: Self) -> Self {
+        self.log_base(base)
+    }
 }
 
 impl Logarithm for Decimal {
@@ -20,6 +43,8 @@ impl Logarithm for Decimal {
     /// Halley’s method. It does it by computing the sequence x_n defined by induction:
     /// x_{n+1} = x_n + ( y - exp(x_n) )/( y + exp(x_n) ).
     /// Halley's method has a cubic convergence rate.
+    /// Numbers slightly above 1 are evaluated with [`Logarithm::ln_1p`] to keep their low order
+    /// digits.
     fn ln(self) -> Self {
         assert!(
             self.is_positive(),
@@ -29,6 +54,8 @@ impl Logarithm for Decimal {
         // If x < 1 we compute -ln(1/x) instead
         if self < Decimal::one() {
             -(Decimal::ONE / self).ln()
+        } else if self - Decimal::ONE <= LN_1P_SERIES_THRESHOLD {
+            (self - Decimal::ONE).ln_1p()
         } else {
             // Because, exp overflows very quickly, we rewrite y = 2^n(1 + x) with 0=< x <1.
             // This is possible because we make sure that y >= 1
@@ -58,6 +85,27 @@ impl Logarithm for Decimal {
         }
     }
 
+    /// Returns the natural logarithm of a [`Decimal`], or `None` if the number is not positive.
+    fn checked_ln(self) -> Option<Self> {
+        self.is_positive().then(|| self.ln())
+    }
+
+    /// Returns ln(1 + x) for a [`Decimal`] x, without losing the low order digits of a small x.
+    /// Panics if x is not greater than -1.
+    fn ln_1p(self) -> Self {
+        assert!(
+            self > -Decimal::ONE,
+            "ln_1p is only defined for numbers greater than -1"
+        );
+
+        if self.checked_abs().unwrap() <= LN_1P_SERIES_THRESHOLD {
+            let ln = scaled_ln_1p(I512::from(self.0), I512::from(Decimal::ONE.0));
+            Decimal(I192::try_from(ln).expect("Overflow"))
+        } else {
+            (Decimal::ONE + self).ln()
+        }
+    }
+
     /// Returns the binary logarithm of a [`Decimal`].
     fn log2(self) -> Self {
         self.ln() / LN_2
@@ -69,18 +117,136 @@ impl Logarithm for Decimal {
     }
 
     /// Returns the logarithm of a [`Decimal`] in a given base.
-    fn lob_base(self, base: Decimal) -> Self {
+    fn log_base(self, base: Self) -> Self {
+        self.ln() / base.ln()
+    }
+}
+
+impl Logarithm for PreciseDecimal {
+    /// Returns the natural logarithm of a [`PreciseDecimal`].
+    ///
+    /// As for a [`Decimal`], ln(y) is computed with Halley's method after rewriting
+    /// y = 2^n * m with 1 <= m < 2, and numbers slightly above 1 are evaluated with
+    /// [`Logarithm::ln_1p`].
+    fn ln(self) -> Self {
+        assert!(
+            self.is_positive(),
+            "Logarithm is only defined for positive numbers"
+        );
+
+        if self < PreciseDecimal::ONE {
+            -(PreciseDecimal::ONE / self).ln()
+        } else if self - PreciseDecimal::ONE <= PreciseDecimal::from(LN_1P_SERIES_THRESHOLD) {
+            (self - PreciseDecimal::ONE).ln_1p()
+        } else {
+            let integer_part = self.0 / PreciseDecimal::ONE.0;
+            let n = 255 - integer_part.leading_zeros();
+            let mantissa = self
+                / PreciseDecimal::from(2)
+                    .checked_powi(n as i64)
+                    .expect("Overflow");
+
+            // Iterations stop when they only change the last decimal, which can oscillate
+            // because of the rounding of exp.
+            let mut result = mantissa - PreciseDecimal::ONE;
+            loop {
+                let exp = result.exp();
+                let next: PreciseDecimal = result + (mantissa - exp) / (mantissa + exp) * 2;
+                let done = (next - result).checked_abs().unwrap() <= PreciseDecimal(I256::ONE);
+                result = next;
+                if done {
+                    break;
+                }
+            }
+
+            result + PreciseDecimal::from(n) * PRECISE_LN_2
+        }
+    }
+
+    /// Returns the natural logarithm of a [`PreciseDecimal`], or `None` if the number is not
+    /// positive.
+    fn checked_ln(self) -> Option<Self> {
+        self.is_positive().then(|| self.ln())
+    }
+
+    /// Returns ln(1 + x) for a [`PreciseDecimal`] x, without losing the low order digits of a
+    /// small x.
+    /// Panics if x is not greater than -1.
+    fn ln_1p(self) -> Self {
+        assert!(
+            self > -PreciseDecimal::ONE,
+            "ln_1p is only defined for numbers greater than -1"
+        );
+
+        if self.checked_abs().unwrap() <= PreciseDecimal::from(LN_1P_SERIES_THRESHOLD) {
+            let ln = scaled_ln_1p(I512::from(self.0), I512::from(PreciseDecimal::ONE.0));
+            PreciseDecimal(I256::try_from(ln).expect("Overflow"))
+        } else {
+            (PreciseDecimal::ONE + self).ln()
+        }
+    }
+
+    /// Returns the binary logarithm of a [`PreciseDecimal`].
+    fn log2(self) -> Self {
+        self.ln() / PRECISE_LN_2
+    }
+
+    /// Returns the decimal logarithm of a [`PreciseDecimal`].
+    fn log10(self) -> Self {
+        self.ln() / PRECISE_LN_10
+    }
+
+    /// Returns the logarithm of a [`PreciseDecimal`] in a given base.
+    fn log_base(self, base: Self) -> Self {
         self.ln() / base.ln()
     }
 }
 
+/// Computes ln(1 + x) for a fixed point number x given by its integer representation and the
+/// representation of one, rounded to the nearest representable number.
+///
+/// The series ln(1 + x) = 2 * sum z^(2k+1) / (2k+1), with z = x / (2 + x), is evaluated with 18
+/// extra decimals. It converges quickly because |z| <= 1/3 when |x| <= 1/2.
+fn scaled_ln_1p(x: I512, one: I512) -> I512 {
+    let guard = I512::from(Decimal::ONE.0);
+    let one = one * guard;
+    let x = x * guard;
+
+    let z = x * one / (one * I512::from(2) + x);
+    let z_squared = z * z / one;
+    let mut sum = z;
+    let mut power = z;
+    let mut k = 1u32;
+    loop {
+        power = power * z_squared / one;
+        let term = power / I512::from(2 * k + 1);
+        if term == I512::ZERO {
+            break;
+        }
+        sum += term;
+        k += 1;
+    }
+
+    let ln = sum * I512::from(2);
+    let half = guard / I512::from(2);
+    if ln.is_negative() {
+        (ln - half) / guard
+    } else {
+        (ln + half) / guard
+    }
+}
+
 #[cfg(test)]
 mod test_ln {
     use crate::exponential::Exponential;
     use crate::internal_prelude::*;
-    use crate::logarithm::{Logarithm, LN_2};
+    use crate::logarithm::{Logarithm, LN_2, PRECISE_LN_10, PRECISE_LN_2};
     use crate::RELATIVE_PRECISION;
-    use radix_common_derive::dec;
+    use radix_common_derive::{dec, pdec};
+
+    fn precise_rel_prec(value: PreciseDecimal, true_val: PreciseDecimal) -> PreciseDecimal {
+        (value - true_val).checked_abs().unwrap() / true_val.checked_abs().unwrap()
+    }
 
     #[test]
     #[should_panic]
@@ -94,6 +260,15 @@ mod test_ln {
         let _m = Decimal::zero().ln();
     }
 
+    #[test]
+    fn test_checked_ln() {
+        assert_eq!(dec!(-5).checked_ln(), None);
+        assert_eq!(Decimal::zero().checked_ln(), None);
+        assert_eq!(PreciseDecimal::ZERO.checked_ln(), None);
+        assert_eq!(dec!(2).checked_ln(), Some(dec!(2).ln()));
+        assert_eq!(Decimal::ONE.checked_ln(), Some(Decimal::ZERO));
+    }
+
     #[test]
     fn test_ln_1() {
         assert!(Decimal::ONE.ln().checked_abs().unwrap() <= RELATIVE_PRECISION)
@@ -140,4 +315,149 @@ mod test_ln {
             / dec!("135.305999368893231589");
         assert!(rel_prec < RELATIVE_PRECISION);
     }
+
+    #[test]
+    fn test_ln_close_to_one() {
+        let rel_prec = (dec!("1.00000000000000001").ln() - dec!("0.00000000000000001"))
+            .checked_abs()
+            .unwrap()
+            / dec!("0.00000000000000001");
+        assert!(rel_prec < RELATIVE_PRECISION);
+
+        let rel_prec = (dec!("0.99999999999999999").ln() + dec!("0.00000000000000001"))
+            .checked_abs()
+            .unwrap()
+            / dec!("0.00000000000000001");
+        assert!(rel_prec < RELATIVE_PRECISION);
+    }
+
+    #[test]
+    fn test_ln_1p_small() {
+        assert_eq!(
+            dec!("0.00000000000000001").ln_1p(),
+            dec!("0.00000000000000001")
+        );
+        assert_eq!(
+            dec!("-0.00000000000000001").ln_1p(),
+            dec!("-0.00000000000000001")
+        );
+        assert_eq!(Decimal::ZERO.ln_1p(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_ln_1p_large() {
+        let ln_4 = dec!("1.386294361119890618");
+        let rel_prec = (dec!(3).ln_1p() - ln_4).checked_abs().unwrap() / ln_4;
+        assert!(rel_prec < RELATIVE_PRECISION);
+        let rel_prec = (dec!("-0.75").ln_1p() + ln_4).checked_abs().unwrap() / ln_4;
+        assert!(rel_prec < RELATIVE_PRECISION);
+    }
+
+    #[test]
+    #[should_panic(expected = "ln_1p is only defined for numbers greater than -1")]
+    fn test_ln_1p_minus_one() {
+        let _m = dec!(-1).ln_1p();
+    }
+
+    #[test]
+    fn test_log_base() {
+        let rel_prec = (dec!(1000).log_base(dec!(10)) - dec!(3))
+            .checked_abs()
+            .unwrap()
+            / dec!(3);
+        assert!(rel_prec < RELATIVE_PRECISION);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_lob_base_alias() {
+        assert_eq!(dec!(1000).lob_base(dec!(10)), dec!(1000).log_base(dec!(10)));
+    }
+
+    #[test]
+    fn test_precise_log_base() {
+        let rel_prec = precise_rel_prec(pdec!(1000).log_base(pdec!(10)), pdec!(3));
+        assert!(rel_prec < PreciseDecimal::from(RELATIVE_PRECISION));
+    }
+
+    #[test]
+    fn test_precise_ln_close_to_one() {
+        let rel_prec = precise_rel_prec(
+            pdec!("1.00000000000000001").ln(),
+            pdec!("0.000000000000000009999999999999999950"),
+        );
+        assert!(rel_prec < PreciseDecimal::from(RELATIVE_PRECISION));
+
+        let rel_prec = precise_rel_prec(
+            pdec!("0.99999999999999999").ln(),
+            pdec!("-0.000000000000000010000000000000000050"),
+        );
+        assert!(rel_prec < PreciseDecimal::from(RELATIVE_PRECISION));
+    }
+
+    #[test]
+    fn test_precise_ln_1p_small() {
+        assert_eq!(
+            pdec!("0.00000000000000001").ln_1p(),
+            pdec!("0.000000000000000009999999999999999950")
+        );
+        assert_eq!(
+            pdec!("-0.00000000000000001").ln_1p(),
+            pdec!("-0.000000000000000010000000000000000050")
+        );
+    }
+
+    #[test]
+    fn test_precise_ln_2() {
+        assert_eq!(pdec!(2).ln(), PRECISE_LN_2);
+        assert_eq!(pdec!("0.5").ln(), -PRECISE_LN_2);
+        assert_eq!(pdec!(10).ln(), PRECISE_LN_10);
+    }
+
+    #[test]
+    fn test_precise_ln_12() {
+        let rel_prec = precise_rel_prec(
+            pdec!(12).ln(),
+            pdec!("2.484906649788000310229709479838878841"),
+        );
+        assert!(rel_prec < PreciseDecimal::from(RELATIVE_PRECISION));
+    }
+
+    #[test]
+    fn test_precise_ln_smallest() {
+        let rel_prec = precise_rel_prec(
+            PreciseDecimal(I256::ONE).ln(),
+            pdec!("-82.893063347785644624647692368637111474"),
+        );
+        assert!(rel_prec < PreciseDecimal::from(RELATIVE_PRECISION));
+    }
+
+    #[test]
+    fn test_precise_log2_and_log10() {
+        let rel_prec = precise_rel_prec(pdec!(1024).log2(), pdec!(10));
+        assert!(rel_prec < PreciseDecimal::from(RELATIVE_PRECISION));
+        let rel_prec = precise_rel_prec(pdec!(1000).log10(), pdec!(3));
+        assert!(rel_prec < PreciseDecimal::from(RELATIVE_PRECISION));
+    }
+
+    #[test]
+    fn test_precise_ln_exp() {
+        let rel_prec = precise_rel_prec(pdec!(42).exp().ln(), pdec!(42));
+        assert!(rel_prec < PreciseDecimal::from(RELATIVE_PRECISION));
+    }
+
+    #[test]
+    fn test_precise_agrees_with_decimal() {
+        let mut x = dec!("0.000001");
+        while x < dec!(1000000000000) {
+            let rel_prec =
+                precise_rel_prec(PreciseDecimal::from(x.ln()), PreciseDecimal::from(x).ln());
+            assert!(
+                rel_prec < PreciseDecimal::from(RELATIVE_PRECISION),
+                "Logarithms differ at {}",
+                x
+            );
+            x *= dec!("7.3");
+        }
+    }
 }
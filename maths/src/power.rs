@@ -2,13 +2,284 @@ use crate::exponential::Exponential;
 use crate::internal_prelude::*;
 use crate::logarithm::Logarithm;
 
-pub trait Power {
+pub trait Power: Sized {
     fn pow(self, exp: Self) -> Self;
+    fn checked_pow(self, exp: Self) -> Option<Self>;
 }
 
 impl Power for Decimal {
     /// Returns number to the exp.
+    ///
+    /// Integer exponents are computed exactly by exponentiation by squaring and allow negative
+    /// numbers, whose sign is then given by the parity of the exponent. Other exponents are
+    /// computed as exp(exp * ln(number)).
+    /// Panics if zero is raised to a negative power, if a negative number is raised to a
+    /// non-integer power or if the result overflows.
     fn pow(self, exp: Self) -> Self {
-        (exp * self.ln()).exp()
+        if self.is_zero() && exp.is_negative() {
+            panic!("Zero cannot be raised to a negative power");
+        }
+        if self.is_negative() && !is_integer(exp) {
+            panic!("Negative numbers cannot be raised to a non-integer power");
+        }
+        self.checked_pow(exp).expect("Overflow")
+    }
+
+    /// Returns number to the exp, or `None` if the result overflows or is not defined.
+    fn checked_pow(self, exp: Self) -> Option<Self> {
+        if exp.is_zero() {
+            Some(Decimal::ONE)
+        } else if self.is_zero() {
+            exp.is_positive().then_some(Decimal::ZERO)
+        } else if is_integer(exp) {
+            integer_pow(self, exp)
+        } else if self.is_negative() {
+            None
+        } else {
+            exp.checked_mul(self.ln())?.checked_exp()
+        }
+    }
+}
+
+impl Power for PreciseDecimal {
+    /// Returns number to the exp, with the same rules as for a [`Decimal`].
+    ///
+    /// Integer exponents are computed by exponentiation by squaring, each product being rounded
+    /// to 36 decimals. Other exponents are computed as exp(exp * ln(number)).
+    /// Panics if zero is raised to a negative power, if a negative number is raised to a
+    /// non-integer power or if the result overflows.
+    fn pow(self, exp: Self) -> Self {
+        if self.is_zero() && exp.is_negative() {
+            panic!("Zero cannot be raised to a negative power");
+        }
+        if self.is_negative() && !is_precise_integer(exp) {
+            panic!("Negative numbers cannot be raised to a non-integer power");
+        }
+        self.checked_pow(exp).expect("Overflow")
+    }
+
+    /// Returns number to the exp, or `None` if the result overflows or is not defined.
+    fn checked_pow(self, exp: Self) -> Option<Self> {
+        if exp.is_zero() {
+            Some(PreciseDecimal::ONE)
+        } else if self.is_zero() {
+            exp.is_positive().then_some(PreciseDecimal::ZERO)
+        } else if is_precise_integer(exp) {
+            precise_integer_pow(self, exp)
+        } else if self.is_negative() {
+            None
+        } else {
+            exp.checked_mul(self.ln())?.checked_exp()
+        }
+    }
+}
+
+fn is_integer(x: Decimal) -> bool {
+    x.0 % Decimal::ONE.0 == I192::ZERO
+}
+
+fn is_precise_integer(x: PreciseDecimal) -> bool {
+    x.0 % PreciseDecimal::ONE.0 == I256::ZERO
+}
+
+/// Computes a non-zero number to an integer power by exponentiation by squaring, with 36 decimals.
+fn integer_pow(base: Decimal, exp: Decimal) -> Option<Decimal> {
+    precise_integer_pow(PreciseDecimal::from(base), PreciseDecimal::from(exp))?
+        .checked_truncate(RoundingMode::ToNearestMidpointAwayFromZero)
+}
+
+/// Computes a non-zero [`PreciseDecimal`] to an integer power by exponentiation by squaring.
+fn precise_integer_pow(base: PreciseDecimal, exp: PreciseDecimal) -> Option<PreciseDecimal> {
+    let exp_integer = exp.0 / PreciseDecimal::ONE.0;
+    match i64::try_from(exp_integer) {
+        Ok(exp) => base.checked_powi(exp),
+        // Exponents that do not fit in an i64 only have a representable result for a base of
+        // magnitude 1, or when the result vanishes.
+        Err(_) => {
+            let magnitude = base.checked_abs()?;
+            if magnitude == PreciseDecimal::ONE {
+                let is_odd = exp_integer % I256::from(2) != I256::ZERO;
+                Some(if base.is_negative() && is_odd {
+                    -PreciseDecimal::ONE
+                } else {
+                    PreciseDecimal::ONE
+                })
+            } else if (magnitude > PreciseDecimal::ONE) == exp.is_positive() {
+                None
+            } else {
+                Some(PreciseDecimal::ZERO)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_pow {
+    use crate::exponential::{Exponential, MAX_EXP_ARGUMENT, PRECISE_BIGGEST_NON_OVERFLOW};
+    use crate::internal_prelude::*;
+    use crate::power::Power;
+    use crate::RELATIVE_PRECISION;
+    use radix_common_derive::{dec, pdec};
+
+    fn assert_close(value: Decimal, true_val: Decimal) {
+        let rel_prec = (true_val - value).checked_abs().unwrap() / true_val.checked_abs().unwrap();
+        assert!(rel_prec < RELATIVE_PRECISION);
+    }
+
+    #[test]
+    fn test_integer_exponent_is_exact() {
+        assert_eq!(dec!(2).pow(dec!(10)), dec!(1024));
+        assert_eq!(dec!("1.1").pow(dec!(2)), dec!("1.21"));
+        assert_eq!(dec!(10).pow(dec!(18)), dec!("1000000000000000000"));
+    }
+
+    #[test]
+    fn test_negative_integer_exponent() {
+        assert_eq!(dec!(2).pow(dec!(-2)), dec!("0.25"));
+        assert_eq!(dec!(10).pow(dec!(-18)), dec!("0.000000000000000001"));
+        assert_eq!(dec!(10).pow(dec!(-19)), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_negative_base() {
+        assert_eq!(dec!(-2).pow(dec!(3)), dec!(-8));
+        assert_eq!(dec!(-2).pow(dec!(4)), dec!(16));
+        assert_eq!(dec!(-2).pow(dec!(-1)), dec!("-0.5"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Negative numbers cannot be raised to a non-integer power")]
+    fn test_negative_base_fractional_exponent() {
+        let _m = dec!(-2).pow(dec!("0.5"));
+    }
+
+    #[test]
+    fn test_zero_base() {
+        assert_eq!(Decimal::ZERO.pow(Decimal::ZERO), Decimal::ONE);
+        assert_eq!(Decimal::ZERO.pow(dec!(3)), Decimal::ZERO);
+        assert_eq!(Decimal::ZERO.pow(dec!("0.5")), Decimal::ZERO);
+        assert_eq!(Decimal::ZERO.checked_pow(dec!(-1)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Zero cannot be raised to a negative power")]
+    fn test_zero_base_negative_exponent() {
+        let _m = Decimal::ZERO.pow(dec!(-1));
+    }
+
+    #[test]
+    fn test_fractional_exponent() {
+        assert_close(dec!(2).pow(dec!("0.5")), dec!("1.414213562373095048"));
+        assert_close(dec!(10).pow(dec!("2.5")), dec!("316.227766016837933199"));
+        assert_close(dec!("0.5").pow(dec!("-1.5")), dec!("2.828427124746190097"));
+    }
+
+    #[test]
+    fn test_huge_exponent() {
+        let huge = dec!("10000000000000000000000");
+        assert_eq!(Decimal::ONE.pow(huge), Decimal::ONE);
+        assert_eq!(dec!(-1).pow(dec!("10000000000000000001")), dec!(-1));
+        assert_eq!(dec!("0.5").pow(huge), Decimal::ZERO);
+        assert_eq!(dec!(2).checked_pow(huge), None);
+    }
+
+    #[test]
+    fn test_checked_pow_overflow() {
+        assert_eq!(dec!(10).checked_pow(dec!(40)), None);
+        assert_eq!(dec!(10).checked_pow(dec!("40.5")), None);
+        assert_eq!(Decimal::MAX.checked_pow(dec!(2)), None);
+        assert!(dec!("2.718281828459045235")
+            .checked_pow(dec!("90.5"))
+            .is_some());
+        assert_eq!(
+            dec!("2.718281828459045235").checked_pow(MAX_EXP_ARGUMENT + dec!("0.01")),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow")]
+    fn test_pow_overflow() {
+        let _m = dec!(10).pow(dec!(40));
+    }
+
+    fn assert_precise_close(value: PreciseDecimal, true_val: PreciseDecimal) {
+        let rel_prec = (true_val - value).checked_abs().unwrap() / true_val.checked_abs().unwrap();
+        assert!(rel_prec < PreciseDecimal::from(RELATIVE_PRECISION));
+    }
+
+    #[test]
+    fn test_precise_integer_exponent() {
+        assert_eq!(pdec!(2).pow(pdec!(10)), pdec!(1024));
+        assert_eq!(pdec!("1.1").pow(pdec!(2)), pdec!("1.21"));
+        assert_eq!(pdec!(2).pow(pdec!(-2)), pdec!("0.25"));
+        assert_eq!(pdec!(-2).pow(pdec!(3)), pdec!(-8));
+        assert_eq!(
+            pdec!(10).pow(pdec!(-36)),
+            pdec!("0.000000000000000000000000000000000001")
+        );
+    }
+
+    #[test]
+    fn test_precise_zero_base() {
+        assert_eq!(
+            PreciseDecimal::ZERO.pow(PreciseDecimal::ZERO),
+            PreciseDecimal::ONE
+        );
+        assert_eq!(PreciseDecimal::ZERO.pow(pdec!("0.5")), PreciseDecimal::ZERO);
+        assert_eq!(PreciseDecimal::ZERO.checked_pow(pdec!(-1)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Negative numbers cannot be raised to a non-integer power")]
+    fn test_precise_negative_base_fractional_exponent() {
+        let _m = pdec!(-2).pow(pdec!("0.5"));
+    }
+
+    #[test]
+    fn test_precise_fractional_exponent() {
+        assert_precise_close(
+            pdec!(2).pow(pdec!("0.5")),
+            pdec!("1.414213562373095048801688724209698078"),
+        );
+        assert_precise_close(
+            pdec!(10).pow(pdec!("2.5")),
+            pdec!("316.227766016837933199889354443271853371"),
+        );
+    }
+
+    #[test]
+    fn test_precise_huge_exponent() {
+        let huge = pdec!("10000000000000000000000");
+        assert_eq!(PreciseDecimal::ONE.pow(huge), PreciseDecimal::ONE);
+        assert_eq!(pdec!("0.5").pow(huge), PreciseDecimal::ZERO);
+        assert_eq!(pdec!(2).checked_pow(huge), None);
+    }
+
+    #[test]
+    fn test_precise_checked_pow_overflow() {
+        let e = PreciseDecimal::ONE.exp();
+        assert!(e.checked_pow(pdec!("93.5")).is_some());
+        assert_eq!(
+            e.checked_pow(PRECISE_BIGGEST_NON_OVERFLOW + pdec!("0.01")),
+            None
+        );
+        assert_eq!(pdec!(10).checked_pow(pdec!(42)), None);
+    }
+
+    #[test]
+    fn test_precise_agrees_with_decimal() {
+        for (base, exp) in [
+            (dec!(2), dec!("0.5")),
+            (dec!("1.05"), dec!("12.25")),
+            (dec!("0.5"), dec!("-1.5")),
+            (dec!(10), dec!("2.5")),
+            (dec!(3), dec!(7)),
+        ] {
+            assert_precise_close(
+                PreciseDecimal::from(base.pow(exp)),
+                PreciseDecimal::from(base).pow(PreciseDecimal::from(exp)),
+            );
+        }
     }
 }
@@ -0,0 +1,253 @@
+use crate::exponential::Exponential;
+use crate::internal_prelude::*;
+use crate::logarithm::Logarithm;
+
+pub trait Root: Sized {
+    fn sqrt(self) -> Self;
+    fn nth_root(self, n: u32) -> Self;
+}
+
+impl Root for Decimal {
+    /// Returns the square root of a [`Decimal`], rounded to the nearest 18 decimals number.
+    /// Panics if the number is negative.
+    fn sqrt(self) -> Self {
+        self.nth_root(2)
+    }
+
+    /// Returns the nth root of a [`Decimal`], rounded to the nearest 18 decimals number.
+    /// Panics if n is 0 or if n is even and the number is negative.
+    ///
+    /// Roots whose exact computation does not fit in 512 bits are evaluated as exp(ln(x) / n) with
+    /// 36 decimals before being rounded.
+    fn nth_root(self, n: u32) -> Self {
+        check_root_arguments(self.is_negative(), n);
+        match scaled_nth_root(I512::from(self.0), I512::from(Decimal::ONE.0), n) {
+            Some(root) => Decimal(I192::try_from(root).expect("Overflow")),
+            None => {
+                let root = root_from_ln(PreciseDecimal::from(self), n);
+                root.checked_truncate(RoundingMode::ToNearestMidpointAwayFromZero)
+                    .expect("Overflow")
+            }
+        }
+    }
+}
+
+impl Root for PreciseDecimal {
+    /// Returns the square root of a [`PreciseDecimal`], rounded to the nearest 36 decimals number.
+    /// Panics if the number is negative.
+    fn sqrt(self) -> Self {
+        self.nth_root(2)
+    }
+
+    /// Returns the nth root of a [`PreciseDecimal`], rounded to the nearest 36 decimals number.
+    /// Panics if n is 0 or if n is even and the number is negative.
+    ///
+    /// Roots whose exact computation does not fit in 512 bits are evaluated as exp(ln(x) / n), and
+    /// their last decimal can then be off by one.
+    fn nth_root(self, n: u32) -> Self {
+        check_root_arguments(self.is_negative(), n);
+        match scaled_nth_root(I512::from(self.0), I512::from(PreciseDecimal::ONE.0), n) {
+            Some(root) => PreciseDecimal(I256::try_from(root).expect("Overflow")),
+            None => root_from_ln(self, n),
+        }
+    }
+}
+
+fn check_root_arguments(negative: bool, n: u32) {
+    assert!(n != 0, "The 0th root is not defined");
+    assert!(
+        !negative || n % 2 == 1,
+        "Even roots are only defined for non-negative numbers"
+    );
+}
+
+/// Computes the nth root of a [`PreciseDecimal`] as exp(ln(x) / n), keeping the sign of x.
+fn root_from_ln(x: PreciseDecimal, n: u32) -> PreciseDecimal {
+    if x.is_zero() {
+        return x;
+    }
+    let root = (x.checked_abs().unwrap().ln() / PreciseDecimal::from(n)).exp();
+    if x.is_negative() {
+        -root
+    } else {
+        root
+    }
+}
+
+/// Computes the nth root of a fixed point number given by its integer representation and the
+/// representation of one, rounded to the nearest representable number, or returns `None` if the
+/// computation does not fit in an [`I512`].
+///
+/// If x = value / one, the representation of its nth root is the nth root of value * one^(n-1).
+fn scaled_nth_root(value: I512, one: I512, n: u32) -> Option<I512> {
+    let negative = value.is_negative();
+    let magnitude = value.abs();
+    let scaled = magnitude.checked_mul(checked_pow(one, n - 1)?)?;
+    let scaled_times_two_pow = scaled.checked_mul(checked_pow(I512::from(2), n)?)?;
+    let root = integer_nth_root(scaled, n);
+
+    // The exact root is above root + 1/2 if and only if (2 * root + 1)^n <= 2^n * scaled. An
+    // overflowing left hand side is above the right hand side, which fits.
+    let upper_half = root * I512::from(2) + I512::ONE;
+    let rounded = match checked_pow(upper_half, n) {
+        Some(power) if power <= scaled_times_two_pow => root + I512::ONE,
+        _ => root,
+    };
+
+    Some(if negative { -rounded } else { rounded })
+}
+
+/// Computes base^exp by exponentiation by squaring, or returns `None` if it overflows.
+fn checked_pow(mut base: I512, mut exp: u32) -> Option<I512> {
+    let mut result = I512::ONE;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.checked_mul(base)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = base.checked_mul(base)?;
+        }
+    }
+    Some(result)
+}
+
+/// Computes the floor of the nth root of a non-negative integer with Newton's method.
+///
+/// The iterations start above the root and the sequence x_{k+1} = ((n-1) * x_k + y / x_k^(n-1)) / n
+/// decreases until it reaches the floor of the root. The integer y is expected to be small enough
+/// for y * 2^n to fit in an [`I512`].
+fn integer_nth_root(y: I512, n: u32) -> I512 {
+    if y == I512::ZERO {
+        return I512::ZERO;
+    }
+
+    let bits = 512 - y.leading_zeros();
+    let n_big = I512::from(n);
+    let mut current = checked_pow(I512::from(2), bits.div_ceil(n)).expect("Overflow");
+    loop {
+        // current^(n-1) can only overflow when it is above y, the quotient then being zero.
+        let quotient = checked_pow(current, n - 1).map_or(I512::ZERO, |power| y / power);
+        let next = (current * I512::from(n - 1) + quotient) / n_big;
+        if next >= current {
+            return current;
+        }
+        current = next;
+    }
+}
+
+#[cfg(test)]
+mod test_root {
+    use crate::exponential::SMALLEST_NON_ZERO;
+    use crate::internal_prelude::*;
+    use crate::root::Root;
+    use crate::RELATIVE_PRECISION;
+    use radix_common_derive::{dec, pdec};
+
+    #[test]
+    fn test_sqrt_two() {
+        assert_eq!(dec!(2).sqrt(), dec!("1.414213562373095049"));
+        assert_eq!(
+            pdec!(2).sqrt(),
+            pdec!("1.414213562373095048801688724209698079")
+        );
+    }
+
+    #[test]
+    fn test_perfect_squares() {
+        assert_eq!(dec!(1024).sqrt(), dec!(32));
+        assert_eq!(dec!("0.25").sqrt(), dec!("0.5"));
+        assert_eq!(dec!("0.000000000000000001").sqrt(), dec!("0.000000001"));
+    }
+
+    #[test]
+    fn test_sqrt_zero() {
+        assert_eq!(Decimal::ZERO.sqrt(), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_sqrt_max() {
+        let true_val = dec!("56022770974786139918.731938227458171762");
+        let rel_prec = (true_val - Decimal::MAX.sqrt()).checked_abs().unwrap() / true_val;
+        assert!(rel_prec < RELATIVE_PRECISION);
+    }
+
+    #[test]
+    #[should_panic(expected = "Even roots are only defined for non-negative numbers")]
+    fn test_sqrt_neg() {
+        let _m = dec!(-4).sqrt();
+    }
+
+    #[test]
+    fn test_cbrt_two() {
+        assert_eq!(dec!(2).nth_root(3), dec!("1.259921049894873165"));
+    }
+
+    #[test]
+    fn test_fifth_root_ten() {
+        let true_val = dec!("1.584893192461113485");
+        let rel_prec = (true_val - dec!(10).nth_root(5)).checked_abs().unwrap() / true_val;
+        assert!(rel_prec < RELATIVE_PRECISION);
+    }
+
+    #[test]
+    fn test_odd_root_of_negative() {
+        assert_eq!(dec!(-8).nth_root(3), dec!(-2));
+    }
+
+    #[test]
+    fn test_nth_root_smallest_non_zero() {
+        let true_val = dec!("-3.460690264956226042");
+        let rel_prec = (true_val - SMALLEST_NON_ZERO.nth_root(3))
+            .checked_abs()
+            .unwrap()
+            / true_val.checked_abs().unwrap();
+        assert!(rel_prec < RELATIVE_PRECISION);
+    }
+
+    #[test]
+    fn test_nth_root_smallest_decimal() {
+        assert_eq!(Decimal(I192::ONE).nth_root(3), dec!("0.000001"));
+    }
+
+    #[test]
+    fn test_first_root() {
+        assert_eq!(dec!("42.42").nth_root(1), dec!("42.42"));
+    }
+
+    #[test]
+    #[should_panic(expected = "The 0th root is not defined")]
+    fn test_zeroth_root() {
+        let _m = dec!(2).nth_root(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Even roots are only defined for non-negative numbers")]
+    fn test_even_root_neg() {
+        let _m = dec!(-16).nth_root(4);
+    }
+
+    #[test]
+    fn test_large_root() {
+        assert_eq!(dec!(2).nth_root(1000), dec!("1.000693387462580633"));
+        assert_eq!(dec!(2).nth_root(u32::MAX), dec!("1.000000000161385904"));
+        assert_eq!(dec!(-2).nth_root(u32::MAX), dec!("-1.000000000161385904"));
+        assert_eq!(Decimal::ZERO.nth_root(u32::MAX), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_roots_of_max() {
+        assert_eq!(
+            Decimal::MAX.nth_root(3),
+            dec!("14641190473997.345813510937532904")
+        );
+        assert_eq!(Decimal::MAX.nth_root(7), dec!("438924.559231414425037749"));
+    }
+
+    #[test]
+    fn test_precise_large_root() {
+        let true_val = pdec!("1.389495494373137637129985217353011622");
+        let difference = (pdec!(10).nth_root(7) - true_val).checked_abs().unwrap();
+        assert!(difference <= PreciseDecimal(I256::ONE));
+    }
+}
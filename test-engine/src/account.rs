@@ -1,18 +1,47 @@
 use crate::engine_interface::EngineInterface;
 use crate::internal_prelude::*;
 
+/// What controls an account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountOwner {
+    /// Account controlled by the signature of its key.
+    Signature,
+    /// Securified account, controlled by a proof of its owner badge.
+    Badge(NonFungibleGlobalId),
+}
+
 #[derive(Debug, Clone)]
 pub struct Account {
     component_address: ComponentAddress,
-    public_key: Secp256k1PublicKey,
+    public_key: PublicKey,
+    owner: AccountOwner,
 }
 
 impl Account {
     pub fn new(engine_interface: &mut EngineInterface) -> Self {
         let (public_key, _, component_address) = engine_interface.new_account();
+        Self {
+            public_key: public_key.into(),
+            component_address,
+            owner: AccountOwner::Signature,
+        }
+    }
+
+    pub fn new_ed25519(engine_interface: &mut EngineInterface) -> Self {
+        let (public_key, _, component_address) = engine_interface.new_ed25519_account();
+        Self {
+            public_key: public_key.into(),
+            component_address,
+            owner: AccountOwner::Signature,
+        }
+    }
+
+    pub fn with_public_key(engine_interface: &mut EngineInterface, public_key: PublicKey) -> Self {
+        let component_address = engine_interface.new_account_with_public_key(public_key);
         Self {
             public_key,
             component_address,
+            owner: AccountOwner::Signature,
         }
     }
 
@@ -20,11 +49,24 @@ impl Account {
         &self.component_address
     }
 
+    /// Returns the proof controlling the account: the signature of its key, or its owner badge
+    /// once the account is securified.
     pub fn proof(&self) -> NonFungibleGlobalId {
-        NonFungibleGlobalId::from_public_key(&self.public_key)
+        match &self.owner {
+            AccountOwner::Signature => NonFungibleGlobalId::from_public_key(&self.public_key),
+            AccountOwner::Badge(badge) => badge.clone(),
+        }
+    }
+
+    pub fn owner(&self) -> &AccountOwner {
+        &self.owner
+    }
+
+    pub fn securify(&mut self, owner_badge: NonFungibleGlobalId) {
+        self.owner = AccountOwner::Badge(owner_badge);
     }
 
     pub fn public_key(&self) -> PublicKey {
-        PublicKey::from(self.public_key)
+        self.public_key
     }
 }
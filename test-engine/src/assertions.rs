@@ -0,0 +1,255 @@
+use std::fmt::{Display, Formatter};
+
+use crate::internal_prelude::*;
+use crate::references::{ComponentReference, ResourceReference};
+use crate::test_engine::TestEngine;
+
+/// Checks that a decimal is within a tolerance of an expected value, both bounds included.
+/// Returns a message with the absolute and relative differences otherwise.
+///
+/// # Arguments
+/// * `actual`: checked decimal.
+/// * `expected`: expected value.
+/// * `tolerance`: maximum absolute difference between the two values.
+pub fn check_dec_approx_eq<A, E, T>(actual: A, expected: E, tolerance: T) -> Result<(), String>
+where
+    A: TryInto<Decimal>,
+    E: TryInto<Decimal>,
+    T: TryInto<Decimal>,
+    <A as TryInto<Decimal>>::Error: std::fmt::Debug,
+    <E as TryInto<Decimal>>::Error: std::fmt::Debug,
+    <T as TryInto<Decimal>>::Error: std::fmt::Debug,
+{
+    let actual = actual.try_into().unwrap();
+    let expected = expected.try_into().unwrap();
+    let tolerance = tolerance.try_into().unwrap();
+    if tolerance.is_negative() {
+        panic!("Negative tolerance {}", tolerance);
+    }
+
+    let difference = (actual - expected).checked_abs().unwrap();
+    if difference <= tolerance {
+        return Ok(());
+    }
+    Err(format!(
+        "assertion `actual ≈ expected` failed\n    actual: {}\n  expected: {}\ndifference: {}{}\n tolerance: {}",
+        actual,
+        expected,
+        difference,
+        relative_difference(difference, expected),
+        tolerance
+    ))
+}
+
+/// Checks that the balance of an entity in a resource is equal to an expected value. Returns a
+/// message with the reference names of the entity and the resource otherwise.
+///
+/// # Arguments
+/// * `test_engine`: engine holding the entity.
+/// * `entity`: reference name or address of the entity.
+/// * `resource`: reference name or address of the resource.
+/// * `expected`: expected balance.
+pub fn check_balance<C: ComponentReference, R: ResourceReference, D: TryInto<Decimal>>(
+    test_engine: &mut TestEngine,
+    entity: C,
+    resource: R,
+    expected: D,
+) -> Result<(), String>
+where
+    <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+{
+    let entity = entity.address(test_engine);
+    let resource = resource.address(test_engine);
+    let expected = expected.try_into().unwrap();
+    let actual = test_engine.balance_of(entity, resource);
+    if actual == expected {
+        return Ok(());
+    }
+
+    let names = test_engine.display_names_by_node_id();
+    let name = |node_id: NodeId| {
+        names
+            .get(&node_id)
+            .cloned()
+            .unwrap_or_else(|| format!("{:?}", node_id))
+    };
+    Err(format!(
+        "Balance of {} in {} is {}, expected {}\ndifference: {}{}",
+        name(entity.into_node_id()),
+        name(resource.into_node_id()),
+        actual,
+        expected,
+        actual - expected,
+        relative_difference((actual - expected).checked_abs().unwrap(), expected)
+    ))
+}
+
+fn relative_difference(difference: Decimal, expected: Decimal) -> String {
+    if expected.is_zero() {
+        return String::new();
+    }
+    let percentage = difference / expected.checked_abs().unwrap() * dec!(100);
+    format!(
+        " ({}% of expected)",
+        DisplayAmount::new(percentage).decimals(4)
+    )
+}
+
+/// Decimal displayed with thousands separators, for log output:
+///
+/// ```ignore
+/// let amount = DisplayAmount::new(dec!("-1234567.891")).decimals(2);
+/// assert_eq!(amount.to_string(), "-1,234,567.89");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayAmount {
+    amount: Decimal,
+    decimals: Option<u8>,
+}
+
+impl DisplayAmount {
+    /// Returns an amount displayed with all its significant decimals.
+    ///
+    /// # Arguments
+    /// * `amount`: displayed amount.
+    pub fn new<D: TryInto<Decimal>>(amount: D) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        Self {
+            amount: amount.try_into().unwrap(),
+            decimals: None,
+        }
+    }
+
+    /// Sets the number of shown decimals. The amount is rounded to the nearest value, midpoints
+    /// away from zero, and padded with zeros.
+    ///
+    /// # Arguments
+    /// * `decimals`: number of shown decimals, at most 18.
+    pub fn decimals(mut self, decimals: u8) -> Self {
+        if decimals > 18 {
+            panic!("Cannot show {} decimals, a Decimal has 18", decimals);
+        }
+        self.decimals = Some(decimals);
+        self
+    }
+}
+
+impl Display for DisplayAmount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let amount = match self.decimals {
+            Some(decimals) => self
+                .amount
+                .checked_round(decimals, RoundingMode::ToNearestMidpointAwayFromZero)
+                .unwrap(),
+            None => self.amount,
+        };
+        let formatted = amount.to_string();
+        let (sign, digits) = match formatted.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", formatted.as_str()),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+
+        let mut grouped = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+
+        let fraction = match self.decimals {
+            Some(decimals) => format!("{:0<width$}", fraction, width = decimals as usize),
+            None => fraction.to_string(),
+        };
+        if fraction.is_empty() {
+            write!(f, "{}{}", sign, grouped)
+        } else {
+            write!(f, "{}{}.{}", sign, grouped, fraction)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_approx_eq_within_tolerance() {
+        assert!(check_dec_approx_eq(dec!("99.99"), 100, dec!("0.1")).is_ok());
+        assert!(check_dec_approx_eq(dec!("100.05"), 100, dec!("0.1")).is_ok());
+        assert!(check_dec_approx_eq(100, 100, 0).is_ok());
+    }
+
+    #[test]
+    fn test_approx_eq_tolerance_boundary() {
+        assert!(check_dec_approx_eq(dec!("100.1"), 100, dec!("0.1")).is_ok());
+        assert!(check_dec_approx_eq(dec!("99.9"), 100, dec!("0.1")).is_ok());
+        assert!(check_dec_approx_eq(dec!("100.100000000000000001"), 100, dec!("0.1")).is_err());
+        assert!(check_dec_approx_eq(dec!("99.899999999999999999"), 100, dec!("0.1")).is_err());
+    }
+
+    #[test]
+    fn test_approx_eq_negative_amounts() {
+        assert!(check_dec_approx_eq(dec!("-100.05"), -100, dec!("0.1")).is_ok());
+        assert!(check_dec_approx_eq(dec!("-0.05"), dec!("0.05"), dec!("0.1")).is_ok());
+        assert!(check_dec_approx_eq(dec!("-0.1"), dec!("0.1"), dec!("0.1")).is_err());
+    }
+
+    #[test]
+    fn test_approx_eq_message() {
+        let message = check_dec_approx_eq(dec!("99"), -100, dec!("0.1")).unwrap_err();
+        assert!(message.contains("actual: 99\n"));
+        assert!(message.contains("expected: -100\n"));
+        assert!(message.contains("difference: 199 (199.0000% of expected)"));
+
+        let message = check_dec_approx_eq(1, 0, 0).unwrap_err();
+        assert!(message.contains("difference: 1\n"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Negative tolerance -1")]
+    fn test_approx_eq_negative_tolerance() {
+        let _ = check_dec_approx_eq(1, 1, -1);
+    }
+
+    #[test]
+    fn test_display_amount() {
+        assert_eq!(DisplayAmount::new(0).to_string(), "0");
+        assert_eq!(DisplayAmount::new(999).to_string(), "999");
+        assert_eq!(DisplayAmount::new(1000).to_string(), "1,000");
+        assert_eq!(
+            DisplayAmount::new(dec!("1234567.891")).to_string(),
+            "1,234,567.891"
+        );
+        assert_eq!(
+            DisplayAmount::new(dec!("-1234567.891")).to_string(),
+            "-1,234,567.891"
+        );
+        assert_eq!(DisplayAmount::new(dec!("-123.4")).to_string(), "-123.4");
+    }
+
+    #[test]
+    fn test_display_amount_decimals() {
+        let amount = dec!("1234567.895");
+        assert_eq!(
+            DisplayAmount::new(amount).decimals(2).to_string(),
+            "1,234,567.90"
+        );
+        assert_eq!(
+            DisplayAmount::new(amount).decimals(0).to_string(),
+            "1,234,568"
+        );
+        assert_eq!(
+            DisplayAmount::new(-amount).decimals(2).to_string(),
+            "-1,234,567.90"
+        );
+        assert_eq!(DisplayAmount::new(12).decimals(3).to_string(), "12.000");
+        assert_eq!(
+            DisplayAmount::new(dec!("-0.004")).decimals(2).to_string(),
+            "0.00"
+        );
+    }
+}
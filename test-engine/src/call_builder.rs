@@ -1,14 +1,72 @@
-use std::collections::BTreeSet;
+use std::cmp::min;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
 use std::vec::Vec;
 
+use radix_engine::object_modules::metadata::SetMetadataEvent;
+use radix_transactions::manifest::decompiler::decompile_with_known_naming;
+
 use crate::account::Account;
+use crate::engine_interface::ExecutionOptions;
 use crate::environment::{EnvironmentEncode, Fungible, NonFungible};
 use crate::internal_prelude::*;
+use crate::logger::TransactionContext;
+use crate::manifest_layout::{InjectionSummary, LayoutDiff};
 use crate::method_call::SimpleMethodCaller;
-use crate::references::{ComponentReference, GlobalReference, ReferenceName, ResourceReference};
+use crate::references::{
+    ComponentReference, GlobalReference, PackageReference, ReferenceName, ResourceReference,
+};
 use crate::test_engine::TestEngine;
 use crate::to_id::ToId;
 
+/// Buckets and proofs already consumed by an instruction of the manifest.
+#[derive(Default)]
+struct ConsumedObjects {
+    buckets: HashSet<ManifestBucket>,
+    proofs: HashSet<ManifestProof>,
+}
+
+impl ConsumedObjects {
+    /// Marks the buckets and proofs passed as arguments of an instruction as consumed.
+    /// Panics if one of them has already been consumed by a previous instruction.
+    fn consume(&mut self, names: &ManifestObjectNames, args: &ManifestValue) {
+        let mut buckets = vec![];
+        let mut proofs = vec![];
+        collect_buckets_and_proofs(args, &mut buckets, &mut proofs);
+
+        for bucket in buckets {
+            if !self.buckets.insert(bucket) {
+                panic!(
+                    "Bucket \"{}\" has already been consumed by a previous instruction",
+                    names
+                        .bucket_names
+                        .get(&bucket)
+                        .cloned()
+                        .unwrap_or_else(|| format!("{:?}", bucket))
+                );
+            }
+        }
+        for proof in proofs {
+            if !self.proofs.insert(proof) {
+                panic!(
+                    "Proof \"{}\" has already been consumed by a previous instruction",
+                    names
+                        .proof_names
+                        .get(&proof)
+                        .cloned()
+                        .unwrap_or_else(|| format!("{:?}", proof))
+                );
+            }
+        }
+    }
+}
+
+/// Prefix of the metadata keys used to retrieve the addresses allocated in a transaction.
+const ALLOCATED_ADDRESS_METADATA_KEY: &str = "test_engine_allocated_address";
+
+#[derive(Clone)]
 struct TransactionManifestData {
     transaction_manifest: TransactionManifestV1,
     object_names: ManifestObjectNames,
@@ -17,14 +75,30 @@ struct TransactionManifestData {
 pub struct CallBuilder<'a> {
     caller: Account,
     manifest_builder: ManifestBuilder,
-    fee_payer: ComponentAddress,
+    fee_payer: Option<ComponentAddress>,
     fee_locked: Decimal,
     test_engine: &'a mut TestEngine,
     output_manifest: Option<(String, String)>,
+    output_reference_names: bool,
     admin_badge: Vec<(ResourceAddress, Option<BTreeSet<NonFungibleLocalId>>)>,
+    /// Non-fungible ids of badges owned by the accounts of the call, read once per call.
+    owned_badge_ids: HashMap<(ComponentAddress, ResourceAddress), BTreeSet<NonFungibleLocalId>>,
     with_trace: bool,
+    execution_trace: bool,
     deposit_destination: ComponentAddress,
     manifest_data: Option<TransactionManifestData>,
+    wallet_layout: bool,
+    raw: bool,
+    additional_signers: Vec<NonFungibleGlobalId>,
+    has_instructions: bool,
+    fee_budget: Option<Decimal>,
+    cost_unit_limit: Option<u32>,
+    execution_timeout: Option<Duration>,
+    consumed_objects: ConsumedObjects,
+    validate_calls: bool,
+    allocated_addresses: Vec<(String, ManifestAddress)>,
+    log_title: Option<String>,
+    log_tx_fee: bool,
 }
 
 impl<'a> CallBuilder<'a> {
@@ -35,13 +109,28 @@ impl<'a> CallBuilder<'a> {
             deposit_destination: *caller.address(),
             caller,
             manifest_builder: ManifestBuilder::new(),
-            fee_payer: FAUCET,
+            fee_payer: None,
             fee_locked: dec!(5000),
             test_engine,
             output_manifest: None,
+            output_reference_names: false,
             admin_badge: vec![],
+            owned_badge_ids: HashMap::new(),
             with_trace: false,
+            execution_trace: false,
             manifest_data: None,
+            wallet_layout: false,
+            raw: false,
+            additional_signers: vec![],
+            has_instructions: false,
+            fee_budget: None,
+            cost_unit_limit: None,
+            execution_timeout: None,
+            consumed_objects: ConsumedObjects::default(),
+            validate_calls: true,
+            allocated_addresses: vec![],
+            log_title: None,
+            log_tx_fee: false,
         }
     }
 
@@ -71,6 +160,25 @@ impl<'a> CallBuilder<'a> {
         self.call_method_internal(component, method_name, args)
     }
 
+    /// Creates a call builder for a function call of the given package and skip the transaction
+    /// execution.
+    ///
+    /// # Arguments
+    /// * `package`: reference name or address of the package.
+    /// * `blueprint_name`: name of the blueprint.
+    /// * `function_name`: name of the function.
+    /// * `args`: environment arguments to call the function.
+    pub fn call_from_package<P: PackageReference>(
+        self,
+        package: P,
+        blueprint_name: &str,
+        function_name: &str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+    ) -> Self {
+        let package = package.address(self.test_engine);
+        self.call_function_internal(package, blueprint_name, function_name, args)
+    }
+
     /// Sets the current component.
     ///
     /// # Arguments
@@ -82,27 +190,74 @@ impl<'a> CallBuilder<'a> {
 
     /// Executes the call.
     pub fn execute(mut self) -> TransactionReceipt {
-        self.manifest_data = Some(TransactionManifestData {
-            object_names: self.manifest_builder.object_names().clone(),
-            transaction_manifest: self.manifest_builder.build(),
-        });
-
-        self.manifest_builder = ManifestBuilder::new();
-
-        self.write_lock();
-        self.write_deposit();
-        self.write_badge();
-        self.output_manifest();
+        let with_update = !self.raw;
+        self.execute_internal(with_update)
+    }
 
-        let receipt = self.test_engine.execute_call(
-            self.manifest_data.unwrap().transaction_manifest,
-            self.with_trace,
-            vec![self.caller.proof()],
-            true,
+    /// Executes the call with an execution trace and asserts that the balance of an entity
+    /// changed by the given amount.
+    /// For a non-fungible resource, use [`CallBuilder::execute_expect_non_fungible_change`].
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the account or component.
+    /// * `resource`: reference name or address of the resource.
+    /// * `amount`: expected balance change.
+    pub fn execute_expect_balance_change<
+        E: ComponentReference,
+        R: ResourceReference,
+        D: TryInto<Decimal>,
+    >(
+        mut self,
+        entity: E,
+        resource: R,
+        amount: D,
+    ) -> TransactionReceipt
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let with_update = !self.raw;
+        self.execution_trace = true;
+        let receipt = self.execute_internal(with_update);
+        self.test_engine.assert_balance_change(
+            &receipt,
+            entity,
+            resource,
+            BalanceChange::Fungible(amount.try_into().unwrap()),
         );
+        receipt
+    }
 
-        Self::output_logs(&receipt);
-
+    /// Executes the call with an execution trace and asserts that the given non-fungibles were
+    /// added to and removed from an entity.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the account or component.
+    /// * `resource`: reference name or address of the non-fungible resource.
+    /// * `added`: ids expected to be added.
+    /// * `removed`: ids expected to be removed.
+    pub fn execute_expect_non_fungible_change<
+        E: ComponentReference,
+        R: ResourceReference,
+        T: ToId,
+    >(
+        mut self,
+        entity: E,
+        resource: R,
+        added: Vec<T>,
+        removed: Vec<T>,
+    ) -> TransactionReceipt {
+        let with_update = !self.raw;
+        self.execution_trace = true;
+        let receipt = self.execute_internal(with_update);
+        self.test_engine.assert_balance_change(
+            &receipt,
+            entity,
+            resource,
+            BalanceChange::NonFungible {
+                added: added.into_iter().map(|id| id.to_id()).collect(),
+                removed: removed.into_iter().map(|id| id.to_id()).collect(),
+            },
+        );
         receipt
     }
 
@@ -128,7 +283,7 @@ impl<'a> CallBuilder<'a> {
     where
         <D as TryInto<Decimal>>::Error: std::fmt::Debug,
     {
-        self.fee_payer = locker.address(self.test_engine);
+        self.fee_payer = Some(locker.address(self.test_engine));
         self.fee_locked = amount.try_into().unwrap();
         self
     }
@@ -187,6 +342,164 @@ impl<'a> CallBuilder<'a> {
         )
     }
 
+    /// Transfers fungible resources from the caller's account to the given recipient with
+    /// `try_deposit_or_refund`, so that the resources come back to the caller if the deposit rules
+    /// of the recipient reject them.
+    ///
+    /// # Arguments
+    /// * `recipient`: resources to transfer to.
+    /// * `resource`: reference name of the resource to transfer.
+    /// * `amount`: amount of resources to transfer.
+    pub fn transfer_or_refund<
+        E: ReferenceName,
+        R: ReferenceName + Clone + 'static,
+        D: TryInto<Decimal> + Clone + 'static,
+    >(
+        self,
+        recipient: E,
+        resource: R,
+        amount: D,
+    ) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        self.call_from_component(
+            recipient,
+            "try_deposit_or_refund",
+            vec![
+                Box::new(Fungible::Bucket(resource.clone(), amount)),
+                Box::new(None::<u64>),
+            ],
+        )
+    }
+
+    /// Transfers fungible resources from the caller's account to the given recipient as an
+    /// authorized depositor: a proof of the badge is created and the badge is given to the
+    /// recipient, which accepts the deposit if the badge is one of its authorized depositors.
+    ///
+    /// # Arguments
+    /// * `recipient`: resources to transfer to.
+    /// * `resource`: reference name of the resource to transfer.
+    /// * `amount`: amount of resources to transfer.
+    /// * `badge`: reference name or address of the authorized depositor badge.
+    pub fn transfer_with_authorized_badge<
+        E: ReferenceName,
+        R: ReferenceName + Clone + 'static,
+        D: TryInto<Decimal> + Clone + 'static,
+        B: ResourceReference,
+    >(
+        self,
+        recipient: E,
+        resource: R,
+        amount: D,
+        badge: B,
+    ) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let badge = badge.address(self.test_engine);
+        self.call_from_component(
+            recipient,
+            "try_deposit_or_abort",
+            vec![
+                Box::new(Fungible::Bucket(resource.clone(), amount)),
+                Box::new(Some(ResourceOrNonFungible::Resource(badge))),
+            ],
+        )
+        .with_badge(badge)
+    }
+
+    /// Transfers fungible resources from the caller's account to several recipients. The summed
+    /// amount is withdrawn once and each recipient gets its share with `try_deposit_or_abort`, so
+    /// that the whole airdrop fails if one of the recipients refuses the deposit.
+    /// Panics if there is no recipient.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource to transfer.
+    /// * `recipients`: reference names of the recipients with the amount they receive.
+    pub fn airdrop<R: ResourceReference, E: ReferenceName, D: TryInto<Decimal>>(
+        self,
+        resource: R,
+        recipients: Vec<(E, D)>,
+    ) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        if recipients.is_empty() {
+            panic!("An airdrop needs at least one recipient");
+        }
+        let resource_address = resource.address(self.test_engine);
+        let recipients: Vec<(E, Decimal)> = recipients
+            .into_iter()
+            .map(|(recipient, amount)| (recipient, amount.try_into().unwrap()))
+            .collect();
+        let total = recipients
+            .iter()
+            .fold(Decimal::zero(), |total, (_, amount)| total + *amount);
+
+        recipients.into_iter().fold(
+            self.withdraw(resource_address, total),
+            |call_builder, (recipient, amount)| {
+                call_builder.call_from_component(
+                    recipient,
+                    "try_deposit_or_abort",
+                    vec![
+                        Box::new(Fungible::BucketFromWorkTop(resource_address, amount)),
+                        Box::new(None::<u64>),
+                    ],
+                )
+            },
+        )
+    }
+
+    /// Transfers non-fungible resources from the caller's account to several recipients. All the
+    /// ids are withdrawn once and each recipient gets its ids with `try_deposit_or_abort`, so that
+    /// the whole airdrop fails if one of the recipients refuses the deposit.
+    /// Panics if there is no recipient.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource to transfer.
+    /// * `recipients`: reference names of the recipients with the ids they receive.
+    pub fn airdrop_non_fungibles<R: ResourceReference, E: ReferenceName, T: ToId>(
+        mut self,
+        resource: R,
+        recipients: Vec<(E, Vec<T>)>,
+    ) -> Self {
+        if recipients.is_empty() {
+            panic!("An airdrop needs at least one recipient");
+        }
+        let resource_address = resource.address(self.test_engine);
+        let recipients: Vec<(E, Vec<NonFungibleLocalId>)> = recipients
+            .into_iter()
+            .map(|(recipient, ids)| (recipient, ids.into_iter().map(|id| id.to_id()).collect()))
+            .collect();
+        let all_ids: Vec<NonFungibleLocalId> = recipients
+            .iter()
+            .flat_map(|(_, ids)| ids.iter().cloned())
+            .collect();
+
+        let account = *self.caller.address();
+        self.has_instructions = true;
+        self.manifest_builder = self.manifest_builder.call_method(
+            account,
+            "withdraw_non_fungibles",
+            manifest_args!(resource_address, all_ids),
+        );
+
+        recipients
+            .into_iter()
+            .fold(self, |call_builder, (recipient, ids)| {
+                call_builder.call_from_component(
+                    recipient,
+                    "try_deposit_or_abort",
+                    vec![
+                        Box::new(NonFungible::BucketFromWorktop(resource_address, ids)),
+                        Box::new(None::<u64>),
+                    ],
+                )
+            })
+    }
+
     /// Outputs the manifest to the given path.
     ///
     /// # Arguments
@@ -197,7 +510,21 @@ impl<'a> CallBuilder<'a> {
         self
     }
 
-    /// Calls the method with the given admin badge.
+    /// Outputs the manifest to the given path, with the addresses of the entities of the engine
+    /// replaced by their reference name (e.g. `{account:default}` or `{resource:gum}`), so that
+    /// the outputted manifest does not depend on the addresses assigned to the entities.
+    ///
+    /// # Arguments
+    /// * `path`: path where to output the manifest.
+    /// * `name`: name of the outputted file.
+    pub fn output_with_reference_names(self, path: impl ToString, name: impl ToString) -> Self {
+        let mut call_builder = self.output(path, name);
+        call_builder.output_reference_names = true;
+        call_builder
+    }
+
+    /// Calls the method with the given admin badge. For a non-fungible badge, the proof contains
+    /// the smallest id owned by the caller.
     ///
     /// # Arguments
     /// * `badge_name` : reference name of the resource used as admin badge.
@@ -207,8 +534,9 @@ impl<'a> CallBuilder<'a> {
             None
         } else {
             Some(
-                self.test_engine
-                    .ids_owned_at_address(resource)
+                self.owned_badge_ids(resource)
+                    .first()
+                    .cloned()
                     .into_iter()
                     .collect(),
             )
@@ -218,12 +546,89 @@ impl<'a> CallBuilder<'a> {
         self
     }
 
+    /// Calls the method with a proof of the given ids of a non-fungible admin badge. The ids
+    /// owned by the caller are not read from the ledger.
+    ///
+    /// # Arguments
+    /// * `badge`: reference name of the non-fungible resource used as admin badge.
+    /// * `ids`: ids of the non-fungibles to prove.
+    pub fn with_badge_ids<R: ResourceReference, T: ToId>(mut self, badge: R, ids: Vec<T>) -> Self {
+        let resource = badge.address(self.test_engine);
+        if resource.is_fungible() {
+            panic!("Cannot prove ids of a fungible badge, use with_badge instead");
+        }
+
+        let ids_tree = ids.into_iter().map(|id| id.to_id()).collect();
+        self.admin_badge.push((resource, Some(ids_tree)));
+        self
+    }
+
+    /// Calls the method with a proof of all the ids of a non-fungible admin badge owned by the
+    /// caller.
+    ///
+    /// # Arguments
+    /// * `badge`: reference name of the non-fungible resource used as admin badge.
+    pub fn with_badge_all_ids<R: ResourceReference>(mut self, badge: R) -> Self {
+        let resource = badge.address(self.test_engine);
+        if resource.is_fungible() {
+            panic!("Cannot prove ids of a fungible badge, use with_badge instead");
+        }
+
+        let ids_tree = self.owned_badge_ids(resource).clone();
+        self.admin_badge.push((resource, Some(ids_tree)));
+        self
+    }
+
+    /// Returns the ids of a non-fungible badge owned by the caller, reading them from the ledger
+    /// only the first time they are needed in the call.
+    fn owned_badge_ids(&mut self, resource: ResourceAddress) -> &BTreeSet<NonFungibleLocalId> {
+        let account = *self.caller.address();
+        let test_engine = &mut self.test_engine;
+        self.owned_badge_ids
+            .entry((account, resource))
+            .or_insert_with(|| {
+                test_engine
+                    .ids_owned_at_address(account, resource)
+                    .into_iter()
+                    .collect()
+            })
+    }
+
+    /// Signs the transaction with the given account in addition to the caller, so that its proof
+    /// is in the initial auth zone.
+    ///
+    /// # Arguments
+    /// * `name`: reference name of the signing account.
+    pub fn with_additional_signer<N: ReferenceName>(mut self, name: N) -> Self {
+        let account = self.test_engine.account(name).clone();
+        self.additional_signers
+            .extend(self.test_engine.signer_proof(&account));
+        self
+    }
+
+    /// Makes the call from the given account instead of the current one, without changing the
+    /// current account: resources are withdrawn from and deposited to this account and the
+    /// transaction is signed by it.
+    /// Panics if instructions were already added to the call.
+    ///
+    /// # Arguments
+    /// * `name`: reference name of the calling account.
+    pub fn with_caller<N: ReferenceName>(mut self, name: N) -> Self {
+        if self.has_instructions {
+            panic!("The caller must be set before adding instructions to the call");
+        }
+        self.caller = self.test_engine.account(name).clone();
+        self.deposit_destination = *self.caller.address();
+        self
+    }
+
     /// Withdraws resource from an account
     pub fn withdraw<R: ResourceReference>(mut self, resource: R, amount: Decimal) -> Self {
-        let account = self.test_engine.current_account().address();
+        let account = *self.caller.address();
         let resource_address = resource.address(self.test_engine);
+        self.has_instructions = true;
         self.manifest_builder = self.manifest_builder.call_method(
-            *account,
+            account,
             "withdraw",
             manifest_args!(resource_address, amount),
         );
@@ -231,6 +636,480 @@ impl<'a> CallBuilder<'a> {
         self
     }
 
+    /// Sets a metadata value of an entity.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `key`: metadata key.
+    /// * `value`: new metadata value.
+    pub fn set_metadata<G: GlobalReference>(
+        mut self,
+        entity: G,
+        key: &str,
+        value: MetadataValue,
+    ) -> Self {
+        let address = entity.address(self.test_engine);
+        self.has_instructions = true;
+        self.manifest_builder = self.manifest_builder.set_metadata(address, key, value);
+        self
+    }
+
+    /// Sets the owner role of an entity.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `rule`: new owner rule.
+    pub fn set_owner_role<G: GlobalReference>(mut self, entity: G, rule: AccessRule) -> Self {
+        let address = entity.address(self.test_engine);
+        self.has_instructions = true;
+        self.manifest_builder = self.manifest_builder.set_owner_role(address, rule);
+        self
+    }
+
+    /// Sets the rule of a role of the main module of an entity.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `role_name`: name of the role.
+    /// * `rule`: new rule of the role.
+    pub fn set_role<G: GlobalReference>(
+        mut self,
+        entity: G,
+        role_name: &str,
+        rule: AccessRule,
+    ) -> Self {
+        let address = entity.address(self.test_engine);
+        self.has_instructions = true;
+        self.manifest_builder = self
+            .manifest_builder
+            .set_main_role(address, role_name, rule);
+        self
+    }
+
+    /// Sets the royalty of a method of a component.
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component.
+    /// * `method`: name of the method.
+    /// * `amount`: new royalty of the method.
+    pub fn set_component_royalty<C: ComponentReference>(
+        mut self,
+        component: C,
+        method: &str,
+        amount: RoyaltyAmount,
+    ) -> Self {
+        let address = component.address(self.test_engine);
+        self.has_instructions = true;
+        self.manifest_builder = self
+            .manifest_builder
+            .set_component_royalty(address, method, amount);
+        self
+    }
+
+    /// Claims the royalties accumulated by a component.
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component.
+    pub fn claim_component_royalties<C: ComponentReference>(mut self, component: C) -> Self {
+        let address = component.address(self.test_engine);
+        self.has_instructions = true;
+        self.manifest_builder = self.manifest_builder.claim_component_royalties(address);
+        self
+    }
+
+    /// Locks the owner role of an entity, so that it cannot be updated anymore.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    pub fn lock_owner_role<G: GlobalReference>(mut self, entity: G) -> Self {
+        let address = entity.address(self.test_engine);
+        self.has_instructions = true;
+        self.manifest_builder = self.manifest_builder.lock_owner_role(address);
+        self
+    }
+
+    /// Freezes withdrawals, deposits and burns of a resource in every vault of a component.
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component or account.
+    /// * `resource`: reference name or address of the resource.
+    pub fn freeze_vault<C: ComponentReference, R: ResourceReference>(
+        self,
+        component: C,
+        resource: R,
+    ) -> Self {
+        self.call_vaults(
+            component,
+            resource,
+            VAULT_FREEZE_IDENT,
+            VaultFreezeFlags::all(),
+        )
+    }
+
+    /// Unfreezes withdrawals, deposits and burns of a resource in every vault of a component.
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component or account.
+    /// * `resource`: reference name or address of the resource.
+    pub fn unfreeze_vault<C: ComponentReference, R: ResourceReference>(
+        self,
+        component: C,
+        resource: R,
+    ) -> Self {
+        self.call_vaults(
+            component,
+            resource,
+            VAULT_UNFREEZE_IDENT,
+            VaultFreezeFlags::all(),
+        )
+    }
+
+    /// Recalls an amount of a resource from the vaults of a component to the worktop.
+    /// Panics if the vaults of the component do not hold enough of the resource.
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component or account.
+    /// * `resource`: reference name or address of the resource.
+    /// * `amount`: amount to recall.
+    pub fn recall<C: ComponentReference, R: ResourceReference, D: TryInto<Decimal>>(
+        mut self,
+        component: C,
+        resource: R,
+        amount: D,
+    ) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let component = component.address(self.test_engine);
+        let resource = resource.address(self.test_engine);
+        let amount = amount.try_into().unwrap();
+
+        let vaults = self.test_engine.vaults_with_balances(component, resource);
+        let total = vaults
+            .iter()
+            .fold(Decimal::ZERO, |total, (_, balance)| total + *balance);
+        if total < amount {
+            panic!(
+                "Cannot recall {} of the resource: the component only holds {}",
+                amount, total
+            );
+        }
+
+        let mut remaining = amount;
+        for (vault_id, balance) in vaults {
+            if remaining.is_zero() {
+                break;
+            }
+            let recalled = min(balance, remaining);
+            if recalled.is_zero() {
+                continue;
+            }
+            remaining -= recalled;
+            self.manifest_builder = self
+                .manifest_builder
+                .recall(InternalAddress::new_or_panic(vault_id.0), recalled);
+        }
+        self.has_instructions = true;
+        self
+    }
+
+    fn call_vaults<C: ComponentReference, R: ResourceReference>(
+        mut self,
+        component: C,
+        resource: R,
+        method_name: &str,
+        flags: VaultFreezeFlags,
+    ) -> Self {
+        let component = component.address(self.test_engine);
+        let resource = resource.address(self.test_engine);
+        let vaults = self.test_engine.vaults_with_balances(component, resource);
+        if vaults.is_empty() {
+            panic!("The component has no vault of the resource");
+        }
+        for (vault_id, _) in vaults {
+            self.manifest_builder = self.manifest_builder.call_direct_access_method(
+                InternalAddress::new_or_panic(vault_id.0),
+                method_name,
+                manifest_args!(flags),
+            );
+        }
+        self.has_instructions = true;
+        self
+    }
+
+    /// Asserts that the worktop contains at least the given amount of a resource at this point of
+    /// the manifest.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource.
+    /// * `amount`: minimum amount expected on the worktop.
+    pub fn assert_worktop_contains<R: ResourceReference, D: TryInto<Decimal>>(
+        mut self,
+        resource: R,
+        amount: D,
+    ) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let resource_address = resource.address(self.test_engine);
+        self.has_instructions = true;
+        self.manifest_builder = self
+            .manifest_builder
+            .assert_worktop_contains(resource_address, amount.try_into().unwrap());
+        self
+    }
+
+    /// Asserts that the worktop contains the given non-fungibles at this point of the manifest.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource.
+    /// * `ids`: ids expected on the worktop.
+    pub fn assert_worktop_contains_non_fungibles<R: ResourceReference, T: ToId>(
+        mut self,
+        resource: R,
+        ids: Vec<T>,
+    ) -> Self {
+        let resource_address = resource.address(self.test_engine);
+        self.has_instructions = true;
+        self.manifest_builder = self.manifest_builder.assert_worktop_contains_non_fungibles(
+            resource_address,
+            ids.into_iter().map(|id| id.to_id()),
+        );
+        self
+    }
+
+    /// Takes an amount of a resource from the worktop and puts it in a named bucket, which can
+    /// then be given as argument of a later call with `Environment::Bucket`.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource.
+    /// * `amount`: amount to take from the worktop.
+    /// * `name`: name of the bucket.
+    pub fn take_from_worktop_named<R: ResourceReference, D: TryInto<Decimal>, N: ReferenceName>(
+        mut self,
+        resource: R,
+        amount: D,
+        name: N,
+    ) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let resource_address = resource.address(self.test_engine);
+        self.has_instructions = true;
+        self.manifest_builder = self.manifest_builder.take_from_worktop(
+            resource_address,
+            amount.try_into().unwrap(),
+            name.format(),
+        );
+        self
+    }
+
+    /// Withdraws an amount of a resource from the caller's account and puts it in a named bucket,
+    /// which can then be given as argument of a later call with `Environment::Bucket`.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource.
+    /// * `amount`: amount to withdraw.
+    /// * `name`: name of the bucket.
+    pub fn take_bucket<R: ResourceReference, D: TryInto<Decimal>, N: ReferenceName>(
+        self,
+        resource: R,
+        amount: D,
+        name: N,
+    ) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let resource_address = resource.address(self.test_engine);
+        let amount = amount.try_into().unwrap();
+        self.withdraw(resource_address, amount)
+            .take_from_worktop_named(resource_address, amount, name)
+    }
+
+    /// Creates a proof of an amount of a resource of the caller's account and puts it in a named
+    /// proof, which can then be given as argument of a later call with `Environment::Proof`.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource.
+    /// * `amount`: amount to prove.
+    /// * `name`: name of the proof.
+    pub fn create_proof_named<R: ResourceReference, D: TryInto<Decimal>, N: ReferenceName>(
+        mut self,
+        resource: R,
+        amount: D,
+        name: N,
+    ) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let account = *self.caller.address();
+        let resource_address = resource.address(self.test_engine);
+        let amount = amount.try_into().unwrap();
+        self.has_instructions = true;
+        self.manifest_builder = self
+            .manifest_builder
+            .create_proof_from_account_of_amount(account, resource_address, amount)
+            .create_proof_from_auth_zone_of_amount(resource_address, amount, name.format());
+        self
+    }
+
+    /// Allocates the address of a future component of a blueprint. The address reservation can
+    /// then be given as argument of a later call with `Environment::AddressReservation`, and the
+    /// address itself with `Environment::NamedAddress`.
+    ///
+    /// Once the transaction is committed, the allocated address is registered as a component with
+    /// the reservation name as reference name. Addresses allocated in a raw call are not
+    /// registered.
+    ///
+    /// # Arguments
+    /// * `package`: reference name or address of the package of the blueprint.
+    /// * `blueprint`: name of the blueprint.
+    /// * `reservation_name`: name of the reservation and of the allocated address.
+    pub fn allocate_address<P: PackageReference, N: ReferenceName>(
+        mut self,
+        package: P,
+        blueprint: &str,
+        reservation_name: N,
+    ) -> Self {
+        let package_address = package.address(self.test_engine);
+        let name = reservation_name.format();
+        self.has_instructions = true;
+        self.manifest_builder = self.manifest_builder.allocate_global_address(
+            package_address,
+            blueprint,
+            name.clone(),
+            name.clone(),
+        );
+        let address = self.manifest_builder.named_address(&name);
+        self.allocated_addresses.push((name, address));
+        self
+    }
+
+    /// Disables the validation of the next method and function calls added to the builder, which
+    /// otherwise panic if the method or function does not exist or if the number of arguments does
+    /// not match the blueprint. Useful to test calls that are meant to fail.
+    pub fn without_validation(mut self) -> Self {
+        self.validate_calls = false;
+        self
+    }
+
+    /// Executes the manifest exactly as built: no fee lock, badge proof or deposit instruction is
+    /// injected and the reference names are not updated from the result.
+    /// The manifest must lock its own fees, otherwise the transaction is rejected.
+    pub fn raw(mut self) -> Self {
+        self.raw = true;
+        self
+    }
+
+    /// Orders the injected instructions the way the official wallet does: fees are locked by the
+    /// fee payer account (the caller if none was given) and badge proofs are created right
+    /// before the first instruction that does not call the caller's account, instead of all up
+    /// front.
+    ///
+    /// # Arguments
+    /// * `enabled`: whether to use the wallet compatible layout.
+    pub fn wallet_compatible_layout(mut self, enabled: bool) -> Self {
+        self.wallet_layout = enabled;
+        self
+    }
+
+    /// Returns the manifest that `execute` would run, including the instructions injected by the
+    /// engine to lock fees, create badge proofs and deposit the remaining resources, in the
+    /// textual manifest format. The call can still be executed afterwards, but no instruction can
+    /// be added to it.
+    pub fn preview(&mut self) -> String {
+        let manifest = self.injected_manifest();
+        decompile_with_known_naming(
+            &manifest.transaction_manifest.instructions,
+            &self.test_engine.network(),
+            manifest.object_names,
+        )
+        .unwrap_or_else(|error| panic!("Could not decompile the manifest: {:?}", error))
+    }
+
+    /// Returns the instructions that `execute` would run, including the instructions injected by
+    /// the engine. The call can still be executed afterwards, but no instruction can be added to
+    /// it.
+    pub fn instructions(&mut self) -> Vec<InstructionV1> {
+        self.injected_manifest().transaction_manifest.instructions
+    }
+
+    /// Returns the instructions of the call in the legacy and in the wallet compatible layouts
+    /// and the differences between both, without executing it.
+    pub fn compare_layouts(mut self) -> LayoutDiff {
+        self.build_manifest();
+        let user_manifest = self
+            .manifest_data
+            .as_ref()
+            .unwrap()
+            .transaction_manifest
+            .clone();
+
+        let mut layouts = vec![];
+        for wallet_layout in [false, true] {
+            self.wallet_layout = wallet_layout;
+            self.manifest_data.as_mut().unwrap().transaction_manifest = user_manifest.clone();
+            self.write_lock();
+            self.write_allocated_addresses();
+            self.write_deposit();
+            self.write_badge();
+            layouts.push(self.describe_instructions());
+        }
+
+        let wallet = layouts.pop().unwrap();
+        let legacy = layouts.pop().unwrap();
+        LayoutDiff::new(legacy, wallet)
+    }
+
+    /// Makes the execution panic if the total cost of the transaction exceeds the given budget.
+    ///
+    /// # Arguments
+    /// * `max`: maximum cost in XRD of the transaction.
+    pub fn with_fee_budget<D: TryInto<Decimal>>(mut self, max: D) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        self.fee_budget = Some(max.try_into().unwrap());
+        self
+    }
+
+    /// Sets the maximum number of execution cost units the transaction can consume, so that a
+    /// call stuck in an expensive loop fails as soon as the limit is reached.
+    ///
+    /// # Arguments
+    /// * `limit`: maximum number of execution cost units.
+    pub fn with_cost_unit_limit(mut self, limit: u32) -> Self {
+        self.cost_unit_limit = Some(limit);
+        self
+    }
+
+    /// Makes the execution panic if the transaction runs for longer than the given duration.
+    /// A running transaction cannot be interrupted: to bound the duration of a call, also set a
+    /// cost unit limit with `with_cost_unit_limit`.
+    ///
+    /// # Arguments
+    /// * `timeout`: maximum duration of the execution.
+    pub fn with_execution_timeout(mut self, timeout: Duration) -> Self {
+        self.execution_timeout = Some(timeout);
+        self
+    }
+
+    /// Gives a title to the transaction, passed to the logger of the engine. The default logger
+    /// prints it before the logs of the transaction.
+    ///
+    /// # Arguments
+    /// * `title`: title of the transaction.
+    pub fn with_log_title(mut self, title: &str) -> Self {
+        self.log_title = Some(title.to_string());
+        self
+    }
+
+    /// Makes the logger of the engine log the fees of the transaction.
+    pub fn with_log_tx_fee(mut self) -> Self {
+        self.log_tx_fee = true;
+        self
+    }
+
     /// Displays trace or not.
     ///
     /// # Arguments
@@ -240,12 +1119,26 @@ impl<'a> CallBuilder<'a> {
         self
     }
 
+    /// Executes the transaction with an execution trace, which is needed by
+    /// `get_return_resources` and by the calls and balance changes of [`TestEngine::explain`] and
+    /// of the receipt snapshots. See [`TestEngine::set_execution_trace`] to trace every
+    /// transaction.
+    pub fn with_execution_trace(mut self) -> Self {
+        self.execution_trace = true;
+        self
+    }
+
     pub(crate) fn call_method_internal(
         mut self,
-        component: impl ResolvableGlobalAddress,
+        component: impl Into<GlobalAddress>,
         method_name: &str,
         args: Vec<Box<dyn EnvironmentEncode>>,
     ) -> Self {
+        let component = component.into();
+        if self.validate_calls {
+            self.test_engine
+                .check_method_call(component, method_name, args.len());
+        }
         let mut manifest_builder = self.manifest_builder;
 
         let mut buf = Vec::new();
@@ -265,38 +1158,20 @@ impl<'a> CallBuilder<'a> {
         }
 
         let value = manifest_decode(&buf).unwrap();
+        self.consumed_objects
+            .consume(&manifest_builder.object_names(), &value);
         let manifest_arg = ManifestArgs::new_from_tuple_or_panic(value);
 
         let manifest_builder = manifest_builder.call_method(component, method_name, manifest_arg);
 
         self.manifest_builder = manifest_builder;
+        self.has_instructions = true;
 
         self
     }
 
     pub(crate) fn execute_no_update(mut self) -> TransactionReceipt {
-        self.manifest_data = Some(TransactionManifestData {
-            object_names: self.manifest_builder.object_names().clone(),
-            transaction_manifest: self.manifest_builder.build(),
-        });
-
-        self.manifest_builder = ManifestBuilder::new();
-
-        self.write_lock();
-        self.write_deposit();
-        self.write_badge();
-        self.output_manifest();
-
-        let receipt = self.test_engine.execute_call(
-            self.manifest_data.unwrap().transaction_manifest,
-            self.with_trace,
-            vec![self.caller.proof()],
-            false,
-        );
-
-        Self::output_logs(&receipt);
-
-        receipt
+        self.execute_internal(false)
     }
 
     pub(crate) fn call_function_internal(
@@ -306,6 +1181,14 @@ impl<'a> CallBuilder<'a> {
         function_name: &str,
         args: Vec<Box<dyn EnvironmentEncode>>,
     ) -> Self {
+        if self.validate_calls {
+            self.test_engine.check_function_call(
+                package_address,
+                blueprint_name,
+                function_name,
+                args.len(),
+            );
+        }
         let mut manifest_builder = self.manifest_builder;
 
         let mut buf = Vec::new();
@@ -325,6 +1208,8 @@ impl<'a> CallBuilder<'a> {
         }
 
         let value = manifest_decode(&buf).unwrap();
+        self.consumed_objects
+            .consume(&manifest_builder.object_names(), &value);
         let manifest_arg = ManifestArgs::new_from_tuple_or_panic(value);
 
         let manifest_builder = manifest_builder.call_function(
@@ -335,23 +1220,209 @@ impl<'a> CallBuilder<'a> {
         );
 
         self.manifest_builder = manifest_builder;
+        self.has_instructions = true;
 
         self
     }
 
+    fn execute_internal(&mut self, with_update: bool) -> TransactionReceipt {
+        self.build_manifest();
+        let user_instructions = self
+            .manifest_data
+            .as_ref()
+            .unwrap()
+            .transaction_manifest
+            .instructions
+            .clone();
+        self.write_injected_instructions();
+        self.output_manifest();
+
+        let injection_summary = InjectionSummary::new(
+            &user_instructions,
+            &self
+                .manifest_data
+                .as_ref()
+                .unwrap()
+                .transaction_manifest
+                .instructions,
+            self.describe_instructions(),
+        );
+        self.test_engine.set_injection_summary(injection_summary);
+
+        let mut initial_proofs: Vec<NonFungibleGlobalId> = self
+            .test_engine
+            .signer_proof(&self.caller)
+            .into_iter()
+            .collect();
+        initial_proofs.append(&mut self.additional_signers);
+
+        let start = Instant::now();
+        let receipt = self.test_engine.execute_call(
+            self.manifest_data.take().unwrap().transaction_manifest,
+            ExecutionOptions {
+                kernel_trace: self.with_trace,
+                execution_trace: self.execution_trace,
+                cost_unit_limit: self.cost_unit_limit,
+            },
+            initial_proofs,
+            with_update,
+            &TransactionContext {
+                title: self.log_title.clone(),
+                log_tx_fee: self.log_tx_fee,
+            },
+        );
+        let elapsed = start.elapsed();
+
+        if with_update && !self.raw {
+            self.register_allocated_addresses(&receipt);
+        }
+
+        if let Some(budget) = self.fee_budget {
+            let cost = receipt.fee_summary.total_cost();
+            if cost > budget {
+                panic!(
+                    "Transaction cost {} XRD, which exceeds the fee budget of {} XRD",
+                    cost, budget
+                );
+            }
+        }
+
+        if let Some(timeout) = self.execution_timeout {
+            if elapsed > timeout {
+                panic!(
+                    "Transaction ran for {:?}, which exceeds the execution timeout of {:?}",
+                    elapsed, timeout
+                );
+            }
+        }
+
+        receipt
+    }
+
+    fn build_manifest(&mut self) {
+        let manifest_builder =
+            std::mem::replace(&mut self.manifest_builder, ManifestBuilder::new());
+        if self.manifest_data.is_some() {
+            // The manifest has already been built by a preview.
+            if !manifest_builder.build().instructions.is_empty() {
+                panic!("Instructions cannot be added to a call after its preview");
+            }
+            return;
+        }
+        self.manifest_data = Some(TransactionManifestData {
+            object_names: manifest_builder.object_names().clone(),
+            transaction_manifest: manifest_builder.build(),
+        });
+    }
+
+    /// Writes the instructions injected by the engine around the ones of the user, unless the
+    /// call is raw.
+    fn write_injected_instructions(&mut self) {
+        if !self.raw {
+            self.write_lock();
+            self.write_allocated_addresses();
+            self.write_deposit();
+            self.write_badge();
+        }
+    }
+
+    /// Returns the manifest that would be executed, including the injected instructions, while
+    /// keeping the manifest of the user to be executed later.
+    fn injected_manifest(&mut self) -> TransactionManifestData {
+        self.build_manifest();
+        let user_manifest = self.manifest_data.clone().unwrap();
+        self.write_injected_instructions();
+        self.manifest_data.replace(user_manifest).unwrap()
+    }
+
     fn write_lock(&mut self) {
+        let fee_payer = match (self.fee_payer, self.wallet_layout) {
+            (Some(fee_payer), _) => fee_payer,
+            (None, false) if !self.test_engine.is_self_funded() => FAUCET,
+            (None, _) => *self.caller.address(),
+        };
         let manifest = &mut self.manifest_data.as_mut().unwrap().transaction_manifest;
 
         manifest.instructions.insert(
             0,
             InstructionV1::CallMethod {
-                address: DynamicGlobalAddress::from(self.fee_payer),
+                address: DynamicGlobalAddress::from(fee_payer),
                 method_name: "lock_fee".to_string(),
                 args: manifest_args!(self.fee_locked).resolve(),
             },
         );
     }
 
+    /// Writes the addresses allocated in the transaction to the metadata of the caller's account,
+    /// and removes them right away. The `SetMetadataEvent`s then give the allocated addresses,
+    /// which the transaction outputs do not contain.
+    fn write_allocated_addresses(&mut self) {
+        let caller = DynamicGlobalAddress::from(*self.caller.address());
+        let manifest = &mut self.manifest_data.as_mut().unwrap().transaction_manifest;
+
+        for (index, (_, address)) in self.allocated_addresses.iter().enumerate() {
+            let key = format!("{}_{}", ALLOCATED_ADDRESS_METADATA_KEY, index);
+            let value = ManifestValue::Enum {
+                discriminator: METADATA_VALUE_GLOBAL_ADDRESS_DISCRIMINATOR,
+                fields: vec![ManifestValue::Custom {
+                    value: ManifestCustomValue::Address(*address),
+                }],
+            };
+            manifest
+                .instructions
+                .push(InstructionV1::CallMetadataMethod {
+                    address: caller,
+                    method_name: METADATA_SET_IDENT.to_string(),
+                    args: ManifestValue::Tuple {
+                        fields: vec![ManifestValue::String { value: key.clone() }, value],
+                    },
+                });
+            manifest
+                .instructions
+                .push(InstructionV1::CallMetadataMethod {
+                    address: caller,
+                    method_name: METADATA_REMOVE_IDENT.to_string(),
+                    args: manifest_args!(key).resolve(),
+                });
+        }
+    }
+
+    /// Registers the addresses allocated in a committed transaction under their reservation name.
+    fn register_allocated_addresses(&mut self, receipt: &TransactionReceipt) {
+        let TransactionResult::Commit(commit) = &receipt.result else {
+            return;
+        };
+        let caller = *self.caller.address();
+        for (EventTypeIdentifier(emitter, event_name), data) in &commit.application_events {
+            let Emitter::Method(node_id, ModuleId::Metadata) = emitter else {
+                continue;
+            };
+            if *node_id != caller.into_node_id() || event_name != "SetMetadataEvent" {
+                continue;
+            }
+            let Ok(SetMetadataEvent {
+                key,
+                value: MetadataValue::GlobalAddress(address),
+            }) = scrypto_decode::<SetMetadataEvent>(data)
+            else {
+                continue;
+            };
+            let Some(index) = key
+                .strip_prefix(ALLOCATED_ADDRESS_METADATA_KEY)
+                .and_then(|index| index.strip_prefix('_'))
+                .and_then(|index| index.parse::<usize>().ok())
+            else {
+                continue;
+            };
+            if let (Some((name, _)), Ok(component)) = (
+                self.allocated_addresses.get(index),
+                ComponentAddress::try_from(address),
+            ) {
+                self.test_engine.insert_component(name.clone(), component);
+            }
+        }
+    }
+
     fn write_deposit(&mut self) {
         let manifest = &mut self.manifest_data.as_mut().unwrap().transaction_manifest;
 
@@ -362,11 +1433,29 @@ impl<'a> CallBuilder<'a> {
         });
     }
     fn write_badge(&mut self) {
+        let caller = *self.caller.address();
         let manifest = &mut self.manifest_data.as_mut().unwrap().transaction_manifest;
+
+        // The fee lock is the first instruction and the deposit the last one.
+        let proofs_index = if self.wallet_layout {
+            (1..manifest.instructions.len() - 1)
+                .find(|index| match &manifest.instructions[*index] {
+                    InstructionV1::CallMethod {
+                        address: DynamicGlobalAddress::Static(address),
+                        ..
+                    } => *address != GlobalAddress::from(caller),
+                    InstructionV1::CallMethod { .. } | InstructionV1::CallFunction { .. } => true,
+                    _ => false,
+                })
+                .unwrap_or(manifest.instructions.len() - 1)
+        } else {
+            1
+        };
+
         for (badge, opt_ids) in &self.admin_badge {
             if badge.is_fungible() {
                 manifest.instructions.insert(
-                    1,
+                    proofs_index,
                     InstructionV1::CallMethod {
                         address: DynamicGlobalAddress::from(*self.caller.address()),
                         method_name: "create_proof_of_amount".to_string(),
@@ -375,7 +1464,7 @@ impl<'a> CallBuilder<'a> {
                 )
             } else {
                 manifest.instructions.insert(
-                    1,
+                    proofs_index,
                     InstructionV1::CallMethod {
                         address: DynamicGlobalAddress::from(*self.caller.address()),
                         method_name: "create_proof_of_non_fungibles".to_string(),
@@ -386,6 +1475,61 @@ impl<'a> CallBuilder<'a> {
         }
     }
 
+    fn describe_instructions(&self) -> Vec<String> {
+        let manifest = &self.manifest_data.as_ref().unwrap().transaction_manifest;
+        manifest
+            .instructions
+            .iter()
+            .map(|instruction| match instruction {
+                InstructionV1::CallMethod {
+                    address,
+                    method_name,
+                    ..
+                } => format!(
+                    "CALL_METHOD {} \"{}\"",
+                    match address {
+                        DynamicGlobalAddress::Static(address) => {
+                            self.test_engine.encode_address(*address)
+                        }
+                        DynamicGlobalAddress::Named(named) => format!("NamedAddress({named})"),
+                    },
+                    method_name
+                ),
+                InstructionV1::CallFunction {
+                    package_address,
+                    blueprint_name,
+                    function_name,
+                    ..
+                } => format!(
+                    "CALL_FUNCTION {} \"{}\" \"{}\"",
+                    match package_address {
+                        DynamicPackageAddress::Static(address) => {
+                            self.test_engine.encode_address((*address).into())
+                        }
+                        DynamicPackageAddress::Named(named) => format!("NamedAddress({named})"),
+                    },
+                    blueprint_name,
+                    function_name
+                ),
+                InstructionV1::CallMetadataMethod {
+                    address,
+                    method_name,
+                    ..
+                } => format!(
+                    "CALL_METADATA_METHOD {} \"{}\"",
+                    match address {
+                        DynamicGlobalAddress::Static(address) => {
+                            self.test_engine.encode_address(*address)
+                        }
+                        DynamicGlobalAddress::Named(named) => format!("NamedAddress({named})"),
+                    },
+                    method_name
+                ),
+                other => format!("{:?}", other),
+            })
+            .collect()
+    }
+
     fn output_manifest(&mut self) {
         let manifest = self.manifest_data.as_mut().unwrap();
 
@@ -404,16 +1548,15 @@ impl<'a> CallBuilder<'a> {
                         panic!("Error when outputting manifest: {:?}", error);
                     }
                 }
-            }
-        }
-    }
 
-    fn output_logs(receipt: &TransactionReceipt) {
-        if let TransactionResult::Commit(commit_result) = &receipt.result {
-            if !commit_result.application_logs.is_empty() {
-                println!("\nApplication logs:");
-                for (level, message) in &commit_result.application_logs {
-                    println!("| [{level}]: {message}")
+                if self.output_reference_names {
+                    let manifest_path = Path::new(path).join(format!("{}.rtm", name));
+                    let manifest = fs::read_to_string(&manifest_path).unwrap();
+                    fs::write(
+                        &manifest_path,
+                        self.test_engine.substitute_reference_names(&manifest),
+                    )
+                    .unwrap_or_else(|error| panic!("Error when outputting manifest: {:?}", error));
                 }
             }
         }
@@ -428,6 +1571,7 @@ impl SimpleMethodCaller for CallBuilder<'_> {
     ) -> TransactionReceipt {
         let component = *self.test_engine.current_component();
         self.call_method_internal(component, method_name, args)
+            .with_execution_trace()
             .execute()
     }
 
@@ -439,6 +1583,7 @@ impl SimpleMethodCaller for CallBuilder<'_> {
     ) -> TransactionReceipt {
         let component = entity_name.address(self.test_engine);
         self.call_method_internal(component, method_name, args)
+            .with_execution_trace()
             .execute()
     }
 
@@ -451,6 +1596,64 @@ impl SimpleMethodCaller for CallBuilder<'_> {
         let component = *self.test_engine.current_component();
         self.call_method_internal(component, method_name, args)
             .with_badge(admin_badge)
+            .with_execution_trace()
             .execute()
     }
+
+    fn call_function(
+        self,
+        blueprint_name: &str,
+        function_name: &str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+    ) -> TransactionReceipt {
+        let package = *self.test_engine.current_package();
+        self.call_function_internal(package, blueprint_name, function_name, args)
+            .with_execution_trace()
+            .execute()
+    }
+
+    fn call_function_from<P: PackageReference>(
+        self,
+        package: P,
+        blueprint_name: &str,
+        function_name: &str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+    ) -> TransactionReceipt {
+        self.call_from_package(package, blueprint_name, function_name, args)
+            .with_execution_trace()
+            .execute()
+    }
+}
+
+/// Collects the buckets and proofs contained in a manifest value.
+fn collect_buckets_and_proofs(
+    value: &ManifestValue,
+    buckets: &mut Vec<ManifestBucket>,
+    proofs: &mut Vec<ManifestProof>,
+) {
+    match value {
+        ManifestValue::Custom {
+            value: ManifestCustomValue::Bucket(bucket),
+        } => buckets.push(*bucket),
+        ManifestValue::Custom {
+            value: ManifestCustomValue::Proof(proof),
+        } => proofs.push(*proof),
+        ManifestValue::Tuple { fields } | ManifestValue::Enum { fields, .. } => {
+            for field in fields {
+                collect_buckets_and_proofs(field, buckets, proofs);
+            }
+        }
+        ManifestValue::Array { elements, .. } => {
+            for element in elements {
+                collect_buckets_and_proofs(element, buckets, proofs);
+            }
+        }
+        ManifestValue::Map { entries, .. } => {
+            for (key, value) in entries {
+                collect_buckets_and_proofs(key, buckets, proofs);
+                collect_buckets_and_proofs(value, buckets, proofs);
+            }
+        }
+        _ => {}
+    }
 }
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use lazy_static::lazy_static;
+
+use crate::internal_prelude::*;
+
+const CACHE_DIRECTORY: &str = "scrypto-test-cache";
+const CODE_FILE: &str = "code.wasm";
+const DEFINITION_FILE: &str = "definition.rpd";
+
+lazy_static! {
+    static ref COMPILED: Mutex<HashMap<PathBuf, (Vec<u8>, PackageDefinition)>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Registry of the packages compiled by the test process.
+///
+/// A package is compiled at most once per process. The compiled code and definition are also
+/// persisted to `target/scrypto-test-cache/<hash>`, where the hash covers the package's `src/`
+/// directory and `Cargo.toml`, so that other test processes can skip the compilation as long as
+/// the sources have not changed.
+pub struct CompiledPackages;
+
+impl CompiledPackages {
+    /// Returns the code and definition of a package, compiling it only if needed.
+    ///
+    /// # Arguments
+    /// * `package_dir`: path of the package.
+    pub fn get<P: AsRef<Path>>(package_dir: P) -> (Vec<u8>, PackageDefinition) {
+        let package_dir = package_dir.as_ref();
+        let key = package_dir
+            .canonicalize()
+            .unwrap_or_else(|_| panic!("Could not find package at {}", package_dir.display()));
+
+        let mut compiled = COMPILED.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(package) = compiled.get(&key) {
+            return package.clone();
+        }
+
+        let cache_dir = Self::cache_path(&key);
+        let package = match Self::load(&cache_dir) {
+            Some(package) => package,
+            None => {
+                let package = PackagePublishingSource::from(key.as_path()).code_and_definition();
+                Self::store(&cache_dir, &package);
+                package
+            }
+        };
+
+        compiled.insert(key, package.clone());
+        package
+    }
+
+    /// Returns the hash of a package's `src/` directory and `Cargo.toml`, used to key the cache.
+    ///
+    /// # Arguments
+    /// * `package_dir`: path of the package.
+    pub fn source_hash<P: AsRef<Path>>(package_dir: P) -> Hash {
+        let package_dir = package_dir.as_ref();
+        let mut files = vec![package_dir.join("Cargo.toml")];
+        Self::collect_files(&package_dir.join("src"), &mut files);
+        files.sort();
+
+        let mut data = vec![];
+        for file in files {
+            let content = fs::read(&file)
+                .unwrap_or_else(|err| panic!("Could not read {}: {}", file.display(), err));
+            let relative_path = file.strip_prefix(package_dir).unwrap_or(&file);
+            data.extend(relative_path.to_string_lossy().as_bytes());
+            data.extend((content.len() as u64).to_le_bytes());
+            data.extend(content);
+        }
+        hash(data)
+    }
+
+    /// Returns the directory where the compiled package is persisted for its current sources.
+    ///
+    /// # Arguments
+    /// * `package_dir`: path of the package.
+    pub fn cache_path<P: AsRef<Path>>(package_dir: P) -> PathBuf {
+        Self::cache_dir().join(Self::source_hash(package_dir).to_string())
+    }
+
+    fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                Self::collect_files(&path, files);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+
+    fn cache_dir() -> PathBuf {
+        std::env::var_os("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("target"))
+            .join(CACHE_DIRECTORY)
+    }
+
+    fn load(cache_dir: &Path) -> Option<(Vec<u8>, PackageDefinition)> {
+        let code = fs::read(cache_dir.join(CODE_FILE)).ok()?;
+        let definition = fs::read(cache_dir.join(DEFINITION_FILE)).ok()?;
+        let definition = manifest_decode(&definition).ok()?;
+        Some((code, definition))
+    }
+
+    fn store(cache_dir: &Path, package: &(Vec<u8>, PackageDefinition)) {
+        // Files are written to a temporary directory first so that concurrent test processes never
+        // read a partially written entry. Failing to persist the cache is not an error.
+        let tmp_dir = cache_dir.with_extension(format!("tmp-{}", std::process::id()));
+        let written = fs::create_dir_all(&tmp_dir).is_ok()
+            && fs::write(tmp_dir.join(CODE_FILE), &package.0).is_ok()
+            && manifest_encode(&package.1)
+                .map(|definition| fs::write(tmp_dir.join(DEFINITION_FILE), definition).is_ok())
+                .unwrap_or(false);
+        if !written || fs::rename(&tmp_dir, cache_dir).is_err() {
+            let _ = fs::remove_dir_all(&tmp_dir);
+        }
+    }
+}
+
+/// Handle to a package compiled lazily, at most once per process, through [`CompiledPackages`].
+///
+/// Handles are usually declared with the `global_packages!` macro and given to
+/// `TestEngine::new_package`.
+pub struct CompiledPackage {
+    path: &'static str,
+    package: OnceLock<(Vec<u8>, PackageDefinition)>,
+}
+
+impl CompiledPackage {
+    /// Creates a handle to the package at the given path, without compiling it.
+    ///
+    /// # Arguments
+    /// * `path`: path of the package.
+    pub const fn new(path: &'static str) -> Self {
+        Self {
+            path,
+            package: OnceLock::new(),
+        }
+    }
+
+    /// Returns the path of the package.
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Returns the code and definition of the package, compiling it on first use.
+    pub fn code_and_definition(&self) -> &(Vec<u8>, PackageDefinition) {
+        self.package
+            .get_or_init(|| CompiledPackages::get(self.path))
+    }
+}
+
+/// Source from which a package can be published: either a path to the package or an already
+/// compiled package.
+pub trait PackageSource {
+    fn code_and_definition(self) -> (Vec<u8>, PackageDefinition);
+}
+
+impl PackageSource for &str {
+    fn code_and_definition(self) -> (Vec<u8>, PackageDefinition) {
+        CompiledPackages::get(self)
+    }
+}
+
+impl PackageSource for String {
+    fn code_and_definition(self) -> (Vec<u8>, PackageDefinition) {
+        CompiledPackages::get(self)
+    }
+}
+
+impl PackageSource for &Path {
+    fn code_and_definition(self) -> (Vec<u8>, PackageDefinition) {
+        CompiledPackages::get(self)
+    }
+}
+
+impl PackageSource for PathBuf {
+    fn code_and_definition(self) -> (Vec<u8>, PackageDefinition) {
+        CompiledPackages::get(self)
+    }
+}
+
+impl PackageSource for &CompiledPackage {
+    fn code_and_definition(self) -> (Vec<u8>, PackageDefinition) {
+        CompiledPackage::code_and_definition(self).clone()
+    }
+}
+
+impl PackageSource for &(Vec<u8>, PackageDefinition) {
+    fn code_and_definition(self) -> (Vec<u8>, PackageDefinition) {
+        self.clone()
+    }
+}
+
+/// Royalties of the functions and methods of a package, by blueprint name. The functions and
+/// methods without royalties are free.
+pub type PackageRoyalties<'a> = HashMap<&'a str, Vec<(&'a str, RoyaltyAmount)>>;
+
+/// Enables the package royalties of the given blueprints in a package definition.
+/// Panics if a blueprint or a function does not exist in the package.
+pub(crate) fn with_royalties(
+    mut definition: PackageDefinition,
+    royalties: PackageRoyalties,
+) -> PackageDefinition {
+    for (blueprint_name, function_royalties) in royalties {
+        let blueprint = definition
+            .blueprints
+            .get_mut(blueprint_name)
+            .unwrap_or_else(|| panic!("The package has no blueprint named {}", blueprint_name));
+        let mut config: IndexMap<String, RoyaltyAmount> = blueprint
+            .schema
+            .functions
+            .functions
+            .keys()
+            .map(|function| (function.clone(), RoyaltyAmount::Free))
+            .collect();
+        for (function, amount) in function_royalties {
+            match config.get_mut(function) {
+                Some(royalty) => *royalty = amount,
+                None => panic!(
+                    "Blueprint {} has no function or method named {}",
+                    blueprint_name, function
+                ),
+            }
+        }
+        blueprint.royalty_config = PackageRoyaltyConfig::Enabled(config);
+    }
+    definition
+}
@@ -0,0 +1,166 @@
+use radix_blueprint_schema_init::TypeRef;
+
+use crate::compiled_packages::PackageSource;
+use crate::internal_prelude::*;
+use crate::references::ComponentReference;
+use crate::test_engine::TestEngine;
+
+/// Typed proxy to a component, generated with the `component_proxy!` macro.
+pub trait ComponentProxy {
+    /// Name of the blueprint of the component.
+    const BLUEPRINT: &'static str;
+    /// Names and number of arguments of the methods of the proxy.
+    const METHODS: &'static [(&'static str, usize)];
+
+    fn from_address(component: ComponentAddress) -> Self;
+}
+
+impl TestEngine {
+    /// Returns a typed proxy to a component.
+    /// Panics if one of the methods of the proxy does not exist in the blueprint of the package or
+    /// does not take the declared number of arguments.
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component.
+    /// * `package`: path of the package or compiled package defining the blueprint of the component.
+    pub fn proxy<P: ComponentProxy, C: ComponentReference, S: PackageSource>(
+        &self,
+        component: C,
+        package: S,
+    ) -> P {
+        let (_, definition) = package.code_and_definition();
+        check_proxy_methods(&definition, P::BLUEPRINT, P::METHODS);
+        P::from_address(component.address(self))
+    }
+}
+
+fn check_proxy_methods(
+    definition: &PackageDefinition,
+    blueprint: &str,
+    methods: &[(&'static str, usize)],
+) {
+    let blueprint_definition = definition
+        .blueprints
+        .get(blueprint)
+        .unwrap_or_else(|| panic!("There is no blueprint {} in the package", blueprint));
+    let schema = blueprint_definition.schema.schema.v1();
+    let functions = &blueprint_definition.schema.functions.functions;
+
+    let errors: Vec<String> = methods
+        .iter()
+        .filter_map(|(method, arity)| {
+            let function = match functions.get(*method) {
+                None => return Some(format!("- {}: no such method", method)),
+                Some(function) => function,
+            };
+            if function.receiver.is_none() {
+                return Some(format!("- {}: is a function, not a method", method));
+            }
+            let actual_arity = match function.input {
+                TypeRef::Static(type_id) => match schema.resolve_type_kind(type_id) {
+                    Some(TypeKind::Tuple { field_types }) => field_types.len(),
+                    _ => return None,
+                },
+                TypeRef::Generic(_) => return None,
+            };
+            (actual_arity != *arity).then(|| {
+                format!(
+                    "- {}: takes {} arguments but {} are declared",
+                    method, actual_arity, arity
+                )
+            })
+        })
+        .collect();
+
+    if !errors.is_empty() {
+        panic!(
+            "Invalid proxy for blueprint {}:\n{}",
+            blueprint,
+            errors.join("\n")
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_component_proxy {
+    use radix_blueprint_schema_init::{FunctionSchemaInit, ReceiverInfo, TypeRef};
+
+    use super::check_proxy_methods;
+    use crate::internal_prelude::*;
+
+    fn function(
+        aggregator: &mut TypeAggregator<ScryptoCustomTypeKind>,
+        is_method: bool,
+        arity: usize,
+    ) -> FunctionSchemaInit {
+        let input = match arity {
+            0 => aggregator.add_child_type_and_descendents::<()>(),
+            1 => aggregator.add_child_type_and_descendents::<(Decimal,)>(),
+            _ => aggregator.add_child_type_and_descendents::<(Decimal, Decimal)>(),
+        };
+        FunctionSchemaInit {
+            receiver: is_method.then(ReceiverInfo::normal_ref_mut),
+            input: TypeRef::Static(input),
+            output: TypeRef::Static(aggregator.add_child_type_and_descendents::<()>()),
+            export: String::new(),
+        }
+    }
+
+    fn package_definition() -> PackageDefinition {
+        let mut aggregator = TypeAggregator::<ScryptoCustomTypeKind>::new();
+        let mut functions = index_map_new();
+        functions.insert("new".to_string(), function(&mut aggregator, false, 0));
+        functions.insert("swap".to_string(), function(&mut aggregator, true, 1));
+        functions.insert(
+            "add_liquidity".to_string(),
+            function(&mut aggregator, true, 2),
+        );
+
+        let mut blueprint = BlueprintDefinitionInit::default();
+        blueprint.schema.schema = generate_full_schema(aggregator);
+        blueprint.schema.functions.functions = functions;
+
+        let mut definition = PackageDefinition::default();
+        definition
+            .blueprints
+            .insert("Radiswap".to_string(), blueprint);
+        definition
+    }
+
+    #[test]
+    fn test_valid_proxy() {
+        check_proxy_methods(
+            &package_definition(),
+            "Radiswap",
+            &[("swap", 1), ("add_liquidity", 2)],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "- remove_liquidity: no such method")]
+    fn test_missing_method() {
+        check_proxy_methods(
+            &package_definition(),
+            "Radiswap",
+            &[("swap", 1), ("remove_liquidity", 1)],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "- add_liquidity: takes 2 arguments but 1 are declared")]
+    fn test_wrong_arity() {
+        check_proxy_methods(&package_definition(), "Radiswap", &[("add_liquidity", 1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "- new: is a function, not a method")]
+    fn test_function_instead_of_method() {
+        check_proxy_methods(&package_definition(), "Radiswap", &[("new", 0)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "There is no blueprint Gumball in the package")]
+    fn test_missing_blueprint() {
+        check_proxy_methods(&package_definition(), "Gumball", &[]);
+    }
+}
@@ -0,0 +1,68 @@
+use std::fmt::{Display, Formatter};
+
+/// Execution cost units consumed by successive executions of the same logical call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostTrend {
+    costs: Vec<u32>,
+    slope: f64,
+}
+
+impl CostTrend {
+    pub(crate) fn new(costs: Vec<u32>) -> Self {
+        let slope = Self::fit_slope(&costs);
+        Self { costs, slope }
+    }
+
+    /// Returns the execution cost units consumed by each call.
+    pub fn costs(&self) -> &Vec<u32> {
+        &self.costs
+    }
+
+    /// Returns the growth of the execution cost in cost units per call, fitted by least squares.
+    pub fn slope(&self) -> f64 {
+        self.slope
+    }
+
+    /// Asserts that the execution cost does not grow by more than the given tolerance per call.
+    ///
+    /// # Arguments
+    /// * `tolerance_per_call`: maximum accepted growth, in cost units per call.
+    pub fn assert_cost_stable(&self, tolerance_per_call: f64) -> &Self {
+        if self.slope > tolerance_per_call {
+            panic!(
+                "Execution cost grows by {:.2} cost units per call, more than the tolerance of {} \n{}",
+                self.slope, tolerance_per_call, self
+            );
+        }
+        self
+    }
+
+    fn fit_slope(costs: &[u32]) -> f64 {
+        if costs.len() < 2 {
+            return 0.0;
+        }
+
+        let n = costs.len() as f64;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = costs.iter().map(|cost| *cost as f64).sum::<f64>() / n;
+
+        let mut covariance = 0.0;
+        let mut variance = 0.0;
+        for (x, y) in costs.iter().enumerate() {
+            let dx = x as f64 - mean_x;
+            covariance += dx * (*y as f64 - mean_y);
+            variance += dx * dx;
+        }
+        covariance / variance
+    }
+}
+
+impl Display for CostTrend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Execution cost units per call:")?;
+        for (index, cost) in self.costs.iter().enumerate() {
+            writeln!(f, "| {index}: {cost}")?;
+        }
+        Ok(())
+    }
+}
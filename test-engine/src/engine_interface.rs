@@ -1,57 +1,359 @@
 use std::collections::BTreeMap;
-use std::path::Path;
+
+use radix_engine::object_modules::royalty::{
+    ComponentRoyaltyAccumulatorFieldPayload, ComponentRoyaltyField,
+};
+use radix_engine::system::system_db_reader::{ObjectCollectionKey, SystemDatabaseReader};
+use radix_engine::system::type_info::TypeInfoSubstate;
 
 use crate::account::Account;
 use crate::internal_prelude::*;
 
+/// Amount of XRD given by the faucet, minted instead to the new accounts of an engine without
+/// faucet.
+const FAUCET_FREE_XRD: Decimal = dec!(10000);
+
 pub struct EngineInterface {
     simulator: DefaultLedgerSimulator,
+    seed: Option<u64>,
+    genesis_epoch: Epoch,
+    has_faucet: bool,
 }
 
 impl EngineInterface {
     pub fn new() -> Self {
-        let test_runner_builder = LedgerSimulatorBuilder::new()
-            .with_custom_genesis(CustomGenesis::default(
+        Self::new_with_seed(None)
+    }
+
+    /// Creates an engine whose transaction nonces and account keys are derived from the seed, if
+    /// any, instead of the simulator's counters.
+    pub fn new_with_seed(seed: Option<u64>) -> Self {
+        Self::new_with_genesis(
+            CustomGenesis::default(
                 Epoch::of(1),
                 CustomGenesis::default_consensus_manager_config(),
-            ))
-            .without_kernel_trace()
-            .build();
+            ),
+            false,
+            seed,
+        )
+    }
+
+    /// Creates an engine from a custom genesis.
+    pub fn new_with_genesis(genesis: CustomGenesis, kernel_trace: bool, seed: Option<u64>) -> Self {
+        let genesis_epoch = genesis.genesis_epoch;
+        let test_runner_builder = LedgerSimulatorBuilder::new().with_custom_genesis(genesis);
+        let test_runner_builder = if kernel_trace {
+            test_runner_builder.with_kernel_trace()
+        } else {
+            test_runner_builder.without_kernel_trace()
+        };
 
         Self {
-            simulator: test_runner_builder,
+            simulator: test_runner_builder.build(),
+            seed,
+            genesis_epoch,
+            has_faucet: true,
         }
     }
 
-    pub fn publish_package<P: AsRef<Path>>(&mut self, package_dir: P) -> TransactionReceipt {
-        self.simulator.try_publish_package(package_dir.as_ref())
+    /// Creates an engine running on a copy of the state of a database, such as a state
+    /// snapshot exported from a network. The database itself is never written to.
+    ///
+    /// The transaction nonces and account keys are derived from the content of the database, so
+    /// that the transactions of the engine do not collide with the ones which built the state.
+    /// If the database has no faucet, the transactions of the engine itself, such as the funding
+    /// of new accounts, are executed without fees.
+    pub fn from_database<D: SubstateDatabase + ListableSubstateDatabase>(database: &D) -> Self {
+        let mut copy = InMemorySubstateDatabase::standard();
+        let mut state_hash = HashAccumulator::new();
+        for partition_key in database.list_partition_keys() {
+            let mut entries = IndexMap::new();
+            for (sort_key, value) in database.list_entries(&partition_key) {
+                state_hash = state_hash
+                    .update(&partition_key.node_key)
+                    .update([partition_key.partition_num])
+                    .update(&sort_key.0)
+                    .update(&value);
+                entries.insert(sort_key, DatabaseUpdate::Set(value));
+            }
+            copy.commit(&DatabaseUpdates::from_delta_maps(indexmap!(
+                partition_key => entries
+            )));
+        }
+        let seed = u64::from_le_bytes(state_hash.finalize().0[..8].try_into().unwrap());
+
+        // A database can only be bootstrapped once, so the simulator is bootstrapped on its own
+        // database which is then replaced by the copy.
+        let mut simulator = LedgerSimulatorBuilder::new().without_kernel_trace().build();
+        *simulator.substate_db_mut() = copy;
+        let has_faucet = SystemDatabaseReader::new(simulator.substate_db())
+            .get_type_info(FAUCET.as_node_id())
+            .is_ok();
+        let genesis_epoch = simulator.get_current_epoch();
+
+        Self {
+            simulator,
+            seed: Some(seed),
+            genesis_epoch,
+            has_faucet,
+        }
+    }
+
+    /// Returns an independent copy of the engine, sharing nothing with the original one.
+    pub fn fork(&self) -> Self {
+        Self {
+            simulator: LedgerSimulatorBuilder::new()
+                .build_from_snapshot(self.simulator.create_snapshot()),
+            seed: self.seed,
+            genesis_epoch: self.genesis_epoch,
+            has_faucet: self.has_faucet,
+        }
+    }
+
+    pub fn simulator_mut(&mut self) -> &mut DefaultLedgerSimulator {
+        &mut self.simulator
+    }
+
+    pub fn substate_db(&self) -> &InMemorySubstateDatabase {
+        self.simulator.substate_db()
+    }
+
+    /// Returns whether the ledger has a faucet, which is the case unless the engine was created
+    /// from a database without one.
+    pub fn has_faucet(&self) -> bool {
+        self.has_faucet
     }
 
     pub fn publish_compiled_package(
         &mut self,
         code: Vec<u8>,
         definition: PackageDefinition,
+        owner_role: OwnerRole,
     ) -> TransactionReceipt {
-        let manifest = ManifestBuilder::new()
-            .lock_fee_from_faucet()
-            .publish_package_advanced(None, code, definition, BTreeMap::new(), OwnerRole::None)
+        let manifest = self
+            .engine_manifest_builder()
+            .publish_package_advanced(None, code, definition, BTreeMap::new(), owner_role)
             .build();
 
-        self.simulator.execute_manifest(manifest, vec![])
+        self.execute_engine_manifest(manifest)
     }
 
     pub fn new_account(&mut self) -> (Secp256k1PublicKey, Secp256k1PrivateKey, ComponentAddress) {
-        self.simulator.new_account(false)
+        let private_key = Secp256k1PrivateKey::from_u64(self.next_private_key()).unwrap();
+        let public_key = private_key.public_key();
+        let account = self.new_account_with_public_key(public_key.into());
+        (public_key, private_key, account)
+    }
+
+    pub fn new_ed25519_account(
+        &mut self,
+    ) -> (Ed25519PublicKey, Ed25519PrivateKey, ComponentAddress) {
+        let private_key = Ed25519PrivateKey::from_u64(self.next_private_key()).unwrap();
+        let public_key = private_key.public_key();
+        let account = self.new_account_with_public_key(public_key.into());
+        (public_key, private_key, account)
+    }
+
+    /// Creates an account owned by the signature of a key. The owner role is updatable so that
+    /// the account can be securified with [`EngineInterface::securify_account`].
+    pub fn new_account_with_public_key(&mut self, public_key: PublicKey) -> ComponentAddress {
+        let owner_rule = rule!(require(NonFungibleGlobalId::from_public_key(&public_key)));
+        let manifest = self
+            .engine_manifest_builder()
+            .new_account_advanced(OwnerRole::Updatable(owner_rule), None)
+            .build();
+        let account = self
+            .execute_engine_manifest(manifest)
+            .expect_commit_success()
+            .new_component_addresses()[0];
+
+        if self.has_faucet {
+            let manifest = self
+                .engine_manifest_builder()
+                .get_free_xrd_from_faucet()
+                .try_deposit_entire_worktop_or_abort(account, None)
+                .build();
+            self.execute_engine_manifest(manifest)
+                .expect_commit_success();
+        } else {
+            self.mint_xrd(account, FAUCET_FREE_XRD);
+        }
+
+        account
+    }
+
+    /// Securifies an account: its owner role becomes a proof of a newly minted owner badge,
+    /// which is deposited to the account.
+    ///
+    /// Only pre-securified accounts can call `securify` themselves, so the transaction is
+    /// executed with the authorization checks disabled.
+    pub fn securify_account(&mut self, account: ComponentAddress) -> TransactionReceipt {
+        let manifest = self
+            .engine_manifest_builder()
+            .call_method(account, ACCOUNT_SECURIFY_IDENT, manifest_args!())
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build();
+        self.execute_manifest_without_auth(manifest)
+    }
+
+    /// Mints XRD and deposits it to the given account. The fees are paid by the faucet, or
+    /// disabled if the ledger has no faucet.
+    ///
+    /// XRD can only be minted by the consensus manager, so the transaction is executed with the
+    /// authorization checks disabled.
+    pub fn mint_xrd(&mut self, account: ComponentAddress, amount: Decimal) {
+        let manifest = self
+            .engine_manifest_builder()
+            .mint_fungible(XRD, amount)
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build();
+        self.execute_manifest_without_auth(manifest)
+            .expect_commit_success();
+    }
+
+    /// Creates a non-fungible resource without initial supply, whose non-fungibles can only be
+    /// minted with [`EngineInterface::mint_non_fungibles`] and
+    /// [`EngineInterface::mint_ruid_non_fungibles`].
+    pub fn new_non_fungible<D: ManifestEncode + NonFungibleData>(
+        &mut self,
+        id_type: NonFungibleIdType,
+    ) -> ResourceAddress {
+        // Minting has to be enabled through the mint roles, even if nobody can mint.
+        let resource_roles = NonFungibleResourceRoles {
+            mint_roles: mint_roles! {
+                minter => rule!(deny_all);
+                minter_updater => rule!(deny_all);
+            },
+            ..Default::default()
+        };
+        let builder = self.engine_manifest_builder();
+        let builder = match id_type {
+            NonFungibleIdType::RUID => builder.create_ruid_non_fungible_resource(
+                OwnerRole::None,
+                true,
+                Default::default(),
+                resource_roles,
+                None::<Vec<D>>,
+            ),
+            _ => builder.create_non_fungible_resource(
+                OwnerRole::None,
+                id_type,
+                true,
+                resource_roles,
+                Default::default(),
+                None::<Vec<(NonFungibleLocalId, D)>>,
+            ),
+        };
+        self.execute_engine_manifest(builder.build())
+            .expect_commit_success()
+            .new_resource_addresses()[0]
+    }
+
+    /// Mints non-fungibles with the given ids and deposits them to the given account. The fees
+    /// are paid by the faucet and the transaction is executed with the authorization checks
+    /// disabled.
+    pub fn mint_non_fungibles<D: ManifestEncode>(
+        &mut self,
+        account: ComponentAddress,
+        resource: ResourceAddress,
+        entries: Vec<(NonFungibleLocalId, D)>,
+    ) -> TransactionReceipt {
+        let manifest = self
+            .engine_manifest_builder()
+            .mint_non_fungible(resource, entries)
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build();
+        self.execute_manifest_without_auth(manifest)
+    }
+
+    /// Mints non-fungibles with random ids and deposits them to the given account. The fees are
+    /// paid by the faucet and the transaction is executed with the authorization checks disabled.
+    pub fn mint_ruid_non_fungibles<D: ManifestEncode>(
+        &mut self,
+        account: ComponentAddress,
+        resource: ResourceAddress,
+        entries: Vec<D>,
+    ) -> TransactionReceipt {
+        let manifest = self
+            .engine_manifest_builder()
+            .mint_ruid_non_fungible(resource, entries)
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build();
+        self.execute_manifest_without_auth(manifest)
     }
 
     pub fn execute_manifest(
         &mut self,
         manifest: TransactionManifestV1,
-        with_trace: bool,
+        options: ExecutionOptions,
         initial_proofs: Vec<NonFungibleGlobalId>,
     ) -> TransactionReceipt {
-        let nonce = self.simulator.next_transaction_nonce();
-        let exec_config = ExecutionConfig::for_test_transaction().with_kernel_trace(with_trace);
+        let mut exec_config =
+            ExecutionConfig::for_test_transaction().with_kernel_trace(options.kernel_trace);
+        if options.execution_trace {
+            exec_config.execution_trace = Some(MAX_EXECUTION_TRACE_DEPTH);
+        }
+        if let (Some(limit), Some(overrides)) = (
+            options.cost_unit_limit,
+            exec_config.system_overrides.as_mut(),
+        ) {
+            overrides.costing_parameters =
+                Some(CostingParameters::babylon_genesis().with_execution_cost_unit_limit(limit));
+        }
+        self.execute_manifest_with_config(manifest, exec_config, initial_proofs)
+    }
+
+    /// Returns a manifest builder for the transactions of the engine itself, whose fees are paid
+    /// by the faucet, if any.
+    fn engine_manifest_builder(&self) -> ManifestBuilder {
+        if self.has_faucet {
+            ManifestBuilder::new().lock_fee_from_faucet()
+        } else {
+            ManifestBuilder::new()
+        }
+    }
+
+    /// Executes a transaction of the engine itself, built with
+    /// [`EngineInterface::engine_manifest_builder`].
+    fn execute_engine_manifest(&mut self, manifest: TransactionManifestV1) -> TransactionReceipt {
+        let exec_config = self.engine_execution_config();
+        self.execute_manifest_with_config(manifest, exec_config, vec![])
+    }
+
+    fn execute_manifest_without_auth(
+        &mut self,
+        manifest: TransactionManifestV1,
+    ) -> TransactionReceipt {
+        let mut exec_config = self.engine_execution_config();
+        if let Some(overrides) = exec_config.system_overrides.as_mut() {
+            overrides.disable_auth = true;
+        }
+        self.execute_manifest_with_config(manifest, exec_config, vec![])
+    }
+
+    /// Returns the configuration of the transactions of the engine itself, which are free when
+    /// there is no faucet to pay their fees.
+    fn engine_execution_config(&self) -> ExecutionConfig {
+        let mut exec_config = ExecutionConfig::for_test_transaction();
+        if let (false, Some(overrides)) = (self.has_faucet, exec_config.system_overrides.as_mut()) {
+            overrides.costing_parameters = Some(CostingParameters {
+                execution_cost_unit_price: Decimal::ZERO,
+                finalization_cost_unit_price: Decimal::ZERO,
+                state_storage_price: Decimal::ZERO,
+                archive_storage_price: Decimal::ZERO,
+                ..CostingParameters::babylon_genesis()
+            });
+        }
+        exec_config
+    }
+
+    fn execute_manifest_with_config(
+        &mut self,
+        manifest: TransactionManifestV1,
+        exec_config: ExecutionConfig,
+        initial_proofs: Vec<NonFungibleGlobalId>,
+    ) -> TransactionReceipt {
+        let nonce = self.next_transaction_nonce();
 
         self.simulator.execute_transaction(
             TestTransaction::new_from_nonce(manifest, nonce)
@@ -62,25 +364,105 @@ impl EngineInterface {
         )
     }
 
+    fn next_transaction_nonce(&mut self) -> u32 {
+        let nonce = self.simulator.next_transaction_nonce();
+        match self.seed {
+            None => nonce,
+            Some(seed) => Self::derive_from_seed(seed, "nonce", nonce.into()) as u32,
+        }
+    }
+
+    fn next_private_key(&mut self) -> u64 {
+        let key = self.simulator.next_private_key();
+        match self.seed {
+            // 0 is not a valid private key.
+            Some(seed) => Self::derive_from_seed(seed, "private key", key).max(1),
+            None => key,
+        }
+    }
+
+    fn derive_from_seed(seed: u64, domain: &str, counter: u64) -> u64 {
+        let hash = hash(format!("{}:{}:{}", domain, seed, counter));
+        u64::from_le_bytes(hash.0[..8].try_into().unwrap())
+    }
+
     pub fn get_metadata(&mut self, address: GlobalAddress, key: &str) -> Option<MetadataValue> {
         self.simulator.get_metadata(address, key)
     }
 
     pub fn nft_ids(
         &mut self,
-        account: ComponentAddress,
+        component: ComponentAddress,
         resource_address: ResourceAddress,
     ) -> Vec<NonFungibleLocalId> {
-        let account_vault = self
+        let vaults = self
             .simulator
-            .get_component_vaults(account, resource_address);
-        let account_vault = account_vault.first();
-        account_vault.map_or(vec![], |vault_id| {
-            match self.simulator.inspect_non_fungible_vault(*vault_id) {
-                None => vec![],
-                Some((_amount, ids)) => ids.collect(),
+            .get_component_vaults(component, resource_address);
+        let mut ids = vec![];
+        for vault_id in vaults {
+            if let Some((_amount, vault_ids)) = self.simulator.inspect_non_fungible_vault(vault_id)
+            {
+                ids.extend(vault_ids);
             }
-        })
+        }
+        ids
+    }
+
+    /// Returns the balances of all the vaults of a resource owned by a component.
+    pub fn vault_balances(
+        &mut self,
+        component: ComponentAddress,
+        resource: ResourceAddress,
+    ) -> Vec<Decimal> {
+        self.vaults_with_balances(component, resource)
+            .into_iter()
+            .map(|(_, balance)| balance)
+            .collect()
+    }
+
+    /// Returns the ids and balances of all the vaults of a resource owned by a component.
+    pub fn vaults_with_balances(
+        &mut self,
+        component: ComponentAddress,
+        resource: ResourceAddress,
+    ) -> Vec<(NodeId, Decimal)> {
+        self.simulator
+            .get_component_vaults(component, resource)
+            .into_iter()
+            .filter_map(|vault_id| {
+                self.simulator
+                    .inspect_vault_balance(vault_id)
+                    .map(|balance| (vault_id, balance))
+            })
+            .collect()
+    }
+
+    pub fn component_vaults(
+        &mut self,
+        component: ComponentAddress,
+        resource: ResourceAddress,
+    ) -> Vec<NodeId> {
+        self.simulator.get_component_vaults(component, resource)
+    }
+
+    /// Returns the balance of a fungible vault, or `None` if there is no fungible vault with this
+    /// id.
+    pub fn fungible_vault_balance(&mut self, vault: NodeId) -> Option<Decimal> {
+        if !vault.is_internal_fungible_vault() {
+            return None;
+        }
+        self.simulator.inspect_fungible_vault(vault)
+    }
+
+    /// Returns the ids held by a non-fungible vault, or `None` if there is no non-fungible vault
+    /// with this id.
+    pub fn non_fungible_vault_balance(&mut self, vault: NodeId) -> Option<Vec<NonFungibleLocalId>> {
+        if !vault.is_internal_non_fungible_vault() {
+            return None;
+        }
+        self.simulator
+            .inspect_non_fungible_vault(vault)
+            .map(|(_amount, ids)| ids.collect())
     }
 
     pub fn balance(&mut self, account: ComponentAddress, resource: ResourceAddress) -> Decimal {
@@ -91,9 +473,100 @@ impl EngineInterface {
         &mut self,
         account: ComponentAddress,
         initial_amount: Decimal,
+        divisibility: u8,
     ) -> ResourceAddress {
         self.simulator
-            .create_fungible_resource(initial_amount, 18, account)
+            .create_fungible_resource(initial_amount, divisibility, account)
+    }
+
+    /// Executes the instruction creating a resource, deposits its initial supply to the given
+    /// account and returns the address of the resource.
+    pub fn new_resource(
+        &mut self,
+        create_instruction: InstructionV1,
+        account: ComponentAddress,
+    ) -> ResourceAddress {
+        let (manifest_builder, _) = self
+            .engine_manifest_builder()
+            .add_instruction_advanced(create_instruction);
+        let manifest = manifest_builder
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build();
+        self.execute_engine_manifest(manifest)
+            .expect_commit_success()
+            .new_resource_addresses()[0]
+    }
+
+    pub fn new_fungible_advanced(
+        &mut self,
+        account: ComponentAddress,
+        initial_amount: Decimal,
+        divisibility: u8,
+        resource_roles: FungibleResourceRoles,
+        metadata: Vec<(&str, MetadataValue)>,
+    ) -> ResourceAddress {
+        let mut metadata_init = MetadataInit::new();
+        for (key, value) in metadata {
+            metadata_init.set_metadata(key, value);
+        }
+        let manifest = self
+            .engine_manifest_builder()
+            .create_fungible_resource(
+                OwnerRole::None,
+                true,
+                divisibility,
+                resource_roles,
+                ModuleConfig {
+                    init: metadata_init,
+                    roles: RoleAssignmentInit::default(),
+                },
+                Some(initial_amount),
+            )
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build();
+        self.execute_engine_manifest(manifest)
+            .expect_commit_success()
+            .new_resource_addresses()[0]
+    }
+
+    /// Returns the total supply of a resource, or `None` if the resource does not track it.
+    pub fn total_supply(&self, resource: ResourceAddress) -> Option<Decimal> {
+        let reader = SystemDatabaseReader::new(self.simulator.substate_db());
+        if resource.is_fungible() {
+            reader
+                .read_typed_object_field::<FungibleResourceManagerTotalSupplyFieldPayload>(
+                    resource.as_node_id(),
+                    ModuleId::Main,
+                    FungibleResourceManagerField::TotalSupply.field_index(),
+                )
+                .ok()
+                .map(|payload| payload.fully_update_and_into_latest_version())
+        } else {
+            reader
+                .read_typed_object_field::<NonFungibleResourceManagerTotalSupplyFieldPayload>(
+                    resource.as_node_id(),
+                    ModuleId::Main,
+                    NonFungibleResourceManagerField::TotalSupply.field_index(),
+                )
+                .ok()
+                .map(|payload| payload.fully_update_and_into_latest_version())
+        }
+    }
+
+    /// Returns the divisibility of a resource, 0 for a non-fungible resource, or `None` if the
+    /// resource does not exist.
+    pub fn divisibility(&self, resource: ResourceAddress) -> Option<u8> {
+        if !resource.is_fungible() {
+            return Some(0);
+        }
+        SystemDatabaseReader::new(self.simulator.substate_db())
+            .read_typed_object_field::<FungibleResourceManagerDivisibilityFieldPayload>(
+                resource.as_node_id(),
+                ModuleId::Main,
+                FungibleResourceManagerField::Divisibility.field_index(),
+            )
+            .ok()
+            .map(|payload| payload.fully_update_and_into_latest_version())
     }
 
     pub fn set_epoch(&mut self, epoch: Epoch) {
@@ -104,14 +577,100 @@ impl EngineInterface {
         self.simulator.get_current_epoch()
     }
 
-    pub fn advance_time(&mut self, time: u64) {
-        let current_time = self
-            .simulator
-            .get_current_time(TimePrecisionV2::Second)
-            .seconds_since_unix_epoch;
+    pub fn genesis_epoch(&self) -> Epoch {
+        self.genesis_epoch
+    }
+
+    /// Moves to the next epoch through the consensus manager, so that the validator set is updated
+    /// and the emissions are distributed.
+    pub fn next_epoch_with_consensus(&mut self) {
+        let epoch = self.get_epoch();
+        while self.get_epoch() == epoch {
+            let next_round =
+                Round::of(self.simulator.get_consensus_manager_state().round.number() + 1);
+            self.simulator
+                .advance_to_round(next_round)
+                .expect_commit_success();
+        }
+    }
+
+    pub fn new_validator_key(&mut self) -> Secp256k1PublicKey {
+        Secp256k1PrivateKey::from_u64(self.next_private_key())
+            .unwrap()
+            .public_key()
+    }
+
+    /// Returns the balance of the royalty vault of a component, or `None` if the component has
+    /// no royalties.
+    pub fn component_royalty_balance(&self, component: ComponentAddress) -> Option<Decimal> {
+        let reader = SystemDatabaseReader::new(self.simulator.substate_db());
+        let accumulator = reader
+            .read_typed_object_field::<ComponentRoyaltyAccumulatorFieldPayload>(
+                component.as_node_id(),
+                ModuleId::Royalty,
+                ComponentRoyaltyField::Accumulator.field_index(),
+            )
+            .ok()?
+            .fully_update_and_into_latest_version();
+        let balance = reader
+            .read_typed_object_field::<FungibleVaultBalanceFieldPayload>(
+                accumulator.royalty_vault.0.as_node_id(),
+                ModuleId::Main,
+                FungibleVaultField::Balance.field_index(),
+            )
+            .unwrap()
+            .fully_update_and_into_latest_version();
+        Some(balance.amount())
+    }
+
+    pub fn get_validator_info(&self, validator: ComponentAddress) -> ValidatorSubstate {
+        self.simulator.get_validator_info(validator)
+    }
+
+    pub fn active_validators(&self) -> Vec<ComponentAddress> {
+        let reader = SystemDatabaseReader::new(self.simulator.substate_db());
+        reader
+            .read_typed_object_field::<ConsensusManagerCurrentValidatorSetFieldPayload>(
+                CONSENSUS_MANAGER.as_node_id(),
+                ModuleId::Main,
+                ConsensusManagerField::CurrentValidatorSet.field_index(),
+            )
+            .unwrap()
+            .fully_update_and_into_latest_version()
+            .validator_set
+            .validators_by_stake_desc
+            .into_keys()
+            .collect()
+    }
+
+    pub fn create_snapshot(&self) -> LedgerSimulatorSnapshot {
+        self.simulator.create_snapshot()
+    }
+
+    pub fn restore_snapshot(&mut self, snapshot: LedgerSimulatorSnapshot) {
+        self.simulator.restore_snapshot(snapshot);
+    }
+
+    pub fn advance_time_ms(&mut self, time_ms: u64) {
+        // The target is computed from the exact millisecond timestamp of the ledger so that
+        // repeated small advances never accumulate rounding errors.
+        let target = self.get_current_time_ms() + time_ms as i64;
+        self.set_current_time_ms(target);
+    }
 
+    pub fn set_current_time_ms(&mut self, timestamp_ms: i64) {
+        let next_round = Round::of(self.simulator.get_consensus_manager_state().round.number() + 1);
         self.simulator
-            .advance_to_round_at_timestamp(Round::of(1), (current_time + (time as i64)) * 1000);
+            .advance_to_round_at_timestamp(next_round, timestamp_ms)
+            .expect_commit_success();
+    }
+
+    pub fn get_current_time_ms(&mut self) -> i64 {
+        self.simulator.get_current_proposer_timestamp_ms()
+    }
+
+    pub fn get_current_time(&mut self, precision: TimePrecision) -> Instant {
+        self.simulator.get_current_time(precision)
     }
 
     pub fn create_pre_allocated_token(
@@ -121,41 +680,110 @@ impl EngineInterface {
         network_definition: NetworkDefinition,
         default_account: &Account,
     ) -> ResourceAddress {
-        let dec = AddressBech32Decoder::new(&network_definition);
-        let mut pre_allocated_addresses: Vec<PreAllocatedAddress> = Vec::new();
+        let resource_addr = Self::decode_global_address(address, &network_definition);
 
-        let resource_addr: GlobalAddress = GlobalAddress::try_from_bech32(&dec, address).unwrap();
+        self.create_pre_allocated_resource(
+            resource_addr,
+            FUNGIBLE_RESOURCE_MANAGER_BLUEPRINT,
+            InstructionV1::CallFunction {
+                package_address: RESOURCE_PACKAGE.into(),
+                blueprint_name: FUNGIBLE_RESOURCE_MANAGER_BLUEPRINT.to_string(),
+                function_name: FUNGIBLE_RESOURCE_MANAGER_CREATE_WITH_INITIAL_SUPPLY_IDENT
+                    .to_string(),
+                args: to_manifest_value_and_unwrap!(
+                    &FungibleResourceManagerCreateWithInitialSupplyManifestInput {
+                        owner_role: OwnerRole::None,
+                        divisibility: 18,
+                        track_total_supply: false,
+                        metadata: Default::default(),
+                        resource_roles: FungibleResourceRoles::default(),
+                        initial_supply,
+                        address_reservation: Some(ManifestAddressReservation(0)),
+                    }
+                ),
+            },
+            default_account,
+        )
+    }
 
-        pre_allocated_addresses.push(
-            (
-                BlueprintId {
-                    package_address: RESOURCE_PACKAGE,
-                    blueprint_name: FUNGIBLE_RESOURCE_MANAGER_BLUEPRINT.to_string(),
-                },
-                resource_addr,
-            )
-                .into(),
-        );
+    pub fn create_pre_allocated_non_fungible<D: ManifestEncode + NonFungibleData>(
+        &mut self,
+        address: &str,
+        initial_nfts: Vec<(NonFungibleLocalId, D)>,
+        network_definition: NetworkDefinition,
+        default_account: &Account,
+    ) -> ResourceAddress {
+        let resource_addr = Self::decode_global_address(address, &network_definition);
+        if resource_addr.as_node_id().entity_type()
+            != Some(EntityType::GlobalNonFungibleResourceManager)
+        {
+            panic!("Address {} is not a non-fungible resource address", address);
+        }
+
+        let id_type = initial_nfts
+            .first()
+            .map(|(id, _)| id.id_type())
+            .unwrap_or(NonFungibleIdType::Integer);
+        let entries = initial_nfts
+            .into_iter()
+            .map(|(id, data)| (id, (to_manifest_value_and_unwrap!(&data),)))
+            .collect();
+
+        self.create_pre_allocated_resource(
+            resource_addr,
+            NON_FUNGIBLE_RESOURCE_MANAGER_BLUEPRINT,
+            InstructionV1::CallFunction {
+                package_address: RESOURCE_PACKAGE.into(),
+                blueprint_name: NON_FUNGIBLE_RESOURCE_MANAGER_BLUEPRINT.to_string(),
+                function_name: NON_FUNGIBLE_RESOURCE_MANAGER_CREATE_WITH_INITIAL_SUPPLY_IDENT
+                    .to_string(),
+                args: to_manifest_value_and_unwrap!(
+                    &NonFungibleResourceManagerCreateWithInitialSupplyManifestInput {
+                        owner_role: OwnerRole::None,
+                        id_type,
+                        track_total_supply: false,
+                        non_fungible_schema:
+                            NonFungibleDataSchema::new_local_without_self_package_replacement::<D>(
+                            ),
+                        entries,
+                        resource_roles: NonFungibleResourceRoles::default(),
+                        metadata: Default::default(),
+                        address_reservation: Some(ManifestAddressReservation(0)),
+                    }
+                ),
+            },
+            default_account,
+        )
+    }
+
+    fn decode_global_address(
+        address: &str,
+        network_definition: &NetworkDefinition,
+    ) -> GlobalAddress {
+        let dec = AddressBech32Decoder::new(network_definition);
+        GlobalAddress::try_from_bech32(&dec, address)
+            .unwrap_or_else(|| panic!("Could not decode address {}", address))
+    }
+
+    fn create_pre_allocated_resource(
+        &mut self,
+        resource_addr: GlobalAddress,
+        blueprint_name: &str,
+        create_instruction: InstructionV1,
+        default_account: &Account,
+    ) -> ResourceAddress {
+        let pre_allocated_addresses: Vec<PreAllocatedAddress> = vec![(
+            BlueprintId {
+                package_address: RESOURCE_PACKAGE,
+                blueprint_name: blueprint_name.to_string(),
+            },
+            resource_addr,
+        )
+            .into()];
 
         let receipt = self.simulator.execute_system_transaction(
             vec![
-                InstructionV1::CallFunction {
-                    package_address: RESOURCE_PACKAGE.into(),
-                    blueprint_name: FUNGIBLE_RESOURCE_MANAGER_BLUEPRINT.to_string(),
-                    function_name: FUNGIBLE_RESOURCE_MANAGER_CREATE_WITH_INITIAL_SUPPLY_IDENT
-                        .to_string(),
-                    args: to_manifest_value_and_unwrap!(
-                        &FungibleResourceManagerCreateWithInitialSupplyManifestInput {
-                            owner_role: OwnerRole::None,
-                            divisibility: 18,
-                            track_total_supply: false,
-                            metadata: Default::default(),
-                            resource_roles: FungibleResourceRoles::default(),
-                            initial_supply,
-                            address_reservation: Some(ManifestAddressReservation(0)),
-                        }
-                    ),
-                },
+                create_instruction,
                 InstructionV1::CallMethod {
                     address: DynamicGlobalAddress::Static(GlobalAddress::new_or_panic(
                         (*default_account.address()).into(),
@@ -177,6 +805,84 @@ impl EngineInterface {
         self.simulator.component_state(component_address)
     }
 
+    pub fn set_state<T: ScryptoEncode + ScryptoDecode, F: FnOnce(&mut T)>(
+        &mut self,
+        component_address: ComponentAddress,
+        f: F,
+    ) {
+        let node_id = component_address.as_node_id();
+        let substate_key: SubstateKey = ComponentField::State0.into();
+        let substate = self
+            .simulator
+            .substate_db()
+            .get_mapped::<SpreadPrefixKeyMapper, FieldSubstate<ScryptoValue>>(
+                node_id,
+                MAIN_BASE_PARTITION,
+                &substate_key,
+            )
+            .unwrap_or_else(|| panic!("Component {:?} has no state", component_address));
+        let lock_status = substate.lock_status();
+
+        let mut state: T = scrypto_decode(&scrypto_encode(&substate.into_payload()).unwrap())
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Could not decode the state of component {:?} as {}: {:?}",
+                    component_address,
+                    std::any::type_name::<T>(),
+                    err
+                )
+            });
+        f(&mut state);
+
+        self.simulator
+            .substate_db_mut()
+            .put_mapped::<SpreadPrefixKeyMapper, _>(
+                node_id,
+                MAIN_BASE_PARTITION,
+                &substate_key,
+                &FieldSubstate::new_field(state, lock_status),
+            );
+    }
+
+    /// Returns the state of a component without decoding it, or `None` if the component has no
+    /// state.
+    pub fn get_raw_state(&self, component_address: ComponentAddress) -> Option<ScryptoValue> {
+        let substate_key: SubstateKey = ComponentField::State0.into();
+        self.simulator
+            .substate_db()
+            .get_mapped::<SpreadPrefixKeyMapper, FieldSubstate<ScryptoValue>>(
+                component_address.as_node_id(),
+                MAIN_BASE_PARTITION,
+                &substate_key,
+            )
+            .map(|substate| substate.into_payload())
+    }
+
+    /// Returns the names of the fields of the state of a component, read from the schema of its
+    /// blueprint, or `None` if the state is not a struct with named fields.
+    pub fn state_field_names(&self, component_address: ComponentAddress) -> Option<Vec<String>> {
+        let reader = SystemDatabaseReader::new(self.simulator.substate_db());
+        let target = reader
+            .get_blueprint_type_target(component_address.as_node_id(), ModuleId::Main)
+            .ok()?;
+        let payload_schema = reader
+            .get_blueprint_payload_schema(
+                &target,
+                &BlueprintPayloadIdentifier::Field(ComponentField::State0.field_index()),
+            )
+            .ok()?;
+        let metadata = payload_schema
+            .schema
+            .v1()
+            .resolve_type_metadata(payload_schema.type_id)?;
+        match &metadata.child_names {
+            Some(ChildNames::NamedFields(names)) => {
+                Some(names.iter().map(|name| name.to_string()).collect())
+            }
+            _ => None,
+        }
+    }
+
     pub fn get_kvs_entry<K: ScryptoEncode, V: ScryptoEncode + ScryptoDecode>(
         &self,
         kv_store_id: Own,
@@ -185,6 +891,138 @@ impl EngineInterface {
         self.simulator.get_kv_store_entry(kv_store_id, key)
     }
 
+    /// Returns the blueprint of a global entity, or `None` if the address is not an object.
+    pub fn blueprint_of(&self, address: GlobalAddress) -> Option<BlueprintId> {
+        SystemDatabaseReader::new(self.simulator.substate_db())
+            .get_object_info(address)
+            .ok()
+            .map(|info| info.blueprint_info.blueprint_id)
+    }
+
+    /// Returns the schema of the payload of an event, or `None` if the emitter no longer exists
+    /// or the event has no static schema.
+    pub fn event_schema(
+        &self,
+        event_type_identifier: &EventTypeIdentifier,
+    ) -> Option<(LocalTypeId, VersionedScryptoSchema)> {
+        let reader = SystemDatabaseReader::new(self.simulator.substate_db());
+        let EventTypeIdentifier(emitter, name) = event_type_identifier;
+        let blueprint_id = match emitter {
+            Emitter::Method(node_id, ModuleId::Main) => match reader.get_type_info(node_id).ok()? {
+                TypeInfoSubstate::Object(info) => info.blueprint_info.blueprint_id,
+                _ => return None,
+            },
+            Emitter::Method(_, module) => module.static_blueprint()?,
+            Emitter::Function(blueprint_id) => blueprint_id.clone(),
+        };
+        let definition = reader.get_blueprint_definition(&blueprint_id).ok()?;
+        match definition.interface.get_event_payload_def(name)? {
+            BlueprintPayloadDef::Static(type_identifier) => {
+                let schema = reader
+                    .get_schema(
+                        blueprint_id.package_address.as_node_id(),
+                        &type_identifier.0,
+                    )
+                    .ok()?;
+                Some((type_identifier.1, schema.as_ref().clone()))
+            }
+            BlueprintPayloadDef::Generic(_) => None,
+        }
+    }
+
+    /// Returns the names of the blueprints of a package.
+    pub fn blueprint_names(&self, package_address: PackageAddress) -> Vec<String> {
+        SystemDatabaseReader::new(self.simulator.substate_db())
+            .get_package_definition(package_address)
+            .into_keys()
+            .map(|key| key.blueprint)
+            .collect()
+    }
+
+    /// Returns the functions and methods of a blueprint, or `None` if the blueprint does not
+    /// exist.
+    pub fn blueprint_functions(
+        &self,
+        blueprint_id: &BlueprintId,
+    ) -> Option<Vec<BlueprintFunction>> {
+        let reader = SystemDatabaseReader::new(self.simulator.substate_db());
+        let definition = reader.get_blueprint_definition(blueprint_id).ok()?;
+        let functions = definition
+            .interface
+            .functions
+            .iter()
+            .map(|(name, schema)| {
+                let arity = match &schema.input {
+                    BlueprintPayloadDef::Static(ScopedTypeId(schema_hash, type_id)) => reader
+                        .get_schema(blueprint_id.package_address.as_node_id(), schema_hash)
+                        .ok()
+                        .and_then(|schema| match schema.v1().resolve_type_kind(*type_id) {
+                            Some(TypeKind::Tuple { field_types }) => Some(field_types.len()),
+                            _ => None,
+                        }),
+                    BlueprintPayloadDef::Generic(_) => None,
+                };
+                BlueprintFunction {
+                    name: name.clone(),
+                    is_method: schema.receiver.is_some(),
+                    arity,
+                }
+            })
+            .collect();
+        Some(functions)
+    }
+
+    /// Returns the non-fungible data of an NFT without decoding it.
+    pub fn get_raw_non_fungible_data(
+        &self,
+        resource_address: ResourceAddress,
+        id: NonFungibleLocalId,
+    ) -> Option<ScryptoValue> {
+        let reader = SystemDatabaseReader::new(self.simulator.substate_db());
+        let payload = reader
+            .read_object_collection_entry::<_, NonFungibleResourceManagerDataEntryPayload>(
+                resource_address.as_node_id(),
+                ModuleId::Main,
+                ObjectCollectionKey::KeyValue(
+                    NonFungibleResourceManagerCollection::DataKeyValue.collection_index(),
+                    &id,
+                ),
+            )
+            .ok()??;
+        Some(scrypto_decode(&scrypto_encode(&payload).unwrap()).unwrap())
+    }
+
+    /// Returns the names of the fields of the non-fungible data of a resource, read from the
+    /// schema of the resource, or `None` if the data is not a struct with named fields.
+    pub fn non_fungible_field_names(
+        &self,
+        resource_address: ResourceAddress,
+    ) -> Option<Vec<String>> {
+        let reader = SystemDatabaseReader::new(self.simulator.substate_db());
+        let target = reader
+            .get_blueprint_type_target(resource_address.as_node_id(), ModuleId::Main)
+            .ok()?;
+        let payload_schema = reader
+            .get_blueprint_payload_schema(
+                &target,
+                &BlueprintPayloadIdentifier::KeyValueEntry(
+                    NonFungibleResourceManagerCollection::DataKeyValue.collection_index(),
+                    KeyOrValue::Value,
+                ),
+            )
+            .ok()?;
+        let metadata = payload_schema
+            .schema
+            .v1()
+            .resolve_type_metadata(payload_schema.type_id)?;
+        match &metadata.child_names {
+            Some(ChildNames::NamedFields(names)) => {
+                Some(names.iter().map(|name| name.to_string()).collect())
+            }
+            _ => None,
+        }
+    }
+
     pub fn get_non_fungible_data<T: NonFungibleData>(
         &mut self,
         resource_address: ResourceAddress,
@@ -193,3 +1031,22 @@ impl EngineInterface {
         self.simulator.get_non_fungible_data(resource_address, id)
     }
 }
+
+/// Function or method of a blueprint.
+pub struct BlueprintFunction {
+    pub name: String,
+    pub is_method: bool,
+    /// Number of arguments, or `None` if it could not be read from the schema of the blueprint.
+    pub arity: Option<usize>,
+}
+
+/// Options of the execution of a transaction built by the user.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecutionOptions {
+    /// Whether the kernel trace is printed.
+    pub kernel_trace: bool,
+    /// Whether the transaction is executed with an execution trace.
+    pub execution_trace: bool,
+    /// Maximum number of execution cost units the transaction can consume.
+    pub cost_unit_limit: Option<u32>,
+}
@@ -29,6 +29,24 @@ pub enum Environment<N: ReferenceName + Clone> {
     Component(N),
     Package(N),
     Resource(N),
+    /// Bucket previously created in the same call with `CallBuilder::take_bucket` or
+    /// `CallBuilder::take_from_worktop_named`.
+    Bucket(N),
+    /// Proof previously created in the same call with `CallBuilder::create_proof_named`.
+    Proof(N),
+    /// Same as [`Fungible::Bucket`].
+    FungibleBucket(N, Decimal),
+    /// Same as [`Fungible::Proof`].
+    FungibleProof(N, Decimal),
+    /// Same as [`NonFungible::Bucket`].
+    NonFungibleBucket(N, Vec<NonFungibleLocalId>),
+    /// Same as [`NonFungible::Proof`].
+    NonFungibleProof(N, Vec<NonFungibleLocalId>),
+    /// Address reservation previously created in the same call with
+    /// `CallBuilder::allocate_address`.
+    AddressReservation(N),
+    /// Address previously allocated in the same call with `CallBuilder::allocate_address`.
+    NamedAddress(N),
 }
 
 impl<N: ReferenceName + Clone> ToEncode for Environment<N> {
@@ -36,7 +54,7 @@ impl<N: ReferenceName + Clone> ToEncode for Environment<N> {
         &self,
         test_engine: &mut TestEngine,
         manifest_builder: ManifestBuilder,
-        _caller: ComponentAddress,
+        caller: ComponentAddress,
     ) -> (
         ManifestBuilder,
         Box<dyn Encode<ManifestCustomValueKind, ManifestEncoder<'a>>>,
@@ -58,6 +76,42 @@ impl<N: ReferenceName + Clone> ToEncode for Environment<N> {
                 let package = test_engine.get_package(address.clone());
                 (manifest_builder, Box::new(package))
             }
+            Environment::Bucket(name) => {
+                let bucket = manifest_builder.bucket(name.format());
+                (manifest_builder, Box::new(bucket))
+            }
+            Environment::Proof(name) => {
+                let proof = manifest_builder.proof(name.format());
+                (manifest_builder, Box::new(proof))
+            }
+            Environment::AddressReservation(name) => {
+                let reservation = manifest_builder.address_reservation(name.format());
+                (manifest_builder, Box::new(reservation))
+            }
+            Environment::NamedAddress(name) => {
+                let address = manifest_builder.named_address(name.format());
+                (manifest_builder, Box::new(address))
+            }
+            Environment::FungibleBucket(resource, amount) => Fungible::Bucket(
+                resource.clone(),
+                *amount,
+            )
+            .to_encode(test_engine, manifest_builder, caller),
+            Environment::FungibleProof(resource, amount) => Fungible::Proof(
+                resource.clone(),
+                *amount,
+            )
+            .to_encode(test_engine, manifest_builder, caller),
+            Environment::NonFungibleBucket(resource, ids) => NonFungible::Bucket(
+                resource.clone(),
+                ids.clone(),
+            )
+            .to_encode(test_engine, manifest_builder, caller),
+            Environment::NonFungibleProof(resource, ids) => NonFungible::Proof(
+                resource.clone(),
+                ids.clone(),
+            )
+            .to_encode(test_engine, manifest_builder, caller),
         }
     }
 }
@@ -84,6 +138,39 @@ where
     BucketFromWorkTop(R, D),
     Proof(R, D),
     ProofFromAuthZone(R, D),
+    /// Bucket of XRD taken from the faucet in the same transaction, so that the caller does not
+    /// need to hold any XRD. The faucet gives 10000 XRD once per transaction, and the XRD left on
+    /// the worktop is deposited to the caller. Build it with [`Fungible::from_faucet`].
+    FromFaucet(D),
+    /// Empty bucket of a resource, which the caller does not need to hold. Build it with
+    /// [`Fungible::empty`].
+    Empty(R),
+}
+
+/// XRD given by the faucet in a transaction.
+const FAUCET_FREE_AMOUNT: Decimal = dec!(10000);
+
+impl<D: TryInto<Decimal> + Clone> Fungible<&'static str, D>
+where
+    <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+{
+    /// Returns a [`Fungible::FromFaucet`] bucket.
+    ///
+    /// # Arguments
+    /// * `amount`: amount of XRD to take from the faucet.
+    pub fn from_faucet(amount: D) -> Self {
+        Fungible::FromFaucet(amount)
+    }
+}
+
+impl<R: ResourceReference + Clone> Fungible<R, Decimal> {
+    /// Returns a [`Fungible::Empty`] bucket.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource of the bucket.
+    pub fn empty(resource: R) -> Self {
+        Fungible::Empty(resource)
+    }
 }
 
 impl<R: ResourceReference + Clone, D: TryInto<Decimal> + Clone> ToEncode for Fungible<R, D>
@@ -103,6 +190,7 @@ where
             Fungible::Bucket(resource, amount) => {
                 let resource_address = resource.address(test_engine);
                 let amount = amount.clone().try_into().unwrap();
+                test_engine.check_divisibility(resource_address, amount);
 
                 let manifest_builder = manifest_builder.call_method(
                     caller,
@@ -156,6 +244,34 @@ where
                 );
                 (manifest_builder, Box::new(proof.new_proof.unwrap()))
             }
+            Fungible::FromFaucet(amount) => {
+                let amount = amount.clone().try_into().unwrap();
+                if amount > FAUCET_FREE_AMOUNT {
+                    panic!(
+                        "Cannot take {} XRD from the faucet, which gives {} XRD per transaction",
+                        amount, FAUCET_FREE_AMOUNT
+                    );
+                }
+
+                let manifest_builder =
+                    manifest_builder.call_method(FAUCET, "free", manifest_args!());
+                let (manifest_builder, bucket) =
+                    manifest_builder.add_instruction_advanced(InstructionV1::TakeFromWorktop {
+                        resource_address: XRD,
+                        amount,
+                    });
+                (manifest_builder, Box::new(bucket.new_bucket.unwrap()))
+            }
+            Fungible::Empty(resource) => {
+                let resource_address = resource.address(test_engine);
+
+                let (manifest_builder, bucket) =
+                    manifest_builder.add_instruction_advanced(InstructionV1::TakeFromWorktop {
+                        resource_address,
+                        amount: Decimal::ZERO,
+                    });
+                (manifest_builder, Box::new(bucket.new_bucket.unwrap()))
+            }
         }
     }
 }
@@ -241,6 +357,8 @@ pub enum NonFungible<R: ResourceReference + Clone> {
     BucketFromWorktop(R, Vec<NonFungibleLocalId>),
     Proof(R, Vec<NonFungibleLocalId>),
     ProofFromAuthZone(R, Vec<NonFungibleLocalId>),
+    /// Empty bucket of a resource, which the caller does not need to hold.
+    Empty(R),
 }
 
 impl<R: ResourceReference + Clone> ToEncode for NonFungible<R> {
@@ -305,6 +423,16 @@ impl<R: ResourceReference + Clone> ToEncode for NonFungible<R> {
                 );
                 (manifest_builder, Box::new(proof.new_proof.unwrap()))
             }
+            NonFungible::Empty(resource) => {
+                let resource_address = resource.address(test_engine);
+                let (manifest_builder, bucket) = manifest_builder.add_instruction_advanced(
+                    InstructionV1::TakeNonFungiblesFromWorktop {
+                        resource_address,
+                        ids: vec![],
+                    },
+                );
+                (manifest_builder, Box::new(bucket.new_bucket.unwrap()))
+            }
         }
     }
 }
@@ -369,6 +497,20 @@ impl<R: ResourceReference + Clone> ToEncode for NonFungibleAll<R> {
     }
 }
 
+impl<R: ResourceReference + Clone> EnvironmentEncode for NonFungibleAll<R> {
+    fn encode(
+        &self,
+        test_engine: &mut TestEngine,
+        manifest_builder: ManifestBuilder,
+        encoder: &mut ManifestEncoder,
+        caller: ComponentAddress,
+    ) -> ManifestBuilder {
+        let (manifest_builder, encoded) = self.to_encode(test_engine, manifest_builder, caller);
+        encoder.encode(encoded.as_ref()).expect("Could not encode");
+        manifest_builder
+    }
+}
+
 pub struct EnvVec {
     elements: Vec<Box<dyn ToEncode>>,
 }
@@ -419,6 +561,316 @@ impl EnvironmentEncode for EnvVec {
     }
 }
 
+impl ToEncode for EnvVec {
+    fn to_encode<'a>(
+        &self,
+        test_engine: &mut TestEngine,
+        manifest_builder: ManifestBuilder,
+        caller: ComponentAddress,
+    ) -> (
+        ManifestBuilder,
+        Box<dyn Encode<ManifestCustomValueKind, ManifestEncoder<'a>>>,
+    ) {
+        let (manifest_builder, value) =
+            to_manifest_value(self, test_engine, manifest_builder, caller);
+        (manifest_builder, Box::new(value))
+    }
+}
+
+pub struct EnvMap {
+    entries: Vec<(Box<dyn ToEncode>, Box<dyn ToEncode>)>,
+    value_kinds: Option<(ManifestValueKind, ManifestValueKind)>,
+}
+
+impl EnvMap {
+    pub fn from_vec(entries: Vec<(Box<dyn ToEncode>, Box<dyn ToEncode>)>) -> Self {
+        Self {
+            entries,
+            value_kinds: None,
+        }
+    }
+
+    /// Creates an empty map of the given key and value types.
+    pub fn empty<K: Categorize<ManifestCustomValueKind>, V: Categorize<ManifestCustomValueKind>>(
+    ) -> Self {
+        Self {
+            entries: vec![],
+            value_kinds: Some((K::value_kind(), V::value_kind())),
+        }
+    }
+}
+
+impl EnvironmentEncode for EnvMap {
+    fn encode(
+        &self,
+        test_engine: &mut TestEngine,
+        manifest_builder: ManifestBuilder,
+        encoder: &mut ManifestEncoder,
+        caller: ComponentAddress,
+    ) -> ManifestBuilder {
+        let mut manifest_builder = manifest_builder;
+
+        encoder.write_value_kind(ValueKind::Map).expect("");
+        let size = self.entries.len();
+        let mut encoded = Vec::new();
+        for (key, value) in &self.entries {
+            let (mb, key) = key.to_encode(test_engine, manifest_builder, caller);
+            let (mb, value) = value.to_encode(test_engine, mb, caller);
+            manifest_builder = mb;
+            encoded.push((key, value));
+        }
+
+        match (encoded.first(), self.value_kinds) {
+            (Some((key, value)), _) => {
+                key.encode_value_kind(encoder).expect("Error");
+                value.encode_value_kind(encoder).expect("Error");
+            }
+            (None, Some((key_kind, value_kind))) => {
+                encoder.write_value_kind(key_kind).unwrap();
+                encoder.write_value_kind(value_kind).unwrap();
+            }
+            (None, None) => {
+                panic!("Cannot infer the key and value kinds of an empty map, use EnvMap::empty instead")
+            }
+        }
+        encoder.write_size(size).expect("");
+
+        for (key, value) in &encoded {
+            encoder.encode_deeper_body(key.as_ref()).expect("OK");
+            encoder.encode_deeper_body(value.as_ref()).expect("OK");
+        }
+        manifest_builder
+    }
+}
+
+impl ToEncode for EnvMap {
+    fn to_encode<'a>(
+        &self,
+        test_engine: &mut TestEngine,
+        manifest_builder: ManifestBuilder,
+        caller: ComponentAddress,
+    ) -> (
+        ManifestBuilder,
+        Box<dyn Encode<ManifestCustomValueKind, ManifestEncoder<'a>>>,
+    ) {
+        let (manifest_builder, value) =
+            to_manifest_value(self, test_engine, manifest_builder, caller);
+        (manifest_builder, Box::new(value))
+    }
+}
+
+pub struct EnvTuple {
+    elements: Vec<Box<dyn ToEncode>>,
+}
+
+impl EnvTuple {
+    pub fn from_vec(elements: Vec<Box<dyn ToEncode>>) -> Self {
+        Self { elements }
+    }
+}
+
+impl EnvironmentEncode for EnvTuple {
+    fn encode(
+        &self,
+        test_engine: &mut TestEngine,
+        manifest_builder: ManifestBuilder,
+        encoder: &mut ManifestEncoder,
+        caller: ComponentAddress,
+    ) -> ManifestBuilder {
+        let mut manifest_builder = manifest_builder;
+
+        encoder.write_value_kind(ValueKind::Tuple).expect("");
+        encoder.write_size(self.elements.len()).expect("");
+        for elem in &self.elements {
+            let (mb, encode) = elem.to_encode(test_engine, manifest_builder, caller);
+            manifest_builder = mb;
+            encoder.encode(encode.as_ref()).expect("OK");
+        }
+        manifest_builder
+    }
+}
+
+impl ToEncode for EnvTuple {
+    fn to_encode<'a>(
+        &self,
+        test_engine: &mut TestEngine,
+        manifest_builder: ManifestBuilder,
+        caller: ComponentAddress,
+    ) -> (
+        ManifestBuilder,
+        Box<dyn Encode<ManifestCustomValueKind, ManifestEncoder<'a>>>,
+    ) {
+        let (manifest_builder, value) =
+            to_manifest_value(self, test_engine, manifest_builder, caller);
+        (manifest_builder, Box::new(value))
+    }
+}
+
+/// Optional argument whose value can contain references resolved by the engine. `None` encodes
+/// the same way whatever the type expected by the blueprint.
+pub enum EnvOption {
+    Some(Box<dyn ToEncode>),
+    None,
+}
+
+impl EnvironmentEncode for EnvOption {
+    fn encode(
+        &self,
+        test_engine: &mut TestEngine,
+        manifest_builder: ManifestBuilder,
+        encoder: &mut ManifestEncoder,
+        caller: ComponentAddress,
+    ) -> ManifestBuilder {
+        match self {
+            EnvOption::Some(value) => encode_variant(
+                OPTION_VARIANT_SOME,
+                Some(value.as_ref()),
+                test_engine,
+                manifest_builder,
+                encoder,
+                caller,
+            ),
+            EnvOption::None => encode_variant(
+                OPTION_VARIANT_NONE,
+                None,
+                test_engine,
+                manifest_builder,
+                encoder,
+                caller,
+            ),
+        }
+    }
+}
+
+impl<T: ToEncode + 'static> From<Option<T>> for EnvOption {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => EnvOption::Some(Box::new(value)),
+            None => EnvOption::None,
+        }
+    }
+}
+
+impl ToEncode for EnvOption {
+    fn to_encode<'a>(
+        &self,
+        test_engine: &mut TestEngine,
+        manifest_builder: ManifestBuilder,
+        caller: ComponentAddress,
+    ) -> (
+        ManifestBuilder,
+        Box<dyn Encode<ManifestCustomValueKind, ManifestEncoder<'a>>>,
+    ) {
+        let (manifest_builder, value) =
+            to_manifest_value(self, test_engine, manifest_builder, caller);
+        (manifest_builder, Box::new(value))
+    }
+}
+
+/// Result argument whose value can contain references resolved by the engine.
+pub enum EnvResult {
+    Ok(Box<dyn ToEncode>),
+    Err(Box<dyn ToEncode>),
+}
+
+impl EnvironmentEncode for EnvResult {
+    fn encode(
+        &self,
+        test_engine: &mut TestEngine,
+        manifest_builder: ManifestBuilder,
+        encoder: &mut ManifestEncoder,
+        caller: ComponentAddress,
+    ) -> ManifestBuilder {
+        let (discriminator, value) = match self {
+            EnvResult::Ok(value) => (RESULT_VARIANT_OK, value),
+            EnvResult::Err(value) => (RESULT_VARIANT_ERR, value),
+        };
+        encode_variant(
+            discriminator,
+            Some(value.as_ref()),
+            test_engine,
+            manifest_builder,
+            encoder,
+            caller,
+        )
+    }
+}
+
+impl ToEncode for EnvResult {
+    fn to_encode<'a>(
+        &self,
+        test_engine: &mut TestEngine,
+        manifest_builder: ManifestBuilder,
+        caller: ComponentAddress,
+    ) -> (
+        ManifestBuilder,
+        Box<dyn Encode<ManifestCustomValueKind, ManifestEncoder<'a>>>,
+    ) {
+        let (manifest_builder, value) =
+            to_manifest_value(self, test_engine, manifest_builder, caller);
+        (manifest_builder, Box::new(value))
+    }
+}
+
+/// Encodes an enum variant with at most one field, as the variants of `Option` and `Result`.
+fn encode_variant(
+    discriminator: u8,
+    field: Option<&dyn ToEncode>,
+    test_engine: &mut TestEngine,
+    manifest_builder: ManifestBuilder,
+    encoder: &mut ManifestEncoder,
+    caller: ComponentAddress,
+) -> ManifestBuilder {
+    encoder.write_value_kind(ValueKind::Enum).expect("");
+    encoder.write_discriminator(discriminator).expect("");
+    match field {
+        None => {
+            encoder.write_size(0).expect("");
+            manifest_builder
+        }
+        Some(field) => {
+            encoder.write_size(1).expect("");
+            let (manifest_builder, encode) = field.to_encode(test_engine, manifest_builder, caller);
+            encoder.encode(encode.as_ref()).expect("OK");
+            manifest_builder
+        }
+    }
+}
+
+/// Encodes a collection and decodes it back to a [`ManifestValue`] so that it can be nested in
+/// another collection.
+fn to_manifest_value<E: EnvironmentEncode>(
+    collection: &E,
+    test_engine: &mut TestEngine,
+    manifest_builder: ManifestBuilder,
+    caller: ComponentAddress,
+) -> (ManifestBuilder, ManifestValue) {
+    let mut buf = Vec::new();
+    let mut encoder = ManifestEncoder::new(&mut buf, MANIFEST_SBOR_V1_MAX_DEPTH);
+    encoder
+        .write_payload_prefix(MANIFEST_SBOR_V1_PAYLOAD_PREFIX)
+        .unwrap();
+    let manifest_builder = collection.encode(test_engine, manifest_builder, &mut encoder, caller);
+
+    (manifest_builder, manifest_decode(&buf).unwrap())
+}
+
+impl<T: for<'a> Encode<ManifestCustomValueKind, ManifestEncoder<'a>> + Clone + 'static> ToEncode
+    for T
+{
+    fn to_encode<'a>(
+        &self,
+        _test_engine: &mut TestEngine,
+        manifest_builder: ManifestBuilder,
+        _caller: ComponentAddress,
+    ) -> (
+        ManifestBuilder,
+        Box<dyn Encode<ManifestCustomValueKind, ManifestEncoder<'a>>>,
+    ) {
+        (manifest_builder, Box::new(self.clone()))
+    }
+}
+
 impl<T: for<'a> Encode<ManifestCustomValueKind, ManifestEncoder<'a>>> EnvironmentEncode for T {
     fn encode(
         &self,
@@ -1,18 +1,132 @@
+use std::any::type_name;
+
 use crate::internal_prelude::*;
+use representations::{DisplayMode, PrintMode, ValueDisplayParameters};
 
 pub trait FromInstruction {
-    fn from(instructions: Vec<InstructionOutput>) -> Self;
+    fn from(output: &InstructionOutput) -> Self;
 }
 
 impl<T: ScryptoDecode> FromInstruction for T {
-    fn from(mut instructions: Vec<InstructionOutput>) -> Self {
-        instructions.pop();
-        let bytes = match instructions.pop().unwrap() {
+    fn from(output: &InstructionOutput) -> Self {
+        let bytes = match output {
             InstructionOutput::None => {
-                panic!("The method does not return anything")
+                panic!("The instruction is not a method or function call")
             }
             InstructionOutput::CallReturn(bytes) => bytes,
         };
-        scrypto_decode::<T>(&bytes).expect("Could not parse method return into given type 2")
+        scrypto_decode::<T>(bytes)
+            .unwrap_or_else(|error| panic!("{}", decode_error_message::<T>(bytes, error)))
+    }
+}
+
+/// Builds the message of a failed decoding of a method return into `T`, with a rendering of the
+/// value actually returned by the method.
+fn decode_error_message<T>(bytes: &[u8], error: DecodeError) -> String {
+    let expected = type_name::<T>();
+    let returned_arity = match scrypto_decode::<ScryptoValue>(bytes) {
+        Ok(ScryptoValue::Tuple { fields }) => Some(fields.len()),
+        _ => None,
+    };
+    match (returned_arity, tuple_arity(expected)) {
+        (Some(0), _) => format!(
+            "The method returned nothing, but a value of type {} was expected",
+            expected
+        ),
+        (Some(returned), Some(arity)) if returned != arity => format!(
+            "The method returned a tuple of {} values, but a tuple of {} values was expected: {}\nReturned value: {}",
+            returned,
+            arity,
+            expected,
+            render_value(bytes)
+        ),
+        _ => format!(
+            "Could not decode the return of the method into {}: {:?}\nReturned value: {}",
+            expected,
+            error,
+            render_value(bytes)
+        ),
+    }
+}
+
+/// Renders an encoded Scrypto value on a single line, as Rust-like code.
+fn render_value(bytes: &[u8]) -> String {
+    match ScryptoRawPayload::new_from_valid_slice_with_checks(bytes) {
+        Some(payload) => payload.to_string(ValueDisplayParameters::Schemaless {
+            display_mode: DisplayMode::RustLike,
+            print_mode: PrintMode::SingleLine,
+            custom_context: ScryptoValueDisplayContext::with_optional_bech32(None),
+            depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+        }),
+        None => format!("{:?}", scrypto_decode::<ScryptoValue>(bytes)),
+    }
+}
+
+/// Returns the number of fields of a tuple type from its name, or `None` if the type is not a
+/// tuple.
+fn tuple_arity(type_name: &str) -> Option<usize> {
+    let inner = type_name.strip_prefix('(')?.strip_suffix(')')?.trim();
+    if inner.is_empty() {
+        return Some(0);
+    }
+
+    let mut depth = 0;
+    let mut commas = 0;
+    for char in inner.chars() {
+        match char {
+            '(' | '<' | '[' => depth += 1,
+            ')' | '>' | ']' => depth -= 1,
+            ',' if depth == 0 => commas += 1,
+            _ => {}
+        }
+    }
+    if inner.ends_with(',') {
+        Some(commas)
+    } else {
+        Some(commas + 1)
+    }
+}
+
+#[cfg(test)]
+mod test_from_instruction {
+    use super::{decode_error_message, tuple_arity};
+    use crate::internal_prelude::*;
+
+    fn message<T: ScryptoDecode, V: ScryptoEncode>(value: V) -> String {
+        let bytes = scrypto_encode(&value).unwrap();
+        let error = scrypto_decode::<T>(&bytes).err().unwrap();
+        decode_error_message::<T>(&bytes, error)
+    }
+
+    #[test]
+    fn test_tuple_arity() {
+        assert_eq!(tuple_arity("()"), Some(0));
+        assert_eq!(tuple_arity("(u32,)"), Some(1));
+        assert_eq!(
+            tuple_arity("(u32, alloc::vec::Vec<(u8, u8)>, [u8; 2])"),
+            Some(3)
+        );
+        assert_eq!(tuple_arity("core::option::Option<(u8, u8)>"), None);
+    }
+
+    #[test]
+    fn test_returned_nothing_message() {
+        assert_eq!(
+            message::<Decimal, ()>(()),
+            "The method returned nothing, but a value of type radix_common::math::decimal::Decimal was expected"
+        );
+    }
+
+    #[test]
+    fn test_wrong_arity_message() {
+        assert_eq!(
+            message::<(u8, u8), _>((1u8, 2u8, 3u8)),
+            "The method returned a tuple of 3 values, but a tuple of 2 values was expected: (u8, u8)\nReturned value: Tuple(1u8, 2u8, 3u8)"
+        );
+    }
+
+    #[test]
+    fn test_wrong_type_message() {
+        assert!(message::<u64, _>(String::from("gumball")).ends_with("Returned value: \"gumball\""));
     }
 }
@@ -6,3 +6,9 @@ pub use radix_transactions::manifest::dumper::dump_manifest_to_file_system;
 pub use radix_transactions::model::InstructionV1;
 pub use radix_transactions::prelude::*;
 pub use scrypto_test::prelude::*;
+
+// Names re-exported by several of the preludes above
+pub use radix_common::prelude::TRANSACTION_TRACKER_BLUEPRINT;
+pub use radix_engine::system::system_modules::execution_trace::ResourceSpecifier;
+pub use radix_engine_interface::prelude::FungibleBucket;
+pub use radix_transactions::prelude::PrepareError;
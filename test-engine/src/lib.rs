@@ -3,12 +3,32 @@ mod engine_interface;
 mod from_instruction;
 mod references;
 
+mod assertions;
 mod call_builder;
+mod compiled_packages;
+mod component_proxy;
+mod cost_trend;
 mod environment;
 mod internal_prelude;
+mod logger;
 mod macros;
+mod manifest_file;
+mod manifest_layout;
+mod metadata_policy;
 mod method_call;
+mod multi_engine;
+mod nft_fixture;
 pub mod prelude;
+mod receipt_snapshot;
+mod receipt_summary;
 pub(crate) mod receipt_traits;
+mod recorder;
+mod resource_builder;
+mod scenario;
+mod seed;
+mod snapshot;
+#[cfg(feature = "proptest")]
+mod strategies;
 mod test_engine;
+mod test_engine_builder;
 mod to_id;
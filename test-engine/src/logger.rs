@@ -0,0 +1,156 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::internal_prelude::*;
+
+/// Information about an executed transaction, given to the logger of the engine.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionContext {
+    /// Title of the transaction, set with `CallBuilder::with_log_title`.
+    pub title: Option<String>,
+    /// Whether the fees of the transaction should be logged, set with
+    /// `CallBuilder::with_log_tx_fee`.
+    pub log_tx_fee: bool,
+}
+
+/// Receives every transaction executed by a [`TestEngine`](crate::prelude::TestEngine) and its
+/// warnings.
+pub trait TestLogger {
+    /// Called after the execution of a transaction.
+    ///
+    /// # Arguments
+    /// * `context`: information about the transaction.
+    /// * `receipt`: receipt of the transaction.
+    fn on_transaction(&mut self, context: &TransactionContext, receipt: &TransactionReceipt);
+
+    /// Called when the engine emits a warning, such as a reference name collision. Warnings are
+    /// ignored by default.
+    ///
+    /// # Arguments
+    /// * `warning`: message of the warning.
+    fn on_warning(&mut self, _warning: &str) {}
+}
+
+/// Default logger, printing the title, the fees and the application logs of the transactions, and
+/// the warnings of the engine, to the standard output.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutLogger;
+
+impl TestLogger for StdoutLogger {
+    fn on_transaction(&mut self, context: &TransactionContext, receipt: &TransactionReceipt) {
+        if let Some(title) = &context.title {
+            println!("\n{}", title);
+        }
+        if context.log_tx_fee {
+            println!("Transaction fee: {} XRD", receipt.fee_summary.total_cost());
+        }
+        if let TransactionResult::Commit(commit_result) = &receipt.result {
+            if !commit_result.application_logs.is_empty() {
+                println!("\nApplication logs:");
+                for (level, message) in &commit_result.application_logs {
+                    println!("| [{level}]: {message}")
+                }
+            }
+        }
+    }
+
+    fn on_warning(&mut self, warning: &str) {
+        println!("Warning: {}", warning);
+    }
+}
+
+/// Logger ignoring every transaction and warning.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullLogger;
+
+impl TestLogger for NullLogger {
+    fn on_transaction(&mut self, _context: &TransactionContext, _receipt: &TransactionReceipt) {}
+}
+
+#[derive(Debug, Default)]
+struct MemoryLogs {
+    titles: Vec<String>,
+    application_logs: Vec<(Level, String)>,
+    fee_summaries: Vec<TransactionFeeSummary>,
+    warnings: Vec<String>,
+}
+
+/// Logger keeping the titles, application logs and fee summaries of the transactions, and the
+/// warnings of the engine, in memory.
+/// Clones share the same records, so that a clone can be given to the engine and the records
+/// read from the original:
+///
+/// ```ignore
+/// let logger = MemoryLogger::new();
+/// test_engine.set_logger(logger.clone());
+/// test_engine.call_method("update_price", env_args!());
+/// assert!(logger.contains_log(Level::Warn, "price stale"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryLogger {
+    logs: Rc<RefCell<MemoryLogs>>,
+}
+
+impl MemoryLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the titles of the logged transactions.
+    pub fn titles(&self) -> Vec<String> {
+        self.logs.borrow().titles.clone()
+    }
+
+    /// Returns the application logs emitted by the committed transactions, in emission order.
+    pub fn application_logs(&self) -> Vec<(Level, String)> {
+        self.logs.borrow().application_logs.clone()
+    }
+
+    /// Returns the fee summaries of the transactions logged with `CallBuilder::with_log_tx_fee`.
+    pub fn fee_summaries(&self) -> Vec<TransactionFeeSummary> {
+        self.logs.borrow().fee_summaries.clone()
+    }
+
+    /// Returns the warnings emitted by the engine, in emission order.
+    pub fn warnings(&self) -> Vec<String> {
+        self.logs.borrow().warnings.clone()
+    }
+
+    /// Returns whether an application log of the given level contains a given text.
+    ///
+    /// # Arguments
+    /// * `level`: level of the log.
+    /// * `text`: text to look for in the message of the log.
+    pub fn contains_log(&self, level: Level, text: &str) -> bool {
+        self.logs
+            .borrow()
+            .application_logs
+            .iter()
+            .any(|(log_level, message)| *log_level == level && message.contains(text))
+    }
+
+    /// Removes all the records.
+    pub fn clear(&self) {
+        *self.logs.borrow_mut() = MemoryLogs::default();
+    }
+}
+
+impl TestLogger for MemoryLogger {
+    fn on_transaction(&mut self, context: &TransactionContext, receipt: &TransactionReceipt) {
+        let mut logs = self.logs.borrow_mut();
+        if let Some(title) = &context.title {
+            logs.titles.push(title.clone());
+        }
+        if context.log_tx_fee {
+            logs.fee_summaries.push(receipt.fee_summary.clone());
+        }
+        if let TransactionResult::Commit(commit_result) = &receipt.result {
+            logs.application_logs
+                .extend(commit_result.application_logs.iter().cloned());
+        }
+    }
+
+    fn on_warning(&mut self, warning: &str) {
+        self.logs.borrow_mut().warnings.push(warning.to_string());
+    }
+}
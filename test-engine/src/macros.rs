@@ -32,18 +32,57 @@ macro_rules! env_vec {
     }};
 }
 
+#[macro_export]
+macro_rules! env_map {
+     ($( $key:expr => $value:expr ),* $(,)?) => {{
+         use test_engine::prelude::*;
+
+         let mut temp_vec: Vec<(Box<dyn ToEncode>, Box<dyn ToEncode>)> = vec![];
+            $(
+                temp_vec.push((Box::new($key), Box::new($value)));
+            )*
+         EnvMap::from_vec(temp_vec)
+    }};
+}
+
+#[macro_export]
+macro_rules! env_tuple {
+    () => (
+        EnvTuple::from_vec(vec![])
+    );
+
+     ($( $x:expr ),*) => {{
+         use test_engine::prelude::*;
+
+         let mut temp_vec: Vec<Box<dyn ToEncode>> = vec![];
+            $(
+                temp_vec.push(Box::new($x));
+            )*
+         EnvTuple::from_vec(temp_vec)
+    }};
+}
+
 #[macro_export]
 macro_rules! global_package {
     ($name:ident, $path:expr) => {
         use test_engine::prelude::*;
 
         lazy_static! {
-            static ref $name: (Vec<u8>, PackageDefinition) =
-                { PackagePublishingSource::from($path).code_and_definition() };
+            static ref $name: (Vec<u8>, PackageDefinition) = { CompiledPackages::get($path) };
         }
     };
 }
 
+#[macro_export]
+macro_rules! global_packages {
+    ($( $name:ident => $path:expr ),* $(,)?) => {
+        $(
+            static $name: test_engine::prelude::CompiledPackage =
+                test_engine::prelude::CompiledPackage::new($path);
+        )*
+    };
+}
+
 #[macro_export]
 macro_rules! nf_ids {
     () => (
@@ -64,6 +103,75 @@ macro_rules! nf_ids {
 #[macro_export]
 macro_rules! none {
     () => {
-        None::<u64>
+        $crate::prelude::EnvOption::None
+    };
+}
+
+#[macro_export]
+macro_rules! env_some {
+    ($x:expr) => {
+        $crate::prelude::EnvOption::Some(Box::new($x))
+    };
+}
+
+#[macro_export]
+macro_rules! component_proxy {
+    ($name:ident { $( fn $method:ident ( $( $arg:ident : $ty:ty ),* $(,)? ); )* }) => {
+        $crate::component_proxy!($name for $name { $( fn $method ( $( $arg : $ty ),* ); )* });
+    };
+
+    ($name:ident for $blueprint:ident { $( fn $method:ident ( $( $arg:ident : $ty:ty ),* $(,)? ); )* }) => {
+        pub struct $name {
+            component: test_engine::prelude::ComponentAddress,
+        }
+
+        impl test_engine::prelude::ComponentProxy for $name {
+            const BLUEPRINT: &'static str = stringify!($blueprint);
+            const METHODS: &'static [(&'static str, usize)] = &[
+                $( (stringify!($method), <[&str]>::len(&[$( stringify!($arg) ),*])) ),*
+            ];
+
+            fn from_address(component: test_engine::prelude::ComponentAddress) -> Self {
+                Self { component }
+            }
+        }
+
+        impl $name {
+            pub fn address(&self) -> test_engine::prelude::ComponentAddress {
+                self.component
+            }
+
+            $(
+                pub fn $method(
+                    &self,
+                    engine: &mut test_engine::prelude::TestEngine,
+                    $( $arg: $ty ),*
+                ) -> test_engine::prelude::TransactionReceipt {
+                    use test_engine::prelude::*;
+
+                    engine.call_method_from(self.component, stringify!($method), env_args!($( $arg ),*))
+                }
+            )*
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! assert_dec_approx_eq {
+    ($actual:expr, $expected:expr, $tolerance:expr $(,)?) => {
+        if let Err(message) = $crate::prelude::check_dec_approx_eq($actual, $expected, $tolerance) {
+            panic!("{}", message);
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! assert_balance {
+    ($test_engine:expr, $entity:expr, $resource:expr, $expected:expr $(,)?) => {
+        if let Err(message) =
+            $crate::prelude::check_balance(&mut $test_engine, $entity, $resource, $expected)
+        {
+            panic!("{}", message);
+        }
     };
 }
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use radix_transactions::manifest::compiler::{
+    compile_error_diagnostics, CompileErrorDiagnosticsStyle,
+};
+use radix_transactions::manifest::{compile, BlobProvider, CompileError};
+
+use crate::engine_interface::ExecutionOptions;
+use crate::internal_prelude::*;
+use crate::logger::TransactionContext;
+use crate::test_engine::TestEngine;
+
+impl TestEngine {
+    /// Reads a manifest file, replaces its placeholders, then executes it signed by the current
+    /// account. The reference names of the engine are updated as for any other call.
+    ///
+    /// A placeholder `${key}` is replaced by the value of `key` in `substitutions` if there is
+    /// one. Otherwise `${account}` is replaced by the address of the current account, and any other
+    /// `${name}` by the address of the entity with this reference name, which can be prefixed by
+    /// its kind as in `${resource:gum}`.
+    /// Panics if the manifest cannot be read or compiled, with the line and column of the error.
+    ///
+    /// # Arguments
+    /// * `path`: path of the `.rtm` file.
+    /// * `substitutions`: values of the placeholders that are not reference names.
+    pub fn execute_manifest_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        substitutions: HashMap<&str, String>,
+    ) -> TransactionReceipt {
+        let path = path.as_ref();
+        let manifest = fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("Could not read {}: {}", path.display(), err));
+        let manifest = self.substitute_placeholders(path, &manifest, &substitutions);
+
+        let manifest =
+            compile(&manifest, &self.network(), BlobProvider::new()).unwrap_or_else(|err| {
+                let (line, column) = Self::compile_error_position(&err);
+                panic!(
+                    "Could not compile {} at line {}, column {}:\n{}",
+                    path.display(),
+                    line,
+                    column,
+                    compile_error_diagnostics(
+                        &manifest,
+                        err,
+                        CompileErrorDiagnosticsStyle::PlainText
+                    )
+                )
+            });
+
+        let account = self.current_account().clone();
+        let signers = self.signer_proof(&account).into_iter().collect();
+        self.execute_call(
+            manifest,
+            ExecutionOptions::default(),
+            signers,
+            true,
+            &TransactionContext::default(),
+        )
+    }
+
+    fn substitute_placeholders(
+        &self,
+        path: &Path,
+        manifest: &str,
+        substitutions: &HashMap<&str, String>,
+    ) -> String {
+        let mut substituted = String::with_capacity(manifest.len());
+        let mut rest = manifest;
+        while let Some(start) = rest.find("${") {
+            substituted.push_str(&rest[..start]);
+            let Some(length) = rest[start..].find('}') else {
+                let (line, column) =
+                    Self::text_position(manifest, manifest.len() - rest.len() + start);
+                panic!(
+                    "Unterminated placeholder in {} at line {}, column {}",
+                    path.display(),
+                    line,
+                    column
+                );
+            };
+            let key = &rest[start + 2..start + length];
+            let value = match substitutions.get(key) {
+                Some(value) => value.clone(),
+                None if key == "account" => {
+                    self.encode_address((*self.current_account_address()).into())
+                }
+                None => self.encode_address(self.get_global(key)),
+            };
+            substituted.push_str(&value);
+            rest = &rest[start + length + 1..];
+        }
+        substituted.push_str(rest);
+        substituted
+    }
+
+    /// Returns the 1-indexed line and column of the start of a compilation error.
+    fn compile_error_position(error: &CompileError) -> (usize, usize) {
+        let span = match error {
+            CompileError::LexerError(error) => error.span,
+            CompileError::ParserError(error) => error.span,
+            CompileError::GeneratorError(error) => error.span,
+        };
+        (span.start.line_number(), span.start.line_char_index + 1)
+    }
+
+    /// Returns the 1-indexed line and column of a byte offset of a text.
+    fn text_position(text: &str, offset: usize) -> (usize, usize) {
+        let before = &text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+        (line, column)
+    }
+}
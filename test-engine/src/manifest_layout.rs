@@ -0,0 +1,133 @@
+use std::fmt::{Display, Formatter};
+
+use crate::internal_prelude::*;
+
+/// Differences between the legacy layout of a call, where the fee lock and the badge proofs are
+/// injected at the start of the manifest, and the layout produced by the official wallet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutDiff {
+    /// Instructions of the manifest in the legacy layout.
+    pub legacy: Vec<String>,
+    /// Instructions of the manifest in the wallet compatible layout.
+    pub wallet: Vec<String>,
+    /// Instructions present in both layouts but at a different position.
+    pub moved: Vec<String>,
+    /// Instructions only present in the legacy layout.
+    pub legacy_only: Vec<String>,
+    /// Instructions only present in the wallet compatible layout.
+    pub wallet_only: Vec<String>,
+}
+
+impl LayoutDiff {
+    pub(crate) fn new(legacy: Vec<String>, wallet: Vec<String>) -> Self {
+        let mut moved = vec![];
+        let mut wallet_only = vec![];
+        let mut matched = vec![false; legacy.len()];
+
+        for (wallet_index, instruction) in wallet.iter().enumerate() {
+            let legacy_index = legacy
+                .iter()
+                .enumerate()
+                .position(|(i, other)| !matched[i] && other == instruction);
+            match legacy_index {
+                Some(legacy_index) => {
+                    matched[legacy_index] = true;
+                    if legacy_index != wallet_index {
+                        moved.push(instruction.clone());
+                    }
+                }
+                None => wallet_only.push(instruction.clone()),
+            }
+        }
+
+        let legacy_only = legacy
+            .iter()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+            .map(|(instruction, _)| instruction.clone())
+            .collect();
+
+        Self {
+            legacy,
+            wallet,
+            moved,
+            legacy_only,
+            wallet_only,
+        }
+    }
+
+    /// Returns whether both layouts are identical.
+    pub fn is_empty(&self) -> bool {
+        self.legacy == self.wallet
+    }
+}
+
+impl Display for LayoutDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Legacy layout:")?;
+        for (index, instruction) in self.legacy.iter().enumerate() {
+            writeln!(f, "| {index}: {instruction}")?;
+        }
+        writeln!(f, "Wallet layout:")?;
+        for (index, instruction) in self.wallet.iter().enumerate() {
+            writeln!(f, "| {index}: {instruction}")?;
+        }
+        for instruction in &self.moved {
+            writeln!(f, "Moved: {instruction}")?;
+        }
+        for instruction in &self.legacy_only {
+            writeln!(f, "Legacy only: {instruction}")?;
+        }
+        for instruction in &self.wallet_only {
+            writeln!(f, "Wallet only: {instruction}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Instructions injected by the toolkit in a manifest, as opposed to the ones given by the user.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InjectionSummary {
+    /// Index in the executed manifest and description of each injected instruction.
+    pub injected: Vec<(usize, String)>,
+}
+
+impl InjectionSummary {
+    pub(crate) fn new(
+        user_instructions: &[InstructionV1],
+        executed: &[InstructionV1],
+        descriptions: Vec<String>,
+    ) -> Self {
+        let mut user_instructions = user_instructions.iter().peekable();
+        let injected = executed
+            .iter()
+            .zip(descriptions)
+            .enumerate()
+            .filter_map(|(index, (instruction, description))| {
+                if user_instructions.peek() == Some(&instruction) {
+                    user_instructions.next();
+                    None
+                } else {
+                    Some((index, description))
+                }
+            })
+            .collect();
+
+        Self { injected }
+    }
+
+    /// Returns whether no instruction was injected.
+    pub fn is_empty(&self) -> bool {
+        self.injected.is_empty()
+    }
+}
+
+impl Display for InjectionSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Injected instructions:")?;
+        for (index, instruction) in &self.injected {
+            writeln!(f, "| {index}: {instruction}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use crate::internal_prelude::*;
+
+/// Metadata keys checked by a [`MetadataPolicy`].
+pub const POLICY_METADATA_KEYS: [&str; 4] = ["name", "symbol", "description", "icon_url"];
+
+/// Rules that the metadata of a resource should follow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataPolicy {
+    /// Keys that must be set.
+    pub required_keys: Vec<String>,
+    /// Maximum number of characters of the symbol.
+    pub symbol_max_length: Option<usize>,
+    /// Whether the symbol must be uppercase.
+    pub symbol_uppercase: bool,
+    /// Whether the description, if set, must not be empty.
+    pub non_empty_description: bool,
+    /// Whether the icon url, if set, must be a well-formed http(s) url.
+    /// The check is syntactic only.
+    pub valid_icon_url: bool,
+    /// Whether the symbol must differ from the symbols of the other resources.
+    pub unique_symbol: bool,
+}
+
+impl Default for MetadataPolicy {
+    fn default() -> Self {
+        Self {
+            required_keys: vec!["name".to_string(), "symbol".to_string()],
+            symbol_max_length: Some(8),
+            symbol_uppercase: true,
+            non_empty_description: true,
+            valid_icon_url: true,
+            unique_symbol: true,
+        }
+    }
+}
+
+/// Rule of a [`MetadataPolicy`] violated by the metadata of a resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataViolation {
+    /// Metadata key of the violation.
+    pub key: String,
+    /// Actual value of the key, if set.
+    pub value: Option<String>,
+    /// Violated rule.
+    pub rule: String,
+}
+
+impl Display for MetadataViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.value {
+            None => write!(f, "{} is not set: {}", self.key, self.rule),
+            Some(value) => write!(f, "{} = \"{}\": {}", self.key, value, self.rule),
+        }
+    }
+}
+
+impl MetadataPolicy {
+    /// Returns the violations of the policy by the given metadata.
+    ///
+    /// # Arguments
+    /// * `metadata`: metadata values of the resource, by key.
+    /// * `other_symbols`: symbols of the other resources, used to check symbol uniqueness.
+    pub fn check(
+        &self,
+        metadata: &HashMap<String, MetadataValue>,
+        other_symbols: &[String],
+    ) -> Vec<MetadataViolation> {
+        let mut violations = vec![];
+        let mut violation = |key: &str, value: Option<&String>, rule: String| {
+            violations.push(MetadataViolation {
+                key: key.to_string(),
+                value: value.cloned(),
+                rule,
+            })
+        };
+        let values: HashMap<&str, String> = metadata
+            .iter()
+            .map(|(key, value)| (key.as_str(), metadata_value_to_string(value)))
+            .collect();
+
+        for key in &self.required_keys {
+            if !values.contains_key(key.as_str()) {
+                violation(key, None, "key is required".to_string());
+            }
+        }
+
+        if let Some(symbol) = values.get("symbol") {
+            if let Some(max_length) = self.symbol_max_length {
+                if symbol.chars().count() > max_length {
+                    violation(
+                        "symbol",
+                        Some(symbol),
+                        format!("symbol is longer than {} characters", max_length),
+                    );
+                }
+            }
+            if self.symbol_uppercase && symbol.to_uppercase() != *symbol {
+                violation(
+                    "symbol",
+                    Some(symbol),
+                    "symbol is not uppercase".to_string(),
+                );
+            }
+            if self.unique_symbol && other_symbols.contains(symbol) {
+                violation(
+                    "symbol",
+                    Some(symbol),
+                    "symbol is already used by another resource".to_string(),
+                );
+            }
+        }
+
+        if let Some(description) = values.get("description") {
+            if self.non_empty_description && description.trim().is_empty() {
+                violation(
+                    "description",
+                    Some(description),
+                    "description is empty".to_string(),
+                );
+            }
+        }
+
+        if let Some(icon_url) = values.get("icon_url") {
+            if self.valid_icon_url && !is_well_formed_url(icon_url) {
+                violation(
+                    "icon_url",
+                    Some(icon_url),
+                    "icon url is not a well-formed http(s) url".to_string(),
+                );
+            }
+        }
+
+        violations
+    }
+}
+
+pub(crate) fn metadata_value_to_string(value: &MetadataValue) -> String {
+    match value {
+        MetadataValue::String(value) => value.clone(),
+        MetadataValue::Url(url) => url.as_str().to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+fn is_well_formed_url(url: &str) -> bool {
+    let rest = match url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+    {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = host.split(':').next().unwrap_or("");
+
+    !url.chars().any(char::is_whitespace)
+        && !host.is_empty()
+        && (host == "localhost" || host.contains('.'))
+        && !host.starts_with('.')
+        && !host.ends_with('.')
+}
@@ -1,8 +1,10 @@
 use crate::call_builder::CallBuilder;
 use crate::environment::EnvironmentEncode;
 use crate::internal_prelude::*;
-use crate::references::{GlobalReference, ResourceReference};
+use crate::references::{GlobalReference, PackageReference, ResourceReference};
 
+/// Simple calls are executed with an execution trace, so that the resources they return can be read
+/// with `get_return_resources`.
 pub trait SimpleMethodCaller {
     /// Makes a simple call to a method of the current component.
     ///
@@ -40,11 +42,39 @@ pub trait SimpleMethodCaller {
         admin_badge: R,
         args: Vec<Box<dyn EnvironmentEncode>>,
     ) -> TransactionReceipt;
+
+    /// Makes a simple call to a function of a blueprint of the current package.
+    ///
+    /// # Arguments
+    /// * `blueprint_name`: name of the blueprint.
+    /// * `function_name`: name of the function.
+    /// * `args`: environment arguments to call the function.
+    fn call_function(
+        self,
+        blueprint_name: &str,
+        function_name: &str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+    ) -> TransactionReceipt;
+
+    /// Makes a simple call to a function of a blueprint of a given package.
+    ///
+    /// # Arguments
+    /// * `package`: reference name or address of the package.
+    /// * `blueprint_name`: name of the blueprint.
+    /// * `function_name`: name of the function.
+    /// * `args`: environment arguments to call the function.
+    fn call_function_from<P: PackageReference>(
+        self,
+        package: P,
+        blueprint_name: &str,
+        function_name: &str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+    ) -> TransactionReceipt;
 }
 
 pub trait ComplexMethodCaller {
     /// Returns a new call builder.
-    fn build_call(&mut self) -> CallBuilder;
+    fn build_call(&mut self) -> CallBuilder<'_>;
 
     /// Returns a call builder with an initial method call.
     ///
@@ -55,7 +85,7 @@ pub trait ComplexMethodCaller {
         &mut self,
         method_name: &str,
         args: Vec<Box<dyn EnvironmentEncode>>,
-    ) -> CallBuilder;
+    ) -> CallBuilder<'_>;
 
     /// Returns a call builder with an initial method call to a given entity.
     ///
@@ -68,5 +98,35 @@ pub trait ComplexMethodCaller {
         global_address: G,
         method_name: &str,
         args: Vec<Box<dyn EnvironmentEncode>>,
-    ) -> CallBuilder;
+    ) -> CallBuilder<'_>;
+
+    /// Returns a call builder with an initial call to a function of a blueprint of the current
+    /// package.
+    ///
+    /// # Arguments
+    /// * `blueprint_name`: name of the blueprint.
+    /// * `function_name`: name of the function.
+    /// * `args`: environment arguments to call the function.
+    fn call_function_builder(
+        &mut self,
+        blueprint_name: &str,
+        function_name: &str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+    ) -> CallBuilder<'_>;
+
+    /// Returns a call builder with an initial call to a function of a blueprint of a given
+    /// package.
+    ///
+    /// # Arguments
+    /// * `package`: reference name or address of the package.
+    /// * `blueprint_name`: name of the blueprint.
+    /// * `function_name`: name of the function.
+    /// * `args`: environment arguments to call the function.
+    fn call_function_builder_from<P: PackageReference>(
+        &mut self,
+        package: P,
+        blueprint_name: &str,
+        function_name: &str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+    ) -> CallBuilder<'_>;
 }
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::internal_prelude::*;
+use crate::receipt_traits::{execution_events, Outcome};
+use crate::references::ReferenceName;
+use crate::test_engine::TestEngine;
+
+/// Set of independent named [`TestEngine`]s sharing a clock, to simulate interactions between
+/// several ledgers, such as a bridge relaying the events of one ledger to another.
+pub struct MultiEngine {
+    engines: HashMap<String, TestEngine>,
+}
+
+impl MultiEngine {
+    /// Returns an orchestrator without engines.
+    pub fn new() -> Self {
+        Self {
+            engines: HashMap::new(),
+        }
+    }
+
+    /// Adds an engine. Its time is moved forward to the latest time of the other engines.
+    /// Panics if an engine with the same name already exists.
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the engine.
+    /// * `test_engine`: added engine.
+    pub fn add_engine<N: ReferenceName>(
+        &mut self,
+        name: N,
+        mut test_engine: TestEngine,
+    ) -> &mut Self {
+        if self.engines.contains_key(&name.format()) {
+            panic!("An engine named {} already exists", name.display());
+        }
+        let latest_time = self
+            .engines
+            .values_mut()
+            .map(|engine| engine.get_current_time())
+            .max_by_key(|time| time.seconds_since_unix_epoch);
+        if let Some(latest_time) = latest_time {
+            if latest_time.seconds_since_unix_epoch
+                > test_engine.get_current_time().seconds_since_unix_epoch
+            {
+                test_engine.set_time(latest_time);
+            }
+        }
+        self.engines.insert(name.format(), test_engine);
+        self
+    }
+
+    /// Returns an engine.
+    /// Panics if there is no engine with the given name.
+    ///
+    /// # Arguments
+    /// * `name`: name of the engine.
+    pub fn engine<N: ReferenceName>(&self, name: N) -> &TestEngine {
+        self.engines
+            .get(&name.format())
+            .unwrap_or_else(|| panic!("There is no engine named {}", name.display()))
+    }
+
+    /// Returns a mutable reference to an engine.
+    /// Panics if there is no engine with the given name.
+    ///
+    /// # Arguments
+    /// * `name`: name of the engine.
+    pub fn engine_mut<N: ReferenceName>(&mut self, name: N) -> &mut TestEngine {
+        self.engines
+            .get_mut(&name.format())
+            .unwrap_or_else(|| panic!("There is no engine named {}", name.display()))
+    }
+
+    /// Executes a call on an engine, then relays the events of a given type it emitted to another
+    /// engine: each event is turned into a call on the other engine. The events of the payment of
+    /// the fees are not relayed. Panics if the call or one of the relayed calls does not succeed.
+    ///
+    /// Returns the receipt of the call and the receipts of the relayed calls.
+    ///
+    /// # Arguments
+    /// * `from`: name of the engine executing the call.
+    /// * `call`: closure executing the call on the engine.
+    /// * `to`: name of the engine receiving the events.
+    /// * `relay`: closure executing the call relaying an event on the receiving engine.
+    pub fn relay_events<E, N, M, F, G>(
+        &mut self,
+        from: N,
+        call: F,
+        to: M,
+        mut relay: G,
+    ) -> (TransactionReceipt, Vec<TransactionReceipt>)
+    where
+        E: ScryptoEvent,
+        N: ReferenceName,
+        M: ReferenceName,
+        F: FnOnce(&mut TestEngine) -> TransactionReceipt,
+        G: FnMut(&mut TestEngine, E) -> TransactionReceipt,
+    {
+        let receipt = call(self.engine_mut(from)).assert_is_success();
+        let events: Vec<E> = execution_events(receipt.expect_commit_success())
+            .filter(|(EventTypeIdentifier(_, name), _)| name == E::EVENT_NAME)
+            .filter_map(|(_, data)| scrypto_decode::<E>(data).ok())
+            .collect();
+
+        let to_engine = self.engine_mut(to);
+        let relayed = events
+            .into_iter()
+            .map(|event| relay(to_engine, event).assert_is_success())
+            .collect();
+        (receipt, relayed)
+    }
+
+    /// Advances the time of all the engines by the given amount of seconds.
+    ///
+    /// # Arguments
+    /// * `seconds`: amount of seconds to advance the time by.
+    pub fn advance_time(&mut self, seconds: u64) {
+        for engine in self.engines.values_mut() {
+            engine.advance_time(seconds);
+        }
+    }
+
+    /// Advances the time of all the engines by the given duration, with a millisecond precision.
+    ///
+    /// # Arguments
+    /// * `duration`: duration to advance the time by.
+    pub fn advance_time_by(&mut self, duration: Duration) -> &mut Self {
+        for engine in self.engines.values_mut() {
+            engine.advance_time_by(duration);
+        }
+        self
+    }
+
+    /// Sets the time of all the engines to the given instant.
+    /// Panics if the instant is before the current time of one of the engines.
+    ///
+    /// # Arguments
+    /// * `time`: new time of the ledgers.
+    pub fn set_time(&mut self, time: Instant) {
+        for engine in self.engines.values_mut() {
+            engine.set_time(time);
+        }
+    }
+}
+
+impl Default for MultiEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
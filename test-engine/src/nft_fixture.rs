@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+
+use crate::internal_prelude::*;
+use crate::references::{ComponentReference, ReferenceName};
+use crate::test_engine::TestEngine;
+
+/// Default maximum number of non-fungibles minted by a transaction of an [`NftFixture`]. It keeps
+/// the events listing the minted ids well under their size limit, whatever the type of the ids.
+pub const DEFAULT_NFT_CHUNK_SIZE: usize = 100;
+
+/// Maximum size of the encoded data of the non-fungibles minted by a transaction of an
+/// [`NftFixture`], half of the maximum size of the arguments of a call.
+pub const MAX_NFT_CHUNK_BYTES: usize = MAX_INVOKE_PAYLOAD_SIZE / 2;
+
+enum FixtureNfts<D> {
+    Integer(Vec<(u64, D)>),
+    Ruid(Vec<D>),
+}
+
+/// Builder of a non-fungible collection minted in batches, returned by
+/// [`TestEngine::new_nft_fixture`]. The non-fungibles are minted in as few transactions as the
+/// limits of a transaction allow.
+pub struct NftFixture<'a, D: NonFungibleData + ManifestEncode> {
+    test_engine: &'a mut TestEngine,
+    name: String,
+    nfts: FixtureNfts<D>,
+    chunk_size: usize,
+}
+
+impl<'a, D: NonFungibleData + ManifestEncode> NftFixture<'a, D> {
+    pub(crate) fn new<N: ReferenceName>(test_engine: &'a mut TestEngine, name: N) -> Self {
+        Self {
+            test_engine,
+            name: name.display(),
+            nfts: FixtureNfts::Integer(vec![]),
+            chunk_size: DEFAULT_NFT_CHUNK_SIZE,
+        }
+    }
+
+    /// Adds non-fungibles with integer ids, their data being computed from their id.
+    /// Panics if the fixture has RUIDs.
+    ///
+    /// # Arguments
+    /// * `ids`: integer ids of the non-fungibles.
+    /// * `data`: function returning the data of the non-fungible of a given id.
+    pub fn with_sequence<I: IntoIterator<Item = u64>, F: FnMut(u64) -> D>(
+        mut self,
+        ids: I,
+        mut data: F,
+    ) -> Self {
+        match &mut self.nfts {
+            FixtureNfts::Integer(nfts) => nfts.extend(ids.into_iter().map(|id| (id, data(id)))),
+            FixtureNfts::Ruid(_) => panic!(
+                "Cannot add integer ids to {}: it already has RUIDs",
+                self.name
+            ),
+        }
+        self
+    }
+
+    /// Adds non-fungibles with random ids, their data being computed from their index, starting
+    /// at 0. Panics if the fixture has integer ids.
+    ///
+    /// # Arguments
+    /// * `count`: number of non-fungibles.
+    /// * `data`: function returning the data of the non-fungible of a given index.
+    pub fn random_ruids<F: FnMut(u64) -> D>(mut self, count: u64, mut data: F) -> Self {
+        self.nfts = match self.nfts {
+            FixtureNfts::Integer(nfts) if nfts.is_empty() => {
+                FixtureNfts::Ruid((0..count).map(&mut data).collect())
+            }
+            FixtureNfts::Ruid(mut nfts) => {
+                nfts.extend((0..count).map(&mut data));
+                FixtureNfts::Ruid(nfts)
+            }
+            FixtureNfts::Integer(_) => panic!(
+                "Cannot add RUIDs to {}: it already has integer ids",
+                self.name
+            ),
+        };
+        self
+    }
+
+    /// Sets the maximum number of non-fungibles minted by a transaction,
+    /// [`DEFAULT_NFT_CHUNK_SIZE`] by default.
+    ///
+    /// # Arguments
+    /// * `chunk_size`: maximum number of non-fungibles per transaction, at least 1.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        if chunk_size == 0 {
+            panic!(
+                "Cannot mint the non-fungibles of {} by chunks of 0",
+                self.name
+            );
+        }
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Creates the collection, mints its non-fungibles to an account and returns their ids, in
+    /// the order they were added. Panics if an integer id was added twice or if a transaction
+    /// fails.
+    ///
+    /// # Arguments
+    /// * `account`: reference name or address of the account receiving the non-fungibles.
+    pub fn mint_to<C: ComponentReference>(self, account: C) -> Vec<NonFungibleLocalId> {
+        let account = account.address(self.test_engine);
+        let (chunk_size, name) = (self.chunk_size, self.name);
+        match self.nfts {
+            FixtureNfts::Integer(nfts) => {
+                let mut added = HashSet::new();
+                if let Some((id, _)) = nfts.iter().find(|(id, _)| !added.insert(*id)) {
+                    panic!("Id {} was added twice to {}", id, name);
+                }
+                let collection = self
+                    .test_engine
+                    .new_nft_collection::<D, _>(name.as_str(), NonFungibleIdType::Integer);
+
+                let mut minted = vec![];
+                for chunk in split_in_chunks(nfts, chunk_size, |(_, data)| encoded_size(data)) {
+                    let ids: Vec<NonFungibleLocalId> = chunk
+                        .iter()
+                        .map(|(id, _)| NonFungibleLocalId::integer(*id))
+                        .collect();
+                    let entries = chunk
+                        .into_iter()
+                        .map(|(id, data)| (NonFungibleLocalId::integer(id), data))
+                        .collect();
+                    self.test_engine.mint_nfts_to(account, collection, entries);
+                    minted.extend(ids);
+                }
+                minted
+            }
+            FixtureNfts::Ruid(nfts) => {
+                let collection = self
+                    .test_engine
+                    .new_nft_collection::<D, _>(name.as_str(), NonFungibleIdType::RUID);
+
+                let mut minted = vec![];
+                for chunk in split_in_chunks(nfts, chunk_size, encoded_size) {
+                    minted.extend(
+                        self.test_engine
+                            .mint_ruid_nfts_to(account, collection, chunk),
+                    );
+                }
+                minted
+            }
+        }
+    }
+}
+
+fn encoded_size<D: ManifestEncode>(data: &D) -> usize {
+    manifest_encode(data).map_or(0, |bytes| bytes.len())
+}
+
+/// Splits items in consecutive chunks of at most `max_count` items, whose total size is at most
+/// [`MAX_NFT_CHUNK_BYTES`]. An item bigger than this limit is alone in its chunk.
+fn split_in_chunks<T, F: Fn(&T) -> usize>(items: Vec<T>, max_count: usize, size: F) -> Vec<Vec<T>> {
+    let mut chunks = vec![];
+    let mut chunk: Vec<T> = vec![];
+    let mut chunk_bytes = 0;
+    for item in items {
+        let item_bytes = size(&item);
+        if !chunk.is_empty()
+            && (chunk.len() == max_count || chunk_bytes + item_bytes > MAX_NFT_CHUNK_BYTES)
+        {
+            chunks.push(std::mem::take(&mut chunk));
+            chunk_bytes = 0;
+        }
+        chunk_bytes += item_bytes;
+        chunk.push(item);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod test_nft_fixture {
+    use super::*;
+
+    #[test]
+    fn test_split_in_chunks_by_count() {
+        let chunks = split_in_chunks((1..=7).collect(), 3, |_| 1);
+        assert_eq!(chunks, vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]]);
+        assert!(split_in_chunks(Vec::<u8>::new(), 3, |_| 1).is_empty());
+    }
+
+    #[test]
+    fn test_split_in_chunks_by_size() {
+        let third = MAX_NFT_CHUNK_BYTES / 3;
+        let sizes = vec![third, third, third, third, MAX_NFT_CHUNK_BYTES + 1, 1];
+        let chunks = split_in_chunks(sizes, 100, |size| *size);
+        assert_eq!(
+            chunks,
+            vec![
+                vec![third, third, third],
+                vec![third],
+                vec![MAX_NFT_CHUNK_BYTES + 1],
+                vec![1]
+            ]
+        );
+    }
+}
@@ -5,10 +5,32 @@ pub use lazy_static::lazy_static;
 pub use crate::internal_prelude::*;
 
 // This crate's types
+pub use crate::assertions::*;
 pub use crate::call_builder::*;
+pub use crate::compiled_packages::*;
+pub use crate::component_proxy::*;
+pub use crate::cost_trend::*;
 pub use crate::environment::*;
+pub use crate::logger::*;
+pub use crate::manifest_layout::*;
+pub use crate::metadata_policy::*;
 pub use crate::method_call::*;
+pub use crate::multi_engine::*;
+pub use crate::nft_fixture::*;
+pub use crate::receipt_snapshot::*;
 pub use crate::receipt_traits::*;
+pub use crate::recorder::*;
+pub use crate::references::{EntityKind, ReferenceError};
+pub use crate::resource_builder::*;
+pub use crate::scenario::*;
+pub use crate::seed::*;
+pub use crate::snapshot::*;
+#[cfg(feature = "proptest")]
+pub use crate::strategies::*;
 pub use crate::test_engine::*;
-pub use crate::to_id::ToId;
-pub use crate::{env_args, env_vec, global_package, nf_ids, none};
+pub use crate::test_engine_builder::*;
+pub use crate::to_id::{ToId, ToIdError};
+pub use crate::{
+    assert_balance, assert_dec_approx_eq, component_proxy, env_args, env_map, env_some, env_tuple,
+    env_vec, global_package, global_packages, nf_ids, none,
+};
@@ -0,0 +1,248 @@
+use std::fs;
+use std::path::Path;
+
+use representations::{DisplayMode, PrintMode, ValueDisplayParameters};
+use serde::{Deserialize, Serialize};
+
+use crate::internal_prelude::*;
+use crate::receipt_summary::{balance_changes, emitter_name, outcome_description, SummaryNames};
+use crate::receipt_traits::execution_events;
+use crate::test_engine::TestEngine;
+
+/// Environment variable which, when set, makes [`ReceiptSnapshot::assert_matches_snapshot`]
+/// overwrite the existing snapshot files instead of comparing them.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "TEST_ENGINE_UPDATE_SNAPSHOTS";
+
+/// Number of decimals of the fee of a snapshot created by [`ToSnapshot::to_snapshot`].
+pub const DEFAULT_FEE_DECIMALS: u8 = 2;
+
+/// Stable description of a transaction, for golden tests. Addresses known by the engine are
+/// replaced by their reference name.
+///
+/// The events of the locking and of the payment of the fees are left out, as they only depend
+/// on the cost of the transaction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptSnapshot {
+    /// Outcome of the transaction, as described by [`TestEngine::explain`].
+    pub outcome: String,
+    /// Total cost in XRD of the transaction, rounded to the nearest value.
+    pub fee: String,
+    /// Net balance changes, as `<entity>: <signed amount> <resource>`. Only known when the
+    /// transaction was executed with an execution trace, see [`TestEngine::set_execution_trace`].
+    pub balance_changes: Vec<String>,
+    pub events: Vec<SnapshotEvent>,
+    /// Values returned by the instructions of the manifest.
+    pub return_values: Vec<String>,
+}
+
+/// Event of a [`ReceiptSnapshot`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotEvent {
+    pub name: String,
+    /// Global entity or blueprint emitting the event, `None` for internal entities.
+    pub emitter: Option<String>,
+    /// Content of the event, annotated with the names of its type and fields when the schema of
+    /// the event is known.
+    pub data: String,
+}
+
+pub trait ToSnapshot {
+    fn to_snapshot(&self, test_engine: &TestEngine) -> ReceiptSnapshot;
+    fn to_snapshot_with_fee_decimals(
+        &self,
+        test_engine: &TestEngine,
+        decimals: u8,
+    ) -> ReceiptSnapshot;
+}
+
+impl ToSnapshot for TransactionReceipt {
+    /// Returns the snapshot of a transaction, with a fee rounded to [`DEFAULT_FEE_DECIMALS`]
+    /// decimals.
+    ///
+    /// # Arguments
+    /// * `test_engine`: engine which executed the transaction, to name its entities.
+    fn to_snapshot(&self, test_engine: &TestEngine) -> ReceiptSnapshot {
+        self.to_snapshot_with_fee_decimals(test_engine, DEFAULT_FEE_DECIMALS)
+    }
+
+    /// Returns the snapshot of a transaction.
+    ///
+    /// # Arguments
+    /// * `test_engine`: engine which executed the transaction, to name its entities.
+    /// * `decimals`: number of decimals of the fee, at most 18.
+    fn to_snapshot_with_fee_decimals(
+        &self,
+        test_engine: &TestEngine,
+        decimals: u8,
+    ) -> ReceiptSnapshot {
+        if decimals > 18 {
+            panic!(
+                "Cannot round the fee to {} decimals, a Decimal has 18",
+                decimals
+            );
+        }
+        let names = SummaryNames::new(test_engine);
+        let fee = self
+            .fee_summary
+            .total_cost()
+            .checked_round(decimals, RoundingMode::ToNearestMidpointAwayFromZero)
+            .unwrap();
+        let mut snapshot = ReceiptSnapshot {
+            outcome: outcome_description(self),
+            fee: fee.to_string(),
+            balance_changes: vec![],
+            events: vec![],
+            return_values: vec![],
+        };
+
+        let commit = match &self.result {
+            TransactionResult::Commit(commit) => commit,
+            _ => return snapshot,
+        };
+        if let Some(trace) = &commit.execution_trace {
+            snapshot.balance_changes = balance_changes(&names, trace);
+        }
+        snapshot.events = execution_events(commit)
+            .map(|(event_type_identifier, data)| {
+                let EventTypeIdentifier(emitter, name) = event_type_identifier;
+                let schema = test_engine.event_schema(event_type_identifier);
+                SnapshotEvent {
+                    name: name.clone(),
+                    emitter: emitter_name(&names, emitter),
+                    data: render(&names, data, schema),
+                }
+            })
+            .collect();
+        if let TransactionOutcome::Success(outputs) = &commit.outcome {
+            snapshot.return_values = outputs
+                .iter()
+                .filter_map(|output| match output {
+                    InstructionOutput::CallReturn(bytes) => Some(render(&names, bytes, None)),
+                    InstructionOutput::None => None,
+                })
+                .collect();
+        }
+        snapshot
+    }
+}
+
+impl ReceiptSnapshot {
+    /// Compares the snapshot with the one stored in a file, and panics with the differing lines if
+    /// they are not equal. The file is written if it does not exist yet, or if the
+    /// [`UPDATE_SNAPSHOTS_VAR`] environment variable is set.
+    ///
+    /// # Arguments
+    /// * `path`: path of the JSON file storing the expected snapshot.
+    pub fn assert_matches_snapshot<P: AsRef<Path>>(&self, path: P) {
+        let path = path.as_ref();
+        let actual = serde_json::to_string_pretty(self).expect("Could not serialize the snapshot");
+        if !path.exists() || std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).unwrap_or_else(|err| {
+                    panic!("Could not create directory {}: {}", parent.display(), err)
+                });
+            }
+            fs::write(path, actual + "\n")
+                .unwrap_or_else(|err| panic!("Could not write {}: {}", path.display(), err));
+            return;
+        }
+
+        let expected = fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("Could not read {}: {}", path.display(), err));
+        let expected: ReceiptSnapshot = serde_json::from_str(&expected).unwrap_or_else(|err| {
+            panic!(
+                "Could not parse snapshot {}: {}. Set {} to regenerate it",
+                path.display(),
+                err,
+                UPDATE_SNAPSHOTS_VAR
+            )
+        });
+        if expected != *self {
+            let expected = serde_json::to_string_pretty(&expected).unwrap();
+            panic!(
+                "Receipt does not match snapshot {} (- expected, + actual), set {} to update it:\n{}",
+                path.display(),
+                UPDATE_SNAPSHOTS_VAR,
+                line_diff(&expected, &actual)
+            );
+        }
+    }
+}
+
+/// Renders an encoded Scrypto value on a single line, annotated with its schema if any.
+fn render(
+    names: &SummaryNames,
+    bytes: &[u8],
+    schema: Option<(LocalTypeId, VersionedScryptoSchema)>,
+) -> String {
+    let payload = match ScryptoRawPayload::new_from_valid_slice_with_checks(bytes) {
+        Some(payload) => payload,
+        None => return format!("{:?}", scrypto_decode::<ScryptoValue>(bytes)),
+    };
+    let custom_context = ScryptoValueDisplayContext::with_optional_bech32(Some(names.encoder()));
+    let rendered = match &schema {
+        Some((type_id, schema)) => payload.to_string(ValueDisplayParameters::Annotated {
+            display_mode: DisplayMode::RustLike,
+            print_mode: PrintMode::SingleLine,
+            custom_context,
+            schema: schema.v1(),
+            type_id: *type_id,
+            depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+        }),
+        None => payload.to_string(ValueDisplayParameters::Schemaless {
+            display_mode: DisplayMode::RustLike,
+            print_mode: PrintMode::SingleLine,
+            custom_context,
+            depth_limit: SCRYPTO_SBOR_V1_MAX_DEPTH,
+        }),
+    };
+    names.resolve(&rendered)
+}
+
+/// Returns the lines of two texts, prefixed by `-` when only in the expected text, by `+` when
+/// only in the actual text and by a space when in both.
+fn line_diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // Lengths of the longest common subsequences of the suffixes of both texts.
+    let mut common = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            diff.push(format!("  {}", expected[i]));
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || common[i + 1][j] >= common[i][j + 1])
+        {
+            diff.push(format!("- {}", expected[i]));
+            i += 1;
+        } else {
+            diff.push(format!("+ {}", actual[j]));
+            j += 1;
+        }
+    }
+    diff.join("\n")
+}
+
+#[cfg(test)]
+mod test_line_diff {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff("a\nb\nc", "a\nd\nc\ne");
+        assert_eq!(diff, "  a\n- b\n+ d\n  c\n+ e");
+    }
+}
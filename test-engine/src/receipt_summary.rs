@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use radix_engine::system::system_modules::execution_trace::{BucketSnapshot, ResourceSummary};
+
+use crate::internal_prelude::*;
+use crate::receipt_traits::{abort_message, rejection_message};
+use crate::test_engine::TestEngine;
+
+impl TestEngine {
+    /// Returns a compact summary of a transaction, where the addresses of the entities of the
+    /// engine are replaced by their reference name: the outcome, the calls moving resources, the
+    /// balance changes, the new entities and the names of the emitted events. Unknown addresses are
+    /// written as is. The calls and balance changes are only listed when the transaction was
+    /// executed with an execution trace, see [`TestEngine::set_execution_trace`].
+    ///
+    /// # Arguments
+    /// * `receipt`: receipt of the transaction.
+    pub fn explain(&self, receipt: &TransactionReceipt) -> String {
+        let names = SummaryNames::new(self);
+        let mut summary = String::new();
+        writeln!(summary, "Outcome: {}", outcome_description(receipt)).unwrap();
+        let commit = match &receipt.result {
+            TransactionResult::Commit(commit) => commit,
+            _ => return summary,
+        };
+        writeln!(summary, "Cost: {} XRD", receipt.fee_summary.total_cost()).unwrap();
+
+        if let Some(trace) = &commit.execution_trace {
+            let calls: Vec<String> = trace
+                .execution_traces
+                .iter()
+                .flat_map(|trace| trace.children.iter())
+                .filter_map(|call| {
+                    let identifier = match &call.origin {
+                        TraceOrigin::ScryptoMethod(identifier)
+                        | TraceOrigin::ScryptoFunction(identifier) => identifier,
+                        _ => return None,
+                    };
+                    // The worktop moves the resources between the instructions.
+                    if identifier.blueprint_id.blueprint_name == WORKTOP_BLUEPRINT {
+                        return None;
+                    }
+                    let receiver = match &call.current_frame_actor {
+                        TraceActor::Method(node_id) => Some(names.of(node_id)),
+                        TraceActor::NonMethod => None,
+                    };
+                    let mut line = format!(
+                        "#{} {}::{}",
+                        call.instruction_index,
+                        identifier.blueprint_id.blueprint_name,
+                        identifier.ident
+                    );
+                    if let Some(receiver) = receiver {
+                        write!(line, " on {}", receiver).unwrap();
+                    }
+                    if !call.input.is_empty() {
+                        write!(line, ", in: {}", names.resources(&call.input)).unwrap();
+                    }
+                    if !call.output.is_empty() {
+                        write!(line, ", out: {}", names.resources(&call.output)).unwrap();
+                    }
+                    Some(line)
+                })
+                .collect();
+            write_section(&mut summary, "Calls moving resources", calls);
+
+            let changes = balance_changes(&names, trace);
+            write_section(&mut summary, "Balance changes", changes);
+        }
+
+        let new_entities = commit
+            .new_package_addresses()
+            .iter()
+            .map(|address| format!("package {}", names.of(address.as_node_id())))
+            .chain(
+                commit
+                    .new_component_addresses()
+                    .iter()
+                    .map(|address| format!("component {}", names.of(address.as_node_id()))),
+            )
+            .chain(
+                commit
+                    .new_resource_addresses()
+                    .iter()
+                    .map(|address| format!("resource {}", names.of(address.as_node_id()))),
+            )
+            .collect();
+        write_section(&mut summary, "New entities", new_entities);
+
+        let events = commit
+            .application_events
+            .iter()
+            .map(
+                |(EventTypeIdentifier(emitter, name), _)| match emitter_name(&names, emitter) {
+                    Some(emitter) => format!("{} from {}", name, emitter),
+                    None => name.clone(),
+                },
+            )
+            .collect();
+        write_section(&mut summary, "Events", events);
+
+        summary
+    }
+
+    /// Prints the summary of a transaction returned by [`TestEngine::explain`].
+    ///
+    /// # Arguments
+    /// * `receipt`: receipt of the transaction.
+    pub fn print_summary(&self, receipt: &TransactionReceipt) {
+        print!("{}", self.explain(receipt));
+    }
+}
+
+/// Returns the outcome of a transaction: success, the panic message or the error of a failure,
+/// or the reason of a rejection or an abortion.
+pub(crate) fn outcome_description(receipt: &TransactionReceipt) -> String {
+    let commit = match &receipt.result {
+        TransactionResult::Commit(commit) => commit,
+        TransactionResult::Reject(reject) => {
+            return rejection_message(reject, &receipt.fee_summary)
+        }
+        TransactionResult::Abort(abort) => return abort_message(abort),
+    };
+    match &commit.outcome {
+        TransactionOutcome::Success(_) => "success".to_string(),
+        TransactionOutcome::Failure(RuntimeError::ApplicationError(
+            ApplicationError::PanicMessage(message),
+        )) => format!("panicked with {}", message),
+        TransactionOutcome::Failure(failure) => format!("failed with {}", failure),
+    }
+}
+
+/// Returns the net balance changes of a transaction by entity and resource, as
+/// `<entity>: <signed amount> <resource>`.
+pub(crate) fn balance_changes(
+    names: &SummaryNames,
+    trace: &TransactionExecutionTrace,
+) -> Vec<String> {
+    let mut changes: Vec<(NodeId, ResourceAddress, Decimal)> = vec![];
+    for change in trace.resource_changes.values().flatten() {
+        match changes.iter_mut().find(|(node_id, resource, _)| {
+            *node_id == change.node_id && *resource == change.resource_address
+        }) {
+            Some((_, _, amount)) => *amount += change.amount,
+            None => changes.push((change.node_id, change.resource_address, change.amount)),
+        }
+    }
+    changes
+        .into_iter()
+        .filter(|(_, _, amount)| !amount.is_zero())
+        .map(|(node_id, resource, amount)| {
+            let sign = if amount.is_positive() { "+" } else { "" };
+            format!(
+                "{}: {}{} {}",
+                names.of(&node_id),
+                sign,
+                amount,
+                names.of(resource.as_node_id())
+            )
+        })
+        .collect()
+}
+
+/// Returns the name of the emitter of an event: the global entity or the blueprint emitting it, or
+/// `None` for internal entities.
+pub(crate) fn emitter_name(names: &SummaryNames, emitter: &Emitter) -> Option<String> {
+    match emitter {
+        Emitter::Method(node_id, _) if node_id.is_global() => Some(names.of(node_id)),
+        Emitter::Function(blueprint_id) => Some(blueprint_id.blueprint_name.clone()),
+        Emitter::Method(_, _) => None,
+    }
+}
+
+fn write_section(summary: &mut String, title: &str, lines: Vec<String>) {
+    if lines.is_empty() {
+        return;
+    }
+    writeln!(summary, "{}:", title).unwrap();
+    for line in lines {
+        writeln!(summary, "  {}", line).unwrap();
+    }
+}
+
+pub(crate) struct SummaryNames {
+    names: HashMap<NodeId, String>,
+    encoder: AddressBech32Encoder,
+}
+
+impl SummaryNames {
+    pub(crate) fn new(test_engine: &TestEngine) -> Self {
+        Self {
+            names: test_engine.display_names_by_node_id(),
+            encoder: AddressBech32Encoder::new(&test_engine.network()),
+        }
+    }
+
+    pub(crate) fn of(&self, node_id: &NodeId) -> String {
+        match self.names.get(node_id) {
+            Some(name) => name.clone(),
+            None => self
+                .encoder
+                .encode(node_id.as_bytes())
+                .unwrap_or_else(|_| format!("{:?}", node_id)),
+        }
+    }
+
+    /// Replaces the known addresses of a text by their name.
+    pub(crate) fn resolve(&self, text: &str) -> String {
+        self.names
+            .iter()
+            .fold(text.to_string(), |text, (node_id, name)| {
+                match self.encoder.encode(node_id.as_bytes()) {
+                    Ok(address) => text.replace(&address, name),
+                    Err(_) => text,
+                }
+            })
+    }
+
+    pub(crate) fn encoder(&self) -> &AddressBech32Encoder {
+        &self.encoder
+    }
+
+    fn resources(&self, summary: &ResourceSummary) -> String {
+        let buckets = summary.buckets.values().map(|bucket| match bucket {
+            BucketSnapshot::Fungible {
+                resource_address,
+                liquid,
+            } => format!("{} {}", liquid, self.of(resource_address.as_node_id())),
+            BucketSnapshot::NonFungible {
+                resource_address,
+                liquid,
+            } => format!(
+                "{} {}",
+                liquid
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                self.of(resource_address.as_node_id())
+            ),
+        });
+        let proofs = summary.proofs.values().map(|proof| {
+            format!(
+                "proof of {}",
+                self.of(proof.resource_address().as_node_id())
+            )
+        });
+        buckets.chain(proofs).collect::<Vec<String>>().join(", ")
+    }
+}
@@ -1,9 +1,29 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+
+use radix_engine::blueprints::account::{DepositEvent, RejectedDepositEvent, WithdrawEvent};
+use radix_engine::system::system_modules::execution_trace::{
+    ExecutionTrace, ResourceSpecifier, TraceActor,
+};
+
 use crate::from_instruction::FromInstruction;
 use crate::internal_prelude::*;
 
 pub trait Outcome {
     fn assert_is_success(self) -> Self;
     fn assert_failed_with(self, error: &str) -> Self;
+    fn assert_panicked_with(self, message: &str) -> Self;
+    #[cfg(feature = "regex")]
+    fn assert_failed_with_regex(self, pattern: &str) -> Self;
+    fn assert_failed_containing_amount<D: TryInto<Decimal>>(self, amount: D) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug;
+    fn assert_failed_with_auth_error(self) -> Self;
+    fn assert_rejected(self) -> Self;
+    fn assert_rejected_with(self, reason: &str) -> Self;
+    fn assert_refunded<D: TryInto<Decimal>>(self, resource: ResourceAddress, amount: D) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug;
 }
 
 impl Outcome for TransactionReceipt {
@@ -18,10 +38,10 @@ impl Outcome for TransactionReceipt {
                 }
             },
             TransactionResult::Reject(reject) => {
-                panic!("Transaction rejected with: {}", reject.reason);
+                panic!("{}", rejection_message(reject, &self.fee_summary));
             }
             TransactionResult::Abort(abort) => {
-                panic!("Transaction aborted with: {}", abort.reason);
+                panic!("{}", abort_message(abort));
             }
         }
     }
@@ -51,38 +71,888 @@ impl Outcome for TransactionReceipt {
                 }
             },
             TransactionResult::Reject(reject) => {
-                panic!("Transaction rejected with: {}", reject.reason);
+                panic!("{}", rejection_message(reject, &self.fee_summary));
             }
             TransactionResult::Abort(abort) => {
-                panic!("Transaction aborted with: {}", abort.reason);
+                panic!("{}", abort_message(abort));
             }
         }
     }
+
+    /// Asserts that the transaction failed because of a panic with a given message.
+    /// Only the panic message is compared, without the location added by Scrypto.
+    /// Panics if the transaction succeeded, failed with another error or was rejected/aborted.
+    ///
+    /// # Arguments
+    /// * `message` : Expected panic message.
+    fn assert_panicked_with(self, message: &str) -> Self {
+        match &self.result {
+            TransactionResult::Commit(commit) => match &commit.outcome {
+                TransactionOutcome::Success(_) => {
+                    panic!("Transaction succeeded !");
+                }
+                TransactionOutcome::Failure(RuntimeError::ApplicationError(
+                    ApplicationError::PanicMessage(payload),
+                )) => {
+                    let panic_message = payload
+                        .rsplit_once(" @ ")
+                        .map_or(payload.as_str(), |(panic_message, _)| panic_message);
+                    if panic_message == message {
+                        self
+                    } else {
+                        panic!(
+                            "Transaction did not panic with expected message ! \n\
+                                Message: {} \n\
+                                Expected Message: {}",
+                            panic_message, message
+                        );
+                    }
+                }
+                TransactionOutcome::Failure(failure) => {
+                    panic!(
+                        "Transaction did not fail with a panic ! \n\
+                            Error: {} \n\
+                            Expected Message: {}",
+                        failure, message
+                    );
+                }
+            },
+            TransactionResult::Reject(reject) => {
+                panic!("{}", rejection_message(reject, &self.fee_summary));
+            }
+            TransactionResult::Abort(abort) => {
+                panic!("{}", abort_message(abort));
+            }
+        }
+    }
+
+    /// Asserts that the error of a failed transaction matches a regular expression.
+    /// Panics if the pattern is invalid, or if the transaction succeeded, failed with an error
+    /// not matching the pattern or was rejected/aborted.
+    ///
+    /// # Arguments
+    /// * `pattern` : Regular expression that the error message should match.
+    #[cfg(feature = "regex")]
+    fn assert_failed_with_regex(self, pattern: &str) -> Self {
+        let regex = regex::Regex::new(pattern)
+            .unwrap_or_else(|err| panic!("Invalid pattern {}: {}", pattern, err));
+        let failure = failure_of(&self).to_string();
+        if !regex.is_match(&failure) {
+            panic!(
+                "Transaction did not fail with an error matching the pattern ! \n\
+                    Error: {} \n\
+                    Pattern: {}",
+                failure, pattern
+            );
+        }
+        self
+    }
+
+    /// Asserts that the error of a failed transaction contains a given amount, whatever its
+    /// formatting: `8.5`, `8.50` and `8.500000000000000000` all match an amount of 8.5.
+    /// Panics if the transaction succeeded, failed with an error not containing the amount or was
+    /// rejected/aborted.
+    ///
+    /// # Arguments
+    /// * `amount` : Amount expected in the error message.
+    fn assert_failed_containing_amount<D: TryInto<Decimal>>(self, amount: D) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let amount = amount.try_into().unwrap();
+        let failure = failure_of(&self).to_string();
+        if !amounts_in(&failure).contains(&amount) {
+            panic!(
+                "Transaction did not fail with an error containing the amount ! \n\
+                    Error: {} \n\
+                    Expected Amount: {}",
+                failure, amount
+            );
+        }
+        self
+    }
+
+    /// Asserts that the transaction failed because of an authorization error.
+    /// Panics if the transaction succeeded, failed with another error or was rejected/aborted.
+    fn assert_failed_with_auth_error(self) -> Self {
+        match &self.result {
+            TransactionResult::Commit(commit) => match &commit.outcome {
+                TransactionOutcome::Success(_) => {
+                    panic!("Transaction succeeded !");
+                }
+                TransactionOutcome::Failure(RuntimeError::SystemModuleError(
+                    SystemModuleError::AuthError(_),
+                )) => self,
+                TransactionOutcome::Failure(failure) => {
+                    panic!(
+                        "Transaction did not fail with an auth error ! \n\
+                            Error: {}",
+                        failure
+                    );
+                }
+            },
+            TransactionResult::Reject(reject) => {
+                panic!("{}", rejection_message(reject, &self.fee_summary));
+            }
+            TransactionResult::Abort(abort) => {
+                panic!("{}", abort_message(abort));
+            }
+        }
+    }
+
+    /// Asserts that the transaction was rejected.
+    /// Panics if the transaction was committed or aborted.
+    fn assert_rejected(self) -> Self {
+        match &self.result {
+            TransactionResult::Reject(_) => self,
+            TransactionResult::Commit(commit) => match &commit.outcome {
+                TransactionOutcome::Success(_) => {
+                    panic!("Transaction succeeded !");
+                }
+                TransactionOutcome::Failure(failure) => {
+                    panic!("Transaction was not rejected but failed with: {}", failure);
+                }
+            },
+            TransactionResult::Abort(abort) => {
+                panic!("{}", abort_message(abort));
+            }
+        }
+    }
+
+    /// Asserts that the transaction was rejected with a reason containing the given string.
+    /// Panics if the transaction was committed, aborted or rejected for another reason.
+    ///
+    /// # Arguments
+    /// * `reason` : Expected part of the rejection reason.
+    fn assert_rejected_with(self, reason: &str) -> Self {
+        match &self.result {
+            TransactionResult::Reject(reject) => {
+                if reject.reason.to_string().contains(reason) {
+                    self
+                } else {
+                    panic!(
+                        "Transaction was not rejected with expected reason ! \n\
+                            {} \n\
+                            Expected Reason: {}",
+                        rejection_message(reject, &self.fee_summary),
+                        reason
+                    );
+                }
+            }
+            TransactionResult::Commit(commit) => match &commit.outcome {
+                TransactionOutcome::Success(_) => {
+                    panic!("Transaction succeeded !");
+                }
+                TransactionOutcome::Failure(failure) => {
+                    panic!("Transaction was not rejected but failed with: {}", failure);
+                }
+            },
+            TransactionResult::Abort(abort) => {
+                panic!("{}", abort_message(abort));
+            }
+        }
+    }
+
+    /// Asserts that a deposit of the given amount of a fungible resource was rejected by the
+    /// deposit rules of an account and refunded, so that the balances of the accounts in this
+    /// resource did not change, fees excluded.
+    /// Panics if the transaction did not succeed, if another amount was rejected or if an
+    /// account withdrew or received resources that were not refunded.
+    ///
+    /// # Arguments
+    /// * `resource`: address of the resource.
+    /// * `amount`: expected refunded amount.
+    fn assert_refunded<D: TryInto<Decimal>>(self, resource: ResourceAddress, amount: D) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let receipt = self.assert_is_success();
+        let amount = amount.try_into().unwrap();
+        let commit = receipt.expect_commit_success();
+        let encoder = AddressBech32Encoder::for_simulator();
+
+        let mut rejected = Decimal::ZERO;
+        let mut balance_changes: IndexMap<NodeId, Decimal> = IndexMap::new();
+        for (EventTypeIdentifier(emitter, event_name), data) in &commit.application_events {
+            let Emitter::Method(node_id, ModuleId::Main) = emitter else {
+                continue;
+            };
+            if !matches!(
+                node_id.entity_type(),
+                Some(
+                    EntityType::GlobalAccount
+                        | EntityType::GlobalVirtualSecp256k1Account
+                        | EntityType::GlobalVirtualEd25519Account
+                )
+            ) {
+                continue;
+            }
+            match event_name.as_str() {
+                "RejectedDepositEvent" => {
+                    if let Ok(RejectedDepositEvent::Fungible(address, rejected_amount)) =
+                        scrypto_decode(data)
+                    {
+                        if address == resource {
+                            rejected += rejected_amount;
+                        }
+                    }
+                }
+                "DepositEvent" => {
+                    if let Ok(DepositEvent::Fungible(address, deposited)) = scrypto_decode(data) {
+                        if address == resource {
+                            *balance_changes.entry(*node_id).or_default() += deposited;
+                        }
+                    }
+                }
+                "WithdrawEvent" => {
+                    if let Ok(WithdrawEvent::Fungible(address, withdrawn)) = scrypto_decode(data) {
+                        if address == resource {
+                            *balance_changes.entry(*node_id).or_default() -= withdrawn;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let resource_name = encoder.encode(resource.as_node_id().as_bytes()).unwrap();
+        if rejected != amount {
+            panic!(
+                "Expected a refund of {} {}, but {} were rejected",
+                amount, resource_name, rejected
+            );
+        }
+        for (account, change) in balance_changes {
+            if !change.is_zero() {
+                panic!(
+                    "Balance of {} in {} changed by {} although the deposit was refunded",
+                    encoder.encode(account.as_bytes()).unwrap(),
+                    resource_name,
+                    change
+                );
+            }
+        }
+        receipt
+    }
+}
+
+/// Returns the error of a failed transaction.
+/// Panics if the transaction succeeded or was rejected/aborted.
+fn failure_of(receipt: &TransactionReceipt) -> &RuntimeError {
+    match &receipt.result {
+        TransactionResult::Commit(commit) => match &commit.outcome {
+            TransactionOutcome::Success(_) => {
+                panic!("Transaction succeeded !");
+            }
+            TransactionOutcome::Failure(failure) => failure,
+        },
+        TransactionResult::Reject(reject) => {
+            panic!("{}", rejection_message(reject, &receipt.fee_summary));
+        }
+        TransactionResult::Abort(abort) => {
+            panic!("{}", abort_message(abort));
+        }
+    }
+}
+
+/// Returns the amounts written in a text, as the decimal numbers it contains.
+fn amounts_in(text: &str) -> Vec<Decimal> {
+    text.split(|char: char| !(char.is_ascii_digit() || char == '.' || char == '-'))
+        .filter_map(|word| {
+            // A number can end a sentence.
+            let number = word.trim_end_matches('.');
+            Decimal::from_str(number).ok()
+        })
+        .collect()
+}
+
+/// Describes why a transaction was rejected. When the rejection comes from the fees, the fee
+/// summary of the transaction is added to the description.
+pub(crate) fn rejection_message(
+    reject: &RejectResult,
+    fee_summary: &TransactionFeeSummary,
+) -> String {
+    let message = format!(
+        "Transaction was rejected without being committed: {}",
+        reject.reason
+    );
+    let fee_related = matches!(
+        reject.reason,
+        RejectionReason::SuccessButFeeLoanNotRepaid
+            | RejectionReason::ErrorBeforeLoanAndDeferredCostsRepaid(
+                RuntimeError::SystemModuleError(SystemModuleError::CostingError(_))
+            )
+    );
+    if !fee_related {
+        return message;
+    }
+    format!(
+        "{} \n\
+            Fee summary: {} XRD of execution ({} cost units), {} XRD of finalization ({} cost units), \
+            {} XRD of storage, {} XRD of royalties, {} XRD of tips, {} XRD in total",
+        message,
+        fee_summary.total_execution_cost_in_xrd,
+        fee_summary.total_execution_cost_units_consumed,
+        fee_summary.total_finalization_cost_in_xrd,
+        fee_summary.total_finalization_cost_units_consumed,
+        fee_summary.total_storage_cost_in_xrd,
+        fee_summary.total_royalty_cost_in_xrd,
+        fee_summary.total_tipping_cost_in_xrd,
+        fee_summary.total_cost()
+    )
+}
+
+/// Describes why a transaction was aborted.
+pub(crate) fn abort_message(abort: &AbortResult) -> String {
+    format!(
+        "Transaction was aborted without being committed: {}",
+        abort.reason
+    )
+}
+
+/// Returns the events emitted during the execution of the manifest of a committed transaction,
+/// leaving out the events of the locking and of the payment of the fees.
+pub(crate) fn execution_events(
+    commit: &CommitResult,
+) -> impl Iterator<Item = &(EventTypeIdentifier, Vec<u8>)> {
+    // The fees are paid after the execution of the manifest.
+    let fee_payment = commit
+        .application_events
+        .iter()
+        .position(|(EventTypeIdentifier(_, name), _)| name == "PayFeeEvent")
+        .unwrap_or(commit.application_events.len());
+    commit.application_events[..fee_payment]
+        .iter()
+        .filter(|(EventTypeIdentifier(_, name), _)| name != "LockFeeEvent")
 }
 
 pub trait GetReturn<T> {
     fn get_return(&self) -> T;
+    fn get_return_of_call(&self, call: usize) -> T;
 }
 
 impl<T> GetReturn<T> for TransactionReceipt
 where
     T: FromInstruction,
 {
-    /// Returns the method's return from a receipt.
+    /// Returns the return of the last method or function call of the transaction. The calls
+    /// injected by the engine, such as fee locks, proof creations and deposits, are ignored.
+    /// Panics if the transaction was rejected, aborted or failed, or if the return cannot be
+    /// decoded as `T`.
     fn get_return(&self) -> T {
+        let outputs = user_call_outputs(self);
+        match outputs.last() {
+            Some(output) => T::from(output),
+            None => panic!("The transaction did not call any method or function"),
+        }
+    }
+
+    /// Returns the return of a given method or function call of the transaction. The calls
+    /// injected by the engine, such as fee locks, proof creations and deposits, are not counted.
+    /// The proof creations and withdrawals of accounts are only recognized when the transaction
+    /// was executed with an execution trace, see `CallBuilder::with_execution_trace`.
+    /// Panics if the transaction was rejected, aborted or failed, or if the return cannot be
+    /// decoded as `T`.
+    ///
+    /// # Arguments
+    /// * `call`: index of the call among the calls of the transaction, starting at 0.
+    fn get_return_of_call(&self, call: usize) -> T {
+        let outputs = user_call_outputs(self);
+        match outputs.get(call) {
+            Some(output) => T::from(output),
+            None => panic!(
+                "There is no call {}: the transaction made {} calls",
+                call,
+                outputs.len()
+            ),
+        }
+    }
+}
+
+/// Returns the outputs of the method and function calls of a successful transaction, without the
+/// calls injected by the engine.
+fn user_call_outputs(receipt: &TransactionReceipt) -> Vec<&InstructionOutput> {
+    let (commit, outputs) = match &receipt.result {
+        TransactionResult::Commit(commit) => match &commit.outcome {
+            TransactionOutcome::Success(outputs) => (commit, outputs),
+            TransactionOutcome::Failure(failure) => {
+                panic!("Transaction failed with: {}", failure);
+            }
+        },
+        TransactionResult::Reject(reject) => {
+            panic!("{}", rejection_message(reject, &receipt.fee_summary));
+        }
+        TransactionResult::Abort(abort) => {
+            panic!("{}", abort_message(abort));
+        }
+    };
+
+    // Calls moving resources are traced, when the transaction has an execution trace: the
+    // withdrawals, proof creations and deposits of accounts are injected ones.
+    let account_blueprint = BlueprintId::new(&ACCOUNT_PACKAGE, ACCOUNT_BLUEPRINT);
+    let account_instructions: HashSet<usize> = commit
+        .execution_trace
+        .iter()
+        .flat_map(|execution_trace| execution_trace.execution_traces.iter())
+        .flat_map(|trace| trace.children.iter())
+        .filter(|trace| match &trace.origin {
+            TraceOrigin::ScryptoMethod(identifier) => identifier.blueprint_id == account_blueprint,
+            _ => false,
+        })
+        .map(|trace| trace.instruction_index)
+        .collect();
+
+    // The fee lock and a deposit of an empty worktop are not traced, but they are always the first
+    // and last instructions of the manifest and return nothing.
+    let unit = scrypto_encode(&()).unwrap();
+    let last_index = outputs.len().saturating_sub(1);
+    outputs
+        .iter()
+        .enumerate()
+        .filter(|(index, output)| match output {
+            InstructionOutput::CallReturn(bytes) => {
+                !account_instructions.contains(index)
+                    && !((*index == 0 || *index == last_index) && *bytes == unit)
+            }
+            InstructionOutput::None => false,
+        })
+        .map(|(_, output)| output)
+        .collect()
+}
+
+pub trait GetEvents {
+    fn get_events<T: ScryptoEvent>(&self) -> Vec<T>;
+    fn assert_event_emitted<T: ScryptoEvent, F: Fn(&T) -> bool>(&self, predicate: F) -> &Self;
+}
+
+impl GetEvents for TransactionReceipt {
+    /// Returns the events of a given type emitted during the transaction, in emission order.
+    /// Events with the same name but a payload that cannot be decoded as `T` are skipped.
+    /// Panics if the transaction was rejected, aborted or failed.
+    fn get_events<T: ScryptoEvent>(&self) -> Vec<T> {
         match &self.result {
             TransactionResult::Commit(commit) => match &commit.outcome {
-                TransactionOutcome::Success(output) => T::from(output.clone()),
+                TransactionOutcome::Success(_) => commit
+                    .application_events
+                    .iter()
+                    .filter(|(identifier, _)| identifier.1 == T::EVENT_NAME)
+                    .filter_map(|(_, data)| scrypto_decode::<T>(data).ok())
+                    .collect(),
+                TransactionOutcome::Failure(failure) => {
+                    panic!("Transaction failed with: {}", failure);
+                }
+            },
+            TransactionResult::Reject(reject) => {
+                panic!("{}", rejection_message(reject, &self.fee_summary));
+            }
+            TransactionResult::Abort(abort) => {
+                panic!("{}", abort_message(abort));
+            }
+        }
+    }
+
+    /// Asserts that an event of a given type matching a predicate was emitted.
+    /// Panics if no such event was emitted or if the transaction did not succeed.
+    ///
+    /// # Arguments
+    /// * `predicate`: condition that the emitted event should satisfy.
+    fn assert_event_emitted<T: ScryptoEvent, F: Fn(&T) -> bool>(&self, predicate: F) -> &Self {
+        let events = self.get_events::<T>();
+        if !events.iter().any(predicate) {
+            panic!(
+                "No {} matching the predicate was emitted. Emitted {}: {}",
+                T::EVENT_NAME,
+                T::EVENT_NAME,
+                events.len()
+            );
+        }
+        self
+    }
+}
+
+/// Resources returned to the worktop by the calls of a transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReturnedResources {
+    pub fungibles: HashMap<ResourceAddress, Decimal>,
+    pub non_fungibles: HashMap<ResourceAddress, Vec<NonFungibleLocalId>>,
+}
+
+pub trait GetReturnResources {
+    fn get_return_resources(&self) -> ReturnedResources;
+}
+
+impl GetReturnResources for TransactionReceipt {
+    /// Returns the resources returned to the worktop by the method and function calls of the
+    /// transaction. Withdrawals from accounts are not counted as returned resources.
+    /// Panics if the transaction was rejected, aborted or failed, or if it was executed without
+    /// execution trace, see `CallBuilder::with_execution_trace`. Simple calls made through
+    /// `SimpleMethodCaller` are always traced.
+    fn get_return_resources(&self) -> ReturnedResources {
+        let commit = match &self.result {
+            TransactionResult::Commit(commit) => match &commit.outcome {
+                TransactionOutcome::Success(_) => commit,
                 TransactionOutcome::Failure(failure) => {
                     panic!("Transaction failed with: {}", failure);
                 }
             },
             TransactionResult::Reject(reject) => {
-                panic!("Transaction rejected with: {}", reject.reason);
+                panic!("{}", rejection_message(reject, &self.fee_summary));
+            }
+            TransactionResult::Abort(abort) => {
+                panic!("{}", abort_message(abort));
+            }
+        };
+        let execution_trace = commit.execution_trace.as_ref().expect(
+            "The transaction was executed without execution trace: execute it with \
+                 CallBuilder::with_execution_trace or TestEngine::set_execution_trace",
+        );
+
+        let account_blueprint = BlueprintId::new(&ACCOUNT_PACKAGE, ACCOUNT_BLUEPRINT);
+        let account_instructions: HashSet<usize> = execution_trace
+            .execution_traces
+            .iter()
+            .flat_map(|trace| trace.children.iter())
+            .filter(|trace| match &trace.origin {
+                TraceOrigin::ScryptoMethod(identifier) => {
+                    identifier.blueprint_id == account_blueprint
+                }
+                _ => false,
+            })
+            .map(|trace| trace.instruction_index)
+            .collect();
+
+        let mut returned = ReturnedResources::default();
+        for (instruction_index, changes) in execution_trace.worktop_changes() {
+            if account_instructions.contains(&instruction_index) {
+                continue;
+            }
+            for change in changes {
+                match change {
+                    WorktopChange::Put(ResourceSpecifier::Amount(resource, amount)) => {
+                        *returned.fungibles.entry(resource).or_default() += amount;
+                    }
+                    WorktopChange::Put(ResourceSpecifier::Ids(resource, ids)) => {
+                        returned
+                            .non_fungibles
+                            .entry(resource)
+                            .or_default()
+                            .extend(ids);
+                    }
+                    WorktopChange::Take(_) => {}
+                }
+            }
+        }
+
+        returned
+    }
+}
+
+pub trait GetBalanceChanges {
+    fn balance_changes_of(
+        &self,
+        entity: ComponentAddress,
+    ) -> HashMap<ResourceAddress, BalanceChange>;
+}
+
+impl GetBalanceChanges for TransactionReceipt {
+    /// Returns the balance changes of an account or component during the transaction, by
+    /// resource. Fees paid by the entity are included.
+    /// Panics if the transaction was rejected or aborted, or if it was executed without
+    /// execution trace, see `CallBuilder::with_execution_trace`.
+    ///
+    /// # Arguments
+    /// * `entity`: address of the account or component.
+    fn balance_changes_of(
+        &self,
+        entity: ComponentAddress,
+    ) -> HashMap<ResourceAddress, BalanceChange> {
+        let commit = match &self.result {
+            TransactionResult::Commit(commit) => commit,
+            TransactionResult::Reject(reject) => {
+                panic!("{}", rejection_message(reject, &self.fee_summary));
             }
             TransactionResult::Abort(abort) => {
-                panic!("Transaction abort with: {}", abort.reason);
+                panic!("{}", abort_message(abort));
+            }
+        };
+        let execution_trace = commit.execution_trace.as_ref().expect(
+            "The transaction was executed without execution trace: execute it with \
+                 CallBuilder::with_execution_trace or TestEngine::set_execution_trace",
+        );
+
+        // Vault puts, takes and fee locks are recorded as resource changes, the other vault
+        // methods only appear in the call traces.
+        let mut entity_vaults: HashSet<NodeId> = execution_trace
+            .resource_changes
+            .values()
+            .flatten()
+            .filter(|change| change.node_id == *entity.as_node_id())
+            .map(|change| change.vault_id)
+            .collect();
+        for trace in &execution_trace.execution_traces {
+            collect_vaults_called_by(trace, entity.as_node_id(), &mut entity_vaults);
+        }
+
+        let mut changes: HashMap<ResourceAddress, BalanceChange> = HashMap::new();
+        for (vault_id, (resource, change)) in commit.vault_balance_changes() {
+            if !entity_vaults.contains(vault_id) {
+                continue;
+            }
+            match changes.get_mut(resource) {
+                Some(total) => *total += change.clone(),
+                None => {
+                    changes.insert(*resource, change.clone());
+                }
+            }
+        }
+
+        changes
+    }
+}
+
+/// Collects the vaults whose methods are called by the given entity in a call trace.
+fn collect_vaults_called_by(trace: &ExecutionTrace, entity: &NodeId, vaults: &mut HashSet<NodeId>) {
+    for child in &trace.children {
+        if let (TraceActor::Method(caller), TraceActor::Method(callee)) =
+            (&trace.current_frame_actor, &child.current_frame_actor)
+        {
+            if caller == entity && callee.is_internal_vault() {
+                vaults.insert(*callee);
+            }
+        }
+        collect_vaults_called_by(child, entity, vaults);
+    }
+}
+
+/// Costs in XRD paid for a transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    pub execution: Decimal,
+    pub finalization: Decimal,
+    pub storage: Decimal,
+    pub royalty: Decimal,
+    pub tipping: Decimal,
+    /// Execution cost units consumed by each kind of costing entry.
+    pub execution_cost_units: BTreeMap<String, u32>,
+}
+
+impl FeeBreakdown {
+    /// Returns the sum of all the costs.
+    pub fn total(&self) -> Decimal {
+        self.execution + self.finalization + self.storage + self.royalty + self.tipping
+    }
+}
+
+pub trait GetFees {
+    fn execution_cost(&self) -> Decimal;
+    fn fee_breakdown(&self) -> FeeBreakdown;
+}
+
+impl GetFees for TransactionReceipt {
+    /// Returns the total cost in XRD paid for the transaction, including the execution,
+    /// finalization, storage, royalty and tipping costs.
+    fn execution_cost(&self) -> Decimal {
+        self.fee_summary.total_cost()
+    }
+
+    /// Returns the costs in XRD paid for the transaction, by kind.
+    fn fee_breakdown(&self) -> FeeBreakdown {
+        FeeBreakdown {
+            execution: self.fee_summary.total_execution_cost_in_xrd,
+            finalization: self.fee_summary.total_finalization_cost_in_xrd,
+            storage: self.fee_summary.total_storage_cost_in_xrd,
+            royalty: self.fee_summary.total_royalty_cost_in_xrd,
+            tipping: self.fee_summary.total_tipping_cost_in_xrd,
+            execution_cost_units: self
+                .fee_details
+                .as_ref()
+                .map(|details| details.execution_cost_breakdown.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Substates written by a transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubstateWrites {
+    /// Substates written on nodes created by the transaction.
+    pub created: usize,
+    /// Substates written on nodes that existed before the transaction, including new entries of
+    /// existing key value stores.
+    pub updated: usize,
+    pub deleted: usize,
+    /// Size of the written substate values.
+    pub bytes_written: usize,
+}
+
+impl SubstateWrites {
+    /// Returns the number of created, updated and deleted substates.
+    pub fn writes(&self) -> usize {
+        self.created + self.updated + self.deleted
+    }
+
+    fn add(&mut self, created: bool, update: &DatabaseUpdate) {
+        match update {
+            DatabaseUpdate::Set(value) => {
+                if created {
+                    self.created += 1;
+                } else {
+                    self.updated += 1;
+                }
+                self.bytes_written += value.len();
+            }
+            DatabaseUpdate::Delete => self.deleted += 1,
+        }
+    }
+}
+
+/// Substate writes of a transaction, split between the substates of user packages, such as the
+/// state of components and their key value stores, and the system ones, such as vaults, fee
+/// payments and metadata.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateUpdateStats {
+    pub user: SubstateWrites,
+    pub system: SubstateWrites,
+    /// Number of user substates written, by node.
+    pub user_writes_by_node: IndexMap<NodeId, usize>,
+}
+
+impl StateUpdateStats {
+    /// Returns the writes of both user and system substates.
+    pub fn total(&self) -> SubstateWrites {
+        SubstateWrites {
+            created: self.user.created + self.system.created,
+            updated: self.user.updated + self.system.updated,
+            deleted: self.user.deleted + self.system.deleted,
+            bytes_written: self.user.bytes_written + self.system.bytes_written,
+        }
+    }
+}
+
+pub trait GetStateUpdates {
+    fn state_update_summary(&self) -> StateUpdateStats;
+}
+
+impl GetStateUpdates for TransactionReceipt {
+    /// Returns the number of substates created, updated and deleted by the transaction and the
+    /// size of the written values. Transactions that were not committed wrote nothing.
+    fn state_update_summary(&self) -> StateUpdateStats {
+        let mut stats = StateUpdateStats::default();
+        let commit = match &self.result {
+            TransactionResult::Commit(commit) => commit,
+            _ => return stats,
+        };
+        let structures = &commit.system_structure.substate_system_structures;
+
+        for (node_id, node_updates) in &commit.state_updates.by_node {
+            let NodeStateUpdates::Delta { by_partition } = node_updates;
+            // The type info of a node is only written when the node is created.
+            let created = structures.get(node_id).is_some_and(|partitions| {
+                partitions
+                    .values()
+                    .flat_map(|keys| keys.values())
+                    .any(|structure| {
+                        matches!(
+                            structure,
+                            SubstateSystemStructure::SystemField(SystemFieldStructure {
+                                field_kind: SystemFieldKind::TypeInfo
+                            })
+                        )
+                    })
+            });
+
+            for (partition, partition_updates) in by_partition {
+                let updates: Vec<(SubstateKey, DatabaseUpdate)> = match partition_updates {
+                    PartitionStateUpdates::Delta { by_substate } => by_substate
+                        .iter()
+                        .map(|(key, update)| (key.clone(), update.clone()))
+                        .collect(),
+                    PartitionStateUpdates::Batch(BatchPartitionStateUpdate::Reset {
+                        new_substate_values,
+                    }) => new_substate_values
+                        .iter()
+                        .map(|(key, value)| (key.clone(), DatabaseUpdate::Set(value.clone())))
+                        .collect(),
+                };
+                for (key, update) in updates {
+                    let is_user = structures
+                        .get(node_id)
+                        .and_then(|partitions| partitions.get(partition))
+                        .and_then(|keys| keys.get(&key))
+                        .is_some_and(is_user_substate);
+                    if is_user {
+                        stats.user.add(created, &update);
+                        *stats.user_writes_by_node.entry(*node_id).or_default() += 1;
+                    } else {
+                        stats.system.add(created, &update);
+                    }
+                }
             }
         }
+        stats
+    }
+}
+
+/// Packages whose substates are written by the system rather than by user blueprints.
+const SYSTEM_PACKAGES: [PackageAddress; 16] = [
+    PACKAGE_PACKAGE,
+    RESOURCE_PACKAGE,
+    ACCOUNT_PACKAGE,
+    IDENTITY_PACKAGE,
+    CONSENSUS_MANAGER_PACKAGE,
+    ACCESS_CONTROLLER_PACKAGE,
+    POOL_PACKAGE,
+    TRANSACTION_PROCESSOR_PACKAGE,
+    METADATA_MODULE_PACKAGE,
+    ROYALTY_MODULE_PACKAGE,
+    ROLE_ASSIGNMENT_MODULE_PACKAGE,
+    TEST_UTILS_PACKAGE,
+    GENESIS_HELPER_PACKAGE,
+    FAUCET_PACKAGE,
+    TRANSACTION_TRACKER_PACKAGE,
+    LOCKER_PACKAGE,
+];
+
+/// Returns whether the schema of a substate is defined by a user package.
+fn is_user_substate(structure: &SubstateSystemStructure) -> bool {
+    let schema_node = |schema: &ObjectSubstateTypeReference| match schema {
+        ObjectSubstateTypeReference::Package(reference) => reference.full_type_id.0.into_node_id(),
+        ObjectSubstateTypeReference::ObjectInstance(reference) => reference.resolved_full_type_id.0,
+    };
+    let node_id = match structure {
+        SubstateSystemStructure::SystemField(_) | SubstateSystemStructure::SystemSchema => {
+            return false
+        }
+        SubstateSystemStructure::KeyValueStoreEntry(entry) => entry.value_full_type_id.0,
+        SubstateSystemStructure::ObjectField(field) => schema_node(&field.value_schema),
+        SubstateSystemStructure::ObjectKeyValuePartitionEntry(entry) => {
+            schema_node(&entry.value_schema)
+        }
+        SubstateSystemStructure::ObjectIndexPartitionEntry(entry) => {
+            schema_node(&entry.value_schema)
+        }
+        SubstateSystemStructure::ObjectSortedIndexPartitionEntry(entry) => {
+            schema_node(&entry.value_schema)
+        }
+    };
+    !SYSTEM_PACKAGES
+        .iter()
+        .any(|package| package.into_node_id() == node_id)
+}
+
+#[cfg(test)]
+mod test_receipt_traits {
+    use super::*;
+
+    #[test]
+    fn test_amounts_in() {
+        let message = "[Buy]: Invalid quantity was provided. This sale can only go through when \
+            8.50 tokens are provided.";
+        assert_eq!(amounts_in(message), vec![dec!("8.5")]);
+        assert_eq!(
+            amounts_in("Decimal(\"-1000\") available, 2000.500000000000000000 requested"),
+            vec![dec!(-1000), dec!("2000.5")]
+        );
+        assert!(amounts_in("no amount here.").is_empty());
     }
 }
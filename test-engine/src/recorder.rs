@@ -0,0 +1,343 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use radix_transactions::manifest::{compile, decompile, BlobProvider};
+use serde::{Deserialize, Serialize};
+
+use crate::engine_interface::ExecutionOptions;
+use crate::internal_prelude::*;
+use crate::logger::TransactionContext;
+use crate::test_engine::TestEngine;
+
+const INDEX_FILE: &str = "index.json";
+
+/// Outcome of a recorded transaction.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordedStatus {
+    Success,
+    Failure,
+    Rejected,
+    Aborted,
+}
+
+impl RecordedStatus {
+    fn of(receipt: &TransactionReceipt) -> Self {
+        match &receipt.result {
+            TransactionResult::Commit(commit) => match &commit.outcome {
+                TransactionOutcome::Success(_) => RecordedStatus::Success,
+                TransactionOutcome::Failure(_) => RecordedStatus::Failure,
+            },
+            TransactionResult::Reject(_) => RecordedStatus::Rejected,
+            TransactionResult::Abort(_) => RecordedStatus::Aborted,
+        }
+    }
+}
+
+impl Display for RecordedStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordedStatus::Success => write!(f, "success"),
+            RecordedStatus::Failure => write!(f, "failure"),
+            RecordedStatus::Rejected => write!(f, "rejected"),
+            RecordedStatus::Aborted => write!(f, "aborted"),
+        }
+    }
+}
+
+/// Entry of the index of a recording.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordedStep {
+    /// Name of the manifest file, relative to the recording directory.
+    pub manifest: String,
+    /// Names of the blob files of the manifest, relative to the recording directory.
+    pub blobs: Vec<String>,
+    /// Non-fungible global ids of the signers of the transaction.
+    pub signers: Vec<String>,
+    /// Reference names of the entities used by the manifest, by address.
+    pub reference_names: BTreeMap<String, String>,
+    pub status: RecordedStatus,
+    /// Total cost in XRD of the transaction.
+    pub fee: String,
+    /// Addresses of the packages, components and resources created by the transaction, in
+    /// creation order.
+    pub new_entities: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct RecordingIndex {
+    steps: Vec<RecordedStep>,
+}
+
+/// First step of a replay whose status differs from the recorded one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayDivergence {
+    /// Index of the step, starting at 1.
+    pub step: usize,
+    pub manifest: String,
+    pub expected: RecordedStatus,
+    pub actual: RecordedStatus,
+}
+
+impl Display for ReplayDivergence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Step {} ({}) ended with {} instead of {}",
+            self.step, self.manifest, self.actual, self.expected
+        )
+    }
+}
+
+pub(crate) struct Recorder {
+    path: PathBuf,
+    index: RecordingIndex,
+}
+
+impl Recorder {
+    fn new(path: PathBuf) -> Self {
+        fs::create_dir_all(&path).unwrap_or_else(|err| {
+            panic!(
+                "Could not create the recording directory {}: {}",
+                path.display(),
+                err
+            )
+        });
+        Self {
+            path,
+            index: RecordingIndex::default(),
+        }
+    }
+
+    fn write(&self, file_name: &str, content: impl AsRef<[u8]>) {
+        let file = self.path.join(file_name);
+        fs::write(&file, content)
+            .unwrap_or_else(|err| panic!("Could not write {}: {}", file.display(), err));
+    }
+}
+
+impl TestEngine {
+    /// Records every transaction executed through a [`CallBuilder`](crate::call_builder::CallBuilder)
+    /// to the given directory, as numbered `.rtm` files and an `index.json` file describing
+    /// the signers, reference names and outcome of each transaction.
+    ///
+    /// # Arguments
+    /// * `path`: directory where to write the recording.
+    pub fn enable_recording<P: AsRef<Path>>(&mut self, path: P) {
+        let recorder = Recorder::new(path.as_ref().to_path_buf());
+        recorder.write(INDEX_FILE, Self::index_to_json(&recorder.index));
+        self.recorder = Some(recorder);
+    }
+
+    /// Stops recording the executed transactions.
+    pub fn disable_recording(&mut self) {
+        self.recorder = None;
+    }
+
+    /// Replays a recording against a fresh engine, stopping at the first transaction whose
+    /// status differs from the recorded one.
+    ///
+    /// Only the transactions executed through a `CallBuilder` are recorded: entities created by
+    /// other means, such as accounts or packages, have to exist with the same addresses in the
+    /// fresh engine, or be created beforehand in an engine given to
+    /// [`TestEngine::replay_recording`].
+    ///
+    /// # Arguments
+    /// * `path`: directory of the recording.
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<TestEngine, ReplayDivergence> {
+        let mut test_engine = TestEngine::new();
+        test_engine.replay_recording(path)?;
+        Ok(test_engine)
+    }
+
+    /// Replays a recording against this engine, stopping at the first transaction whose status
+    /// differs from the recorded one. The entities created during the recording are mapped to the
+    /// entities created during the replay by creation order.
+    ///
+    /// # Arguments
+    /// * `path`: directory of the recording.
+    pub fn replay_recording<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ReplayDivergence> {
+        let path = path.as_ref();
+        let index_file = path.join(INDEX_FILE);
+        let index = fs::read_to_string(&index_file)
+            .unwrap_or_else(|err| panic!("Could not read {}: {}", index_file.display(), err));
+        let index: RecordingIndex = serde_json::from_str(&index).unwrap_or_else(|err| {
+            panic!("Invalid recording index {}: {}", index_file.display(), err)
+        });
+
+        let network = self.network();
+        let decoder = AddressBech32Decoder::new(&network);
+        let mut addresses: HashMap<String, String> = HashMap::new();
+
+        for (step_index, step) in index.steps.iter().enumerate() {
+            let manifest_file = path.join(&step.manifest);
+            let manifest = fs::read_to_string(&manifest_file).unwrap_or_else(|err| {
+                panic!("Could not read {}: {}", manifest_file.display(), err)
+            });
+            let blobs = step
+                .blobs
+                .iter()
+                .map(|blob| {
+                    let blob_file = path.join(blob);
+                    fs::read(&blob_file).unwrap_or_else(|err| {
+                        panic!("Could not read {}: {}", blob_file.display(), err)
+                    })
+                })
+                .collect();
+            let manifest = compile(
+                &Self::map_addresses(&manifest, &addresses),
+                &network,
+                BlobProvider::new_with_blobs(blobs),
+            )
+            .unwrap_or_else(|err| panic!("Could not compile {}: {:?}", step.manifest, err));
+            let signers = step
+                .signers
+                .iter()
+                .map(|signer| {
+                    NonFungibleGlobalId::try_from_canonical_string(
+                        &decoder,
+                        &Self::map_addresses(signer, &addresses),
+                    )
+                    .unwrap_or_else(|err| panic!("Invalid signer {}: {:?}", signer, err))
+                })
+                .collect();
+
+            let receipt = self.execute_call(
+                manifest,
+                ExecutionOptions::default(),
+                signers,
+                true,
+                &TransactionContext::default(),
+            );
+
+            let status = RecordedStatus::of(&receipt);
+            let new_entities = self.new_entities_of(&receipt);
+            if status != step.status || new_entities.len() != step.new_entities.len() {
+                return Err(ReplayDivergence {
+                    step: step_index + 1,
+                    manifest: step.manifest.clone(),
+                    expected: step.status,
+                    actual: status,
+                });
+            }
+            for (old, new) in step.new_entities.iter().zip(new_entities) {
+                // Some local ids, such as the ones of validator owner badges, are derived from the
+                // node id of an entity, which is written in hex in the manifests.
+                addresses.insert(
+                    Self::node_id_hex(&decoder, old),
+                    Self::node_id_hex(&decoder, &new),
+                );
+                addresses.insert(old.clone(), new);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        manifest: &TransactionManifestV1,
+        signers: &[NonFungibleGlobalId],
+        receipt: &TransactionReceipt,
+    ) {
+        let Some(recorder) = &self.recorder else {
+            return;
+        };
+
+        let network = self.network();
+        let encoder = AddressBech32Encoder::new(&network);
+        let name = format!("{:04}", recorder.index.steps.len() + 1);
+
+        let manifest_string = decompile(&manifest.instructions, &network)
+            .unwrap_or_else(|err| panic!("Could not decompile the manifest: {:?}", err));
+        let reference_names = self
+            .reference_names_by_address()
+            .into_iter()
+            .filter(|(address, _)| manifest_string.contains(address.as_str()))
+            .collect();
+        let blobs = manifest
+            .blobs
+            .iter()
+            .map(|(hash, blob)| {
+                let blob_name = format!("{}-{}.blob", name, hash);
+                recorder.write(&blob_name, blob);
+                blob_name
+            })
+            .collect();
+        let manifest_name = format!("{}.rtm", name);
+        recorder.write(&manifest_name, &manifest_string);
+
+        let step = RecordedStep {
+            manifest: manifest_name,
+            blobs,
+            signers: signers
+                .iter()
+                .map(|signer| signer.to_canonical_string(&encoder))
+                .collect(),
+            reference_names,
+            status: RecordedStatus::of(receipt),
+            fee: receipt.fee_summary.total_cost().to_string(),
+            new_entities: self.new_entities_of(receipt),
+        };
+
+        let recorder = self.recorder.as_mut().unwrap();
+        recorder.index.steps.push(step);
+        recorder.write(INDEX_FILE, Self::index_to_json(&recorder.index));
+    }
+
+    fn new_entities_of(&self, receipt: &TransactionReceipt) -> Vec<String> {
+        let TransactionResult::Commit(commit) = &receipt.result else {
+            return vec![];
+        };
+        let encoder = AddressBech32Encoder::new(&self.network());
+        let summary = &commit.state_update_summary;
+        summary
+            .new_packages
+            .iter()
+            .map(|address| address.into_node_id())
+            .chain(
+                summary
+                    .new_components
+                    .iter()
+                    .map(|address| address.into_node_id()),
+            )
+            .chain(
+                summary
+                    .new_resources
+                    .iter()
+                    .map(|address| address.into_node_id()),
+            )
+            .map(|node_id| encoder.encode(node_id.as_bytes()).unwrap())
+            .collect()
+    }
+
+    fn node_id_hex(decoder: &AddressBech32Decoder, address: &str) -> String {
+        let (_, node_id) = decoder
+            .validate_and_decode(address)
+            .unwrap_or_else(|err| panic!("Invalid address {}: {:?}", address, err));
+        node_id.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    fn map_addresses(text: &str, addresses: &HashMap<String, String>) -> String {
+        // Addresses are replaced through placeholders so that an address mapped to another
+        // recorded address is not mapped twice.
+        let addresses: Vec<(&String, &String)> = addresses.iter().collect();
+        let text = addresses
+            .iter()
+            .enumerate()
+            .fold(text.to_string(), |text, (i, (old, _))| {
+                text.replace(old.as_str(), &format!("\u{0}{}\u{0}", i))
+            });
+        addresses
+            .iter()
+            .enumerate()
+            .fold(text, |text, (i, (_, new))| {
+                text.replace(&format!("\u{0}{}\u{0}", i), new)
+            })
+    }
+
+    fn index_to_json(index: &RecordingIndex) -> String {
+        serde_json::to_string_pretty(index).expect("Could not serialize the recording index")
+    }
+}
@@ -1,9 +1,15 @@
+use std::fmt::{Display, Formatter};
+
+use radix_engine_interface::blueprints::component::{Global, TypeInfoMarker};
+
 use crate::internal_prelude::*;
 use crate::test_engine::TestEngine;
 
-///
+/// Name used to reference an entity of the TestEngine.
 pub trait ReferenceName {
     fn format(&self) -> String;
+    /// Returns the name as written, before formatting.
+    fn display(&self) -> String;
 }
 
 /// Reference to a global entity (account or component).
@@ -11,6 +17,11 @@ pub trait ComponentReference {
     fn address(&self, test_engine: &TestEngine) -> ComponentAddress;
 }
 
+/// Reference to a package.
+pub trait PackageReference {
+    fn address(&self, test_engine: &TestEngine) -> PackageAddress;
+}
+
 /// Reference to a resource.
 pub trait ResourceReference {
     fn address(&self, test_engine: &TestEngine) -> ResourceAddress;
@@ -25,18 +36,30 @@ impl ReferenceName for String {
     fn format(&self) -> String {
         self.to_string().to_lowercase().replace(['_', ' '], "")
     }
+
+    fn display(&self) -> String {
+        self.clone()
+    }
 }
 
-impl<'a> ReferenceName for &'a String {
+impl ReferenceName for &String {
     fn format(&self) -> String {
         (*self).format()
     }
+
+    fn display(&self) -> String {
+        (*self).clone()
+    }
 }
 
-impl<'a> ReferenceName for &'a str {
+impl ReferenceName for &str {
     fn format(&self) -> String {
         self.to_string().format()
     }
+
+    fn display(&self) -> String {
+        self.to_string()
+    }
 }
 
 impl<T: ReferenceName> ComponentReference for T {
@@ -51,11 +74,29 @@ impl ComponentReference for ComponentAddress {
     }
 }
 
-impl<'a> ComponentReference for &'a ComponentAddress {
+impl ComponentReference for &ComponentAddress {
     fn address(&self, _test_engine: &TestEngine) -> ComponentAddress {
         **self
     }
 }
+impl<T: ReferenceName> PackageReference for T {
+    fn address(&self, test_engine: &TestEngine) -> PackageAddress {
+        test_engine.get_package(self.format())
+    }
+}
+
+impl PackageReference for PackageAddress {
+    fn address(&self, _test_engine: &TestEngine) -> PackageAddress {
+        *self
+    }
+}
+
+impl PackageReference for &PackageAddress {
+    fn address(&self, _test_engine: &TestEngine) -> PackageAddress {
+        **self
+    }
+}
+
 impl<T: ReferenceName> ResourceReference for T {
     fn address(&self, test_engine: &TestEngine) -> ResourceAddress {
         test_engine.get_resource(self.format())
@@ -68,15 +109,39 @@ impl ResourceReference for ResourceAddress {
     }
 }
 
-impl<'a> ResourceReference for &'a ResourceAddress {
+impl ResourceReference for &ResourceAddress {
     fn address(&self, _test_engine: &TestEngine) -> ResourceAddress {
         **self
     }
 }
 
-impl<T: ComponentReference> GlobalReference for T {
+impl<T: ReferenceName> GlobalReference for T {
     fn address(&self, test_engine: &TestEngine) -> GlobalAddress {
-        GlobalAddress::from(self.address(test_engine))
+        test_engine.get_global(self.format())
+    }
+}
+
+impl GlobalReference for ComponentAddress {
+    fn address(&self, _test_engine: &TestEngine) -> GlobalAddress {
+        GlobalAddress::from(*self)
+    }
+}
+
+impl GlobalReference for &ComponentAddress {
+    fn address(&self, _test_engine: &TestEngine) -> GlobalAddress {
+        GlobalAddress::from(**self)
+    }
+}
+
+impl GlobalReference for PackageAddress {
+    fn address(&self, _test_engine: &TestEngine) -> GlobalAddress {
+        GlobalAddress::from(*self)
+    }
+}
+
+impl GlobalReference for &PackageAddress {
+    fn address(&self, _test_engine: &TestEngine) -> GlobalAddress {
+        GlobalAddress::from(**self)
     }
 }
 
@@ -86,8 +151,301 @@ impl GlobalReference for ResourceAddress {
     }
 }
 
-impl<'a> GlobalReference for &'a ResourceAddress {
+impl GlobalReference for &ResourceAddress {
     fn address(&self, _test_engine: &TestEngine) -> GlobalAddress {
         GlobalAddress::from(**self)
     }
 }
+
+impl GlobalReference for GlobalAddress {
+    fn address(&self, _test_engine: &TestEngine) -> GlobalAddress {
+        *self
+    }
+}
+
+impl GlobalReference for &GlobalAddress {
+    fn address(&self, _test_engine: &TestEngine) -> GlobalAddress {
+        **self
+    }
+}
+
+/// A [`GlobalAddress`] references a component only if it is the address of a component or an
+/// account.
+impl ComponentReference for GlobalAddress {
+    fn address(&self, test_engine: &TestEngine) -> ComponentAddress {
+        ComponentAddress::try_from(*self).unwrap_or_else(|_| {
+            panic!(
+                "{} is not the address of a component",
+                test_engine.encode_address(*self)
+            )
+        })
+    }
+}
+
+impl ComponentReference for &GlobalAddress {
+    fn address(&self, test_engine: &TestEngine) -> ComponentAddress {
+        ComponentReference::address(*self, test_engine)
+    }
+}
+
+/// A [`GlobalAddress`] references a package only if it is the address of a package.
+impl PackageReference for GlobalAddress {
+    fn address(&self, test_engine: &TestEngine) -> PackageAddress {
+        PackageAddress::try_from(*self).unwrap_or_else(|_| {
+            panic!(
+                "{} is not the address of a package",
+                test_engine.encode_address(*self)
+            )
+        })
+    }
+}
+
+impl PackageReference for &GlobalAddress {
+    fn address(&self, test_engine: &TestEngine) -> PackageAddress {
+        PackageReference::address(*self, test_engine)
+    }
+}
+
+/// A [`GlobalAddress`] references a resource only if it is the address of a resource.
+impl ResourceReference for GlobalAddress {
+    fn address(&self, test_engine: &TestEngine) -> ResourceAddress {
+        ResourceAddress::try_from(*self).unwrap_or_else(|_| {
+            panic!(
+                "{} is not the address of a resource",
+                test_engine.encode_address(*self)
+            )
+        })
+    }
+}
+
+impl ResourceReference for &GlobalAddress {
+    fn address(&self, test_engine: &TestEngine) -> ResourceAddress {
+        ResourceReference::address(*self, test_engine)
+    }
+}
+
+/// A [`Reference`], as returned by blueprint methods, references a global entity only if its
+/// node is global.
+impl GlobalReference for Reference {
+    fn address(&self, _test_engine: &TestEngine) -> GlobalAddress {
+        GlobalAddress::try_from(self.0)
+            .unwrap_or_else(|_| panic!("{:?} does not reference a global entity", self.0))
+    }
+}
+
+impl ComponentReference for Reference {
+    fn address(&self, test_engine: &TestEngine) -> ComponentAddress {
+        ComponentReference::address(&GlobalReference::address(self, test_engine), test_engine)
+    }
+}
+
+impl PackageReference for Reference {
+    fn address(&self, test_engine: &TestEngine) -> PackageAddress {
+        PackageReference::address(&GlobalReference::address(self, test_engine), test_engine)
+    }
+}
+
+impl ResourceReference for Reference {
+    fn address(&self, test_engine: &TestEngine) -> ResourceAddress {
+        ResourceReference::address(&GlobalReference::address(self, test_engine), test_engine)
+    }
+}
+
+impl<T: TypeInfoMarker> ComponentReference for Global<T> {
+    fn address(&self, _test_engine: &TestEngine) -> ComponentAddress {
+        self.0
+    }
+}
+
+impl<T: TypeInfoMarker> ComponentReference for &Global<T> {
+    fn address(&self, _test_engine: &TestEngine) -> ComponentAddress {
+        self.0
+    }
+}
+
+impl<T: TypeInfoMarker> GlobalReference for Global<T> {
+    fn address(&self, _test_engine: &TestEngine) -> GlobalAddress {
+        GlobalAddress::from(self.0)
+    }
+}
+
+impl<T: TypeInfoMarker> GlobalReference for &Global<T> {
+    fn address(&self, _test_engine: &TestEngine) -> GlobalAddress {
+        GlobalAddress::from(self.0)
+    }
+}
+
+/// Kind of entity a reference name can explicitly point to by using a `<kind>:<name>` prefix,
+/// e.g. `"resource:treasury"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    Account,
+    Component,
+    Package,
+    Resource,
+}
+
+impl Display for EntityKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntityKind::Account => write!(f, "account"),
+            EntityKind::Component => write!(f, "component"),
+            EntityKind::Package => write!(f, "package"),
+            EntityKind::Resource => write!(f, "resource"),
+        }
+    }
+}
+
+/// Splits a formatted reference name into its explicit entity kind, if any, and the name itself.
+pub(crate) fn split_entity_kind(formatted_name: String) -> (Option<EntityKind>, String) {
+    for kind in [
+        EntityKind::Account,
+        EntityKind::Component,
+        EntityKind::Package,
+        EntityKind::Resource,
+    ] {
+        if let Some(name) = formatted_name.strip_prefix(&format!("{}:", kind)) {
+            return (Some(kind), name.to_string());
+        }
+    }
+    (None, formatted_name)
+}
+
+/// Error returned when a reference name cannot be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReferenceError {
+    /// No entity of the given kind, or of any kind if `None`, is referenced by the name.
+    /// `suggestions` contains the closest existing reference names.
+    NotFound {
+        kind: Option<EntityKind>,
+        name: String,
+        suggestions: Vec<String>,
+    },
+    /// The explicit `<kind>:` prefix of the name references another kind of entity.
+    WrongKind {
+        name: String,
+        expected: EntityKind,
+        found: EntityKind,
+    },
+}
+
+impl Display for ReferenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferenceError::NotFound {
+                kind,
+                name,
+                suggestions,
+            } => {
+                match kind {
+                    Some(kind) => write!(f, "There is no {} with name {}", kind, name)?,
+                    None => write!(f, "There is no entity with name {}", name)?,
+                }
+                if !suggestions.is_empty() {
+                    write!(f, ". Did you mean {}?", suggestions.join(" or "))?;
+                }
+                Ok(())
+            }
+            ReferenceError::WrongKind {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} does not reference a {} but a {}",
+                name, expected, found
+            ),
+        }
+    }
+}
+
+/// Returns the names closest to a formatted reference name by edit distance, sorted, if they are
+/// close enough to be a typo of it.
+pub(crate) fn closest_names<'a, I: IntoIterator<Item = &'a String>>(
+    key: &str,
+    names: I,
+) -> Vec<String> {
+    // Allows one typo every three characters, and at least two.
+    let max_distance = (key.chars().count() / 3).max(2);
+    let mut closest: Vec<String> = vec![];
+    let mut closest_distance = max_distance;
+    for name in names {
+        let distance = edit_distance(key, name);
+        if distance > closest_distance {
+            continue;
+        }
+        if distance < closest_distance || closest.is_empty() {
+            closest_distance = distance;
+            closest = vec![name.clone()];
+        } else {
+            closest.push(name.clone());
+        }
+    }
+    closest.sort();
+    closest.dedup();
+    closest
+}
+
+/// Levenshtein distance between two strings, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod test_references {
+    use super::{closest_names, edit_distance};
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("usd", ""), 3);
+        assert_eq!(edit_distance("dutchauction", "dutchauction"), 0);
+        assert_eq!(edit_distance("dutchhauction", "dutchauction"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_closest_name() {
+        let existing = names(&["dutchauction", "englishauction", "default"]);
+        assert_eq!(
+            closest_names("dutchhauction", &existing),
+            names(&["dutchauction"])
+        );
+    }
+
+    #[test]
+    fn test_closest_names_tie() {
+        let existing = names(&["usd", "btc", "usdc", "usdt"]);
+        assert_eq!(
+            closest_names("usdx", &existing),
+            names(&["usd", "usdc", "usdt"])
+        );
+    }
+
+    #[test]
+    fn test_no_close_name() {
+        let existing = names(&["dutchauction", "englishauction"]);
+        assert!(closest_names("radiswap", &existing).is_empty());
+    }
+
+    #[test]
+    fn test_short_names_allow_two_typos() {
+        let existing = names(&["xrd"]);
+        assert_eq!(closest_names("x", &existing), names(&["xrd"]));
+        assert!(closest_names("abc", &existing).is_empty());
+    }
+}
@@ -0,0 +1,392 @@
+use crate::internal_prelude::*;
+use crate::references::{ComponentReference, ReferenceName, ResourceReference};
+use crate::test_engine::TestEngine;
+use crate::to_id::ToId;
+
+enum ResourceKind {
+    Fungible,
+    NonFungible {
+        id_type: NonFungibleIdType,
+        schema: Box<NonFungibleDataSchema>,
+        entries: IndexMap<NonFungibleLocalId, (ManifestValue,)>,
+    },
+}
+
+/// Builder of a new fungible or non-fungible resource, returned by [`TestEngine::new_resource`]
+/// and [`TestEngine::new_nft_resource`]. Incompatible options are reported when the resource is
+/// created.
+pub struct NewResourceBuilder<'a> {
+    test_engine: &'a mut TestEngine,
+    name: String,
+    kind: ResourceKind,
+    divisibility: Option<u8>,
+    initial_supply: Option<Decimal>,
+    recipient: Option<ComponentAddress>,
+    metadata: Vec<(String, MetadataValue)>,
+    mint_roles: Option<MintRoles<RoleDefinition>>,
+    burn_roles: Option<BurnRoles<RoleDefinition>>,
+    freeze_roles: Option<FreezeRoles<RoleDefinition>>,
+    recall_roles: Option<RecallRoles<RoleDefinition>>,
+    withdraw_roles: Option<WithdrawRoles<RoleDefinition>>,
+    data_update_roles: Option<NonFungibleDataUpdateRoles<RoleDefinition>>,
+}
+
+impl<'a> NewResourceBuilder<'a> {
+    pub(crate) fn new_fungible<N: ReferenceName>(test_engine: &'a mut TestEngine, name: N) -> Self {
+        Self::new(test_engine, name, ResourceKind::Fungible)
+    }
+
+    pub(crate) fn new_non_fungible<N: ReferenceName, D: NonFungibleData>(
+        test_engine: &'a mut TestEngine,
+        name: N,
+        id_type: NonFungibleIdType,
+    ) -> Self {
+        let kind = ResourceKind::NonFungible {
+            id_type,
+            schema: Box::new(
+                NonFungibleDataSchema::new_local_without_self_package_replacement::<D>(),
+            ),
+            entries: IndexMap::new(),
+        };
+        Self::new(test_engine, name, kind)
+    }
+
+    fn new<N: ReferenceName>(test_engine: &'a mut TestEngine, name: N, kind: ResourceKind) -> Self {
+        Self {
+            test_engine,
+            name: name.display(),
+            kind,
+            divisibility: None,
+            initial_supply: None,
+            recipient: None,
+            metadata: vec![],
+            mint_roles: None,
+            burn_roles: None,
+            freeze_roles: None,
+            recall_roles: None,
+            withdraw_roles: None,
+            data_update_roles: None,
+        }
+    }
+
+    /// Sets the divisibility of a fungible resource, 18 by default.
+    ///
+    /// # Arguments
+    /// * `divisibility`: divisibility of the resource.
+    pub fn divisibility(mut self, divisibility: u8) -> Self {
+        self.divisibility = Some(divisibility);
+        self
+    }
+
+    /// Sets the initial supply of a fungible resource, deposited to the current account unless
+    /// another recipient is given with [`NewResourceBuilder::deposit_to`].
+    ///
+    /// # Arguments
+    /// * `amount`: initial supply of the resource.
+    pub fn supply<D: TryInto<Decimal>>(mut self, amount: D) -> Self
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        self.initial_supply = Some(amount.try_into().unwrap());
+        self
+    }
+
+    /// Adds initial non-fungibles to a non-fungible resource, deposited to the current account
+    /// unless another recipient is given with [`NewResourceBuilder::deposit_to`].
+    ///
+    /// # Arguments
+    /// * `nfts`: ids and data of the non-fungibles.
+    pub fn nfts<T: ToId, D: ManifestEncode>(mut self, nfts: Vec<(T, D)>) -> Self {
+        match &mut self.kind {
+            ResourceKind::NonFungible { entries, .. } => {
+                for (id, data) in nfts {
+                    entries.insert(id.to_id(), (to_manifest_value_and_unwrap!(&data),));
+                }
+            }
+            ResourceKind::Fungible => {
+                panic!(
+                    "Cannot add non-fungibles to {}, which is a fungible resource",
+                    self.name
+                )
+            }
+        }
+        self
+    }
+
+    /// Deposits the initial supply to another account than the current one.
+    ///
+    /// # Arguments
+    /// * `account`: reference name or address of the account receiving the initial supply.
+    pub fn deposit_to<C: ComponentReference>(mut self, account: C) -> Self {
+        self.recipient = Some(account.address(self.test_engine));
+        self
+    }
+
+    /// Sets a metadata value of the resource. The values of the `name` and `symbol` metadata are
+    /// also registered as reference names of the resource.
+    ///
+    /// # Arguments
+    /// * `key`: metadata key.
+    /// * `value`: metadata value.
+    pub fn metadata(mut self, key: &str, value: MetadataValue) -> Self {
+        self.metadata.push((key.to_string(), value));
+        self
+    }
+
+    /// Allows the holders of a badge to mint the resource.
+    ///
+    /// # Arguments
+    /// * `badge`: reference name or address of the badge.
+    pub fn mintable_by<R: ResourceReference>(mut self, badge: R) -> Self {
+        let badge = badge.address(self.test_engine);
+        self.mint_roles = mint_roles! {
+            minter => rule!(require(badge));
+            minter_updater => rule!(deny_all);
+        };
+        self
+    }
+
+    /// Allows the holders of a badge to burn the resource.
+    ///
+    /// # Arguments
+    /// * `badge`: reference name or address of the badge.
+    pub fn burnable_by<R: ResourceReference>(mut self, badge: R) -> Self {
+        let badge = badge.address(self.test_engine);
+        self.burn_roles = burn_roles! {
+            burner => rule!(require(badge));
+            burner_updater => rule!(deny_all);
+        };
+        self
+    }
+
+    /// Allows anyone to burn the resource.
+    pub fn burnable_by_all(mut self) -> Self {
+        self.burn_roles = burn_roles! {
+            burner => rule!(allow_all);
+            burner_updater => rule!(deny_all);
+        };
+        self
+    }
+
+    /// Allows the holders of a badge to recall the resource from any vault.
+    ///
+    /// # Arguments
+    /// * `badge`: reference name or address of the badge.
+    pub fn recallable_by<R: ResourceReference>(mut self, badge: R) -> Self {
+        let badge = badge.address(self.test_engine);
+        self.recall_roles = recall_roles! {
+            recaller => rule!(require(badge));
+            recaller_updater => rule!(deny_all);
+        };
+        self
+    }
+
+    /// Allows the holders of a badge to freeze the resource in any vault.
+    ///
+    /// # Arguments
+    /// * `badge`: reference name or address of the badge.
+    pub fn freezable_by<R: ResourceReference>(mut self, badge: R) -> Self {
+        let badge = badge.address(self.test_engine);
+        self.freeze_roles = freeze_roles! {
+            freezer => rule!(require(badge));
+            freezer_updater => rule!(deny_all);
+        };
+        self
+    }
+
+    /// Makes the resource non-transferable: it cannot be withdrawn from the vault it is deposited
+    /// to.
+    pub fn soulbound(mut self) -> Self {
+        self.withdraw_roles = withdraw_roles! {
+            withdrawer => rule!(deny_all);
+            withdrawer_updater => rule!(deny_all);
+        };
+        self
+    }
+
+    /// Allows the holders of a badge to update the mutable fields of the data of a non-fungible
+    /// resource.
+    ///
+    /// # Arguments
+    /// * `badge`: reference name or address of the badge.
+    pub fn data_updatable_by<R: ResourceReference>(mut self, badge: R) -> Self {
+        let badge = badge.address(self.test_engine);
+        self.data_update_roles = non_fungible_data_update_roles! {
+            non_fungible_data_updater => rule!(require(badge));
+            non_fungible_data_updater_updater => rule!(deny_all);
+        };
+        self
+    }
+
+    /// Creates the resource, registers its reference names and returns its address.
+    /// Panics if the options of the builder are incompatible.
+    pub fn create(self) -> ResourceAddress {
+        self.check();
+        let recipient = self
+            .recipient
+            .unwrap_or(*self.test_engine.current_account_address());
+        let mut metadata = MetadataInit::new();
+        for (key, value) in self.metadata {
+            metadata.set_metadata(key, value);
+        }
+        let metadata = ModuleConfig {
+            init: metadata,
+            roles: RoleAssignmentInit::default(),
+        };
+
+        let instruction = match self.kind {
+            ResourceKind::Fungible => {
+                let resource_roles = FungibleResourceRoles {
+                    mint_roles: self.mint_roles,
+                    burn_roles: self.burn_roles,
+                    freeze_roles: self.freeze_roles,
+                    recall_roles: self.recall_roles,
+                    withdraw_roles: self.withdraw_roles,
+                    deposit_roles: None,
+                };
+                let divisibility = self.divisibility.unwrap_or(18);
+                match self.initial_supply {
+                    Some(initial_supply) => create_resource_instruction(
+                        FUNGIBLE_RESOURCE_MANAGER_BLUEPRINT,
+                        FUNGIBLE_RESOURCE_MANAGER_CREATE_WITH_INITIAL_SUPPLY_IDENT,
+                        to_manifest_value_and_unwrap!(
+                            &FungibleResourceManagerCreateWithInitialSupplyManifestInput {
+                                owner_role: OwnerRole::None,
+                                track_total_supply: true,
+                                divisibility,
+                                initial_supply,
+                                resource_roles,
+                                metadata,
+                                address_reservation: None,
+                            }
+                        ),
+                    ),
+                    None => create_resource_instruction(
+                        FUNGIBLE_RESOURCE_MANAGER_BLUEPRINT,
+                        FUNGIBLE_RESOURCE_MANAGER_CREATE_IDENT,
+                        to_manifest_value_and_unwrap!(
+                            &FungibleResourceManagerCreateManifestInput {
+                                owner_role: OwnerRole::None,
+                                track_total_supply: true,
+                                divisibility,
+                                resource_roles,
+                                metadata,
+                                address_reservation: None,
+                            }
+                        ),
+                    ),
+                }
+            }
+            ResourceKind::NonFungible {
+                id_type,
+                schema,
+                entries,
+            } => {
+                let resource_roles = NonFungibleResourceRoles {
+                    mint_roles: self.mint_roles,
+                    burn_roles: self.burn_roles,
+                    freeze_roles: self.freeze_roles,
+                    recall_roles: self.recall_roles,
+                    withdraw_roles: self.withdraw_roles,
+                    deposit_roles: None,
+                    non_fungible_data_update_roles: self.data_update_roles,
+                };
+                if entries.is_empty() {
+                    create_resource_instruction(
+                        NON_FUNGIBLE_RESOURCE_MANAGER_BLUEPRINT,
+                        NON_FUNGIBLE_RESOURCE_MANAGER_CREATE_IDENT,
+                        to_manifest_value_and_unwrap!(
+                            &NonFungibleResourceManagerCreateManifestInput {
+                                owner_role: OwnerRole::None,
+                                id_type,
+                                track_total_supply: true,
+                                non_fungible_schema: *schema,
+                                resource_roles,
+                                metadata,
+                                address_reservation: None,
+                            }
+                        ),
+                    )
+                } else {
+                    create_resource_instruction(
+                        NON_FUNGIBLE_RESOURCE_MANAGER_BLUEPRINT,
+                        NON_FUNGIBLE_RESOURCE_MANAGER_CREATE_WITH_INITIAL_SUPPLY_IDENT,
+                        to_manifest_value_and_unwrap!(
+                            &NonFungibleResourceManagerCreateWithInitialSupplyManifestInput {
+                                owner_role: OwnerRole::None,
+                                id_type,
+                                track_total_supply: true,
+                                non_fungible_schema: *schema,
+                                entries,
+                                resource_roles,
+                                metadata,
+                                address_reservation: None,
+                            }
+                        ),
+                    )
+                }
+            }
+        };
+
+        self.test_engine
+            .create_resource(self.name, instruction, recipient)
+    }
+
+    fn check(&self) {
+        let fail = |reason: &str| panic!("Cannot create resource {}: {}", self.name, reason);
+        let has_supply = match &self.kind {
+            ResourceKind::Fungible => {
+                if self
+                    .divisibility
+                    .is_some_and(|divisibility| divisibility > 18)
+                {
+                    fail("the divisibility cannot be greater than 18");
+                }
+                if self.data_update_roles.is_some() {
+                    fail("a fungible resource has no non-fungible data to update");
+                }
+                self.initial_supply.is_some()
+            }
+            ResourceKind::NonFungible {
+                id_type, entries, ..
+            } => {
+                if self.divisibility.is_some() {
+                    fail("a non-fungible resource has no divisibility");
+                }
+                if self.initial_supply.is_some() {
+                    fail("the initial supply of a non-fungible resource is given with `nfts`");
+                }
+                if *id_type == NonFungibleIdType::RUID && !entries.is_empty() {
+                    fail("the ids of a RUID resource are generated and cannot be given");
+                }
+                !entries.is_empty()
+            }
+        };
+        if !has_supply && self.mint_roles.is_none() {
+            fail("it has no initial supply and cannot be minted");
+        }
+        if self.recipient.is_some() && !has_supply {
+            fail("it has no initial supply to deposit");
+        }
+        if self.withdraw_roles.is_some()
+            && self
+                .recipient
+                .is_some_and(|recipient| recipient != *self.test_engine.current_account_address())
+        {
+            fail("the initial supply of a soulbound resource can only be deposited to the current account");
+        }
+    }
+}
+
+fn create_resource_instruction(
+    blueprint_name: &str,
+    function_name: &str,
+    args: ManifestValue,
+) -> InstructionV1 {
+    InstructionV1::CallFunction {
+        package_address: RESOURCE_PACKAGE.into(),
+        blueprint_name: blueprint_name.to_string(),
+        function_name: function_name.to_string(),
+        args,
+    }
+}
@@ -0,0 +1,394 @@
+use std::fmt::{Display, Formatter};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::Duration;
+
+use crate::environment::EnvironmentEncode;
+use crate::internal_prelude::*;
+use crate::method_call::ComplexMethodCaller;
+use crate::receipt_traits::{abort_message, rejection_message};
+use crate::test_engine::TestEngine;
+
+/// Action of a [`Scenario`]. Every step executing a transaction fails if the transaction does not
+/// succeed.
+pub enum Step {
+    /// Creates a token held by the current account.
+    NewToken { name: &'static str, supply: Decimal },
+    /// Instantiates a component of the current package from an account, with an optional badge.
+    NewComponent {
+        caller: &'static str,
+        name: &'static str,
+        blueprint: &'static str,
+        function: &'static str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+        badge: Option<&'static str>,
+    },
+    /// Calls a method of a component from an account, with an optional badge. The resources
+    /// returned by the method are deposited to the account.
+    Call {
+        caller: &'static str,
+        component: &'static str,
+        method: &'static str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+        badge: Option<&'static str>,
+    },
+    /// Transfers a fungible resource between two accounts.
+    Transfer {
+        from: &'static str,
+        to: &'static str,
+        resource: &'static str,
+        amount: Decimal,
+    },
+    /// Advances the ledger by a number of epochs.
+    AdvanceEpochs(u64),
+    /// Advances the time of the ledger.
+    AdvanceTime(Duration),
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::NewToken { name, supply } => write!(f, "create {} {}", supply, name),
+            Step::NewComponent {
+                caller,
+                name,
+                blueprint,
+                function,
+                badge,
+                ..
+            } => {
+                write!(
+                    f,
+                    "{} instantiates {} with {}::{}",
+                    caller, name, blueprint, function
+                )?;
+                write_badge(f, badge)
+            }
+            Step::Call {
+                caller,
+                component,
+                method,
+                badge,
+                ..
+            } => {
+                write!(f, "{} calls {} on {}", caller, method, component)?;
+                write_badge(f, badge)
+            }
+            Step::Transfer {
+                from,
+                to,
+                resource,
+                amount,
+            } => write!(f, "{} transfers {} {} to {}", from, amount, resource, to),
+            Step::AdvanceEpochs(epochs) => write!(f, "advance {} epochs", epochs),
+            Step::AdvanceTime(duration) => write!(f, "advance time by {:?}", duration),
+        }
+    }
+}
+
+fn write_badge(f: &mut Formatter<'_>, badge: &Option<&'static str>) -> std::fmt::Result {
+    match badge {
+        Some(badge) => write!(f, " with badge {}", badge),
+        None => Ok(()),
+    }
+}
+
+/// Comparison of an [`Assert`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl Comparison {
+    /// Returns whether `actual` compares to `expected` with this comparison.
+    pub fn holds(&self, actual: Decimal, expected: Decimal) -> bool {
+        match self {
+            Comparison::Eq => actual == expected,
+            Comparison::Gt => actual > expected,
+            Comparison::Gte => actual >= expected,
+            Comparison::Lt => actual < expected,
+            Comparison::Lte => actual <= expected,
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Comparison::Eq => write!(f, "=="),
+            Comparison::Gt => write!(f, ">"),
+            Comparison::Gte => write!(f, ">="),
+            Comparison::Lt => write!(f, "<"),
+            Comparison::Lte => write!(f, "<="),
+        }
+    }
+}
+
+/// Custom check of an [`Assert`], returning the reason of the failure if it fails.
+pub type CustomCheck = dyn Fn(&mut TestEngine) -> Result<(), String>;
+
+/// Check of a [`Scenario`] on the state of the ledger.
+pub enum Assert {
+    /// Compares the balance of an account or component in a resource to a value.
+    Balance {
+        account: &'static str,
+        resource: &'static str,
+        op: Comparison,
+        value: Decimal,
+    },
+    /// Runs a custom check, which returns the reason of the failure if it fails.
+    Custom {
+        name: &'static str,
+        check: Box<CustomCheck>,
+    },
+}
+
+impl Display for Assert {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Assert::Balance {
+                account,
+                resource,
+                op,
+                value,
+            } => write!(f, "balance of {} in {} {} {}", account, resource, op, value),
+            Assert::Custom { name, .. } => write!(f, "{}", name),
+        }
+    }
+}
+
+enum Entry {
+    Account(&'static str),
+    Step(Step),
+    Assert(Assert),
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Entry::Account(name) => write!(f, "create account {}", name),
+            Entry::Step(step) => write!(f, "{}", step),
+            Entry::Assert(assert) => write!(f, "assert {}", assert),
+        }
+    }
+}
+
+/// First step of a [`Scenario`] that failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScenarioFailure {
+    /// Index of the step, starting at 1. Accounts, steps and asserts are numbered together, in
+    /// declaration order.
+    pub step: usize,
+    pub description: String,
+    pub reason: String,
+}
+
+impl Display for ScenarioFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Step {} ({}) failed: {}",
+            self.step, self.description, self.reason
+        )
+    }
+}
+
+/// Multi-account test script, declared as a list of steps and asserts executed in order.
+#[derive(Default)]
+pub struct Scenario {
+    entries: Vec<Entry>,
+}
+
+impl Scenario {
+    /// Returns an empty scenario.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new account.
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the account.
+    pub fn account(mut self, name: &'static str) -> Self {
+        self.entries.push(Entry::Account(name));
+        self
+    }
+
+    /// Adds a step to the scenario.
+    ///
+    /// # Arguments
+    /// * `step`: step to add.
+    pub fn step(mut self, step: Step) -> Self {
+        self.entries.push(Entry::Step(step));
+        self
+    }
+
+    /// Adds a check of the state of the ledger to the scenario.
+    ///
+    /// # Arguments
+    /// * `assert`: check to add.
+    pub fn assert(mut self, assert: Assert) -> Self {
+        self.entries.push(Entry::Assert(assert));
+        self
+    }
+
+    /// Runs the scenario against an engine, stopping at the first failing step.
+    /// Steps executing a transaction change the current account of the engine to their caller.
+    ///
+    /// # Arguments
+    /// * `test_engine`: engine on which to run the scenario.
+    pub fn run(self, test_engine: &mut TestEngine) -> Result<(), ScenarioFailure> {
+        for (index, entry) in self.entries.into_iter().enumerate() {
+            let description = entry.to_string();
+            // Unknown reference names and other misuses of the engine panic: they are reported as
+            // a failure of the step.
+            let result = catch_unwind(AssertUnwindSafe(|| entry.run(test_engine)))
+                .unwrap_or_else(|payload| Err(panic_message(payload)));
+            if let Err(reason) = result {
+                return Err(ScenarioFailure {
+                    step: index + 1,
+                    description,
+                    reason,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the scenario against an engine.
+    /// Panics with the first failing step.
+    ///
+    /// # Arguments
+    /// * `test_engine`: engine on which to run the scenario.
+    pub fn assert_run(self, test_engine: &mut TestEngine) {
+        if let Err(failure) = self.run(test_engine) {
+            panic!("{}", failure);
+        }
+    }
+}
+
+impl Entry {
+    fn run(self, test_engine: &mut TestEngine) -> Result<(), String> {
+        match self {
+            Entry::Account(name) => {
+                test_engine.new_account(name);
+                Ok(())
+            }
+            Entry::Step(step) => step.run(test_engine),
+            Entry::Assert(assert) => assert.check(test_engine),
+        }
+    }
+}
+
+impl Step {
+    fn run(self, test_engine: &mut TestEngine) -> Result<(), String> {
+        match self {
+            Step::NewToken { name, supply } => {
+                test_engine.new_token(name, supply);
+                Ok(())
+            }
+            Step::NewComponent {
+                caller,
+                name,
+                blueprint,
+                function,
+                args,
+                badge,
+            } => {
+                test_engine.set_current_account(caller);
+                let receipt = match badge {
+                    Some(badge) => {
+                        test_engine.new_component_with_badge(name, blueprint, function, badge, args)
+                    }
+                    None => test_engine.new_component(name, blueprint, function, args),
+                };
+                check_success(&receipt)
+            }
+            Step::Call {
+                caller,
+                component,
+                method,
+                args,
+                badge,
+            } => {
+                test_engine.set_current_account(caller);
+                let call = test_engine
+                    .build_call()
+                    .call_from_component(component, method, args);
+                let receipt = match badge {
+                    Some(badge) => call.with_badge(badge).execute(),
+                    None => call.execute(),
+                };
+                check_success(&receipt)
+            }
+            Step::Transfer {
+                from,
+                to,
+                resource,
+                amount,
+            } => {
+                test_engine.set_current_account(from);
+                let receipt = test_engine.transfer(to, resource, amount);
+                check_success(&receipt)
+            }
+            Step::AdvanceEpochs(epochs) => {
+                test_engine.advance_epochs(epochs);
+                Ok(())
+            }
+            Step::AdvanceTime(duration) => {
+                test_engine.advance_time_by(duration);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Assert {
+    fn check(self, test_engine: &mut TestEngine) -> Result<(), String> {
+        match self {
+            Assert::Balance {
+                account,
+                resource,
+                op,
+                value,
+            } => {
+                let balance = test_engine.balance_of(account, resource);
+                if op.holds(balance, value) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "balance of {} in {} is {}, expected {} {}",
+                        account, resource, balance, op, value
+                    ))
+                }
+            }
+            Assert::Custom { check, .. } => check(test_engine),
+        }
+    }
+}
+
+fn check_success(receipt: &TransactionReceipt) -> Result<(), String> {
+    match &receipt.result {
+        TransactionResult::Commit(commit) => match &commit.outcome {
+            TransactionOutcome::Success(_) => Ok(()),
+            TransactionOutcome::Failure(failure) => {
+                Err(format!("Transaction failed with: {}", failure))
+            }
+        },
+        TransactionResult::Reject(reject) => Err(rejection_message(reject, &receipt.fee_summary)),
+        TransactionResult::Abort(abort) => Err(abort_message(abort)),
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => message.to_string(),
+            Err(_) => "The step panicked".to_string(),
+        },
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::environment::{Environment, EnvironmentEncode};
+use crate::internal_prelude::*;
+use crate::references::EntityKind;
+use crate::test_engine::TestEngine;
+
+/// Declarative description of the initial state of a [`TestEngine`].
+///
+/// Entries are created in the following order: accounts, tokens, account balances, packages and
+/// components. Decimal amounts are written as strings.
+///
+/// Example of a TOML spec:
+/// ```toml
+/// [[accounts]]
+/// name = "alice"
+/// balances = { usd = "250.5" }
+///
+/// [[tokens]]
+/// name = "usd"
+/// divisibility = 6
+/// initial_supply = "1000000"
+///
+/// [[packages]]
+/// name = "hello"
+/// path = "../hello_world/package"
+///
+/// [[components]]
+/// name = "hello_comp"
+/// package = "hello"
+/// blueprint = "Hello"
+/// function = "instantiate_hello"
+/// args = [{ decimal = "1.5" }, { resource = "usd" }, { string = "hi" }]
+/// ```
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SeedSpec {
+    #[serde(default)]
+    pub accounts: Vec<AccountSeed>,
+    #[serde(default)]
+    pub tokens: Vec<TokenSeed>,
+    #[serde(default)]
+    pub packages: Vec<PackageSeed>,
+    #[serde(default)]
+    pub components: Vec<ComponentSeed>,
+}
+
+/// Account to create, with the balances transferred to it from the current account.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AccountSeed {
+    pub name: String,
+    #[serde(default)]
+    pub balances: BTreeMap<String, String>,
+}
+
+/// Fungible token to create, whose initial supply is deposited to the current account.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TokenSeed {
+    pub name: String,
+    #[serde(default = "default_divisibility")]
+    pub divisibility: u8,
+    pub initial_supply: String,
+}
+
+/// Package to publish. Relative paths are resolved from the directory of the spec file.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PackageSeed {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Component to instantiate. The current package is used if no package is given.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ComponentSeed {
+    pub name: String,
+    pub package: Option<String>,
+    pub blueprint: String,
+    pub function: String,
+    #[serde(default)]
+    pub args: Vec<SeedArgument>,
+}
+
+/// Instantiation argument of a component: a scalar or a reference to an entity of the engine.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum SeedArgument {
+    String(String),
+    Bool(bool),
+    U8(u8),
+    U32(u32),
+    U64(u64),
+    I32(i32),
+    I64(i64),
+    Decimal(String),
+    Account(String),
+    Component(String),
+    Package(String),
+    Resource(String),
+}
+
+/// Entities created when seeding a [`TestEngine`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedSummary {
+    pub accounts: Vec<String>,
+    pub tokens: Vec<String>,
+    pub transfers: Vec<(String, String, Decimal)>,
+    pub packages: Vec<String>,
+    pub components: Vec<String>,
+}
+
+fn default_divisibility() -> u8 {
+    18
+}
+
+impl SeedSpec {
+    /// Parses a spec from a `.toml` or `.json` file.
+    ///
+    /// # Arguments
+    /// * `path`: path of the spec file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .unwrap_or_else(|error| panic!("Could not read seed file {:?}: {}", path, error));
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml::from_str(&content)
+                .unwrap_or_else(|error| panic!("Invalid seed file {:?}: {}", path, error)),
+            Some("json") => serde_json::from_str(&content)
+                .unwrap_or_else(|error| panic!("Invalid seed file {:?}: {}", path, error)),
+            _ => panic!("Seed file {:?} should be a .toml or .json file", path),
+        }
+    }
+}
+
+impl TestEngine {
+    /// Creates the entities described in a `.toml` or `.json` spec file and returns what was
+    /// created. See [`SeedSpec`] for the format of the file.
+    ///
+    /// # Arguments
+    /// * `path`: path of the spec file.
+    pub fn seed_from_file<P: AsRef<Path>>(&mut self, path: P) -> SeedSummary {
+        let spec = SeedSpec::from_file(path.as_ref());
+        let base_dir = path
+            .as_ref()
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        self.seed(spec, base_dir)
+    }
+
+    /// Creates the entities described in a spec and returns what was created.
+    ///
+    /// # Arguments
+    /// * `spec`: description of the entities to create.
+    /// * `base_dir`: directory from which relative package paths are resolved.
+    pub fn seed<P: AsRef<Path>>(&mut self, spec: SeedSpec, base_dir: P) -> SeedSummary {
+        let mut summary = SeedSummary::default();
+
+        for account in &spec.accounts {
+            if self.reference_exists(account.name.as_str(), EntityKind::Account) {
+                seed_error("accounts", &account.name, "account already exists");
+            }
+            self.new_account(account.name.as_str());
+            summary.accounts.push(account.name.clone());
+        }
+
+        for token in &spec.tokens {
+            if self.reference_exists(token.name.as_str(), EntityKind::Resource) {
+                seed_error("tokens", &token.name, "token already exists");
+            }
+            let initial_supply = parse_decimal("tokens", &token.name, &token.initial_supply);
+            self.create_fungible(token.name.as_str(), initial_supply, token.divisibility);
+            summary.tokens.push(token.name.clone());
+        }
+
+        for account in &spec.accounts {
+            for (resource, amount) in &account.balances {
+                if !self.reference_exists(resource.as_str(), EntityKind::Resource) {
+                    seed_error(
+                        "accounts",
+                        &account.name,
+                        &format!("unknown resource {}", resource),
+                    );
+                }
+                let amount = parse_decimal("accounts", &account.name, amount);
+                let receipt = self.transfer(
+                    format!("account:{}", account.name),
+                    format!("resource:{}", resource),
+                    amount,
+                );
+                if !receipt.is_commit_success() {
+                    seed_error(
+                        "accounts",
+                        &account.name,
+                        &format!("could not transfer {} {}", amount, resource),
+                    );
+                }
+                summary
+                    .transfers
+                    .push((account.name.clone(), resource.clone(), amount));
+            }
+        }
+
+        for package in &spec.packages {
+            if self.reference_exists(package.name.as_str(), EntityKind::Package) {
+                seed_error("packages", &package.name, "package already exists");
+            }
+            let path = base_dir.as_ref().join(&package.path);
+            if !path.is_dir() {
+                seed_error(
+                    "packages",
+                    &package.name,
+                    &format!("no package at {:?}", path),
+                );
+            }
+            self.new_package(package.name.as_str(), path);
+            summary.packages.push(package.name.clone());
+        }
+
+        for component in &spec.components {
+            if let Some(package) = &component.package {
+                if !self.reference_exists(package.as_str(), EntityKind::Package) {
+                    seed_error(
+                        "components",
+                        &component.name,
+                        &format!("unknown package {}", package),
+                    );
+                }
+                self.set_current_package(package.as_str());
+            }
+            let args = component
+                .args
+                .iter()
+                .map(|arg| self.seed_argument(&component.name, arg))
+                .collect();
+            let receipt = self.new_component(
+                component.name.as_str(),
+                &component.blueprint,
+                &component.function,
+                args,
+            );
+            if let TransactionResult::Commit(CommitResult {
+                outcome: TransactionOutcome::Failure(failure),
+                ..
+            }) = &receipt.result
+            {
+                seed_error(
+                    "components",
+                    &component.name,
+                    &format!("instantiation failed: {}", failure),
+                );
+            } else if !receipt.is_commit_success() {
+                seed_error("components", &component.name, "instantiation was rejected");
+            }
+            summary.components.push(component.name.clone());
+        }
+
+        summary
+    }
+
+    fn seed_argument(
+        &self,
+        component_name: &str,
+        argument: &SeedArgument,
+    ) -> Box<dyn EnvironmentEncode> {
+        let check_reference = |name: &String, kind: EntityKind| {
+            if !self.reference_exists(name.as_str(), kind) {
+                seed_error(
+                    "components",
+                    component_name,
+                    &format!("unknown {} {}", kind, name),
+                );
+            }
+        };
+
+        match argument {
+            SeedArgument::String(value) => Box::new(value.clone()),
+            SeedArgument::Bool(value) => Box::new(*value),
+            SeedArgument::U8(value) => Box::new(*value),
+            SeedArgument::U32(value) => Box::new(*value),
+            SeedArgument::U64(value) => Box::new(*value),
+            SeedArgument::I32(value) => Box::new(*value),
+            SeedArgument::I64(value) => Box::new(*value),
+            SeedArgument::Decimal(value) => {
+                Box::new(parse_decimal("components", component_name, value))
+            }
+            SeedArgument::Account(name) => {
+                check_reference(name, EntityKind::Account);
+                Box::new(Environment::Account(name.clone()))
+            }
+            SeedArgument::Component(name) => {
+                check_reference(name, EntityKind::Component);
+                Box::new(Environment::Component(name.clone()))
+            }
+            SeedArgument::Package(name) => {
+                check_reference(name, EntityKind::Package);
+                Box::new(Environment::Package(name.clone()))
+            }
+            SeedArgument::Resource(name) => {
+                check_reference(name, EntityKind::Resource);
+                Box::new(Environment::Resource(name.clone()))
+            }
+        }
+    }
+}
+
+fn parse_decimal(section: &str, entry: &str, value: &str) -> Decimal {
+    Decimal::from_str(value)
+        .unwrap_or_else(|_| seed_error(section, entry, &format!("malformed decimal \"{}\"", value)))
+}
+
+fn seed_error(section: &str, entry: &str, reason: &str) -> ! {
+    panic!("Invalid seed entry {}[{}]: {}", section, entry, reason)
+}
@@ -0,0 +1,29 @@
+use std::collections::HashMap;
+
+use crate::account::Account;
+use crate::internal_prelude::*;
+
+/// State of a [`TestEngine`](crate::test_engine::TestEngine) at a given point of a test: the
+/// ledger, including its epoch and time, the reference names of the engine and what it tracks
+/// about the ledger, such as the fees paid and the pools it created.
+///
+/// A snapshot can be restored any number of times with
+/// [`TestEngine::restore`](crate::test_engine::TestEngine::restore).
+#[derive(Clone)]
+pub struct StateSnapshot {
+    pub(crate) ledger: LedgerSimulatorSnapshot,
+    pub(crate) accounts: HashMap<String, Account>,
+    pub(crate) current_account: String,
+    pub(crate) packages: HashMap<String, PackageAddress>,
+    pub(crate) current_package: Option<String>,
+    pub(crate) components: HashMap<String, ComponentAddress>,
+    pub(crate) current_component: Option<String>,
+    pub(crate) resources: HashMap<String, ResourceAddress>,
+    pub(crate) component_resources: HashMap<ComponentAddress, HashMap<String, ResourceAddress>>,
+    pub(crate) last_created_components: Vec<ComponentAddress>,
+    pub(crate) display_names: HashMap<String, String>,
+    pub(crate) fees_paid: HashMap<ComponentAddress, Decimal>,
+    pub(crate) total_fees_paid: Decimal,
+    pub(crate) fee_vault_owners: HashMap<NodeId, ComponentAddress>,
+    pub(crate) pool_managers: HashMap<ComponentAddress, ResourceAddress>,
+}
@@ -0,0 +1,204 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+use proptest::prelude::*;
+use proptest::sample::select;
+use proptest::strategy::{NewTree, Union};
+use proptest::test_runner::TestRunner;
+
+use crate::environment::EnvironmentEncode;
+use crate::internal_prelude::*;
+use crate::method_call::SimpleMethodCaller;
+use crate::references::{GlobalReference, ResourceReference};
+use crate::test_engine::TestEngine;
+
+/// Returns a strategy generating decimals in a range, both bounds included. Shrinks towards the
+/// start of the range.
+///
+/// # Arguments
+/// * `range`: range of the generated decimals.
+pub fn decimal_in(range: RangeInclusive<Decimal>) -> impl Strategy<Value = Decimal> {
+    let (start, end) = range.into_inner();
+    if start > end {
+        panic!("Empty decimal range {}..={}", start, end);
+    }
+    let width = PreciseDecimal::from(end) - PreciseDecimal::from(start);
+    (0..=u64::MAX).prop_map(move |step| {
+        let fraction = PreciseDecimal::from(step) / PreciseDecimal::from(u64::MAX);
+        let offset = (width * fraction)
+            .checked_truncate(RoundingMode::ToZero)
+            .unwrap();
+        (start + offset).min(end)
+    })
+}
+
+/// Returns a strategy picking one of the resources registered in the engine, including XRD.
+/// Resources registered after the strategy has been created are not picked.
+///
+/// # Arguments
+/// * `test_engine`: engine whose resources are picked.
+pub fn any_resource(test_engine: &TestEngine) -> impl Strategy<Value = ResourceAddress> {
+    select(test_engine.registered_resources())
+}
+
+/// Returns a strategy picking one of the given resources.
+///
+/// # Arguments
+/// * `test_engine`: engine used to resolve the references.
+/// * `resources`: reference names or addresses of the resources to pick from.
+pub fn resource_among<R: ResourceReference>(
+    test_engine: &TestEngine,
+    resources: Vec<R>,
+) -> impl Strategy<Value = ResourceAddress> {
+    if resources.is_empty() {
+        panic!("Cannot pick a resource among no resources");
+    }
+    let resources: Vec<ResourceAddress> = resources
+        .into_iter()
+        .map(|resource| resource.address(test_engine))
+        .collect();
+    select(resources)
+}
+
+/// Returns a strategy generating integer non-fungible ids.
+pub fn integer_id() -> impl Strategy<Value = NonFungibleLocalId> {
+    any::<u64>().prop_map(NonFungibleLocalId::integer)
+}
+
+/// Returns a strategy generating string non-fungible ids.
+pub fn string_id() -> impl Strategy<Value = NonFungibleLocalId> {
+    "[_0-9a-zA-Z]{1,64}".prop_map(|id| NonFungibleLocalId::string(id).unwrap())
+}
+
+/// Returns a strategy generating bytes non-fungible ids.
+pub fn bytes_id() -> impl Strategy<Value = NonFungibleLocalId> {
+    prop::collection::vec(any::<u8>(), 1..=64).prop_map(|id| NonFungibleLocalId::bytes(id).unwrap())
+}
+
+/// Returns a strategy generating RUID non-fungible ids.
+pub fn ruid_id() -> impl Strategy<Value = NonFungibleLocalId> {
+    any::<[u8; 32]>().prop_map(NonFungibleLocalId::ruid)
+}
+
+/// Returns a strategy generating non-fungible ids of a given type.
+///
+/// # Arguments
+/// * `id_type`: type of the generated ids.
+pub fn non_fungible_local_id(id_type: NonFungibleIdType) -> BoxedStrategy<NonFungibleLocalId> {
+    match id_type {
+        NonFungibleIdType::Integer => integer_id().boxed(),
+        NonFungibleIdType::String => string_id().boxed(),
+        NonFungibleIdType::Bytes => bytes_id().boxed(),
+        NonFungibleIdType::RUID => ruid_id().boxed(),
+    }
+}
+
+/// Call of a method with generated arguments, produced by a [`MethodCallStrategy`].
+#[derive(Clone)]
+pub struct MethodCall {
+    method_name: String,
+    description: String,
+    args: Rc<dyn Fn() -> Vec<Box<dyn EnvironmentEncode>>>,
+}
+
+impl MethodCall {
+    /// Returns the name of the called method.
+    pub fn method_name(&self) -> &str {
+        &self.method_name
+    }
+
+    /// Returns the environment arguments of the call.
+    pub fn args(&self) -> Vec<Box<dyn EnvironmentEncode>> {
+        (self.args)()
+    }
+
+    /// Calls the method on the current component.
+    ///
+    /// # Arguments
+    /// * `test_engine`: engine to make the call with.
+    pub fn call(&self, test_engine: &mut TestEngine) -> TransactionReceipt {
+        test_engine.call_method(&self.method_name, self.args())
+    }
+
+    /// Calls the method on a given entity.
+    ///
+    /// # Arguments
+    /// * `test_engine`: engine to make the call with.
+    /// * `entity`: reference or address of the entity to call.
+    pub fn call_from<G: GlobalReference>(
+        &self,
+        test_engine: &mut TestEngine,
+        entity: G,
+    ) -> TransactionReceipt {
+        test_engine.call_method_from(entity, &self.method_name, self.args())
+    }
+}
+
+impl fmt::Debug for MethodCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.method_name, self.description)
+    }
+}
+
+/// Strategy picking a method among a declared set and generating its arguments.
+///
+/// Each method is declared with a strategy generating a value and a function turning this value
+/// into environment arguments, usually with `env_args!`:
+///
+/// ```ignore
+/// let calls = MethodCallStrategy::new()
+///     .method("swap", decimal_in(dec!(1)..=dec!(100)), |amount| {
+///         env_args!(Fungible::Bucket("usd", amount))
+///     })
+///     .method("claim", Just(()), |_| env_args!());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MethodCallStrategy {
+    methods: Vec<BoxedStrategy<MethodCall>>,
+}
+
+impl MethodCallStrategy {
+    /// Returns a strategy without methods.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a method to pick.
+    ///
+    /// # Arguments
+    /// * `method_name`: name of the method.
+    /// * `values`: strategy generating the value from which the arguments are built.
+    /// * `args`: function building the environment arguments of the call from a value.
+    pub fn method<S, F>(mut self, method_name: &str, values: S, args: F) -> Self
+    where
+        S: Strategy + 'static,
+        S::Value: Clone + 'static,
+        F: Fn(S::Value) -> Vec<Box<dyn EnvironmentEncode>> + 'static,
+    {
+        let method_name = method_name.to_string();
+        let args = Rc::new(args);
+        let calls = values.prop_map(move |value| {
+            let args = args.clone();
+            MethodCall {
+                method_name: method_name.clone(),
+                description: format!("{:?}", value),
+                args: Rc::new(move || args(value.clone())),
+            }
+        });
+        self.methods.push(calls.boxed());
+        self
+    }
+}
+
+impl Strategy for MethodCallStrategy {
+    type Tree = <Union<BoxedStrategy<MethodCall>> as Strategy>::Tree;
+    type Value = MethodCall;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        if self.methods.is_empty() {
+            panic!("Cannot pick a method call among no methods");
+        }
+        Union::new(self.methods.clone()).new_tree(runner)
+    }
+}
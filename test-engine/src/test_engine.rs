@@ -1,16 +1,43 @@
-use std::collections::hash_map::Entry;
-use std::path::Path;
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
 
-use crate::account::Account;
+use radix_engine::blueprints::pool::v1::constants::TWO_RESOURCE_POOL_BLUEPRINT_IDENT;
+use radix_engine::object_modules::metadata::SetMetadataEvent;
+
+use crate::account::{Account, AccountOwner};
 use crate::call_builder::CallBuilder;
-use crate::engine_interface::EngineInterface;
-use crate::environment::EnvironmentEncode;
+use crate::compiled_packages::{with_royalties, PackageRoyalties, PackageSource};
+use crate::cost_trend::CostTrend;
+use crate::engine_interface::{BlueprintFunction, EngineInterface, ExecutionOptions};
+use crate::environment::{
+    EnvTuple, Environment, EnvironmentEncode, Fungible, NonFungibleAll, ToEncode,
+};
 use crate::internal_prelude::*;
+use crate::logger::{StdoutLogger, TestLogger, TransactionContext};
+use crate::manifest_layout::InjectionSummary;
+use crate::metadata_policy::{
+    metadata_value_to_string, MetadataPolicy, MetadataViolation, POLICY_METADATA_KEYS,
+};
 use crate::method_call::{ComplexMethodCaller, SimpleMethodCaller};
-use crate::receipt_traits::Outcome;
-use crate::references::{ComponentReference, GlobalReference, ReferenceName, ResourceReference};
+use crate::nft_fixture::NftFixture;
+use crate::receipt_traits::{GetBalanceChanges, GetEvents, GetStateUpdates, Outcome};
+use crate::recorder::Recorder;
+use crate::references::{
+    closest_names, split_entity_kind, ComponentReference, EntityKind, GlobalReference,
+    PackageReference, ReferenceError, ReferenceName, ResourceReference,
+};
+use crate::resource_builder::NewResourceBuilder;
+use crate::snapshot::StateSnapshot;
+use crate::test_engine_builder::SELF_FUNDED_XRD;
 use crate::to_id::ToId;
 
+/// Reference names registered by [`TestEngine::with_simulator_tracked`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegisteredEntities {
+    pub components: Vec<(String, ComponentAddress)>,
+    pub resources: Vec<(String, ResourceAddress)>,
+}
+
 pub struct TestEngine {
     engine_interface: EngineInterface,
     accounts: HashMap<String, Account>,
@@ -20,13 +47,80 @@ pub struct TestEngine {
     components: HashMap<String, ComponentAddress>,
     current_component: Option<String>,
     resources: HashMap<String, ResourceAddress>,
+    /// Resources created in the same transaction as a component, by name and symbol.
+    component_resources: HashMap<ComponentAddress, HashMap<String, ResourceAddress>>,
+    last_created_components: Vec<ComponentAddress>,
+    display_names: HashMap<String, String>,
+    strict_references: bool,
+    check_divisibility: bool,
+    execution_trace: bool,
+    reference_warnings: Vec<String>,
+    injection_summary: InjectionSummary,
+    self_funded: bool,
+    /// XRD paid in fees by accounts and components since the last reset of the fee tracking.
+    fees_paid: HashMap<ComponentAddress, Decimal>,
+    total_fees_paid: Decimal,
+    /// Owners of the vaults that paid fees, to avoid looking them up again.
+    fee_vault_owners: HashMap<NodeId, ComponentAddress>,
+    /// Badges required to contribute to the native pools created by the engine.
+    pool_managers: HashMap<ComponentAddress, ResourceAddress>,
+    logger: Box<dyn TestLogger>,
+    pub(crate) recorder: Option<Recorder>,
 }
 
 impl TestEngine {
     /// Returns a new TestEngine.
     pub fn new() -> Self {
-        let mut engine_interface = EngineInterface::new();
+        Self::new_with_engine_interface(EngineInterface::new())
+    }
+
+    /// Returns a new TestEngine whose transaction nonces and account keys are derived from a seed,
+    /// so that two engines with the same seed assign the same addresses to the same entities.
+    ///
+    /// # Arguments
+    /// * `seed`: seed from which nonces and keys are derived.
+    pub fn new_deterministic(seed: u64) -> Self {
+        Self::new_with_engine_interface(EngineInterface::new_with_seed(Some(seed)))
+    }
+
+    /// Returns a new TestEngine started from a custom genesis. The default account, the faucet and
+    /// XRD are registered as for [`TestEngine::new`]. The genesis ends with an epoch change, so the
+    /// engine starts at the epoch following the genesis epoch.
+    ///
+    /// # Arguments
+    /// * `genesis`: genesis of the ledger.
+    pub fn new_with_genesis(genesis: CustomGenesis) -> Self {
+        Self::new_with_engine_interface(EngineInterface::new_with_genesis(genesis, false, None))
+    }
+
+    /// Returns a new TestEngine running on a copy of the state of a database, for instance a
+    /// state snapshot exported from a network, so that local transactions can be executed against
+    /// existing components. The entities of the database are referenced with
+    /// [`TestEngine::register_component`] and [`TestEngine::register_token`]:
+    ///
+    /// ```ignore
+    /// let mut test_engine = TestEngine::from_database(&snapshot_db);
+    /// test_engine.register_component("dex", dex_address);
+    /// test_engine.register_token("usd", usd_address);
+    /// ```
+    ///
+    /// A new default account is created. If the database has no faucet, the default account is
+    /// funded by minting [`SELF_FUNDED_XRD`] XRD and the engine is self-funded, as with
+    /// [`TestEngineBuilder::self_funded`](crate::prelude::TestEngineBuilder::self_funded).
+    ///
+    /// # Arguments
+    /// * `database`: database whose state is copied. It is never written to.
+    pub fn from_database<D: SubstateDatabase + ListableSubstateDatabase>(database: &D) -> Self {
+        let mut test_engine =
+            Self::new_with_engine_interface(EngineInterface::from_database(database));
+        if !test_engine.engine_interface.has_faucet() {
+            test_engine.fund_account("default", SELF_FUNDED_XRD);
+            test_engine.set_self_funded();
+        }
+        test_engine
+    }
 
+    pub(crate) fn new_with_engine_interface(mut engine_interface: EngineInterface) -> Self {
         let default_account = Account::new(&mut engine_interface);
         let mut accounts = HashMap::new();
         accounts.insert("default".format(), default_account);
@@ -36,7 +130,14 @@ impl TestEngine {
         resources.insert("XRD".format(), XRD);
 
         let mut components = HashMap::new();
-        components.insert("faucet".format(), FAUCET);
+        let mut display_names = ["default", "Radix", "XRD"]
+            .into_iter()
+            .map(|name| (name.format(), name.to_string()))
+            .collect::<HashMap<_, _>>();
+        if engine_interface.has_faucet() {
+            components.insert("faucet".format(), FAUCET);
+            display_names.insert("faucet".format(), "faucet".to_string());
+        }
 
         Self {
             engine_interface,
@@ -47,6 +148,21 @@ impl TestEngine {
             components,
             current_component: None,
             resources,
+            component_resources: HashMap::new(),
+            last_created_components: vec![],
+            display_names,
+            strict_references: false,
+            check_divisibility: true,
+            execution_trace: false,
+            reference_warnings: vec![],
+            injection_summary: InjectionSummary::default(),
+            self_funded: false,
+            fees_paid: HashMap::new(),
+            total_fees_paid: Decimal::ZERO,
+            fee_vault_owners: HashMap::new(),
+            pool_managers: HashMap::new(),
+            logger: Box::new(StdoutLogger),
+            recorder: None,
         }
     }
 
@@ -62,21 +178,57 @@ impl TestEngine {
         test_engine
     }
 
-    /// Creates a new package from given path with a reference name.
+    /// Creates a new package from a given path or compiled package with a reference name.
     ///
     /// # Arguments
     /// * `name`: name that will be used to reference the package.
-    /// * `path`: path of the package.
-    pub fn new_package<N: ReferenceName, P: AsRef<Path>>(&mut self, name: N, path: P) {
-        match self.packages.get(&name.format()) {
-            Some(_) => {
-                panic!("A package with name {} already exists", name.format());
-            }
-            None => {
-                let receipt = self.engine_interface.publish_package(path);
-                self.create_package(name, receipt);
-            }
-        }
+    /// * `package`: path of the package or package compiled with `global_packages!`.
+    pub fn new_package<N: ReferenceName, P: PackageSource>(&mut self, name: N, package: P) {
+        let (code, definition) = package.code_and_definition();
+        self.publish_package(name, code, definition, OwnerRole::None);
+    }
+
+    /// Creates a new package owned by a badge, which is required to claim the royalties of the
+    /// package or to set its metadata. The same badge can own several versions of a package.
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the package.
+    /// * `package`: path of the package or package compiled with `global_packages!`.
+    /// * `owner_badge`: reference name or address of the badge owning the package.
+    pub fn new_package_with_owner<N: ReferenceName, P: PackageSource, R: ResourceReference>(
+        &mut self,
+        name: N,
+        package: P,
+        owner_badge: R,
+    ) {
+        let (code, definition) = package.code_and_definition();
+        let owner_role = OwnerRole::Fixed(rule!(require(owner_badge.address(self))));
+        self.publish_package(name, code, definition, owner_role);
+    }
+
+    /// Creates a new package owned by a badge, with package royalties on some functions and
+    /// methods of its blueprints. The other functions and methods of these blueprints are free.
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the package.
+    /// * `package`: path of the package or package compiled with `global_packages!`.
+    /// * `owner_badge`: reference name or address of the badge owning the package.
+    /// * `royalties`: royalties of the functions and methods, by blueprint name.
+    pub fn new_package_with_royalties<N: ReferenceName, P: PackageSource, R: ResourceReference>(
+        &mut self,
+        name: N,
+        package: P,
+        owner_badge: R,
+        royalties: PackageRoyalties,
+    ) {
+        let (code, definition) = package.code_and_definition();
+        let owner_role = OwnerRole::Fixed(rule!(require(owner_badge.address(self))));
+        self.publish_package(
+            name,
+            code,
+            with_royalties(definition, royalties),
+            owner_role,
+        );
     }
 
     /// Adds a global package to the TestEngine.
@@ -89,17 +241,7 @@ impl TestEngine {
         name: N,
         package: &(Vec<u8>, PackageDefinition),
     ) {
-        match self.packages.get(&name.format()) {
-            Some(_) => {
-                panic!("A package with name {} already exists", name.format());
-            }
-            None => {
-                let receipt = self
-                    .engine_interface
-                    .publish_compiled_package(package.0.clone(), package.1.clone());
-                self.create_package(name, receipt);
-            }
-        }
+        self.publish_package(name, package.0.clone(), package.1.clone(), OwnerRole::None);
     }
 
     /// Creates a new account with a reference name.
@@ -107,12 +249,63 @@ impl TestEngine {
     /// # Arguments
     /// * `name`: name that will be used to reference the account.
     pub fn new_account<N: ReferenceName>(&mut self, name: N) {
-        match self.accounts.get(&name.format()) {
-            Some(_) => panic!("An account with name {} already exists", name.format()),
-            None => self
-                .accounts
-                .insert(name.format(), Account::new(&mut self.engine_interface)),
-        };
+        self.create_account(name, Account::new);
+    }
+
+    /// Creates a new account controlled by an Ed25519 key with a reference name.
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the account.
+    pub fn new_account_ed25519<N: ReferenceName>(&mut self, name: N) {
+        self.create_account(name, Account::new_ed25519);
+    }
+
+    /// Creates a new account controlled by the given key with a reference name.
+    /// Using the same key across runs makes the account's proofs and signatures reproducible.
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the account.
+    /// * `private_key`: Secp256k1 or Ed25519 key controlling the account.
+    pub fn new_account_with_keys<N: ReferenceName>(&mut self, name: N, private_key: &PrivateKey) {
+        let public_key = private_key.public_key();
+        self.create_account(name, |engine_interface| {
+            Account::with_public_key(engine_interface, public_key)
+        });
+    }
+
+    /// Securifies an account: its owner badge is deposited to the account, referenced by
+    /// `<name> owner badge`, and replaces the signature of its key to sign its transactions.
+    /// Transactions are only signed while the account holds its owner badge, as the badge has to
+    /// be presented to call methods requiring the owner of the account.
+    ///
+    /// # Arguments
+    /// * `name`: reference name of the account.
+    pub fn securify_account<N: ReferenceName>(&mut self, name: N) -> NonFungibleGlobalId {
+        let display = name.display();
+        let key = Self::try_reference_key(&name, EntityKind::Account)
+            .unwrap_or_else(|error| panic!("{}", error));
+        let account = *self.account(name).address();
+        if let AccountOwner::Badge(_) = self.accounts[&key].owner() {
+            panic!("Account {} is already securified", display);
+        }
+
+        self.engine_interface
+            .securify_account(account)
+            .expect_commit_success();
+
+        let local_id = self
+            .engine_interface
+            .nft_ids(account, ACCOUNT_OWNER_BADGE)
+            .into_iter()
+            .next()
+            .unwrap();
+        let owner_badge = NonFungibleGlobalId::new(ACCOUNT_OWNER_BADGE, local_id);
+        self.accounts
+            .get_mut(&key)
+            .unwrap()
+            .securify(owner_badge.clone());
+        self.insert_resource(format!("{} owner badge", display), ACCOUNT_OWNER_BADGE);
+        owner_badge
     }
 
     /// Instantiates a new component of the current package with a reference name.
@@ -180,6 +373,15 @@ impl TestEngine {
         )
     }
 
+    /// Returns whether the fees of the calls are locked by their caller instead of the faucet.
+    pub fn is_self_funded(&self) -> bool {
+        self.self_funded
+    }
+
+    pub(crate) fn set_self_funded(&mut self) {
+        self.self_funded = true;
+    }
+
     /// Calls faucet with the current account.
     pub fn call_faucet(&mut self) {
         CallBuilder::new(self)
@@ -188,6 +390,32 @@ impl TestEngine {
             .execute();
     }
 
+    /// Gives an arbitrary amount of XRD to the given account, without changing the current account.
+    ///
+    /// The XRD is minted in a single transaction executed without authorization checks, so that
+    /// the balance of the account increases by exactly the given amount:
+    /// - on ledgers with a faucet, the fees of the transaction are paid by the faucet.
+    /// - on ledgers without a faucet, such as those of [`TestEngine::from_database`], the
+    ///   transaction is executed with fees disabled.
+    ///
+    /// # Arguments
+    /// * `account`: reference name of the account to fund.
+    /// * `amount`: amount of XRD to give to the account.
+    pub fn fund_account<N: ReferenceName, D: TryInto<Decimal>>(&mut self, account: N, amount: D)
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let account = *self.get_account(account);
+        let amount = amount.try_into().unwrap();
+        if !amount.is_positive() {
+            panic!(
+                "Cannot fund an account with a non-positive amount of XRD: {}",
+                amount
+            );
+        }
+        self.engine_interface.mint_xrd(account, amount);
+    }
+
     /// Transfers some fungible resources form the current account to the given recipient.
     ///
     /// # Arguments
@@ -229,220 +457,1811 @@ impl TestEngine {
             .execute()
     }
 
-    /// Creates a new token.
+    /// Transfers some fungible resources from the current account to the given recipient, or
+    /// refunds them to the current account if the deposit rules of the recipient reject them.
     ///
     /// # Arguments
-    /// * `token_name`: name that will be used to reference the token.
-    /// * `initial_distribution`: initial distribution of the token.
-    pub fn new_token<N: ReferenceName, D: TryInto<Decimal>>(
+    /// * `recipient`: resources to transfer to.
+    /// * `resource`: reference name of the resource to transfer.
+    /// * `amount`: amount of resources to transfer.
+    pub fn transfer_or_refund<
+        E: ReferenceName,
+        R: ReferenceName + Clone + 'static,
+        D: TryInto<Decimal> + Clone + 'static,
+    >(
         &mut self,
-        token_name: N,
-        initial_distribution: D,
-    ) where
+        recipient: E,
+        resource: R,
+        amount: D,
+    ) -> TransactionReceipt
+    where
         <D as TryInto<Decimal>>::Error: std::fmt::Debug,
     {
-        match self.resources.get(&token_name.format()) {
-            Some(_) => {
-                panic!("Token with name {} already exists", token_name.format());
-            }
-            None => {
-                let account = *self.current_account().address();
-                let token_address = self
-                    .engine_interface
-                    .new_fungible(account, initial_distribution.try_into().unwrap());
-                self.resources.insert(token_name.format(), token_address);
-            }
-        }
+        CallBuilder::new(self)
+            .transfer_or_refund(recipient, resource, amount)
+            .execute()
     }
 
-    /// Creates a new token with a given resource address.
+    /// Transfers some fungible resources from the current account to the given recipient as an
+    /// authorized depositor, with a proof of a badge of the current account.
     ///
     /// # Arguments
-    /// * `token_name`: name that will be used to reference the token.
-    /// * `initial_distribution`: initial distribution of the token.
-    /// * `resource_address`: address of the resource.
-    /// * `network`: network on which the resource has the given address.
-    pub fn new_token_with_address<N: ReferenceName, D: TryInto<Decimal>>(
+    /// * `recipient`: resources to transfer to.
+    /// * `resource`: reference name of the resource to transfer.
+    /// * `amount`: amount of resources to transfer.
+    /// * `badge`: reference name or address of the authorized depositor badge.
+    pub fn transfer_with_authorized_badge<
+        E: ReferenceName,
+        R: ReferenceName + Clone + 'static,
+        D: TryInto<Decimal> + Clone + 'static,
+        B: ResourceReference,
+    >(
         &mut self,
-        token_name: N,
-        initial_supply: D,
-        resource_address: &str,
-        network: NetworkDefinition,
-    ) where
+        recipient: E,
+        resource: R,
+        amount: D,
+        badge: B,
+    ) -> TransactionReceipt
+    where
         <D as TryInto<Decimal>>::Error: std::fmt::Debug,
     {
-        match self.resources.get(&token_name.format()) {
-            Some(_) => {
-                panic!("Token with name {} already exists", token_name.format());
-            }
-            None => {
-                let account = self.current_account().clone();
-                let token_address = self.engine_interface.create_pre_allocated_token(
-                    resource_address,
-                    initial_supply.try_into().unwrap(),
-                    network,
-                    &account,
-                );
-                self.resources.insert(token_name.format(), token_address);
-            }
-        }
+        CallBuilder::new(self)
+            .transfer_with_authorized_badge(recipient, resource, amount, badge)
+            .execute()
     }
 
-    /// Registers a new token with a given resource address.
+    /// Sets the default deposit rule of the current account, which decides whether third parties
+    /// can deposit resources to it.
     ///
     /// # Arguments
-    /// * `token_name`: name that will be used to reference the token.
-    /// * `resource_address`: address of the resource.
-    pub fn register_token<N: ReferenceName>(
-        &mut self,
-        token_name: N,
-        resource_address: ResourceAddress,
-    ) {
-        match self.resources.get(&token_name.format()) {
-            Some(_) => {
-                panic!("Token with name {} already exists", token_name.format());
-            }
-            None => {
-                self.resources.insert(token_name.format(), resource_address);
-            }
-        }
+    /// * `rule`: new default deposit rule.
+    pub fn set_default_deposit_rule(&mut self, rule: DefaultDepositRule) -> TransactionReceipt {
+        let account = *self.current_account_address();
+        CallBuilder::new(self)
+            .call_from_component(
+                account,
+                ACCOUNT_SET_DEFAULT_DEPOSIT_RULE_IDENT,
+                vec![Box::new(rule)],
+            )
+            .execute()
     }
 
-    /// Returns the balance of the current account in the given resource.
+    /// Adds a badge to the authorized depositors of the current account, so that deposits made
+    /// with a proof of this badge are accepted whatever the deposit rules of the account.
     ///
     /// # Arguments
-    /// * `resource`: reference name or address of the resource.
-    pub fn current_balance<R: ResourceReference>(&mut self, resource: R) -> Decimal {
+    /// * `badge`: reference name or address of the badge.
+    pub fn add_authorized_depositor<R: ResourceReference>(
+        &mut self,
+        badge: R,
+    ) -> TransactionReceipt {
         let account = *self.current_account_address();
-        let resource = resource.address(self);
-        self.engine_interface.balance(account, resource)
+        let badge = badge.address(self);
+        CallBuilder::new(self)
+            .call_from_component(
+                account,
+                ACCOUNT_ADD_AUTHORIZED_DEPOSITOR,
+                vec![Box::new(ResourceOrNonFungible::Resource(badge))],
+            )
+            .execute()
     }
 
-    /// Returns the balance of the given entity in the given resource.
+    /// Returns a call builder transferring some fungible resources from the current account to the
+    /// given recipient, so that the transfer can be combined with other calls or options before
+    /// its execution.
     ///
     /// # Arguments
-    /// * `entity`: reference name or address of the entity.
-    /// * `resource`: reference name or address of the resource.
-    pub fn balance_of<E: ComponentReference, R: ResourceReference>(
+    /// * `recipient`: resources to transfer to.
+    /// * `resource`: reference name of the resource to transfer.
+    /// * `amount`: amount of resources to transfer.
+    pub fn transfer_builder<
+        E: ReferenceName,
+        R: ReferenceName + Clone + 'static,
+        D: TryInto<Decimal> + Clone + 'static,
+    >(
         &mut self,
-        entity: E,
+        recipient: E,
         resource: R,
-    ) -> Decimal {
-        let entity = entity.address(self);
-        let resource = resource.address(self);
-        self.engine_interface.balance(entity, resource)
+        amount: D,
+    ) -> CallBuilder<'_>
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        CallBuilder::new(self).transfer(recipient, resource, amount)
     }
 
-    /// Returns the IDs of the given non-fungible resource owned by the current account.
+    /// Returns a call builder transferring non-fungible resources from the current account to the
+    /// given recipient, so that the transfer can be combined with other calls or options before
+    /// its execution.
     ///
     /// # Arguments
-    /// * `resource`: reference name or address of the non-fungible resource.
-    pub fn current_ids_balance<R: ResourceReference>(
+    /// * `recipient`: resources to transfer to.
+    /// * `resource`: reference name of the resource to transfer.
+    /// * `ids`: ids to transfer.
+    pub fn transfer_non_fungibles_builder<
+        E: ReferenceName,
+        R: ReferenceName + Clone + 'static,
+        T: ToId,
+    >(
         &mut self,
+        recipient: E,
         resource: R,
-    ) -> Vec<NonFungibleLocalId> {
-        let account = *self.current_account_address();
-        let resource = resource.address(self);
-        self.engine_interface.nft_ids(account, resource)
+        ids: Vec<T>,
+    ) -> CallBuilder<'_> {
+        CallBuilder::new(self).transfer_non_fungibles(recipient, resource, ids)
     }
 
-    /// Returns the IDs of the given non-fungible resource owned by the given account.
+    /// Transfers fungible resources from the current account to several recipients in a single
+    /// transaction, which fails if one of the recipients refuses the deposit.
+    /// Panics if there is no recipient.
     ///
     /// # Arguments
-    /// * `account`: reference name of the account.
-    /// * `resource`: reference name or address of the resource.
-    pub fn ids_balance_of<E: ComponentReference, R: ResourceReference>(
+    /// * `resource`: reference name of the resource to transfer.
+    /// * `recipients`: reference names of the recipients with the amount they receive.
+    pub fn airdrop<R: ResourceReference, E: ReferenceName, D: TryInto<Decimal>>(
         &mut self,
-        entity: E,
         resource: R,
-    ) -> Vec<NonFungibleLocalId> {
-        let entity = entity.address(self);
-        let resource = resource.address(self);
-        self.engine_interface.nft_ids(entity, resource)
-    }
-
-    /// Moves to next epoch.
-    pub fn next_epoch(&mut self) {
-        let epoch = self.engine_interface.get_epoch();
-        self.engine_interface.set_epoch(epoch.next().unwrap());
+        recipients: Vec<(E, D)>,
+    ) -> TransactionReceipt
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        CallBuilder::new(self)
+            .airdrop(resource, recipients)
+            .execute()
     }
 
-    /// Advances epochs by the given amount.
+    /// Transfers non-fungible resources from the current account to several recipients in a
+    /// single transaction, which fails if one of the recipients refuses the deposit.
+    /// Panics if there is no recipient.
     ///
     /// # Arguments
-    /// * `epochs`: amount of epochs to jump to.
-    pub fn jump_epochs(&mut self, epochs: u64) {
-        let epoch = self.engine_interface.get_epoch();
-        self.engine_interface
-            .set_epoch(epoch.after(epochs).unwrap());
-    }
-
-    pub fn advance_time(&mut self, time: u64) {
-        self.engine_interface.advance_time(time);
+    /// * `resource`: reference name of the resource to transfer.
+    /// * `recipients`: reference names of the recipients with the ids they receive.
+    pub fn airdrop_non_fungibles<R: ResourceReference, E: ReferenceName, T: ToId>(
+        &mut self,
+        resource: R,
+        recipients: Vec<(E, Vec<T>)>,
+    ) -> TransactionReceipt {
+        CallBuilder::new(self)
+            .airdrop_non_fungibles(resource, recipients)
+            .execute()
     }
 
-    /// Jumps back epochs by the given amount.
+    /// Creates a new token.
     ///
     /// # Arguments
-    /// * `epochs`: amount of epochs to jump back to.
-    pub fn jump_back_epochs(&mut self, mut epochs: u64) {
+    /// * `token_name`: name that will be used to reference the token.
+    /// * `initial_distribution`: initial distribution of the token.
+    pub fn new_token<N: ReferenceName, D: TryInto<Decimal>>(
+        &mut self,
+        token_name: N,
+        initial_distribution: D,
+    ) where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        self.create_fungible(token_name, initial_distribution.try_into().unwrap(), 18);
+    }
+
+    /// Creates a new token with the given divisibility and metadata. The token is referenced by its
+    /// name and also by the values of its `name` and `symbol` metadata, if set.
+    ///
+    /// # Arguments
+    /// * `token_name`: name that will be used to reference the token.
+    /// * `initial_supply`: initial supply of the token, deposited to the current account.
+    /// * `divisibility`: divisibility of the token.
+    /// * `metadata`: metadata of the token, such as its symbol, name, icon or description.
+    pub fn new_token_with_metadata<N: ReferenceName, D: TryInto<Decimal>>(
+        &mut self,
+        token_name: N,
+        initial_supply: D,
+        divisibility: u8,
+        metadata: Vec<(&str, MetadataValue)>,
+    ) where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        self.create_fungible_advanced(
+            token_name,
+            initial_supply.try_into().unwrap(),
+            divisibility,
+            FungibleResourceRoles::default(),
+            metadata,
+        );
+    }
+
+    /// Creates a new token whose additional supply can be minted by anyone presenting the given
+    /// badge.
+    ///
+    /// # Arguments
+    /// * `token_name`: name that will be used to reference the token.
+    /// * `initial_supply`: initial supply of the token, deposited to the current account.
+    /// * `divisibility`: divisibility of the token.
+    /// * `minter_badge`: reference name or address of the badge required to mint the token.
+    pub fn new_mintable_token<N: ReferenceName, D: TryInto<Decimal>, R: ResourceReference>(
+        &mut self,
+        token_name: N,
+        initial_supply: D,
+        divisibility: u8,
+        minter_badge: R,
+    ) where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let minter_badge = minter_badge.address(self);
+        self.create_fungible_advanced(
+            token_name,
+            initial_supply.try_into().unwrap(),
+            divisibility,
+            FungibleResourceRoles {
+                mint_roles: mint_roles! {
+                    minter => rule!(require(minter_badge));
+                    minter_updater => rule!(deny_all);
+                },
+                ..Default::default()
+            },
+            vec![],
+        );
+    }
+
+    /// Creates a new token that can be recalled from and frozen in any vault with a badge.
+    ///
+    /// # Arguments
+    /// * `token_name`: name that will be used to reference the token.
+    /// * `initial_supply`: initial supply of the token.
+    /// * `divisibility`: divisibility of the token.
+    /// * `badge`: reference name or address of the badge allowed to recall and freeze the token.
+    pub fn new_recallable_token<N: ReferenceName, D: TryInto<Decimal>, R: ResourceReference>(
+        &mut self,
+        token_name: N,
+        initial_supply: D,
+        divisibility: u8,
+        badge: R,
+    ) where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let badge = badge.address(self);
+        self.create_fungible_advanced(
+            token_name,
+            initial_supply.try_into().unwrap(),
+            divisibility,
+            FungibleResourceRoles {
+                recall_roles: recall_roles! {
+                    recaller => rule!(require(badge));
+                    recaller_updater => rule!(deny_all);
+                },
+                freeze_roles: freeze_roles! {
+                    freezer => rule!(require(badge));
+                    freezer_updater => rule!(deny_all);
+                },
+                ..Default::default()
+            },
+            vec![],
+        );
+    }
+
+    /// Returns a builder of a new fungible resource, created by [`NewResourceBuilder::create`].
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the resource.
+    pub fn new_resource<N: ReferenceName>(&mut self, name: N) -> NewResourceBuilder<'_> {
+        NewResourceBuilder::new_fungible(self, name)
+    }
+
+    /// Returns a builder of a new non-fungible resource, created by
+    /// [`NewResourceBuilder::create`].
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the resource.
+    /// * `id_type`: type of the ids of the non-fungibles of the resource.
+    pub fn new_nft_resource<D: NonFungibleData, N: ReferenceName>(
+        &mut self,
+        name: N,
+        id_type: NonFungibleIdType,
+    ) -> NewResourceBuilder<'_> {
+        NewResourceBuilder::new_non_fungible::<N, D>(self, name, id_type)
+    }
+
+    /// Creates a new token with a given resource address.
+    ///
+    /// # Arguments
+    /// * `token_name`: name that will be used to reference the token.
+    /// * `initial_distribution`: initial distribution of the token.
+    /// * `resource_address`: address of the resource.
+    /// * `network`: network on which the resource has the given address.
+    pub fn new_token_with_address<N: ReferenceName, D: TryInto<Decimal>>(
+        &mut self,
+        token_name: N,
+        initial_supply: D,
+        resource_address: &str,
+        network: NetworkDefinition,
+    ) where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        match self.resources.get(&token_name.format()) {
+            Some(_) => {
+                panic!("Token with name {} already exists", token_name.format());
+            }
+            None => {
+                let account = self.current_account().clone();
+                let token_address = self.engine_interface.create_pre_allocated_token(
+                    resource_address,
+                    initial_supply.try_into().unwrap(),
+                    network,
+                    &account,
+                );
+                self.check_reference_collision(&token_name.format(), EntityKind::Resource);
+                self.register_display_name(&token_name);
+                self.resources.insert(token_name.format(), token_address);
+            }
+        }
+    }
+
+    /// Creates a new non-fungible resource with a given resource address.
+    /// Panics if the address is not the address of a non-fungible resource.
+    ///
+    /// # Arguments
+    /// * `token_name`: name that will be used to reference the resource.
+    /// * `resource_address`: address of the resource.
+    /// * `network`: network on which the resource has the given address.
+    /// * `initial_nfts`: ids and data of the non-fungibles minted to the current account.
+    pub fn new_non_fungible_with_address<N: ReferenceName, D: ManifestEncode + NonFungibleData>(
+        &mut self,
+        token_name: N,
+        resource_address: &str,
+        network: NetworkDefinition,
+        initial_nfts: Vec<(NonFungibleLocalId, D)>,
+    ) {
+        match self.resources.get(&token_name.format()) {
+            Some(_) => {
+                panic!("Token with name {} already exists", token_name.format());
+            }
+            None => {
+                let account = self.current_account().clone();
+                let token_address = self.engine_interface.create_pre_allocated_non_fungible(
+                    resource_address,
+                    initial_nfts,
+                    network,
+                    &account,
+                );
+                self.check_reference_collision(&token_name.format(), EntityKind::Resource);
+                self.register_display_name(&token_name);
+                self.resources.insert(token_name.format(), token_address);
+            }
+        }
+    }
+
+    /// Creates a new non-fungible resource without initial supply and returns its address. The
+    /// non-fungibles of the collection are minted with [`TestEngine::mint_nft`] and
+    /// [`TestEngine::mint_nfts`], or with [`TestEngine::mint_ruid_nfts`] for RUID collections.
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the collection.
+    /// * `id_type`: type of the ids of the non-fungibles of the collection.
+    pub fn new_nft_collection<D: NonFungibleData + ManifestEncode, N: ReferenceName>(
+        &mut self,
+        name: N,
+        id_type: NonFungibleIdType,
+    ) -> ResourceAddress {
+        if self.resources.contains_key(&name.format()) {
+            panic!("Token with name {} already exists", name.format());
+        }
+        let collection = self.engine_interface.new_non_fungible::<D>(id_type);
+        self.insert_resource(name.display(), collection);
+        collection
+    }
+
+    /// Mints a non-fungible of a collection to the current account.
+    ///
+    /// # Arguments
+    /// * `collection`: reference name or address of the collection.
+    /// * `id`: id of the non-fungible.
+    /// * `data`: data of the non-fungible.
+    pub fn mint_nft<R: ResourceReference, T: ToId, D: NonFungibleData + ManifestEncode>(
+        &mut self,
+        collection: R,
+        id: T,
+        data: D,
+    ) -> NonFungibleLocalId {
+        self.mint_nfts(collection, vec![(id, data)]).remove(0)
+    }
+
+    /// Mints non-fungibles of a collection to the current account and returns their ids.
+    ///
+    /// # Arguments
+    /// * `collection`: reference name or address of the collection.
+    /// * `nfts`: ids and data of the non-fungibles.
+    pub fn mint_nfts<R: ResourceReference, T: ToId, D: NonFungibleData + ManifestEncode>(
+        &mut self,
+        collection: R,
+        nfts: Vec<(T, D)>,
+    ) -> Vec<NonFungibleLocalId> {
+        let collection = collection.address(self);
+        let account = *self.current_account_address();
+        let entries = nfts
+            .into_iter()
+            .map(|(id, data)| (id.to_id(), data))
+            .collect();
+        self.mint_nfts_to(account, collection, entries)
+    }
+
+    /// Mints non-fungibles of a RUID collection to the current account and returns their ids, in
+    /// minting order.
+    ///
+    /// # Arguments
+    /// * `collection`: reference name or address of the collection.
+    /// * `nfts`: data of the non-fungibles.
+    pub fn mint_ruid_nfts<R: ResourceReference, D: NonFungibleData + ManifestEncode>(
+        &mut self,
+        collection: R,
+        nfts: Vec<D>,
+    ) -> Vec<NonFungibleLocalId> {
+        let collection = collection.address(self);
+        let account = *self.current_account_address();
+        self.mint_ruid_nfts_to(account, collection, nfts)
+    }
+
+    /// Returns a builder of a non-fungible collection minted in batches, with integer ids or
+    /// RUIDs, created by [`NftFixture::mint_to`].
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the collection.
+    pub fn new_nft_fixture<D: NonFungibleData + ManifestEncode, N: ReferenceName>(
+        &mut self,
+        name: N,
+    ) -> NftFixture<'_, D> {
+        NftFixture::new(self, name)
+    }
+
+    /// Registers a new token with a given resource address.
+    ///
+    /// # Arguments
+    /// * `token_name`: name that will be used to reference the token.
+    /// * `resource_address`: address of the resource.
+    pub fn register_token<N: ReferenceName>(
+        &mut self,
+        token_name: N,
+        resource_address: ResourceAddress,
+    ) {
+        match self.resources.get(&token_name.format()) {
+            Some(_) => {
+                panic!("Token with name {} already exists", token_name.format());
+            }
+            None => {
+                self.check_reference_collision(&token_name.format(), EntityKind::Resource);
+                self.register_display_name(&token_name);
+                self.resources.insert(token_name.format(), resource_address);
+            }
+        }
+    }
+
+    /// Registers a component, such as a component of the database of an engine created with
+    /// [`TestEngine::from_database`].
+    ///
+    /// # Arguments
+    /// * `component_name`: name that will be used to reference the component.
+    /// * `component`: address of the component, as a [`ComponentAddress`], a [`GlobalAddress`],
+    ///   a typed `Global` or a `Reference`.
+    pub fn register_component<N: ReferenceName, C: ComponentReference>(
+        &mut self,
+        component_name: N,
+        component: C,
+    ) {
+        let component_address = component.address(self);
+        self.insert_component(component_name.format(), component_address);
+    }
+
+    /// Returns the database holding the state of the ledger, from which another engine can be
+    /// created with [`TestEngine::from_database`].
+    pub fn substate_db(&self) -> &InMemorySubstateDatabase {
+        self.engine_interface.substate_db()
+    }
+
+    /// Executes the same logical method call several times and records the execution cost units
+    /// consumed by each call.
+    /// Panics if one of the calls fails.
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component to call.
+    /// * `method_name`: name of the method to call.
+    /// * `args_fn`: returns the environment arguments of the call from its index.
+    /// * `iterations`: number of calls.
+    pub fn cost_trend<G: GlobalReference, F: FnMut(usize) -> Vec<Box<dyn EnvironmentEncode>>>(
+        &mut self,
+        component: G,
+        method_name: &str,
+        mut args_fn: F,
+        iterations: usize,
+    ) -> CostTrend {
+        let address = component.address(self);
+        let costs = (0..iterations)
+            .map(|iteration| {
+                let receipt = CallBuilder::new(self)
+                    .call_method_internal(address, method_name, args_fn(iteration))
+                    .execute();
+                if !receipt.is_commit_success() {
+                    panic!(
+                        "Call {} of {} failed during cost measurement: {:?}",
+                        iteration, method_name, receipt.result
+                    );
+                }
+                receipt.fee_summary.total_execution_cost_units_consumed
+            })
+            .collect();
+
+        CostTrend::new(costs)
+    }
+
+    /// Returns the metadata value of an entity for the given key, if set.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `key`: metadata key.
+    pub fn get_metadata<G: GlobalReference>(
+        &mut self,
+        entity: G,
+        key: &str,
+    ) -> Option<MetadataValue> {
+        let address = entity.address(self);
+        self.engine_interface.get_metadata(address, key)
+    }
+
+    /// Sets a metadata value of an entity with a badge. Setting the `name` metadata of a component,
+    /// or the `name` or `symbol` metadata of a resource, also registers the new value as a
+    /// reference name of the entity.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `key`: metadata key.
+    /// * `value`: new metadata value.
+    /// * `badge`: reference name or address of the badge allowed to set the metadata.
+    pub fn set_metadata<G: GlobalReference, R: ResourceReference>(
+        &mut self,
+        entity: G,
+        key: &str,
+        value: MetadataValue,
+        badge: R,
+    ) -> TransactionReceipt {
+        CallBuilder::new(self)
+            .set_metadata(entity, key, value)
+            .with_badge(badge)
+            .execute()
+    }
+
+    /// Sets a metadata value of a package with its owner badge.
+    ///
+    /// # Arguments
+    /// * `package`: reference name or address of the package.
+    /// * `key`: metadata key.
+    /// * `value`: new metadata value.
+    /// * `badge`: reference name or address of the owner badge of the package.
+    pub fn set_package_metadata<P: PackageReference, R: ResourceReference>(
+        &mut self,
+        package: P,
+        key: &str,
+        value: MetadataValue,
+        badge: R,
+    ) -> TransactionReceipt {
+        let package = package.address(self);
+        self.set_metadata(package, key, value, badge)
+    }
+
+    /// Claims the royalties accumulated by a package with its owner badge. The XRD are deposited
+    /// to the current account.
+    ///
+    /// # Arguments
+    /// * `package`: reference name or address of the package.
+    /// * `badge`: reference name or address of the owner badge of the package.
+    pub fn claim_package_royalties<P: PackageReference, R: ResourceReference>(
+        &mut self,
+        package: P,
+        badge: R,
+    ) -> TransactionReceipt {
+        let package = package.address(self);
+        CallBuilder::new(self)
+            .call_from_component(package, PACKAGE_CLAIM_ROYALTIES_IDENT, vec![])
+            .with_badge(badge)
+            .execute()
+    }
+
+    /// Asserts that the metadata of a resource complies with the given policy.
+    /// Panics with every violation if it does not.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource.
+    /// * `policy`: metadata policy to check.
+    pub fn assert_resource_metadata_compliant<R: ResourceReference>(
+        &mut self,
+        resource: R,
+        policy: &MetadataPolicy,
+    ) {
+        let resource = resource.address(self);
+        let other_symbols = self.resource_symbols(&[resource]);
+        let violations = self.metadata_violations(resource, policy, &other_symbols);
+        if !violations.is_empty() {
+            panic!("{}", self.metadata_report(&[(resource, violations)]));
+        }
+    }
+
+    /// Asserts that the metadata of all resources created by a transaction comply with the given
+    /// policy. New resources must also have different symbols.
+    /// Panics with every violation of every resource if they do not.
+    ///
+    /// # Arguments
+    /// * `receipt`: receipt of the transaction that created the resources.
+    /// * `policy`: metadata policy to check.
+    pub fn assert_all_new_resources_compliant(
+        &mut self,
+        receipt: &TransactionReceipt,
+        policy: &MetadataPolicy,
+    ) {
+        let new_resources = receipt
+            .expect_commit_success()
+            .new_resource_addresses()
+            .iter()
+            .cloned()
+            .collect::<Vec<ResourceAddress>>();
+        let mut other_symbols = self.resource_symbols(&new_resources);
+
+        let mut report = vec![];
+        for resource in new_resources {
+            let violations = self.metadata_violations(resource, policy, &other_symbols);
+            if let Some(symbol) = self.get_metadata(resource, "symbol") {
+                other_symbols.push(metadata_value_to_string(&symbol));
+            }
+            if !violations.is_empty() {
+                report.push((resource, violations));
+            }
+        }
+
+        if !report.is_empty() {
+            panic!("{}", self.metadata_report(&report));
+        }
+    }
+
+    /// Returns the balance of the current account in the given resource.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource.
+    pub fn current_balance<R: ResourceReference>(&mut self, resource: R) -> Decimal {
+        let account = *self.current_account_address();
+        let resource = resource.address(self);
+        self.engine_interface.balance(account, resource)
+    }
+
+    /// Returns the balance of the given entity in the given resource, summed over all the vaults of
+    /// the resource owned by the entity.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `resource`: reference name or address of the resource.
+    pub fn balance_of<E: ComponentReference, R: ResourceReference>(
+        &mut self,
+        entity: E,
+        resource: R,
+    ) -> Decimal {
+        let entity = entity.address(self);
+        let resource = resource.address(self);
+        self.engine_interface.balance(entity, resource)
+    }
+
+    /// Returns the total supply of a resource, or `None` if the resource does not track its total
+    /// supply.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource.
+    pub fn total_supply<R: ResourceReference>(&mut self, resource: R) -> Option<Decimal> {
+        let resource = resource.address(self);
+        self.engine_interface.total_supply(resource)
+    }
+
+    /// Returns the divisibility of a resource: its number of decimals, 0 for a non-fungible
+    /// resource.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource.
+    pub fn divisibility<R: ResourceReference>(&mut self, resource: R) -> u8 {
+        let resource = resource.address(self);
+        self.engine_interface
+            .divisibility(resource)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Could not read the divisibility of {}",
+                    self.encode_address(resource.into())
+                )
+            })
+    }
+
+    /// Rounds an amount to the divisibility of a resource, so that it can be withdrawn.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource.
+    /// * `amount`: amount to round.
+    /// * `mode`: rounding mode.
+    pub fn round_to_divisibility<R: ResourceReference, D: TryInto<Decimal>>(
+        &mut self,
+        resource: R,
+        amount: D,
+        mode: RoundingMode,
+    ) -> Decimal
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let divisibility = self.divisibility(resource);
+        amount
+            .try_into()
+            .unwrap()
+            .checked_round(divisibility, mode)
+            .expect("Overflow")
+    }
+
+    /// Returns the balance of every vault of a resource owned by an entity, in no particular
+    /// order.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `resource`: reference name or address of the resource.
+    pub fn vaults_of<E: ComponentReference, R: ResourceReference>(
+        &mut self,
+        entity: E,
+        resource: R,
+    ) -> Vec<Decimal> {
+        let entity = entity.address(self);
+        let resource = resource.address(self);
+        self.engine_interface.vault_balances(entity, resource)
+    }
+
+    /// Returns the ids of every vault of a resource owned by an entity, including the vaults
+    /// stored in its key value stores, in no particular order.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `resource`: reference name or address of the resource.
+    pub fn vault_ids_of<E: ComponentReference, R: ResourceReference>(
+        &mut self,
+        entity: E,
+        resource: R,
+    ) -> Vec<NodeId> {
+        let entity = entity.address(self);
+        let resource = resource.address(self);
+        self.engine_interface.component_vaults(entity, resource)
+    }
+
+    /// Returns the balance of a fungible vault.
+    /// Panics if there is no fungible vault with this id.
+    ///
+    /// # Arguments
+    /// * `vault`: id of the vault, as returned by [`TestEngine::vault_ids_of`] or read from the
+    ///   state of a component.
+    pub fn fungible_vault_balance(&mut self, vault: NodeId) -> Decimal {
+        self.engine_interface
+            .fungible_vault_balance(vault)
+            .unwrap_or_else(|| panic!("There is no fungible vault with id {:?}", vault))
+    }
+
+    /// Returns the ids held by a non-fungible vault.
+    /// Panics if there is no non-fungible vault with this id.
+    ///
+    /// # Arguments
+    /// * `vault`: id of the vault, as returned by [`TestEngine::vault_ids_of`] or read from the
+    ///   state of a component.
+    pub fn non_fungible_vault_balance(&mut self, vault: NodeId) -> Vec<NonFungibleLocalId> {
+        self.engine_interface
+            .non_fungible_vault_balance(vault)
+            .unwrap_or_else(|| panic!("There is no non-fungible vault with id {:?}", vault))
+    }
+
+    /// Returns the sum of the balances of the vaults of a resource owned by an entity, read vault
+    /// by vault.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `resource`: reference name or address of the resource.
+    pub fn internal_vault_balance<E: ComponentReference, R: ResourceReference>(
+        &mut self,
+        entity: E,
+        resource: R,
+    ) -> Decimal {
+        let is_fungible = resource.address(self).is_fungible();
+        self.vault_ids_of(entity, resource)
+            .into_iter()
+            .map(|vault| match is_fungible {
+                true => self.fungible_vault_balance(vault),
+                false => self.non_fungible_vault_balance(vault).len().into(),
+            })
+            .fold(Decimal::ZERO, |total, balance| total + balance)
+    }
+
+    pub(crate) fn vaults_with_balances(
+        &mut self,
+        component: ComponentAddress,
+        resource: ResourceAddress,
+    ) -> Vec<(NodeId, Decimal)> {
+        self.engine_interface
+            .vaults_with_balances(component, resource)
+    }
+
+    /// Returns the balance changes of an entity during a transaction, by resource.
+    /// Panics if the transaction was rejected or aborted, or if it was executed without
+    /// execution trace, see [`GetBalanceChanges::balance_changes_of`].
+    ///
+    /// # Arguments
+    /// * `receipt`: receipt of the transaction.
+    /// * `entity`: reference name or address of the account or component.
+    pub fn balance_changes_of<E: ComponentReference>(
+        &mut self,
+        receipt: &TransactionReceipt,
+        entity: E,
+    ) -> HashMap<ResourceAddress, BalanceChange> {
+        receipt.balance_changes_of(entity.address(self))
+    }
+
+    /// Asserts that the balance of an entity changed by the expected amount during a
+    /// transaction. A resource without balance change is considered to have changed by zero.
+    /// Panics with the actual balance changes of the entity if the assertion fails, or if the
+    /// transaction was executed without execution trace.
+    ///
+    /// # Arguments
+    /// * `receipt`: receipt of the transaction.
+    /// * `entity`: reference name or address of the account or component.
+    /// * `resource`: reference name or address of the resource.
+    /// * `expected`: expected balance change.
+    pub fn assert_balance_change<E: ComponentReference, R: ResourceReference>(
+        &mut self,
+        receipt: &TransactionReceipt,
+        entity: E,
+        resource: R,
+        expected: BalanceChange,
+    ) {
+        let entity = entity.address(self);
+        let resource = resource.address(self);
+        let changes = self.balance_changes_of(receipt, entity);
+        let actual = changes
+            .get(&resource)
+            .cloned()
+            .unwrap_or_else(|| match &expected {
+                BalanceChange::Fungible(_) => BalanceChange::Fungible(Decimal::zero()),
+                BalanceChange::NonFungible { .. } => BalanceChange::NonFungible {
+                    added: BTreeSet::new(),
+                    removed: BTreeSet::new(),
+                },
+            });
+
+        if actual != expected {
+            let mut actual_changes = String::new();
+            for (resource, change) in &changes {
+                actual_changes.push_str(&format!(
+                    "\n- {}: {}",
+                    self.encode_address((*resource).into()),
+                    format_balance_change(change)
+                ));
+            }
+            panic!(
+                "Unexpected balance change of {} for {} ! \n\
+                    Change: {} \n\
+                    Expected Change: {} \n\
+                    Actual changes:{}",
+                self.encode_address(resource.into()),
+                self.encode_address(entity.into()),
+                format_balance_change(&actual),
+                format_balance_change(&expected),
+                actual_changes
+            );
+        }
+    }
+
+    /// Returns the IDs of the given non-fungible resource owned by the current account.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the non-fungible resource.
+    pub fn current_ids_balance<R: ResourceReference>(
+        &mut self,
+        resource: R,
+    ) -> Vec<NonFungibleLocalId> {
+        let account = *self.current_account_address();
+        let resource = resource.address(self);
+        self.engine_interface.nft_ids(account, resource)
+    }
+
+    /// Returns the IDs of the given non-fungible resource owned by the given entity, in all the
+    /// vaults of the resource owned by the entity.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `resource`: reference name or address of the resource.
+    pub fn ids_balance_of<E: ComponentReference, R: ResourceReference>(
+        &mut self,
+        entity: E,
+        resource: R,
+    ) -> Vec<NonFungibleLocalId> {
+        let entity = entity.address(self);
+        let resource = resource.address(self);
+        self.engine_interface.nft_ids(entity, resource)
+    }
+
+    /// Returns the XRD paid in fees by an account or a component in the transactions executed
+    /// since the creation of the engine or the last call to [`TestEngine::reset_fee_tracking`].
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the account or component.
+    pub fn fees_paid_by<E: ComponentReference>(&self, entity: E) -> Decimal {
+        let entity = entity.address(self);
+        self.fees_paid.get(&entity).copied().unwrap_or_default()
+    }
+
+    /// Returns the XRD paid in fees by all payers in the transactions executed since the creation
+    /// of the engine or the last call to [`TestEngine::reset_fee_tracking`].
+    pub fn total_fees_paid(&self) -> Decimal {
+        self.total_fees_paid
+    }
+
+    /// Resets the fees paid by every account and component to zero.
+    pub fn reset_fee_tracking(&mut self) {
+        self.fees_paid.clear();
+        self.total_fees_paid = Decimal::ZERO;
+    }
+
+    /// Asserts that a transaction wrote at most a given number of substates of user packages, to
+    /// pin the storage cost of hot-path methods. System substates, such as vaults, fee payments
+    /// and metadata, are not counted. Panics with the number of writes by entity otherwise.
+    ///
+    /// # Arguments
+    /// * `receipt`: receipt of the transaction.
+    /// * `max_writes`: maximum number of created, updated and deleted user substates.
+    pub fn assert_max_writes(&self, receipt: &TransactionReceipt, max_writes: usize) {
+        let stats = receipt.state_update_summary();
+        if stats.user.writes() <= max_writes {
+            return;
+        }
+        let names = self.display_names_by_node_id();
+        let writes: Vec<String> = stats
+            .user_writes_by_node
+            .iter()
+            .map(|(node_id, writes)| {
+                let name = names
+                    .get(node_id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("{:?}", node_id));
+                format!("{}: {}", name, writes)
+            })
+            .collect();
+        panic!(
+            "Transaction wrote {} user substates, which exceeds the maximum of {} ({})",
+            stats.user.writes(),
+            max_writes,
+            writes.join(", ")
+        );
+    }
+
+    /// Returns a snapshot of the ledger, of the reference names of the engine and of the fees and
+    /// pools it tracks, to be restored later with [`TestEngine::restore`].
+    pub fn snapshot(&mut self) -> StateSnapshot {
+        StateSnapshot {
+            ledger: self.engine_interface.create_snapshot(),
+            accounts: self.accounts.clone(),
+            current_account: self.current_account.clone(),
+            packages: self.packages.clone(),
+            current_package: self.current_package.clone(),
+            components: self.components.clone(),
+            current_component: self.current_component.clone(),
+            resources: self.resources.clone(),
+            component_resources: self.component_resources.clone(),
+            last_created_components: self.last_created_components.clone(),
+            display_names: self.display_names.clone(),
+            fees_paid: self.fees_paid.clone(),
+            total_fees_paid: self.total_fees_paid,
+            fee_vault_owners: self.fee_vault_owners.clone(),
+            pool_managers: self.pool_managers.clone(),
+        }
+    }
+
+    /// Restores the ledger, including its epoch and time, the reference names of the engine and the
+    /// fees and pools it tracks to a snapshot. Entities created after the snapshot cannot be
+    /// referenced anymore.
+    ///
+    /// # Arguments
+    /// * `snapshot`: snapshot to restore.
+    pub fn restore(&mut self, snapshot: &StateSnapshot) {
+        let snapshot = snapshot.clone();
+        self.engine_interface.restore_snapshot(snapshot.ledger);
+        self.accounts = snapshot.accounts;
+        self.current_account = snapshot.current_account;
+        self.packages = snapshot.packages;
+        self.current_package = snapshot.current_package;
+        self.components = snapshot.components;
+        self.current_component = snapshot.current_component;
+        self.resources = snapshot.resources;
+        self.component_resources = snapshot.component_resources;
+        self.last_created_components = snapshot.last_created_components;
+        self.display_names = snapshot.display_names;
+        self.fees_paid = snapshot.fees_paid;
+        self.total_fees_paid = snapshot.total_fees_paid;
+        self.fee_vault_owners = snapshot.fee_vault_owners;
+        self.pool_managers = snapshot.pool_managers;
+    }
+
+    /// Returns the addresses of the registered resources, sorted and without duplicates.
+    #[cfg(feature = "proptest")]
+    pub(crate) fn registered_resources(&self) -> Vec<ResourceAddress> {
+        let mut resources: Vec<ResourceAddress> = self.resources.values().copied().collect();
+        resources.sort();
+        resources.dedup();
+        resources
+    }
+
+    /// Returns an independent copy of the engine, with the same ledger and reference names.
+    /// Published packages are not recompiled, which makes it a cheap way to start several tests
+    /// or property test cases from the same fixture. The copy does not record calls and logs with
+    /// the default logger.
+    pub fn fork(&self) -> TestEngine {
+        Self {
+            engine_interface: self.engine_interface.fork(),
+            accounts: self.accounts.clone(),
+            current_account: self.current_account.clone(),
+            packages: self.packages.clone(),
+            current_package: self.current_package.clone(),
+            components: self.components.clone(),
+            current_component: self.current_component.clone(),
+            resources: self.resources.clone(),
+            component_resources: self.component_resources.clone(),
+            last_created_components: self.last_created_components.clone(),
+            display_names: self.display_names.clone(),
+            strict_references: self.strict_references,
+            check_divisibility: self.check_divisibility,
+            execution_trace: self.execution_trace,
+            reference_warnings: self.reference_warnings.clone(),
+            injection_summary: InjectionSummary::default(),
+            self_funded: self.self_funded,
+            fees_paid: self.fees_paid.clone(),
+            total_fees_paid: self.total_fees_paid,
+            fee_vault_owners: self.fee_vault_owners.clone(),
+            pool_managers: self.pool_managers.clone(),
+            logger: Box::new(StdoutLogger),
+            recorder: None,
+        }
+    }
+
+    /// Returns the current epoch.
+    pub fn current_epoch(&mut self) -> Epoch {
+        self.engine_interface.get_epoch()
+    }
+
+    /// Sets the current epoch, without going through the consensus manager.
+    ///
+    /// # Arguments
+    /// * `epoch`: new current epoch.
+    pub fn set_epoch(&mut self, epoch: Epoch) {
+        self.engine_interface.set_epoch(epoch);
+    }
+
+    /// Moves to next epoch.
+    pub fn next_epoch(&mut self) {
+        let epoch = self.engine_interface.get_epoch();
+        self.engine_interface.set_epoch(epoch.next().unwrap());
+    }
+
+    /// Advances epochs by the given amount.
+    ///
+    /// # Arguments
+    /// * `epochs`: amount of epochs to jump to.
+    pub fn jump_epochs(&mut self, epochs: u64) {
+        let epoch = self.engine_interface.get_epoch();
+        self.engine_interface
+            .set_epoch(epoch.after(epochs).unwrap());
+    }
+
+    /// Advances epochs by the given amount through the consensus manager. Unlike
+    /// [`TestEngine::jump_epochs`], each epoch change updates the validator set and distributes
+    /// the emissions to the validators and their stakers.
+    ///
+    /// # Arguments
+    /// * `epochs`: amount of epochs to advance by.
+    pub fn advance_epochs(&mut self, epochs: u64) {
+        for _ in 0..epochs {
+            self.engine_interface.next_epoch_with_consensus();
+        }
+    }
+
+    /// Advances the ledger time by the given amount of seconds.
+    ///
+    /// # Arguments
+    /// * `seconds`: amount of seconds to advance the time by.
+    pub fn advance_time(&mut self, seconds: u64) {
+        self.engine_interface.advance_time_ms(seconds * 1000);
+    }
+
+    /// Advances the ledger time by the given duration, with a millisecond precision.
+    ///
+    /// # Arguments
+    /// * `duration`: duration to advance the time by.
+    pub fn advance_time_by(&mut self, duration: Duration) -> &mut Self {
+        let time_ms = u64::try_from(duration.as_millis()).expect("Duration is too long");
+        self.engine_interface.advance_time_ms(time_ms);
+        self
+    }
+
+    /// Advances the ledger time by the given amount of days.
+    ///
+    /// # Arguments
+    /// * `days`: amount of days to advance the time by.
+    pub fn advance_days(&mut self, days: u64) -> &mut Self {
+        self.advance_time_by(Duration::from_secs(days * 24 * 60 * 60))
+    }
+
+    /// Advances the ledger time by the given amount of hours.
+    ///
+    /// # Arguments
+    /// * `hours`: amount of hours to advance the time by.
+    pub fn advance_hours(&mut self, hours: u64) -> &mut Self {
+        self.advance_time_by(Duration::from_secs(hours * 60 * 60))
+    }
+
+    /// Advances the ledger time by the given amount of minutes.
+    ///
+    /// # Arguments
+    /// * `minutes`: amount of minutes to advance the time by.
+    pub fn advance_minutes(&mut self, minutes: u64) -> &mut Self {
+        self.advance_time_by(Duration::from_secs(minutes * 60))
+    }
+
+    /// Sets the ledger time to the given instant.
+    /// Panics if the instant is before the current time.
+    ///
+    /// # Arguments
+    /// * `time`: new time of the ledger.
+    pub fn set_time(&mut self, time: Instant) {
+        let timestamp_ms = time
+            .seconds_since_unix_epoch
+            .checked_mul(1000)
+            .expect("Time is too far in the future");
+        if timestamp_ms < self.engine_interface.get_current_time_ms() {
+            panic!("Cannot set the time before the current time");
+        }
+        self.engine_interface.set_current_time_ms(timestamp_ms);
+    }
+
+    /// Returns the current time rounded to seconds, as seen by a blueprint calling
+    /// `Clock::current_time_rounded_to_seconds`.
+    pub fn get_current_time(&mut self) -> Instant {
+        self.engine_interface
+            .get_current_time(TimePrecision::Second)
+    }
+
+    /// Returns the current time rounded to minutes, as seen by a blueprint calling
+    /// `Clock::current_time_rounded_to_minutes`.
+    pub fn current_time_rounded_to_minutes(&mut self) -> Instant {
+        self.engine_interface
+            .get_current_time(TimePrecision::Minute)
+    }
+
+    /// Returns the current time rounded to seconds, same as [`TestEngine::get_current_time`].
+    pub fn current_time_seconds(&mut self) -> Instant {
+        self.get_current_time()
+    }
+
+    /// Jumps back epochs by the given amount.
+    /// Panics if the resulting epoch is before the genesis epoch.
+    ///
+    /// # Arguments
+    /// * `epochs`: amount of epochs to jump back to.
+    pub fn jump_back_epochs(&mut self, epochs: u64) {
         let epoch = self.engine_interface.get_epoch();
-        while epochs != 0 {
-            epoch.previous();
-            epochs -= 1;
+        let genesis_epoch = self.engine_interface.genesis_epoch();
+        match epoch.number().checked_sub(epochs) {
+            Some(number) if number >= genesis_epoch.number() => {
+                self.engine_interface.set_epoch(Epoch::of(number))
+            }
+            _ => panic!(
+                "Cannot jump back {} epochs from epoch {}: the genesis epoch is {}",
+                epochs,
+                epoch.number(),
+                genesis_epoch.number()
+            ),
         }
-        self.engine_interface.set_epoch(epoch)
     }
 
-    /// Returns an NFT's non-fungible data.
+    /// Returns the addresses of the validators of the current epoch, by descending stake.
+    pub fn active_validators(&self) -> Vec<ComponentAddress> {
+        self.engine_interface.active_validators()
+    }
+
+    /// Creates a new validator owned by the current account, registers it and makes it accept
+    /// delegated stake. The validator creation fee is paid by the current account.
+    ///
+    /// The validator is referenced by the given name, its liquid stake units by `<name> lsu` and
+    /// its claim NFTs by `<name> claim nft`. The owner badges of the validators are referenced by
+    /// `validator owner badge`.
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the validator.
+    pub fn new_validator<N: ReferenceName>(&mut self, name: N) -> ComponentAddress {
+        let name = name.display();
+        let key = self.engine_interface.new_validator_key();
+        let creation_fee = *DEFAULT_VALIDATOR_XRD_COST;
+
+        let receipt = CallBuilder::new(self)
+            .withdraw("XRD", creation_fee)
+            .take_from_worktop_named("XRD", creation_fee, "validator creation fee")
+            .call_from_component(
+                CONSENSUS_MANAGER,
+                CONSENSUS_MANAGER_CREATE_VALIDATOR_IDENT,
+                vec![
+                    Box::new(key),
+                    Box::new(Decimal::ZERO),
+                    Box::new(Environment::Bucket("validator creation fee")),
+                ],
+            )
+            .execute_no_update()
+            .assert_is_success();
+        let validator = receipt.expect_commit_success().new_component_addresses()[0];
+
+        let validator_info = self.engine_interface.get_validator_info(validator);
+        self.insert_component(name.clone(), validator);
+        self.insert_resource(format!("{} lsu", name), validator_info.stake_unit_resource);
+        self.insert_resource(format!("{} claim nft", name), validator_info.claim_nft);
+        self.try_insert_resource("validator owner badge".to_string(), VALIDATOR_OWNER_BADGE);
+
+        CallBuilder::new(self)
+            .call_from_component(validator, VALIDATOR_REGISTER_IDENT, vec![])
+            .call_from_component(
+                validator,
+                VALIDATOR_UPDATE_ACCEPT_DELEGATED_STAKE_IDENT,
+                vec![Box::new(true)],
+            )
+            .with_badge_ids(
+                VALIDATOR_OWNER_BADGE,
+                vec![NonFungibleLocalId::bytes(validator.as_node_id().0).unwrap()],
+            )
+            .execute()
+            .assert_is_success();
+
+        validator
+    }
+
+    /// Creates a native two resource pool owned and managed by a badge. The pool is referenced by
+    /// the given name and its pool units by `<name> unit`.
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the pool.
+    /// * `resource1`: reference name or address of the first resource of the pool.
+    /// * `resource2`: reference name or address of the second resource of the pool.
+    /// * `owner_badge`: reference name or address of the badge owning the pool, also required to
+    ///   contribute to it and to deposit or withdraw from it.
+    pub fn new_two_resource_pool<
+        N: ReferenceName,
+        R1: ResourceReference,
+        R2: ResourceReference,
+        B: ResourceReference,
+    >(
+        &mut self,
+        name: N,
+        resource1: R1,
+        resource2: R2,
+        owner_badge: B,
+    ) -> ComponentAddress {
+        let name = name.display();
+        let resources = (resource1.address(self), resource2.address(self));
+        let owner_badge = owner_badge.address(self);
+
+        let receipt = CallBuilder::new(self)
+            .call_from_package(
+                POOL_PACKAGE,
+                TWO_RESOURCE_POOL_BLUEPRINT_IDENT,
+                TWO_RESOURCE_POOL_INSTANTIATE_IDENT,
+                vec![
+                    Box::new(OwnerRole::Fixed(rule!(require(owner_badge)))),
+                    Box::new(rule!(require(owner_badge))),
+                    Box::new(resources),
+                    Box::new(None::<ManifestAddressReservation>),
+                ],
+            )
+            .execute_no_update()
+            .assert_is_success();
+        let pool = receipt.expect_commit_success().new_component_addresses()[0];
+
+        // Pool units have no name metadata, their address is read from the metadata of the pool.
+        let pool_unit = match self.engine_interface.get_metadata(pool.into(), "pool_unit") {
+            Some(MetadataValue::GlobalAddress(address)) => {
+                ResourceAddress::try_from(address).unwrap()
+            }
+            _ => panic!("Pool {} has no pool unit metadata", name),
+        };
+        self.insert_component(name.clone(), pool);
+        self.insert_resource(format!("{} unit", name), pool_unit);
+        self.pool_managers.insert(pool, owner_badge);
+        pool
+    }
+
+    /// Contributes resources from the current account to a two resource pool created by
+    /// [`TestEngine::new_two_resource_pool`], with its owner badge. The pool units and the
+    /// resources that were not contributed are deposited to the current account.
+    ///
+    /// # Arguments
+    /// * `pool`: reference name or address of the pool.
+    /// * `bucket1`: bucket of the first resource of the pool, e.g. `Fungible::Bucket("usd", 100)`.
+    /// * `bucket2`: bucket of the second resource of the pool.
+    pub fn contribute<P: ComponentReference, B1: ToEncode + 'static, B2: ToEncode + 'static>(
+        &mut self,
+        pool: P,
+        bucket1: B1,
+        bucket2: B2,
+    ) -> TransactionReceipt {
+        let pool = pool.address(self);
+        let manager = *self
+            .pool_managers
+            .get(&pool)
+            .unwrap_or_else(|| panic!("{:?} is not a pool created by the engine", pool));
+        CallBuilder::new(self)
+            .call_from_component(
+                pool,
+                TWO_RESOURCE_POOL_CONTRIBUTE_IDENT,
+                vec![Box::new(EnvTuple::from_vec(vec![
+                    Box::new(bucket1),
+                    Box::new(bucket2),
+                ]))],
+            )
+            .with_badge(manager)
+            .execute()
+    }
+
+    /// Redeems pool units of a two resource pool from the current account, which receives the
+    /// resources of the pool.
+    ///
+    /// # Arguments
+    /// * `pool`: reference name or address of the pool.
+    /// * `pool_units`: bucket of pool units, e.g. `Fungible::Bucket("usd btc pool unit", 10)`.
+    pub fn redeem<P: ComponentReference, B: EnvironmentEncode + 'static>(
+        &mut self,
+        pool: P,
+        pool_units: B,
+    ) -> TransactionReceipt {
+        let pool = pool.address(self);
+        CallBuilder::new(self)
+            .call_from_component(
+                pool,
+                TWO_RESOURCE_POOL_REDEEM_IDENT,
+                vec![Box::new(pool_units)],
+            )
+            .execute()
+    }
+
+    /// Creates a native account locker owned by a badge, which is also required to store and
+    /// recover resources. The locker is referenced by the given name.
+    ///
+    /// # Arguments
+    /// * `name`: name that will be used to reference the locker.
+    /// * `owner_badge`: reference name or address of the badge owning the locker.
+    pub fn new_account_locker<N: ReferenceName, B: ResourceReference>(
+        &mut self,
+        name: N,
+        owner_badge: B,
+    ) -> ComponentAddress {
+        let name = name.display();
+        let owner_badge = owner_badge.address(self);
+
+        let receipt = CallBuilder::new(self)
+            .call_from_package(
+                LOCKER_PACKAGE,
+                ACCOUNT_LOCKER_BLUEPRINT,
+                ACCOUNT_LOCKER_INSTANTIATE_IDENT,
+                vec![
+                    Box::new(OwnerRole::Fixed(rule!(require(owner_badge)))),
+                    Box::new(rule!(require(owner_badge))),
+                    Box::new(rule!(deny_all)),
+                    Box::new(rule!(require(owner_badge))),
+                    Box::new(rule!(deny_all)),
+                    Box::new(None::<ManifestAddressReservation>),
+                ],
+            )
+            .execute_no_update()
+            .assert_is_success();
+        let locker = receipt.expect_commit_success().new_component_addresses()[0];
+        self.insert_component(name, locker);
+        locker
+    }
+
+    /// Stakes XRD from the current account to a validator and returns the liquid stake units
+    /// resource of the validator, referenced by `<validator name> lsu`.
+    ///
+    /// # Arguments
+    /// * `validator`: reference name or address of the validator.
+    /// * `amount`: amount of XRD to stake.
+    pub fn stake<V: ComponentReference, D: TryInto<Decimal>>(
+        &mut self,
+        validator: V,
+        amount: D,
+    ) -> ResourceAddress
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let validator = validator.address(self);
+        CallBuilder::new(self)
+            .call_from_component(
+                validator,
+                VALIDATOR_STAKE_IDENT,
+                vec![Box::new(Fungible::Bucket(
+                    "XRD",
+                    amount.try_into().unwrap(),
+                ))],
+            )
+            .execute()
+            .assert_is_success();
+
+        self.engine_interface
+            .get_validator_info(validator)
+            .stake_unit_resource
+    }
+
+    /// Unstakes liquid stake units of the current account from a validator. The claim NFT received
+    /// can be redeemed with [`TestEngine::claim_xrd`] once the unstaking delay has passed.
+    ///
+    /// # Arguments
+    /// * `validator`: reference name or address of the validator.
+    /// * `lsu_amount`: amount of liquid stake units to unstake.
+    pub fn unstake<V: ComponentReference, D: TryInto<Decimal>>(
+        &mut self,
+        validator: V,
+        lsu_amount: D,
+    ) -> TransactionReceipt
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        let validator = validator.address(self);
+        let lsu = self
+            .engine_interface
+            .get_validator_info(validator)
+            .stake_unit_resource;
+        CallBuilder::new(self)
+            .call_from_component(
+                validator,
+                VALIDATOR_UNSTAKE_IDENT,
+                vec![Box::new(Fungible::Bucket(
+                    lsu,
+                    lsu_amount.try_into().unwrap(),
+                ))],
+            )
+            .execute()
+    }
+
+    /// Claims the XRD of all the claim NFTs of a validator owned by the current account.
+    ///
+    /// # Arguments
+    /// * `validator`: reference name or address of the validator.
+    pub fn claim_xrd<V: ComponentReference>(&mut self, validator: V) -> TransactionReceipt {
+        let validator = validator.address(self);
+        let claim_nft = self
+            .engine_interface
+            .get_validator_info(validator)
+            .claim_nft;
+        CallBuilder::new(self)
+            .call_from_component(
+                validator,
+                VALIDATOR_CLAIM_XRD_IDENT,
+                vec![Box::new(NonFungibleAll::FromAccount(claim_nft))],
+            )
+            .execute()
+    }
+
+    /// Returns an NFT's non-fungible data.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource of the NFT.
+    /// * `id`: local id of the NFT.
+    pub fn get_non_fungible_data<R: ResourceReference, T: ToId, D: NonFungibleData>(
+        &mut self,
+        resource: R,
+        id: T,
+    ) -> D {
+        self.engine_interface
+            .get_non_fungible_data(resource.address(self), id.to_id())
+    }
+
+    /// Returns a field of an NFT's non-fungible data, found by name in the schema of the resource.
+    /// The type of the data does not need to be known by the test.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource of the NFT.
+    /// * `id`: local id of the NFT.
+    /// * `field_name`: name of the field.
+    pub fn get_nf_field<R: ResourceReference, T: ToId, F: ScryptoDecode>(
+        &mut self,
+        resource: R,
+        id: T,
+        field_name: &str,
+    ) -> F {
+        let resource = resource.address(self);
+        let id = id.to_id();
+        let field_names = self
+            .engine_interface
+            .non_fungible_field_names(resource)
+            .unwrap_or_else(|| {
+                panic!(
+                    "The non-fungible data of resource {} has no named fields",
+                    self.encode_address(resource.into())
+                )
+            });
+        let index = field_names
+            .iter()
+            .position(|name| name == field_name)
+            .unwrap_or_else(|| {
+                panic!(
+                    "The non-fungible data has no field named {}. Its fields are: {}",
+                    field_name,
+                    field_names.join(", ")
+                )
+            });
+        let fields = match self
+            .engine_interface
+            .get_raw_non_fungible_data(resource, id.clone())
+        {
+            Some(ScryptoValue::Tuple { fields }) => fields,
+            Some(_) => panic!("The non-fungible data of NFT {} is not a struct", id),
+            None => panic!("There is no NFT with id {}", id),
+        };
+        let bytes = scrypto_encode(&fields[index]).unwrap();
+        scrypto_decode(&bytes).unwrap_or_else(|error| {
+            panic!(
+                "Could not decode field {} into {}: {:?}",
+                field_name,
+                std::any::type_name::<F>(),
+                error
+            )
+        })
+    }
+
+    /// Asserts that an NFT's non-fungible data is equal to the expected data.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource of the NFT.
+    /// * `id`: local id of the NFT.
+    /// * `expected`: expected non-fungible data.
+    pub fn assert_nf_data_eq<
+        R: ResourceReference,
+        T: ToId,
+        D: NonFungibleData + PartialEq + std::fmt::Debug,
+    >(
+        &mut self,
+        resource: R,
+        id: T,
+        expected: D,
+    ) {
+        let id = id.to_id();
+        let data: D = self.get_non_fungible_data(resource, id.clone());
+        assert_eq!(
+            data, expected,
+            "The non-fungible data of NFT {} is not the expected one",
+            id
+        );
+    }
+
+    /// Updates a field of an NFT's non-fungible data.
+    ///
+    /// # Arguments
+    /// * `resource`: reference name or address of the resource of the NFT.
+    /// * `id`: local id of the NFT.
+    /// * `field_name`: name of the field to update.
+    /// * `data`: new data for this field.
+    /// * `badge`: reference name or address of the badge needed to make the update.
+    pub fn update_non_fungible_data<R1: ResourceReference, R2: ResourceReference, T: ToId>(
+        &mut self,
+        resource: R1,
+        id: T,
+        field_name: &str,
+        mut data: Vec<Box<dyn EnvironmentEncode>>,
+        badge: R2,
+    ) -> TransactionReceipt {
+        let resource = resource.address(self);
+        let mut args: Vec<Box<dyn EnvironmentEncode>> =
+            vec![Box::new(id.to_id()), Box::new(field_name.to_string())];
+        args.append(&mut data);
+        CallBuilder::new(self)
+            .call_from_component(resource, "update_non_fungible_data", args)
+            .with_badge(badge)
+            .execute()
+    }
+
+    /// Sets the owner role of an entity.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `rule`: new owner rule.
+    /// * `badge`: reference name or address of the badge satisfying the current owner rule.
+    pub fn set_owner_role<G: GlobalReference, R: ResourceReference>(
+        &mut self,
+        entity: G,
+        rule: AccessRule,
+        badge: R,
+    ) -> TransactionReceipt {
+        CallBuilder::new(self)
+            .set_owner_role(entity, rule)
+            .with_badge(badge)
+            .execute()
+    }
+
+    /// Sets the rule of a role of the main module of an entity.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `role_name`: name of the role.
+    /// * `rule`: new rule of the role.
+    /// * `badge`: reference name or address of the badge satisfying the updater rule of the role.
+    pub fn set_role<G: GlobalReference, R: ResourceReference>(
+        &mut self,
+        entity: G,
+        role_name: &str,
+        rule: AccessRule,
+        badge: R,
+    ) -> TransactionReceipt {
+        CallBuilder::new(self)
+            .set_role(entity, role_name, rule)
+            .with_badge(badge)
+            .execute()
+    }
+
+    /// Sets the royalty of a method of a component with a badge.
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component.
+    /// * `method`: name of the method.
+    /// * `amount`: new royalty of the method.
+    /// * `badge`: reference name or address of the badge allowed to set the royalties.
+    pub fn set_component_royalty<C: ComponentReference, R: ResourceReference>(
+        &mut self,
+        component: C,
+        method: &str,
+        amount: RoyaltyAmount,
+        badge: R,
+    ) -> TransactionReceipt {
+        CallBuilder::new(self)
+            .set_component_royalty(component, method, amount)
+            .with_badge(badge)
+            .execute()
+    }
+
+    /// Claims the royalties accumulated by a component with a badge. The XRD are deposited to the
+    /// current account.
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component.
+    /// * `badge`: reference name or address of the badge allowed to claim the royalties.
+    pub fn claim_component_royalties<C: ComponentReference, R: ResourceReference>(
+        &mut self,
+        component: C,
+        badge: R,
+    ) -> TransactionReceipt {
+        CallBuilder::new(self)
+            .claim_component_royalties(component)
+            .with_badge(badge)
+            .execute()
+    }
+
+    /// Returns the amount of XRD in the royalty vault of a component.
+    /// Panics if the component has no royalties.
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component.
+    pub fn component_royalty_balance<C: ComponentReference>(&mut self, component: C) -> Decimal {
+        let component = component.address(self);
+        self.engine_interface
+            .component_royalty_balance(component)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Component {} has no royalties",
+                    self.encode_address(component.into())
+                )
+            })
+    }
+
+    /// Freezes withdrawals, deposits and burns of a resource in every vault of a component.
     ///
     /// # Arguments
-    /// * `resource`: reference name or address of the resource of the NFT.
-    /// * `id`: local id of the NFT.
-    pub fn get_non_fungible_data<R: ResourceReference, T: ToId, D: NonFungibleData>(
+    /// * `component`: reference name or address of the component or account.
+    /// * `resource`: reference name or address of the resource.
+    /// * `badge`: reference name or address of the badge satisfying the freezer rule of the resource.
+    pub fn freeze_vault<C: ComponentReference, R: ResourceReference, B: ResourceReference>(
         &mut self,
+        component: C,
         resource: R,
-        id: T,
-    ) -> D {
-        self.engine_interface
-            .get_non_fungible_data(resource.address(self), id.to_id())
+        badge: B,
+    ) -> TransactionReceipt {
+        CallBuilder::new(self)
+            .freeze_vault(component, resource)
+            .with_badge(badge)
+            .execute()
     }
 
-    /// Updates a field of an NFT's non-fungible data.
+    /// Unfreezes withdrawals, deposits and burns of a resource in every vault of a component.
     ///
     /// # Arguments
-    /// * `resource`: reference name or address of the resource of the NFT.
-    /// * `id`: local id of the NFT.
-    /// * `field_name`: name of the field to update.
-    /// * `data`: new data for this field.
-    /// * `badge`: reference name or address of the badge needed to make the update.
-    pub fn update_non_fungible_data<R1: ResourceReference, R2: ResourceReference, T: ToId>(
+    /// * `component`: reference name or address of the component or account.
+    /// * `resource`: reference name or address of the resource.
+    /// * `badge`: reference name or address of the badge satisfying the freezer rule of the resource.
+    pub fn unfreeze_vault<C: ComponentReference, R: ResourceReference, B: ResourceReference>(
         &mut self,
-        resource: R1,
-        id: T,
-        field_name: &str,
-        mut data: Vec<Box<dyn EnvironmentEncode>>,
-        badge: R2,
+        component: C,
+        resource: R,
+        badge: B,
     ) -> TransactionReceipt {
-        let resource = resource.address(self);
-        let mut args: Vec<Box<dyn EnvironmentEncode>> =
-            vec![Box::new(id.to_id()), Box::new(field_name.to_string())];
-        args.append(&mut data);
         CallBuilder::new(self)
-            .call_from_component(resource, "update_non_fungible_data", args)
+            .unfreeze_vault(component, resource)
+            .with_badge(badge)
+            .execute()
+    }
+
+    /// Recalls an amount of a resource from the vaults of a component and deposits it to the
+    /// current account.
+    /// Panics if the vaults of the component do not hold enough of the resource.
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component or account.
+    /// * `resource`: reference name or address of the resource.
+    /// * `amount`: amount to recall.
+    /// * `badge`: reference name or address of the badge satisfying the recaller rule of the resource.
+    pub fn recall<
+        C: ComponentReference,
+        R: ResourceReference,
+        D: TryInto<Decimal>,
+        B: ResourceReference,
+    >(
+        &mut self,
+        component: C,
+        resource: R,
+        amount: D,
+        badge: B,
+    ) -> TransactionReceipt
+    where
+        <D as TryInto<Decimal>>::Error: std::fmt::Debug,
+    {
+        CallBuilder::new(self)
+            .recall(component, resource, amount)
+            .with_badge(badge)
+            .execute()
+    }
+
+    /// Locks the owner role of an entity, so that it cannot be updated anymore.
+    ///
+    /// # Arguments
+    /// * `entity`: reference name or address of the entity.
+    /// * `badge`: reference name or address of the badge satisfying the current owner rule.
+    pub fn lock_owner_role<G: GlobalReference, R: ResourceReference>(
+        &mut self,
+        entity: G,
+        badge: R,
+    ) -> TransactionReceipt {
+        CallBuilder::new(self)
+            .lock_owner_role(entity)
             .with_badge(badge)
             .execute()
     }
 
+    /// Returns a rule requiring a proof of the given badge.
+    ///
+    /// # Arguments
+    /// * `badge`: reference name or address of the badge.
+    pub fn require_badge<R: ResourceReference>(&self, badge: R) -> AccessRule {
+        rule!(require(badge.address(self)))
+    }
+
     /// Returns the [`PackageAddress`] of the given pacresourcekage.
     ///
     /// # Arguments
     /// * `name`: reference name of the package.
     pub fn get_package<N: ReferenceName>(&self, name: N) -> PackageAddress {
-        match self.packages.get(&name.format()) {
-            None => panic!("There is no package with name {}", name.format()),
-            Some(address) => *address,
-        }
+        self.try_get_package(name)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Returns the [`PackageAddress`] of the given package, or an error suggesting the closest
+    /// package names if there is none.
+    ///
+    /// # Arguments
+    /// * `name`: reference name of the package.
+    pub fn try_get_package<N: ReferenceName>(
+        &self,
+        name: N,
+    ) -> Result<PackageAddress, ReferenceError> {
+        let key = Self::try_reference_key(&name, EntityKind::Package)?;
+        self.packages.get(&key).copied().ok_or_else(|| {
+            self.reference_not_found(&name, Some(EntityKind::Package), self.packages.keys())
+        })
     }
 
     /// Returns the [`ComponentAddress`] of the given component.
@@ -450,10 +2269,23 @@ impl TestEngine {
     /// # Arguments
     /// * `name`: reference name of the component.
     pub fn get_component<N: ReferenceName>(&self, name: N) -> ComponentAddress {
-        match self.components.get(&name.format()) {
-            None => panic!("There is no component with name {}", name.format()),
-            Some(address) => *address,
-        }
+        self.try_get_component(name)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Returns the [`ComponentAddress`] of the given component, or an error suggesting the closest
+    /// component names if there is none.
+    ///
+    /// # Arguments
+    /// * `name`: reference name of the component.
+    pub fn try_get_component<N: ReferenceName>(
+        &self,
+        name: N,
+    ) -> Result<ComponentAddress, ReferenceError> {
+        let key = Self::try_reference_key(&name, EntityKind::Component)?;
+        self.components.get(&key).copied().ok_or_else(|| {
+            self.reference_not_found(&name, Some(EntityKind::Component), self.components.keys())
+        })
     }
 
     /// Returns the [`ComponentAddress`] of the given account.
@@ -461,18 +2293,27 @@ impl TestEngine {
     /// # Arguments
     /// * `name`: reference name of the account.
     pub fn get_account<N: ReferenceName>(&self, name: N) -> &ComponentAddress {
-        match self.accounts.get(&name.format()) {
-            None => panic!("There is no account with name {}", name.format()),
-            Some(account) => account.address(),
-        }
+        self.account(name).address()
+    }
+
+    /// Returns the [`ComponentAddress`] of the given account, or an error suggesting the closest
+    /// account names if there is none.
+    ///
+    /// # Arguments
+    /// * `name`: reference name of the account.
+    pub fn try_get_account<N: ReferenceName>(
+        &self,
+        name: N,
+    ) -> Result<ComponentAddress, ReferenceError> {
+        self.try_account(name).map(|account| *account.address())
     }
 
     /// Sets the current account.
     ///
     /// # Arguments
     /// * `name`: reference name of the account.
-    pub fn set_current_account<N: ReferenceName>(&mut self, name: N) -> CallBuilder {
-        self.current_account = name.format();
+    pub fn set_current_account<N: ReferenceName>(&mut self, name: N) -> CallBuilder<'_> {
+        self.current_account = Self::reference_key(&name, EntityKind::Account);
         self.get_account(name);
         CallBuilder::new(self)
     }
@@ -481,76 +2322,466 @@ impl TestEngine {
     ///
     /// # Arguments
     /// * `name`: reference name of the component.
-    pub fn set_current_component<N: ReferenceName>(&mut self, name: N) -> CallBuilder {
-        self.current_component = Some(name.format());
+    pub fn set_current_component<N: ReferenceName>(&mut self, name: N) -> CallBuilder<'_> {
+        self.current_component = Some(Self::reference_key(&name, EntityKind::Component));
         self.get_component(name);
         CallBuilder::new(self)
     }
 
-    /// Sets the current package.
-    ///
-    /// # Arguments
-    /// * `name`: reference name of the account.
-    pub fn set_current_package<N: ReferenceName>(&mut self, name: N) -> CallBuilder {
-        self.current_package = Some(name.format());
-        self.get_package(name);
-        CallBuilder::new(self)
+    /// Sets the current package.
+    ///
+    /// # Arguments
+    /// * `name`: reference name of the account.
+    pub fn set_current_package<N: ReferenceName>(&mut self, name: N) -> CallBuilder<'_> {
+        self.current_package = Some(Self::reference_key(&name, EntityKind::Package));
+        self.get_package(name);
+        CallBuilder::new(self)
+    }
+
+    /// Returns the [`ResourceAddress`] of the given resource.
+    ///
+    /// # Arguments
+    /// * `name`: reference name of the resource.
+    pub fn get_resource<N: ReferenceName>(&self, name: N) -> ResourceAddress {
+        self.try_get_resource(name)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Returns the [`ResourceAddress`] of the given resource, or an error suggesting the closest
+    /// resource names if there is none.
+    ///
+    /// # Arguments
+    /// * `name`: reference name of the resource.
+    pub fn try_get_resource<N: ReferenceName>(
+        &self,
+        name: N,
+    ) -> Result<ResourceAddress, ReferenceError> {
+        let key = Self::try_reference_key(&name, EntityKind::Resource)?;
+        self.resources.get(&key).copied().ok_or_else(|| {
+            self.reference_not_found(&name, Some(EntityKind::Resource), self.resources.keys())
+        })
+    }
+
+    /// Returns the [`ResourceAddress`] of a resource created in the same transaction as the given
+    /// component. Use it to disambiguate resources sharing a name across packages.
+    ///
+    /// # Arguments
+    /// * `component`: reference to the component.
+    /// * `name`: name or symbol of the resource.
+    pub fn get_resource_of<C: ComponentReference, N: ReferenceName>(
+        &self,
+        component: C,
+        name: N,
+    ) -> ResourceAddress {
+        let component_address = component.address(self);
+        let resources = self.component_resources.get(&component_address);
+        match resources.and_then(|resources| resources.get(&name.format())) {
+            Some(resource) => *resource,
+            None => panic!(
+                "Component {} has no resource named {}. Its resources are: [{}]",
+                self.encode_address(component_address.into()),
+                name.display(),
+                resources
+                    .map(|resources| {
+                        let mut names: Vec<String> = resources
+                            .keys()
+                            .map(|key| self.display_names.get(key).unwrap_or(key).clone())
+                            .collect();
+                        names.sort();
+                        names.join(", ")
+                    })
+                    .unwrap_or_default()
+            ),
+        }
+    }
+
+    /// Returns the addresses of the components created by the last transaction that updated the
+    /// reference names of the engine, in creation order.
+    pub fn last_created_components(&self) -> &Vec<ComponentAddress> {
+        &self.last_created_components
+    }
+
+    /// Returns the [`PackageAddress`] of the current package.
+    pub fn current_package(&self) -> &PackageAddress {
+        self.packages
+            .get(self.current_package.as_ref().unwrap())
+            .unwrap()
+    }
+
+    /// Returns the [`ComponentAddress`] of the current account.
+    pub fn current_account_address(&self) -> &ComponentAddress {
+        self.accounts.get(&self.current_account).unwrap().address()
+    }
+
+    /// Returns the [`PublicKey`] controlling the current account.
+    pub fn current_account_public_key(&self) -> PublicKey {
+        self.current_account().public_key()
+    }
+
+    /// Returns the [`ComponentAddress`] of the current component.
+    pub fn current_component(&self) -> &ComponentAddress {
+        self.components
+            .get(self.current_component.as_ref().unwrap())
+            .unwrap()
+    }
+
+    /// Returns the state of the current component.
+    pub fn current_component_state<T: ScryptoDecode>(&self) -> T {
+        self.engine_interface.get_state(*self.current_component())
+    }
+
+    /// Returns the state of the given component.
+    ///
+    /// # Arguments
+    /// * `component`: component reference or address for which to get the state.
+    pub fn get_component_state<T: ScryptoDecode, E: ComponentReference>(&self, component: E) -> T {
+        self.engine_interface.get_state(component.address(self))
+    }
+
+    /// Updates the state of the given component by applying a function to its decoded state.
+    ///
+    /// # Arguments
+    /// * `component`: component reference or address for which to update the state.
+    /// * `f`: function mutating the state of the component.
+    pub fn set_component_state<
+        T: ScryptoEncode + ScryptoDecode,
+        E: ComponentReference,
+        F: FnOnce(&mut T),
+    >(
+        &mut self,
+        component: E,
+        f: F,
+    ) {
+        let component = component.address(self);
+        self.engine_interface.set_state(component, f);
+    }
+
+    /// Returns a field of the state of a component, found by name in the schema of its blueprint.
+    /// The type of the state does not need to be known by the test.
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component.
+    /// * `field_name`: name of the field.
+    pub fn state_field<T: ScryptoDecode, C: ComponentReference>(
+        &self,
+        component: C,
+        field_name: &str,
+    ) -> T {
+        let component = component.address(self);
+        let field_names = self
+            .engine_interface
+            .state_field_names(component)
+            .unwrap_or_else(|| {
+                panic!(
+                    "The state of component {} has no named fields",
+                    self.encode_address(component.into())
+                )
+            });
+        let index = field_names
+            .iter()
+            .position(|name| name == field_name)
+            .unwrap_or_else(|| {
+                panic!(
+                    "The state has no field named {}. Its fields are: {}",
+                    field_name,
+                    field_names.join(", ")
+                )
+            });
+        let fields = match self.engine_interface.get_raw_state(component) {
+            Some(ScryptoValue::Tuple { fields }) => fields,
+            _ => panic!(
+                "The state of component {} is not a struct",
+                self.encode_address(component.into())
+            ),
+        };
+        let bytes = scrypto_encode(&fields[index]).unwrap();
+        scrypto_decode(&bytes).unwrap_or_else(|error| {
+            panic!(
+                "Could not decode field {} into {}: {:?}",
+                field_name,
+                std::any::type_name::<T>(),
+                error
+            )
+        })
+    }
+
+    /// Returns the id of a KeyValueStore held in a field of the state of a component, to read it
+    /// with [`TestEngine::get_kvs_value_at`].
+    ///
+    /// # Arguments
+    /// * `component`: reference name or address of the component.
+    /// * `field_name`: name of the field holding the KeyValueStore.
+    pub fn kvs_of_field<C: ComponentReference>(&self, component: C, field_name: &str) -> Own {
+        self.state_field(component, field_name)
+    }
+
+    /// Returns the value of a KeyValueStore at a given key.
+    ///
+    /// # Arguments
+    /// * `kv_store_id`: id of the KeyValueStore.
+    /// * `key`: key of the value to get.
+    pub fn get_kvs_value_at<K: ScryptoEncode, V: ScryptoEncode + ScryptoDecode>(
+        &self,
+        kv_store_id: Own,
+        key: &K,
+    ) -> Option<V> {
+        self.engine_interface.get_kvs_entry(kv_store_id, key)
+    }
+
+    /// Gives direct access to the ledger simulator, for what the engine does not cover, such as
+    /// resources with exotic roles. The closure returns the receipts of the transactions it
+    /// executed: the components and resources they created are registered from their metadata as
+    /// for a call of the engine, and their fees are tracked.
+    ///
+    /// # Arguments
+    /// * `f`: function executing transactions on the simulator and returning their receipts.
+    pub fn with_simulator_tracked<
+        F: FnOnce(&mut DefaultLedgerSimulator) -> Vec<TransactionReceipt>,
+    >(
+        &mut self,
+        f: F,
+    ) -> RegisteredEntities {
+        let components = self.components.clone();
+        let resources = self.resources.clone();
+
+        let receipts = f(self.engine_interface.simulator_mut());
+        for receipt in &receipts {
+            if let TransactionResult::Commit(commit_result) = &receipt.result {
+                self.track_fees(commit_result);
+                self.update_data_from_result(commit_result);
+            }
+        }
+
+        let display_name = |name: &String| self.display_names.get(name).unwrap_or(name).clone();
+        let mut registered = RegisteredEntities {
+            components: self
+                .components
+                .iter()
+                .filter(|(name, _)| !components.contains_key(*name))
+                .map(|(name, address)| (display_name(name), *address))
+                .collect(),
+            resources: self
+                .resources
+                .iter()
+                .filter(|(name, _)| !resources.contains_key(*name))
+                .map(|(name, address)| (display_name(name), *address))
+                .collect(),
+        };
+        registered.components.sort();
+        registered.resources.sort();
+        registered
+    }
+
+    /// Sets whether registering a reference name already used by an entity of another kind
+    /// panics (strict mode) or only emits a warning (default).
+    ///
+    /// # Arguments
+    /// * `strict`: whether to panic on reference name collisions.
+    pub fn set_strict_references(&mut self, strict: bool) {
+        self.strict_references = strict;
+    }
+
+    /// Sets whether withdrawing an amount of a resource that does not respect its divisibility
+    /// panics before building the transaction (default), or is left to the engine, which fails
+    /// the transaction.
+    ///
+    /// # Arguments
+    /// * `check`: whether to check the divisibility of the withdrawn amounts.
+    pub fn set_divisibility_check(&mut self, check: bool) {
+        self.check_divisibility = check;
+    }
+
+    /// Sets whether all the transactions of the engine are executed with an execution trace,
+    /// which is disabled by default as it slows the transactions down. The trace is needed by
+    /// `get_return_resources`, and by the calls and balance changes of [`TestEngine::explain`] and
+    /// of the receipt snapshots. It can also be enabled for a single call with
+    /// [`CallBuilder::with_execution_trace`].
+    ///
+    /// # Arguments
+    /// * `enabled`: whether to trace the transactions.
+    pub fn set_execution_trace(&mut self, enabled: bool) {
+        self.execution_trace = enabled;
+    }
+
+    /// Sets the logger receiving every transaction executed by the engine and its warnings. By
+    /// default, the title, fees and application logs of the transactions and the warnings are
+    /// printed to the standard output.
+    ///
+    /// # Arguments
+    /// * `logger`: logger of the engine, for example a `NullLogger` or a `MemoryLogger`.
+    pub fn set_logger<L: TestLogger + 'static>(&mut self, logger: L) {
+        self.logger = Box::new(logger);
+    }
+
+    /// Returns the warnings emitted when a reference name was registered for entities of
+    /// different kinds. The warnings are also sent to the logger of the engine.
+    pub fn reference_warnings(&self) -> &Vec<String> {
+        &self.reference_warnings
+    }
+
+    /// Returns the instructions injected by the toolkit in the last executed call.
+    pub fn last_injection_summary(&self) -> &InjectionSummary {
+        &self.injection_summary
+    }
+
+    pub(crate) fn set_injection_summary(&mut self, injection_summary: InjectionSummary) {
+        self.injection_summary = injection_summary;
+    }
+
+    pub(crate) fn create_fungible<N: ReferenceName>(
+        &mut self,
+        token_name: N,
+        initial_supply: Decimal,
+        divisibility: u8,
+    ) {
+        match self.resources.get(&token_name.format()) {
+            Some(_) => {
+                panic!("Token with name {} already exists", token_name.format());
+            }
+            None => {
+                let account = *self.current_account().address();
+                let token_address =
+                    self.engine_interface
+                        .new_fungible(account, initial_supply, divisibility);
+                self.check_reference_collision(&token_name.format(), EntityKind::Resource);
+                self.register_display_name(&token_name);
+                self.resources.insert(token_name.format(), token_address);
+            }
+        }
+    }
+
+    pub(crate) fn create_resource(
+        &mut self,
+        name: String,
+        create_instruction: InstructionV1,
+        recipient: ComponentAddress,
+    ) -> ResourceAddress {
+        if self.resources.contains_key(&name.format()) {
+            panic!("Token with name {} already exists", name.format());
+        }
+        let resource = self
+            .engine_interface
+            .new_resource(create_instruction, recipient);
+        self.insert_resource(name, resource);
+        self.insert_metadata_names(resource);
+        resource
+    }
+
+    fn create_fungible_advanced<N: ReferenceName>(
+        &mut self,
+        token_name: N,
+        initial_supply: Decimal,
+        divisibility: u8,
+        resource_roles: FungibleResourceRoles,
+        metadata: Vec<(&str, MetadataValue)>,
+    ) {
+        if self.resources.contains_key(&token_name.format()) {
+            panic!("Token with name {} already exists", token_name.format());
+        }
+        let account = *self.current_account().address();
+        let token_address = self.engine_interface.new_fungible_advanced(
+            account,
+            initial_supply,
+            divisibility,
+            resource_roles,
+            metadata,
+        );
+        self.insert_resource(token_name.format(), token_address);
+        self.insert_metadata_names(token_address);
+    }
+
+    /// Registers the values of the `name` and `symbol` metadata of a resource as reference names.
+    fn insert_metadata_names(&mut self, resource: ResourceAddress) {
+        for key in ["name", "symbol"] {
+            if let Some(name) = self.get_metadata_value_of(key, resource.into()) {
+                self.try_insert_resource(name, resource);
+            }
+        }
+    }
+
+    pub(crate) fn mint_nfts_to<D: NonFungibleData + ManifestEncode>(
+        &mut self,
+        account: ComponentAddress,
+        collection: ResourceAddress,
+        entries: Vec<(NonFungibleLocalId, D)>,
+    ) -> Vec<NonFungibleLocalId> {
+        let receipt = self
+            .engine_interface
+            .mint_non_fungibles(account, collection, entries);
+        Self::minted_ids(receipt)
     }
 
-    /// Returns the [`ResourceAddress`] of the given resource.
-    ///
-    /// # Arguments
-    /// * `name`: reference name of the resource.
-    pub fn get_resource<N: ReferenceName>(&self, name: N) -> ResourceAddress {
-        match self.resources.get(&name.format()) {
-            None => panic!("There is no resource with name {}", name.format()),
-            Some(resource) => *resource,
-        }
+    pub(crate) fn mint_ruid_nfts_to<D: NonFungibleData + ManifestEncode>(
+        &mut self,
+        account: ComponentAddress,
+        collection: ResourceAddress,
+        nfts: Vec<D>,
+    ) -> Vec<NonFungibleLocalId> {
+        let receipt = self
+            .engine_interface
+            .mint_ruid_non_fungibles(account, collection, nfts);
+        Self::minted_ids(receipt)
     }
 
-    /// Returns the [`PackageAddress`] of the current package.
-    pub fn current_package(&self) -> &PackageAddress {
-        self.packages
-            .get(self.current_package.as_ref().unwrap())
-            .unwrap()
+    fn minted_ids(receipt: TransactionReceipt) -> Vec<NonFungibleLocalId> {
+        receipt
+            .assert_is_success()
+            .get_events::<MintNonFungibleResourceEvent>()
+            .into_iter()
+            .flat_map(|event| event.ids)
+            .collect()
     }
 
-    /// Returns the [`ComponentAddress`] of the current account.
-    pub fn current_account_address(&self) -> &ComponentAddress {
-        self.accounts.get(&self.current_account).unwrap().address()
+    pub(crate) fn reference_exists<N: ReferenceName>(&self, name: N, kind: EntityKind) -> bool {
+        let key = Self::reference_key(&name, kind);
+        match kind {
+            EntityKind::Account => self.accounts.contains_key(&key),
+            EntityKind::Component => self.components.contains_key(&key),
+            EntityKind::Package => self.packages.contains_key(&key),
+            EntityKind::Resource => self.resources.contains_key(&key),
+        }
     }
 
-    /// Returns the [`ComponentAddress`] of the current component.
-    pub fn current_component(&self) -> &ComponentAddress {
-        self.components
-            .get(self.current_component.as_ref().unwrap())
-            .unwrap()
+    fn create_account<N: ReferenceName, F: FnOnce(&mut EngineInterface) -> Account>(
+        &mut self,
+        name: N,
+        new_account: F,
+    ) {
+        match self.accounts.get(&name.format()) {
+            Some(_) => panic!("An account with name {} already exists", name.format()),
+            None => {
+                self.check_reference_collision(&name.format(), EntityKind::Account);
+                self.register_display_name(&name);
+                self.accounts
+                    .insert(name.format(), new_account(&mut self.engine_interface))
+            }
+        };
     }
 
-    /// Returns the state of the current component.
-    pub fn current_component_state<T: ScryptoDecode>(&self) -> T {
-        self.engine_interface.get_state(*self.current_component())
+    pub(crate) fn account<N: ReferenceName>(&self, name: N) -> &Account {
+        self.try_account(name)
+            .unwrap_or_else(|error| panic!("{}", error))
     }
 
-    /// Returns the state of the given component.
-    ///
-    /// # Arguments
-    /// * `component`: component reference or address for which to get the state.
-    pub fn get_component_state<T: ScryptoDecode, E: ComponentReference>(&self, component: E) -> T {
-        self.engine_interface.get_state(component.address(self))
+    fn try_account<N: ReferenceName>(&self, name: N) -> Result<&Account, ReferenceError> {
+        let key = Self::try_reference_key(&name, EntityKind::Account)?;
+        self.accounts.get(&key).ok_or_else(|| {
+            self.reference_not_found(&name, Some(EntityKind::Account), self.accounts.keys())
+        })
     }
 
-    /// Returns the value of a KeyValueStore at a given key.
-    ///
-    /// # Arguments
-    /// * `kv_store_id`: id of the KeyValueStore.
-    /// * `key`: key of the value to get.
-    pub fn get_kvs_value_at<K: ScryptoEncode, V: ScryptoEncode + ScryptoDecode>(
-        &self,
-        kv_store_id: Own,
-        key: &K,
-    ) -> Option<V> {
-        self.engine_interface.get_kvs_entry(kv_store_id, key)
+    /// Returns the proof signing the transactions of an account. The owner badge of a securified
+    /// account only signs while the account holds it.
+    pub(crate) fn signer_proof(&mut self, account: &Account) -> Option<NonFungibleGlobalId> {
+        if let AccountOwner::Badge(badge) = account.owner() {
+            let held = self
+                .engine_interface
+                .nft_ids(*account.address(), badge.resource_address());
+            if !held.contains(badge.local_id()) {
+                return None;
+            }
+        }
+        Some(account.proof())
     }
 
     pub(crate) fn current_account(&self) -> &Account {
@@ -560,55 +2791,460 @@ impl TestEngine {
     pub(crate) fn execute_call(
         &mut self,
         manifest: TransactionManifestV1,
-        with_trace: bool,
+        mut options: ExecutionOptions,
         initial_proofs: Vec<NonFungibleGlobalId>,
         with_update: bool,
+        context: &TransactionContext,
     ) -> TransactionReceipt {
+        options.execution_trace |= self.execution_trace;
+        let recorded = self
+            .recorder
+            .is_some()
+            .then(|| (manifest.clone(), initial_proofs.clone()));
         let receipt = self
             .engine_interface
-            .execute_manifest(manifest, with_trace, initial_proofs);
-        if with_update {
-            if let TransactionResult::Commit(commit_result) = &receipt.result {
+            .execute_manifest(manifest, options, initial_proofs);
+        if let TransactionResult::Commit(commit_result) = &receipt.result {
+            self.track_fees(commit_result);
+            if with_update {
                 self.update_data_from_result(commit_result);
             }
         }
+        if let Some((manifest, initial_proofs)) = recorded {
+            self.record(&manifest, &initial_proofs, &receipt);
+        }
+        self.logger.on_transaction(context, &receipt);
         receipt
     }
 
+    /// Adds the fees paid in a transaction to the tally of the accounts and components whose
+    /// vaults paid them.
+    fn track_fees(&mut self, result: &CommitResult) {
+        let paying_vaults = &result.fee_source.paying_vaults;
+        if paying_vaults
+            .keys()
+            .any(|vault| !self.fee_vault_owners.contains_key(vault))
+        {
+            self.update_fee_vault_owners();
+        }
+        for (vault, amount) in paying_vaults {
+            if let Some(owner) = self.fee_vault_owners.get(vault) {
+                *self.fees_paid.entry(*owner).or_default() += *amount;
+            }
+            self.total_fees_paid += *amount;
+        }
+    }
+
+    fn update_fee_vault_owners(&mut self) {
+        let owners: Vec<ComponentAddress> = self
+            .accounts
+            .values()
+            .map(|account| *account.address())
+            .chain(self.components.values().copied())
+            .collect();
+        for owner in owners {
+            for (vault, _) in self.engine_interface.vaults_with_balances(owner, XRD) {
+                self.fee_vault_owners.insert(vault, owner);
+            }
+        }
+    }
+
+    /// Panics if an amount does not respect the divisibility of a resource, unless the check is
+    /// disabled.
+    pub(crate) fn check_divisibility(&mut self, resource: ResourceAddress, amount: Decimal) {
+        if !self.check_divisibility {
+            return;
+        }
+        let divisibility = self.divisibility(resource);
+        if amount.checked_round(divisibility, RoundingMode::ToZero) != Some(amount) {
+            let name = match self.display_names_by_node_id().get(resource.as_node_id()) {
+                Some(name) => name.clone(),
+                None => self.encode_address(resource.into()),
+            };
+            panic!(
+                "Cannot withdraw {} {}: the resource has a divisibility of {}. Round the amount \
+                with round_to_divisibility",
+                amount, name, divisibility
+            );
+        }
+    }
+
+    pub(crate) fn event_schema(
+        &self,
+        event_type_identifier: &EventTypeIdentifier,
+    ) -> Option<(LocalTypeId, VersionedScryptoSchema)> {
+        self.engine_interface.event_schema(event_type_identifier)
+    }
+
     pub(crate) fn network(&self) -> NetworkDefinition {
         NetworkDefinition::simulator()
     }
 
+    /// Replaces the addresses of the entities referenced in the engine by their reference name,
+    /// formatted as `{kind:name}`. When several names reference the same entity, the first one in
+    /// alphabetical order is used.
+    pub(crate) fn substitute_reference_names(&self, text: &str) -> String {
+        self.reference_names_by_address()
+            .iter()
+            .fold(text.to_string(), |text, (address, reference)| {
+                text.replace(address, reference)
+            })
+    }
+
+    /// Returns the reference names of the entities of the engine, formatted as `{kind:name}`, by
+    /// bech32 address. When several names reference the same entity, the first one in
+    /// alphabetical order is used.
+    pub(crate) fn reference_names_by_address(&self) -> HashMap<String, String> {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|(name, account)| (EntityKind::Account, name, account.address().into_node_id()));
+        let components = self
+            .components
+            .iter()
+            .map(|(name, address)| (EntityKind::Component, name, address.into_node_id()));
+        let packages = self
+            .packages
+            .iter()
+            .map(|(name, address)| (EntityKind::Package, name, address.into_node_id()));
+        let resources = self
+            .resources
+            .iter()
+            .map(|(name, address)| (EntityKind::Resource, name, address.into_node_id()));
+
+        let mut references: Vec<(String, NodeId)> = accounts
+            .chain(components)
+            .chain(packages)
+            .chain(resources)
+            .map(|(kind, name, node_id)| (format!("{{{}:{}}}", kind, name), node_id))
+            .collect();
+        references.sort();
+
+        let encoder = AddressBech32Encoder::new(&self.network());
+        let mut addresses = HashMap::new();
+        for (reference, node_id) in references {
+            let address = encoder.encode(node_id.as_bytes()).unwrap();
+            addresses.entry(address).or_insert(reference);
+        }
+        addresses
+    }
+
+    /// Returns the display name of the entities of the engine, by node id. When several names
+    /// reference the same entity, the shortest one is used, as the symbol of a token rather than its
+    /// name.
+    pub(crate) fn display_names_by_node_id(&self) -> HashMap<NodeId, String> {
+        let accounts = self
+            .accounts
+            .iter()
+            .map(|(name, account)| (name, account.address().into_node_id()));
+        let components = self
+            .components
+            .iter()
+            .map(|(name, address)| (name, address.into_node_id()));
+        let packages = self
+            .packages
+            .iter()
+            .map(|(name, address)| (name, address.into_node_id()));
+        let resources = self
+            .resources
+            .iter()
+            .map(|(name, address)| (name, address.into_node_id()));
+
+        let mut names: Vec<(String, NodeId)> = accounts
+            .chain(components)
+            .chain(packages)
+            .chain(resources)
+            .map(|(key, node_id)| {
+                let name = self.display_names.get(key).unwrap_or(key).clone();
+                (name, node_id)
+            })
+            .collect();
+        names.sort_by(|(a, _), (b, _)| a.len().cmp(&b.len()).then(a.cmp(b)));
+
+        let mut by_node_id = HashMap::new();
+        for (name, node_id) in names {
+            by_node_id.entry(node_id).or_insert(name);
+        }
+        by_node_id
+    }
+
     pub(crate) fn ids_owned_at_address(
         &mut self,
+        account: ComponentAddress,
         resource: ResourceAddress,
     ) -> Vec<NonFungibleLocalId> {
-        let account = *self.current_account().address();
         self.engine_interface.nft_ids(account, resource)
     }
 
     pub(crate) fn update_data_from_result(&mut self, result: &CommitResult) {
+        self.last_created_components = result.new_component_addresses().iter().copied().collect();
         for component in result.new_component_addresses() {
-            if let Some(name) = self.get_metadata_value_of("name", (*component).into()) {
-                self.insert_component(name, *component)
+            match self.get_metadata_value_of("name", (*component).into()) {
+                Some(name) => self.insert_component_from_metadata(name, *component),
+                None => self.insert_component_from_blueprint(*component),
             }
         }
 
         self.update_resources_from_result(result);
+        self.update_names_from_metadata_events(result);
     }
 
-    pub(crate) fn get_entity<N: ReferenceName>(&self, name: N) -> ComponentAddress {
-        match self.accounts.get(&name.format()) {
-            Some(account) => *(account.address()),
-            None => match self.components.get(&name.format()) {
-                Some(component) => *component,
-                None => {
-                    panic!("There is no component with name {}!", name.format())
+    /// Registers the names set by the `SetMetadataEvent`s of existing components and resources.
+    fn update_names_from_metadata_events(&mut self, result: &CommitResult) {
+        let new_components = result.new_component_addresses();
+        let new_resources = result.new_resource_addresses();
+        for (EventTypeIdentifier(emitter, event_name), data) in &result.application_events {
+            let Emitter::Method(node_id, ModuleId::Metadata) = emitter else {
+                continue;
+            };
+            if event_name != "SetMetadataEvent" {
+                continue;
+            }
+            let Ok(SetMetadataEvent {
+                key,
+                value: MetadataValue::String(name),
+            }) = scrypto_decode::<SetMetadataEvent>(data)
+            else {
+                continue;
+            };
+
+            if let Ok(resource) = ResourceAddress::try_from(*node_id) {
+                if (key == "name" || key == "symbol") && !new_resources.contains(&resource) {
+                    self.rename_entity(name, resource.into(), EntityKind::Resource);
                 }
+            } else if let Ok(component) = ComponentAddress::try_from(*node_id) {
+                if key == "name"
+                    && !matches!(
+                        node_id.entity_type(),
+                        Some(
+                            EntityType::GlobalAccount
+                                | EntityType::GlobalVirtualSecp256k1Account
+                                | EntityType::GlobalVirtualEd25519Account
+                        )
+                    )
+                    && !new_components.contains(&component)
+                {
+                    self.rename_entity(name, component.into(), EntityKind::Component);
+                }
+            }
+        }
+    }
+
+    /// Registers a new reference name for an existing component or resource, replacing the entity
+    /// previously registered under this name, if any.
+    fn rename_entity(&mut self, name: String, address: GlobalAddress, kind: EntityKind) {
+        self.register_display_name(&name);
+        let name = name.format();
+        self.check_reference_collision(&name, kind);
+        match kind {
+            EntityKind::Resource => {
+                self.resources
+                    .insert(name, ResourceAddress::try_from(address).unwrap());
+            }
+            _ => {
+                self.components
+                    .insert(name, ComponentAddress::try_from(address).unwrap());
+            }
+        }
+    }
+
+    pub(crate) fn get_entity<N: ReferenceName>(&self, name: N) -> ComponentAddress {
+        self.try_get_entity(name)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    pub(crate) fn try_get_entity<N: ReferenceName>(
+        &self,
+        name: N,
+    ) -> Result<ComponentAddress, ReferenceError> {
+        match split_entity_kind(name.format()) {
+            (Some(EntityKind::Account), _) => self.try_get_account(name),
+            (Some(EntityKind::Component), _) => self.try_get_component(name),
+            (Some(kind), _) => Err(ReferenceError::WrongKind {
+                name: name.display(),
+                expected: EntityKind::Component,
+                found: kind,
+            }),
+            (None, key) => match self.accounts.get(&key) {
+                Some(account) => Ok(*account.address()),
+                None => self.components.get(&key).copied().ok_or_else(|| {
+                    self.reference_not_found(
+                        &name,
+                        Some(EntityKind::Component),
+                        self.accounts.keys().chain(self.components.keys()),
+                    )
+                }),
             },
         }
     }
 
+    pub(crate) fn get_global<N: ReferenceName>(&self, name: N) -> GlobalAddress {
+        match split_entity_kind(name.format()) {
+            (Some(EntityKind::Resource), _) => self.get_resource(name).into(),
+            (Some(EntityKind::Package), _) => self.get_package(name).into(),
+            (Some(_), _) => self.get_entity(name).into(),
+            (None, key) => {
+                let candidates = self.entities_named(&key);
+                match candidates.as_slice() {
+                    // Packages are only looked up when no other entity has the name, as components
+                    // are often named after their package.
+                    [] if self.packages.contains_key(&key) => self.packages[&key].into(),
+                    [] => panic!(
+                        "{}",
+                        self.reference_not_found(
+                            &name,
+                            None,
+                            self.accounts
+                                .keys()
+                                .chain(self.components.keys())
+                                .chain(self.packages.keys())
+                                .chain(self.resources.keys()),
+                        )
+                    ),
+                    [(_, address)] => *address,
+                    _ => {
+                        let matches: Vec<String> = candidates
+                            .iter()
+                            .map(|(kind, address)| {
+                                format!("{} {}", kind, self.encode_address(*address))
+                            })
+                            .collect();
+                        let prefixes: Vec<String> = candidates
+                            .iter()
+                            .map(|(kind, _)| format!("\"{}:{}\"", kind, key))
+                            .collect();
+                        panic!(
+                            "Reference name {} is ambiguous, it matches: {}. Use {} to disambiguate",
+                            key,
+                            matches.join(", "),
+                            prefixes.join(" or ")
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn metadata_violations(
+        &mut self,
+        resource: ResourceAddress,
+        policy: &MetadataPolicy,
+        other_symbols: &[String],
+    ) -> Vec<MetadataViolation> {
+        let metadata = POLICY_METADATA_KEYS
+            .iter()
+            .filter_map(|key| {
+                self.get_metadata(resource, key)
+                    .map(|value| (key.to_string(), value))
+            })
+            .collect();
+        policy.check(&metadata, other_symbols)
+    }
+
+    fn resource_symbols(&mut self, excluded: &[ResourceAddress]) -> Vec<String> {
+        let mut resources: Vec<ResourceAddress> = self.resources.values().cloned().collect();
+        resources.sort();
+        resources.dedup();
+        resources
+            .into_iter()
+            .filter(|resource| !excluded.contains(resource))
+            .filter_map(|resource| self.get_metadata(resource, "symbol"))
+            .map(|symbol| metadata_value_to_string(&symbol))
+            .collect()
+    }
+
+    fn metadata_report(&self, report: &[(ResourceAddress, Vec<MetadataViolation>)]) -> String {
+        let mut message = String::from("Metadata policy violations:");
+        for (resource, violations) in report {
+            message.push_str(&format!(
+                "\nResource {}:",
+                self.encode_address((*resource).into())
+            ));
+            for violation in violations {
+                message.push_str(&format!("\n- {}", violation));
+            }
+        }
+        message
+    }
+
+    pub(crate) fn encode_address(&self, address: GlobalAddress) -> String {
+        AddressBech32Encoder::new(&self.network())
+            .encode(address.as_node_id().as_bytes())
+            .unwrap()
+    }
+
+    /// Panics if an entity has no method with the given name and number of arguments.
+    pub(crate) fn check_method_call(
+        &self,
+        address: GlobalAddress,
+        method_name: &str,
+        arity: usize,
+    ) {
+        let Some(blueprint_id) = self.engine_interface.blueprint_of(address) else {
+            return;
+        };
+        let Some(functions) = self.engine_interface.blueprint_functions(&blueprint_id) else {
+            return;
+        };
+        let methods: Vec<&BlueprintFunction> = functions
+            .iter()
+            .filter(|function| function.is_method)
+            .collect();
+        let name = self
+            .display_names_by_node_id()
+            .remove(address.as_node_id())
+            .unwrap_or_else(|| self.encode_address(address));
+        match methods.iter().find(|method| method.name == method_name) {
+            Some(method) => check_arity(method, arity, &name),
+            None => panic!(
+                "{} of blueprint {} has no method named {}. Its methods are: {}",
+                name,
+                blueprint_id.blueprint_name,
+                method_name,
+                function_names(&methods)
+            ),
+        }
+    }
+
+    /// Panics if a blueprint of a package has no function with the given name and number of
+    /// arguments.
+    pub(crate) fn check_function_call(
+        &self,
+        package_address: PackageAddress,
+        blueprint_name: &str,
+        function_name: &str,
+        arity: usize,
+    ) {
+        let blueprint_id = BlueprintId::new(&package_address, blueprint_name);
+        let Some(functions) = self.engine_interface.blueprint_functions(&blueprint_id) else {
+            let blueprint_names = self.engine_interface.blueprint_names(package_address);
+            if blueprint_names.is_empty() {
+                return;
+            }
+            panic!(
+                "The package has no blueprint named {}. Its blueprints are: {}",
+                blueprint_name,
+                blueprint_names.join(", ")
+            );
+        };
+        let functions: Vec<&BlueprintFunction> = functions
+            .iter()
+            .filter(|function| !function.is_method)
+            .collect();
+        match functions
+            .iter()
+            .find(|function| function.name == function_name)
+        {
+            Some(function) => check_arity(function, arity, blueprint_name),
+            None => panic!(
+                "Blueprint {} has no function named {}. Its functions are: {}",
+                blueprint_name,
+                function_name,
+                function_names(&functions)
+            ),
+        }
+    }
+
     fn create_component<N: ReferenceName>(
         &mut self,
         component_name: N,
@@ -637,12 +3273,20 @@ impl TestEngine {
         let mut receipt = receipt.assert_is_success();
 
         if let TransactionResult::Commit(ref mut commit) = &mut receipt.result {
-            let mut components: Vec<&ComponentAddress> =
-                commit.new_component_addresses().iter().collect();
-            if let Some(component) = components.first() {
-                self.components.insert(component_name.format(), **component);
-                components.remove(0);
+            let components: Vec<ComponentAddress> =
+                commit.new_component_addresses().iter().copied().collect();
+            // The first component gets the given name and the others, such as child components,
+            // are numbered after it.
+            for (index, component) in components.iter().enumerate() {
+                let name = match index {
+                    0 => component_name.display(),
+                    _ => format!("{} #{}", component_name.display(), index + 1),
+                };
+                self.check_reference_collision(&name.format(), EntityKind::Component);
+                self.register_display_name(&name);
+                self.components.insert(name.format(), *component);
             }
+            self.last_created_components = components;
 
             if self.current_component.is_none() {
                 self.current_component = Some(component_name.format())
@@ -656,11 +3300,28 @@ impl TestEngine {
         receipt
     }
 
+    fn publish_package<N: ReferenceName>(
+        &mut self,
+        name: N,
+        code: Vec<u8>,
+        definition: PackageDefinition,
+        owner_role: OwnerRole,
+    ) {
+        if self.packages.contains_key(&name.format()) {
+            panic!("A package with name {} already exists", name.format());
+        }
+        let receipt = self
+            .engine_interface
+            .publish_compiled_package(code, definition, owner_role);
+        self.create_package(name, receipt);
+    }
+
     fn create_package<N: ReferenceName>(&mut self, name: N, receipt: TransactionReceipt) {
         match receipt.result {
             TransactionResult::Commit(commit) => {
                 self.packages
                     .insert(name.format(), commit.new_package_addresses()[0]);
+                self.register_display_name(&name);
                 if self.current_package.is_none() {
                     self.current_package = Some(name.format());
                 }
@@ -684,16 +3345,152 @@ impl TestEngine {
 
     fn update_resources_from_result(&mut self, result: &CommitResult) {
         // Update tracked resources
+        let new_components: Vec<ComponentAddress> =
+            result.new_component_addresses().iter().copied().collect();
         for resource in result.new_resource_addresses() {
             if let Some(name) = self.get_metadata_value_of("name", (*resource).into()) {
-                self.insert_resource(name, *resource);
+                self.record_component_resources(&name, *resource, &new_components);
+                self.insert_resource_from_metadata(name, *resource, &new_components);
             }
             if let Some(name) = self.get_metadata_value_of("symbol", (*resource).into()) {
+                self.record_component_resources(&name, *resource, &new_components);
                 self.try_insert_resource(name, *resource);
             }
         }
     }
 
+    /// Records a resource as created alongside the given components, for [`Self::get_resource_of`].
+    fn record_component_resources(
+        &mut self,
+        name: &String,
+        resource_address: ResourceAddress,
+        components: &[ComponentAddress],
+    ) {
+        for component in components {
+            self.component_resources
+                .entry(*component)
+                .or_default()
+                .entry(name.format())
+                .or_insert(resource_address);
+        }
+    }
+
+    /// Registers a resource found by its metadata. If the name is already taken, the existing
+    /// resource keeps it and the new one is registered as `"<component>::<name>"` for each
+    /// component created in the same transaction.
+    fn insert_resource_from_metadata(
+        &mut self,
+        name: String,
+        resource_address: ResourceAddress,
+        components: &[ComponentAddress],
+    ) {
+        if !self.resources.contains_key(&name.format()) {
+            return self.insert_resource(name, resource_address);
+        }
+
+        let qualified_names: Vec<String> = components
+            .iter()
+            .filter_map(|component| self.component_display_name(*component))
+            .map(|component_name| format!("{}::{}", component_name, name))
+            .collect();
+        for qualified_name in &qualified_names {
+            self.try_insert_resource(qualified_name.clone(), resource_address);
+        }
+        self.warn_name_collision(format!(
+            "Resource name {} is already used by {}. The new resource {} is registered as {}",
+            name,
+            self.encode_address(self.resources[&name.format()].into()),
+            self.encode_address(resource_address.into()),
+            if qualified_names.is_empty() {
+                "its address only".to_string()
+            } else {
+                qualified_names.join(", ")
+            }
+        ));
+    }
+
+    /// Registers a component found by its metadata. If the name is already taken, the existing
+    /// component keeps it and the new one is registered as `"<package>::<name>"`.
+    fn insert_component_from_metadata(
+        &mut self,
+        name: String,
+        component_address: ComponentAddress,
+    ) {
+        if !self.components.contains_key(&name.format()) {
+            return self.insert_component(name, component_address);
+        }
+
+        let package_address = self
+            .engine_interface
+            .blueprint_of(component_address.into())
+            .map(|blueprint| blueprint.package_address);
+        let package_name = package_address.map(|package_address| {
+            self.packages
+                .iter()
+                .filter(|(_, address)| **address == package_address)
+                .map(|(key, _)| self.display_names.get(key).unwrap_or(key).clone())
+                .min_by_key(|name| name.len())
+                .unwrap_or_else(|| self.encode_address(package_address.into()))
+        });
+        let qualified_name = match package_name {
+            Some(package_name) => format!("{}::{}", package_name, name),
+            None => self.encode_address(component_address.into()),
+        };
+        let registered = !self.components.contains_key(&qualified_name.format());
+        if registered {
+            self.register_display_name(&qualified_name);
+            self.check_reference_collision(&qualified_name.format(), EntityKind::Component);
+            self.components
+                .insert(qualified_name.format(), component_address);
+        }
+        self.warn_name_collision(format!(
+            "Component name {} is already used by {}. The new component {} is registered as {}",
+            name,
+            self.encode_address(self.components[&name.format()].into()),
+            self.encode_address(component_address.into()),
+            if registered {
+                qualified_name
+            } else {
+                "its address only".to_string()
+            }
+        ));
+    }
+
+    /// Registers a component without name metadata as `"<blueprint> #<n>"`, with the first free
+    /// number. Only components of the packages of the engine are registered.
+    fn insert_component_from_blueprint(&mut self, component_address: ComponentAddress) {
+        let Some(blueprint) = self.engine_interface.blueprint_of(component_address.into()) else {
+            return;
+        };
+        if !self
+            .packages
+            .values()
+            .any(|package| *package == blueprint.package_address)
+        {
+            return;
+        }
+
+        let name = (1..)
+            .map(|index| format!("{} #{}", blueprint.blueprint_name, index))
+            .find(|name| !self.components.contains_key(&name.format()))
+            .unwrap();
+        self.insert_component(name, component_address);
+    }
+
+    /// Returns the shortest reference name of a component, as it was written when registered.
+    fn component_display_name(&self, component_address: ComponentAddress) -> Option<String> {
+        self.components
+            .iter()
+            .filter(|(_, address)| **address == component_address)
+            .map(|(key, _)| self.display_names.get(key).unwrap_or(key).clone())
+            .min_by_key(|name| name.len())
+    }
+
+    fn warn_name_collision(&mut self, warning: String) {
+        self.logger.on_warning(&warning);
+        self.reference_warnings.push(warning);
+    }
+
     fn get_metadata_value_of(&mut self, metadata: &str, address: GlobalAddress) -> Option<String> {
         if let Some(MetadataValue::String(value)) =
             self.engine_interface.get_metadata(address, metadata)
@@ -705,24 +3502,111 @@ impl TestEngine {
     }
 
     fn insert_resource(&mut self, name: String, resource_address: ResourceAddress) {
-        if let Entry::Vacant(e) = self.resources.entry(name.format()) {
-            e.insert(resource_address);
-        } else {
-            panic!("Token with name {} already exists", name.format());
+        self.register_display_name(&name);
+        let name = name.format();
+        if self.resources.contains_key(&name) {
+            panic!("Token with name {} already exists", name);
         }
+        self.check_reference_collision(&name, EntityKind::Resource);
+        self.resources.insert(name, resource_address);
     }
 
     fn try_insert_resource(&mut self, name: String, resource_address: ResourceAddress) {
-        if let Entry::Vacant(e) = self.resources.entry(name.format()) {
-            e.insert(resource_address);
+        self.register_display_name(&name);
+        let name = name.format();
+        if !self.resources.contains_key(&name) {
+            self.check_reference_collision(&name, EntityKind::Resource);
+            self.resources.insert(name, resource_address);
         }
     }
 
-    fn insert_component(&mut self, name: String, component_address: ComponentAddress) {
-        if let Entry::Vacant(e) = self.components.entry(name.format()) {
-            e.insert(component_address);
-        } else {
-            panic!("Component with name {} already exists", name.format());
+    pub(crate) fn insert_component(&mut self, name: String, component_address: ComponentAddress) {
+        self.register_display_name(&name);
+        let name = name.format();
+        if self.components.contains_key(&name) {
+            panic!("Component with name {} already exists", name);
+        }
+        self.check_reference_collision(&name, EntityKind::Component);
+        self.components.insert(name, component_address);
+    }
+
+    /// Returns the key under which the given reference name is stored for the given kind of
+    /// entity, after checking that an explicit `<kind>:` prefix, if any, matches it.
+    fn reference_key<N: ReferenceName>(name: &N, kind: EntityKind) -> String {
+        Self::try_reference_key(name, kind).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    fn try_reference_key<N: ReferenceName>(
+        name: &N,
+        kind: EntityKind,
+    ) -> Result<String, ReferenceError> {
+        match split_entity_kind(name.format()) {
+            (Some(name_kind), _) if name_kind != kind => Err(ReferenceError::WrongKind {
+                name: name.display(),
+                expected: kind,
+                found: name_kind,
+            }),
+            (_, key) => Ok(key),
+        }
+    }
+
+    fn reference_not_found<'a, N: ReferenceName, I: IntoIterator<Item = &'a String>>(
+        &self,
+        name: &N,
+        kind: Option<EntityKind>,
+        keys: I,
+    ) -> ReferenceError {
+        let (_, key) = split_entity_kind(name.format());
+        ReferenceError::NotFound {
+            kind,
+            name: name.display(),
+            suggestions: closest_names(&key, keys)
+                .into_iter()
+                .map(|key| self.display_names.get(&key).cloned().unwrap_or(key))
+                .collect(),
+        }
+    }
+
+    fn register_display_name<N: ReferenceName>(&mut self, name: &N) {
+        self.display_names
+            .entry(name.format())
+            .or_insert_with(|| name.display());
+    }
+
+    fn entities_named(&self, key: &String) -> Vec<(EntityKind, GlobalAddress)> {
+        let mut entities = vec![];
+        if let Some(account) = self.accounts.get(key) {
+            entities.push((EntityKind::Account, (*account.address()).into()));
+        }
+        if let Some(component) = self.components.get(key) {
+            entities.push((EntityKind::Component, (*component).into()));
+        }
+        if let Some(resource) = self.resources.get(key) {
+            entities.push((EntityKind::Resource, (*resource).into()));
+        }
+        entities
+    }
+
+    fn check_reference_collision(&mut self, key: &String, kind: EntityKind) {
+        for (other_kind, address) in self.entities_named(key) {
+            if other_kind == kind {
+                continue;
+            }
+            let warning = format!(
+                "Reference name {} is already used by {} {} and is now registered for a new {}. Use \"{}:{}\" or \"{}:{}\" to disambiguate",
+                key,
+                other_kind,
+                self.encode_address(address),
+                kind,
+                other_kind,
+                key,
+                kind,
+                key
+            );
+            if self.strict_references {
+                panic!("{}", warning);
+            }
+            self.warn_name_collision(warning);
         }
     }
 }
@@ -732,7 +3616,7 @@ impl Default for TestEngine {
     }
 }
 
-impl<'a> SimpleMethodCaller for &'a mut TestEngine {
+impl SimpleMethodCaller for &mut TestEngine {
     fn call_method(
         self,
         method_name: &str,
@@ -749,6 +3633,7 @@ impl<'a> SimpleMethodCaller for &'a mut TestEngine {
         args: Vec<Box<dyn EnvironmentEncode>>,
     ) -> TransactionReceipt {
         self.call_method_builder_from(global_address, method_name, args)
+            .with_execution_trace()
             .execute()
     }
 
@@ -760,12 +3645,36 @@ impl<'a> SimpleMethodCaller for &'a mut TestEngine {
     ) -> TransactionReceipt {
         self.call_method_builder(method_name, args)
             .with_badge(admin_badge)
+            .with_execution_trace()
+            .execute()
+    }
+
+    fn call_function(
+        self,
+        blueprint_name: &str,
+        function_name: &str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+    ) -> TransactionReceipt {
+        self.call_function_builder(blueprint_name, function_name, args)
+            .with_execution_trace()
+            .execute()
+    }
+
+    fn call_function_from<P: PackageReference>(
+        self,
+        package: P,
+        blueprint_name: &str,
+        function_name: &str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+    ) -> TransactionReceipt {
+        self.call_function_builder_from(package, blueprint_name, function_name, args)
+            .with_execution_trace()
             .execute()
     }
 }
 
 impl ComplexMethodCaller for TestEngine {
-    fn build_call(&mut self) -> CallBuilder {
+    fn build_call(&mut self) -> CallBuilder<'_> {
         CallBuilder::new(self)
     }
 
@@ -773,7 +3682,7 @@ impl ComplexMethodCaller for TestEngine {
         &mut self,
         method_name: &str,
         args: Vec<Box<dyn EnvironmentEncode>>,
-    ) -> CallBuilder {
+    ) -> CallBuilder<'_> {
         let component = *self.current_component();
         self.call_method_builder_from(component, method_name, args)
     }
@@ -783,8 +3692,67 @@ impl ComplexMethodCaller for TestEngine {
         global_address: G,
         method_name: &str,
         args: Vec<Box<dyn EnvironmentEncode>>,
-    ) -> CallBuilder {
+    ) -> CallBuilder<'_> {
         let address = global_address.address(self);
         CallBuilder::new(self).call_method_internal(address, method_name, args)
     }
+
+    fn call_function_builder(
+        &mut self,
+        blueprint_name: &str,
+        function_name: &str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+    ) -> CallBuilder<'_> {
+        let package = *self.current_package();
+        self.call_function_builder_from(package, blueprint_name, function_name, args)
+    }
+
+    fn call_function_builder_from<P: PackageReference>(
+        &mut self,
+        package: P,
+        blueprint_name: &str,
+        function_name: &str,
+        args: Vec<Box<dyn EnvironmentEncode>>,
+    ) -> CallBuilder<'_> {
+        let address = package.address(self);
+        CallBuilder::new(self).call_function_internal(address, blueprint_name, function_name, args)
+    }
+}
+
+fn format_balance_change(change: &BalanceChange) -> String {
+    match change {
+        BalanceChange::Fungible(amount) => amount.to_string(),
+        BalanceChange::NonFungible { added, removed } => {
+            let format_ids = |ids: &BTreeSet<NonFungibleLocalId>| {
+                ids.iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            format!(
+                "added [{}], removed [{}]",
+                format_ids(added),
+                format_ids(removed)
+            )
+        }
+    }
+}
+
+fn check_arity(function: &BlueprintFunction, arity: usize, owner: &str) {
+    if let Some(expected) = function.arity {
+        if expected != arity {
+            panic!(
+                "{}::{} takes {} arguments, but {} were given",
+                owner, function.name, expected, arity
+            );
+        }
+    }
+}
+
+fn function_names(functions: &[&BlueprintFunction]) -> String {
+    functions
+        .iter()
+        .map(|function| function.name.as_str())
+        .collect::<Vec<&str>>()
+        .join(", ")
 }
@@ -0,0 +1,112 @@
+use crate::engine_interface::EngineInterface;
+use crate::internal_prelude::*;
+use crate::test_engine::TestEngine;
+
+/// Builder of a [`TestEngine`] with a custom genesis.
+pub struct TestEngineBuilder {
+    starting_epoch: Epoch,
+    validator_count: u64,
+    consensus_manager_config: ConsensusManagerConfig,
+    kernel_trace: bool,
+    self_funded: bool,
+}
+
+/// Amount of XRD given to the default account of a self-funded engine.
+pub const SELF_FUNDED_XRD: Decimal = dec!(1_000_000_000);
+
+impl TestEngineBuilder {
+    /// Returns a builder of a TestEngine with a single validator, starting at epoch 2 as
+    /// [`TestEngine::new`].
+    pub fn new() -> Self {
+        Self {
+            starting_epoch: Epoch::of(2),
+            validator_count: 1,
+            consensus_manager_config: CustomGenesis::default_consensus_manager_config(),
+            kernel_trace: false,
+            self_funded: false,
+        }
+    }
+
+    /// Sets the epoch at which the engine starts. The genesis ends with an epoch change, so the
+    /// genesis epoch is the previous one.
+    /// Panics if the epoch is 0.
+    ///
+    /// # Arguments
+    /// * `epoch`: epoch at which the engine starts.
+    pub fn starting_epoch(mut self, epoch: Epoch) -> Self {
+        if epoch.previous().is_none() {
+            panic!("The engine cannot start before epoch 1");
+        }
+        self.starting_epoch = epoch;
+        self
+    }
+
+    /// Sets the number of validators registered at genesis, each one staked with 1 XRD.
+    /// Panics if the count is 0.
+    ///
+    /// # Arguments
+    /// * `count`: number of validators.
+    pub fn validator_count(mut self, count: u64) -> Self {
+        if count == 0 {
+            panic!("The genesis needs at least one validator");
+        }
+        self.validator_count = count;
+        self
+    }
+
+    /// Sets the configuration of the consensus manager, such as the number of rounds per epoch.
+    ///
+    /// # Arguments
+    /// * `config`: configuration of the consensus manager.
+    pub fn consensus_manager_config(mut self, config: ConsensusManagerConfig) -> Self {
+        self.consensus_manager_config = config;
+        self
+    }
+
+    /// Prints the kernel trace of every transaction.
+    pub fn with_kernel_trace(mut self) -> Self {
+        self.kernel_trace = true;
+        self
+    }
+
+    /// Funds the default account with [`SELF_FUNDED_XRD`] XRD and makes the caller of each call
+    /// lock its fees, instead of the faucet.
+    pub fn self_funded(mut self) -> Self {
+        self.self_funded = true;
+        self
+    }
+
+    /// Builds the TestEngine.
+    pub fn build(self) -> TestEngine {
+        // The genesis validators use the first keys, as the single validator of the default genesis.
+        let validators = (1..=self.validator_count)
+            .map(|key| {
+                let public_key = Secp256k1PrivateKey::from_u64(key).unwrap().public_key();
+                (public_key, Decimal::ONE)
+            })
+            .collect();
+        let staker_public_key = Secp256k1PrivateKey::from_u64(1).unwrap().public_key();
+        let genesis = CustomGenesis::validators_and_single_staker(
+            validators,
+            ComponentAddress::virtual_account_from_public_key(&staker_public_key),
+            Decimal::ZERO,
+            self.starting_epoch.previous().unwrap(),
+            self.consensus_manager_config,
+        );
+
+        let mut test_engine = TestEngine::new_with_engine_interface(
+            EngineInterface::new_with_genesis(genesis, self.kernel_trace, None),
+        );
+        if self.self_funded {
+            test_engine.fund_account("default", SELF_FUNDED_XRD);
+            test_engine.set_self_funded();
+        }
+        test_engine
+    }
+}
+
+impl Default for TestEngineBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,126 +1,191 @@
+use std::fmt::{Display, Formatter};
+
 use crate::internal_prelude::*;
 
+/// Error returned when a value cannot be converted into a non-fungible id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToIdError {
+    /// The value that could not be converted.
+    pub id: String,
+    /// The type of id that the value was parsed as.
+    pub id_type: NonFungibleIdType,
+    /// Why the value is not a valid id of this type.
+    pub reason: String,
+}
+
+impl ToIdError {
+    fn new<I: ToString, R: ToString>(id: I, id_type: NonFungibleIdType, reason: R) -> Self {
+        Self {
+            id: id.to_string(),
+            id_type,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl Display for ToIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (id_type, expected) = match self.id_type {
+            NonFungibleIdType::Integer => ("integer", "a u64 between #, as in #1#"),
+            NonFungibleIdType::String => (
+                "string",
+                "1 to 64 characters among _, 0-9, a-z and A-Z, optionally between <>, as in <SomeId>",
+            ),
+            NonFungibleIdType::Bytes => (
+                "bytes",
+                "1 to 64 bytes in hex between [], optionally prefixed by 0x, as in [0a1b] or [0x0a1b]",
+            ),
+            NonFungibleIdType::RUID => (
+                "RUID",
+                "64 hex characters between {}, optionally in 4 groups of 16 separated by dashes",
+            ),
+        };
+        write!(
+            f,
+            "Invalid {} non-fungible id {}: {}. Expected {}",
+            id_type, self.id, self.reason, expected
+        )
+    }
+}
+
 pub trait ToId {
-    fn to_id(self) -> NonFungibleLocalId;
+    /// Converts the value into a non-fungible id, or returns why it cannot be.
+    fn try_to_id(self) -> Result<NonFungibleLocalId, ToIdError>;
+
+    /// Converts the value into a non-fungible id. Panics if the value is not a valid id.
+    fn to_id(self) -> NonFungibleLocalId
+    where
+        Self: Sized,
+    {
+        self.try_to_id().unwrap_or_else(|error| panic!("{}", error))
+    }
 }
 
 impl ToId for NonFungibleLocalId {
-    fn to_id(self) -> NonFungibleLocalId {
-        self
+    fn try_to_id(self) -> Result<NonFungibleLocalId, ToIdError> {
+        Ok(self)
     }
 }
 
 impl ToId for u64 {
-    fn to_id(self) -> NonFungibleLocalId {
-        NonFungibleLocalId::integer(self)
+    fn try_to_id(self) -> Result<NonFungibleLocalId, ToIdError> {
+        Ok(NonFungibleLocalId::integer(self))
     }
 }
 
 impl ToId for Vec<u8> {
-    fn to_id(self) -> NonFungibleLocalId {
-        NonFungibleLocalId::bytes(self).unwrap()
+    fn try_to_id(self) -> Result<NonFungibleLocalId, ToIdError> {
+        NonFungibleLocalId::bytes(self.clone()).map_err(|error| {
+            ToIdError::new(
+                format!("{:?}", self),
+                NonFungibleIdType::Bytes,
+                format!("{:?}", error),
+            )
+        })
     }
 }
 
 impl ToId for [u8; 32] {
-    fn to_id(self) -> NonFungibleLocalId {
-        NonFungibleLocalId::ruid(self)
+    fn try_to_id(self) -> Result<NonFungibleLocalId, ToIdError> {
+        Ok(NonFungibleLocalId::ruid(self))
     }
 }
 
 impl ToId for String {
-    fn to_id(self) -> NonFungibleLocalId {
-        // Transform the String into a vec of chars
-        let mut chars: Vec<char> = self.chars().collect();
-
-        // Check if it starts by '<', '#', '{' or '['
-        if let Some(first) = chars.first() {
-            match first {
-                '<' => {
-                    chars.remove(0);
-                    let last_char = chars.pop().unwrap();
-                    if last_char == '>' && !chars.is_empty() {
-                        // Then it is an integer nf id and we skip at the end
-                    } else {
-                        chars.push(last_char);
-                    }
-                }
-                '#' => {
-                    chars.remove(0);
-                    let last_char = chars.pop().unwrap();
-                    if last_char == '#' && !chars.is_empty() {
-                        // Then it is an integer nf id
-
-                        let char_str: String = chars.into_iter().collect();
-                        let id: u64 = char_str.parse().unwrap();
-                        return id.to_id();
-                    } else {
-                        chars.push(last_char);
-                    }
-                }
-                '[' => {
-                    chars.remove(0);
-                    let last_char = chars.pop().unwrap();
-                    if last_char == ']' && !chars.is_empty() {
-                        // Then it is a bytes nf id
-
-                        let hex_string: String = chars.into_iter().collect();
-                        let hex = hex_string.as_str();
-                        let bytes: Vec<u8> = (0..hex.len())
-                            .step_by(2)
-                            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
-                            .collect();
-                        return bytes.to_id();
-                    } else {
-                        chars.push(last_char);
-                    }
-                }
-                '{' => {
-                    chars.remove(0);
-                    let last_char = chars.pop().unwrap();
-                    if last_char == '}' && !chars.is_empty() {
-                        // Then it is a ruid nf id
-
-                        let hex_string: String =
-                            chars.into_iter().collect::<String>().replace('-', "");
-                        let hex_cleaned = hex_string.as_str();
-                        let mut bytes = [0u8; 32];
-                        for i in 0..32 {
-                            bytes[i] =
-                                u8::from_str_radix(&hex_cleaned[i * 2..i * 2 + 2], 16).unwrap();
-                        }
-
-                        return bytes.to_id();
-                    } else {
-                        chars.push(last_char);
-                    }
-                }
-                _ => { // Else do nothing }
-                }
-            }
+    fn try_to_id(self) -> Result<NonFungibleLocalId, ToIdError> {
+        self.as_str().try_to_id()
+    }
+}
+
+impl ToId for &str {
+    fn try_to_id(self) -> Result<NonFungibleLocalId, ToIdError> {
+        // An id between matching delimiters is parsed as the corresponding type, as in the
+        // output of `NonFungibleLocalId::to_string`. Any other value is a string id.
+        let delimited = |open: char, close: char| {
+            self.strip_prefix(open)
+                .and_then(|inner| inner.strip_suffix(close))
+        };
+
+        if let Some(inner) = delimited('#', '#') {
+            inner
+                .parse::<u64>()
+                .map(NonFungibleLocalId::integer)
+                .map_err(|_| {
+                    ToIdError::new(
+                        self,
+                        NonFungibleIdType::Integer,
+                        format!("{} is not a u64", inner),
+                    )
+                })
+        } else if let Some(inner) = delimited('[', ']') {
+            let hex = inner
+                .strip_prefix("0x")
+                .or_else(|| inner.strip_prefix("0X"))
+                .unwrap_or(inner);
+            let bytes = parse_hex(hex)
+                .map_err(|reason| ToIdError::new(self, NonFungibleIdType::Bytes, reason))?;
+            NonFungibleLocalId::bytes(bytes).map_err(|error| {
+                ToIdError::new(self, NonFungibleIdType::Bytes, format!("{:?}", error))
+            })
+        } else if let Some(inner) = delimited('{', '}') {
+            parse_ruid(inner)
+                .map(NonFungibleLocalId::ruid)
+                .map_err(|reason| ToIdError::new(self, NonFungibleIdType::RUID, reason))
+        } else {
+            let string = delimited('<', '>').unwrap_or(self);
+            NonFungibleLocalId::string(string).map_err(|error| {
+                ToIdError::new(self, NonFungibleIdType::String, format!("{:?}", error))
+            })
         }
-        // In other cases we consider that it is a String non-fungible id
-        let char_str: String = chars.into_iter().collect();
-        NonFungibleLocalId::string(char_str).unwrap()
     }
 }
 
+/// Decodes an even number of hex characters into bytes.
+fn parse_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if let Some(invalid) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("{:?} is not a hex character", invalid));
+    }
+    if !hex.len().is_multiple_of(2) {
+        return Err(format!("odd number of hex characters ({})", hex.len()));
+    }
+    Ok((0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect())
+}
+
+/// Decodes the 32 bytes of a RUID, written as 64 hex characters, optionally in 4 groups of 16
+/// separated by dashes.
+fn parse_ruid(ruid: &str) -> Result<[u8; 32], String> {
+    let hex = if ruid.contains('-') {
+        let groups: Vec<&str> = ruid.split('-').collect();
+        if groups.len() != 4 || groups.iter().any(|group| group.len() != 16) {
+            return Err("dashes must separate 4 groups of 16 hex characters".to_string());
+        }
+        groups.concat()
+    } else {
+        ruid.to_string()
+    };
+    if hex.len() != 64 {
+        return Err(format!("{} hex characters instead of 64", hex.len()));
+    }
+    Ok(parse_hex(&hex)?.try_into().unwrap())
+}
+
 macro_rules! integer_types_impl {
     ($type_name: ident) => {
         impl ToId for $type_name {
-            fn to_id(self) -> NonFungibleLocalId {
-                NonFungibleLocalId::integer(u64::try_from(self).unwrap())
+            fn try_to_id(self) -> Result<NonFungibleLocalId, ToIdError> {
+                u64::try_from(self)
+                    .map(NonFungibleLocalId::integer)
+                    .map_err(|_| {
+                        ToIdError::new(self, NonFungibleIdType::Integer, "it does not fit in a u64")
+                    })
             }
         }
     };
 }
 
-impl ToId for &str {
-    fn to_id(self) -> NonFungibleLocalId {
-        String::from(self).to_id()
-    }
-}
-
 integer_types_impl!(u8);
 integer_types_impl!(u16);
 integer_types_impl!(u32);
@@ -134,8 +199,8 @@ integer_types_impl!(i128);
 macro_rules! subtypes_impl {
     ($type_name: ident, $subtype_name: ident) => {
         impl ToId for $type_name {
-            fn to_id(self) -> NonFungibleLocalId {
-                NonFungibleLocalId::$subtype_name(self)
+            fn try_to_id(self) -> Result<NonFungibleLocalId, ToIdError> {
+                Ok(NonFungibleLocalId::$subtype_name(self))
             }
         }
     };
@@ -181,4 +246,77 @@ mod test_to_ids {
         assert_eq!(str_2.to_id(), NonFungibleLocalId::string("SomeId").unwrap());
         assert_eq!(str_3.to_id(), NonFungibleLocalId::string("blabla").unwrap())
     }
+
+    #[test]
+    fn test_nf_ids_from_prefixed_hex() {
+        assert_eq!(
+            "[0x0a1b]".to_id(),
+            NonFungibleLocalId::bytes(vec![10, 27]).unwrap()
+        );
+        assert_eq!("[0a1b]".to_id(), "[0x0a1b]".to_id());
+    }
+
+    #[test]
+    fn test_nf_ids_from_ruid_with_and_without_dashes() {
+        let dashed = "{0000000000000000-1111111111111111-2222222222222222-3333333333333333}";
+        let undashed = "{0000000000000000111111111111111122222222222222223333333333333333}";
+        assert_eq!(dashed.to_id(), undashed.to_id());
+    }
+
+    #[test]
+    fn test_nf_ids_round_trip() {
+        let ids = [
+            NonFungibleLocalId::integer(42),
+            NonFungibleLocalId::string("Some_Id").unwrap(),
+            NonFungibleLocalId::bytes(vec![0, 255, 16]).unwrap(),
+            NonFungibleLocalId::ruid([7; 32]),
+        ];
+        for id in ids {
+            assert_eq!(id.to_string().to_id(), id);
+        }
+    }
+
+    #[test]
+    fn test_nf_ids_from_malformed_strings() {
+        let error = "#12a#".try_to_id().unwrap_err();
+        assert_eq!(error.id_type, NonFungibleIdType::Integer);
+        assert_eq!(
+            error.to_string(),
+            "Invalid integer non-fungible id #12a#: 12a is not a u64. Expected a u64 between #, as in #1#"
+        );
+
+        let error = "[abc]".try_to_id().unwrap_err();
+        assert_eq!(error.id, "[abc]");
+        assert_eq!(error.reason, "odd number of hex characters (3)");
+
+        let error = "[0xzz]".try_to_id().unwrap_err();
+        assert_eq!(error.reason, "'z' is not a hex character");
+
+        let error = "{0a1b}".try_to_id().unwrap_err();
+        assert_eq!(error.id_type, NonFungibleIdType::RUID);
+        assert_eq!(error.reason, "4 hex characters instead of 64");
+
+        let error = "{00000000-1111111111111111-2222222222222222-3333333333333333}"
+            .try_to_id()
+            .unwrap_err();
+        assert_eq!(
+            error.reason,
+            "dashes must separate 4 groups of 16 hex characters"
+        );
+
+        let error = "<not valid>".try_to_id().unwrap_err();
+        assert_eq!(error.id_type, NonFungibleIdType::String);
+
+        let error = (-1i32).try_to_id().unwrap_err();
+        assert_eq!(error.id, "-1");
+        assert_eq!(error.id_type, NonFungibleIdType::Integer);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Invalid bytes non-fungible id [abc]: odd number of hex characters (3)"
+    )]
+    fn test_nf_ids_to_id_panics_with_error() {
+        "[abc]".to_id();
+    }
 }
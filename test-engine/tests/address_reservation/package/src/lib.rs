@@ -0,0 +1,37 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod pair {
+    struct Pair {
+        own_address: ComponentAddress,
+        partner: ComponentAddress,
+    }
+
+    impl Pair {
+        pub fn instantiate_pair(
+            address_reservation: GlobalAddressReservation,
+            partner: ComponentAddress,
+        ) -> Global<Pair> {
+            let own_address =
+                ComponentAddress::try_from(Runtime::get_reservation_address(&address_reservation))
+                    .unwrap();
+
+            Self {
+                own_address,
+                partner,
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .with_address(address_reservation)
+            .globalize()
+        }
+
+        pub fn own_address(&self) -> ComponentAddress {
+            self.own_address
+        }
+
+        pub fn partner(&self) -> ComponentAddress {
+            self.partner
+        }
+    }
+}
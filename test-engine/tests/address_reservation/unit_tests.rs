@@ -0,0 +1,64 @@
+mod address_reservation_tests {
+    use test_engine::prelude::*;
+
+    global_packages! {
+        ADDRESS_RESERVATION_PACKAGE => "tests/address_reservation/package",
+    }
+
+    fn initialize() -> TestEngine {
+        let mut test_engine = TestEngine::new();
+        test_engine.new_package("pair package", &ADDRESS_RESERVATION_PACKAGE);
+        test_engine
+            .build_call()
+            .allocate_address("pair package", "Pair", "first pair")
+            .allocate_address("pair package", "Pair", "second pair")
+            .call_from_package(
+                "pair package",
+                "Pair",
+                "instantiate_pair",
+                env_args!(
+                    Environment::AddressReservation("first pair"),
+                    Environment::NamedAddress("second pair")
+                ),
+            )
+            .call_from_package(
+                "pair package",
+                "Pair",
+                "instantiate_pair",
+                env_args!(
+                    Environment::AddressReservation("second pair"),
+                    Environment::NamedAddress("first pair")
+                ),
+            )
+            .execute()
+            .assert_is_success();
+        test_engine
+    }
+
+    #[test]
+    fn test_component_stores_its_allocated_address() {
+        let mut test_engine = initialize();
+        let first_pair = test_engine.get_component("first pair");
+        let own_address: ComponentAddress = test_engine
+            .call_method_from("first pair", "own_address", env_args!())
+            .get_return();
+        assert_eq!(own_address, first_pair);
+    }
+
+    #[test]
+    fn test_components_reference_each_other() {
+        let mut test_engine = initialize();
+        let first_pair = test_engine.get_component("first pair");
+        let second_pair = test_engine.get_component("second pair");
+
+        let partner: ComponentAddress = test_engine
+            .call_method_from("first pair", "partner", env_args!())
+            .get_return();
+        assert_eq!(partner, second_pair);
+
+        let partner: ComponentAddress = test_engine
+            .call_method_from("second pair", "partner", env_args!())
+            .get_return();
+        assert_eq!(partner, first_pair);
+    }
+}
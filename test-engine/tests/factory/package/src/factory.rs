@@ -0,0 +1,37 @@
+use crate::pool::pool::Pool;
+use scrypto::prelude::*;
+
+#[blueprint]
+mod factory {
+    struct Factory {
+        pools: Vec<Global<Pool>>,
+    }
+
+    impl Factory {
+        pub fn instantiate() -> Global<Factory> {
+            Self { pools: vec![] }
+                .instantiate()
+                .prepare_to_globalize(OwnerRole::None)
+                .globalize()
+        }
+
+        // The factory is globalized before its pools
+        pub fn instantiate_with_pools() -> Global<Factory> {
+            let factory = Self::instantiate();
+            factory.create_pools();
+            factory
+        }
+
+        pub fn create_pools(&mut self) -> (Global<Pool>, Global<Pool>) {
+            let first = Pool::instantiate(self.pools.len() as u64 + 1);
+            let second = Pool::instantiate(self.pools.len() as u64 + 2);
+            self.pools.push(first);
+            self.pools.push(second);
+            (first, second)
+        }
+
+        pub fn pool_count(&self) -> u64 {
+            self.pools.len() as u64
+        }
+    }
+}
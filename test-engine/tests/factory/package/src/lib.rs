@@ -0,0 +1,4 @@
+mod factory;
+mod pool;
+mod token_factory;
+mod treasury;
@@ -0,0 +1,21 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod pool {
+    struct Pool {
+        id: u64,
+    }
+
+    impl Pool {
+        pub fn instantiate(id: u64) -> Global<Pool> {
+            Self { id }
+                .instantiate()
+                .prepare_to_globalize(OwnerRole::None)
+                .globalize()
+        }
+
+        pub fn id(&self) -> u64 {
+            self.id
+        }
+    }
+}
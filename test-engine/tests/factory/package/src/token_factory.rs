@@ -0,0 +1,47 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod token_factory {
+    struct TokenFactory {}
+
+    impl TokenFactory {
+        pub fn instantiate() -> Global<TokenFactory> {
+            Self {}
+                .instantiate()
+                .prepare_to_globalize(OwnerRole::None)
+                .globalize()
+        }
+
+        // Creates a token following the usual metadata conventions
+        pub fn create_compliant_token(&mut self) -> Bucket {
+            ResourceBuilder::new_fungible(OwnerRole::None)
+                .metadata(metadata!(
+                    init {
+                        "name" => "Factory Dollar", locked;
+                        "symbol" => "FUSD", locked;
+                        "description" => "Stable token created by the factory", locked;
+                        "icon_url" => Url::of("https://factory.example.com/fusd.png"), locked;
+                    }
+                ))
+                .mint_initial_supply(1000)
+                .into()
+        }
+
+        // Creates a compliant token and a token breaking every metadata convention
+        pub fn create_tokens(&mut self) -> (Bucket, Bucket) {
+            let bad_token = ResourceBuilder::new_fungible(OwnerRole::None)
+                .metadata(metadata!(
+                    init {
+                        "name" => "Factory Bad Token", locked;
+                        "symbol" => "fusd-bad-token", locked;
+                        "description" => "", locked;
+                        "icon_url" => Url::of("factory.example.com/icon.png"), locked;
+                    }
+                ))
+                .mint_initial_supply(1000)
+                .into();
+
+            (self.create_compliant_token(), bad_token)
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod treasury {
+    struct Treasury {
+        admin_badge: Vault,
+    }
+
+    impl Treasury {
+        pub fn instantiate() -> Global<Treasury> {
+            let admin_badge = ResourceBuilder::new_fungible(OwnerRole::None)
+                .divisibility(DIVISIBILITY_NONE)
+                .metadata(metadata!(
+                    init {
+                        "name" => "Admin Badge", locked;
+                    }
+                ))
+                .mint_initial_supply(1);
+
+            Self {
+                admin_badge: Vault::with_bucket(admin_badge.into()),
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .metadata(metadata!(
+                init {
+                    "name" => "Treasury", locked;
+                }
+            ))
+            .globalize()
+        }
+
+        pub fn admin_badge_address(&self) -> ResourceAddress {
+            self.admin_badge.resource_address()
+        }
+    }
+}
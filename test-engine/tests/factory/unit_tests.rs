@@ -0,0 +1,229 @@
+mod factory_tests {
+    use test_engine::prelude::*;
+
+    global_package!(FACTORY_PACKAGE, "tests/factory/package");
+
+    #[test]
+    fn test_child_components_of_instantiation_are_numbered() {
+        let mut test_engine = TestEngine::with_package("factory package", &FACTORY_PACKAGE);
+        test_engine.new_component("factory", "Factory", "instantiate_with_pools", env_args!());
+
+        let created = test_engine.last_created_components().clone();
+        assert_eq!(created.len(), 3);
+        assert_eq!(test_engine.get_component("factory"), created[0]);
+        assert_eq!(test_engine.get_component("factory #2"), created[1]);
+        assert_eq!(test_engine.get_component("factory #3"), created[2]);
+
+        let id: u64 = test_engine
+            .call_method_from("factory #3", "id", env_args!())
+            .get_return();
+        assert_eq!(id, 2);
+    }
+
+    #[test]
+    fn test_components_created_by_method_calls_are_registered() {
+        let mut test_engine = TestEngine::with_package("factory package", &FACTORY_PACKAGE);
+        test_engine.new_component("factory", "Factory", "instantiate", env_args!());
+
+        test_engine
+            .call_method("create_pools", env_args!())
+            .assert_is_success();
+        assert_eq!(test_engine.last_created_components().len(), 2);
+        test_engine
+            .call_method("create_pools", env_args!())
+            .assert_is_success();
+        let created = test_engine.last_created_components().clone();
+
+        assert_eq!(test_engine.get_component("Pool #3"), created[0]);
+        assert_eq!(test_engine.get_component("pool #4"), created[1]);
+        for (name, expected_id) in [
+            ("Pool #1", 1u64),
+            ("Pool #2", 2),
+            ("Pool #3", 3),
+            ("Pool #4", 4),
+        ] {
+            let id: u64 = test_engine
+                .call_method_from(name, "id", env_args!())
+                .get_return();
+            assert_eq!(id, expected_id);
+        }
+    }
+}
+
+mod token_factory_tests {
+    use test_engine::prelude::*;
+
+    global_package!(FACTORY_PACKAGE, "tests/factory/package");
+
+    fn instantiate_factory() -> TestEngine {
+        let mut test_engine = TestEngine::with_package("factory package", &FACTORY_PACKAGE);
+        test_engine.new_component("factory", "TokenFactory", "instantiate", env_args!());
+        test_engine
+    }
+
+    #[test]
+    fn test_compliant_token() {
+        let mut test_engine = instantiate_factory();
+        let receipt = test_engine.call_method("create_compliant_token", env_args!());
+
+        test_engine.assert_all_new_resources_compliant(&receipt, &MetadataPolicy::default());
+        test_engine.assert_resource_metadata_compliant("FUSD", &MetadataPolicy::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "- symbol = \"fusd-bad-token\": symbol is not uppercase")]
+    fn test_non_compliant_token() {
+        let mut test_engine = instantiate_factory();
+        let receipt = test_engine.call_method("create_tokens", env_args!());
+
+        test_engine.assert_all_new_resources_compliant(&receipt, &MetadataPolicy::default());
+    }
+
+    #[test]
+    fn test_non_compliant_token_report() {
+        let mut test_engine = instantiate_factory();
+        test_engine
+            .call_method("create_tokens", env_args!())
+            .assert_is_success();
+
+        let policy = MetadataPolicy::default();
+        let mut metadata = HashMap::new();
+        for key in POLICY_METADATA_KEYS {
+            if let Some(value) = test_engine.get_metadata("Factory Bad Token", key) {
+                metadata.insert(key.to_string(), value);
+            }
+        }
+        let violations = policy.check(&metadata, &["FUSD".to_string()]);
+
+        let rules: Vec<(&str, &str)> = violations
+            .iter()
+            .map(|violation| (violation.key.as_str(), violation.rule.as_str()))
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                ("symbol", "symbol is longer than 8 characters"),
+                ("symbol", "symbol is not uppercase"),
+                ("description", "description is empty"),
+                ("icon_url", "icon url is not a well-formed http(s) url"),
+            ]
+        );
+        assert_eq!(
+            violations[3].value,
+            Some("factory.example.com/icon.png".to_string())
+        );
+    }
+}
+
+mod treasury_tests {
+    use test_engine::prelude::*;
+
+    global_packages! {
+        FACTORY_PACKAGE => "tests/factory/package",
+    }
+
+    fn instantiate_from(test_engine: &mut TestEngine, package: &str) {
+        test_engine
+            .build_call()
+            .call_from_package(package, "Treasury", "instantiate", env_args!())
+            .execute()
+            .assert_is_success();
+    }
+
+    fn initialize() -> TestEngine {
+        let mut test_engine = TestEngine::new();
+        test_engine.new_package("lending package", &FACTORY_PACKAGE);
+        test_engine.new_package("exchange package", &FACTORY_PACKAGE);
+        instantiate_from(&mut test_engine, "lending package");
+        instantiate_from(&mut test_engine, "exchange package");
+        test_engine
+    }
+
+    fn admin_badge_of(test_engine: &mut TestEngine, component: &str) -> ResourceAddress {
+        test_engine
+            .call_method_from(component, "admin_badge_address", env_args!())
+            .get_return()
+    }
+
+    #[test]
+    fn test_colliding_component_names_are_qualified() {
+        let test_engine = initialize();
+        let first = test_engine.get_component("Treasury");
+        let second = test_engine.get_component("exchange package::Treasury");
+        assert_ne!(first, second);
+        assert_eq!(test_engine.reference_warnings().len(), 2);
+    }
+
+    #[test]
+    fn test_name_collision_warnings_are_logged() {
+        let mut test_engine = TestEngine::new();
+        let logger = MemoryLogger::new();
+        test_engine.set_logger(logger.clone());
+        test_engine.new_package("lending package", &FACTORY_PACKAGE);
+        test_engine.new_package("exchange package", &FACTORY_PACKAGE);
+        instantiate_from(&mut test_engine, "lending package");
+        instantiate_from(&mut test_engine, "exchange package");
+
+        assert_eq!(logger.warnings().len(), 2);
+        assert_eq!(&logger.warnings(), test_engine.reference_warnings());
+    }
+
+    #[test]
+    fn test_colliding_resource_names_are_qualified() {
+        let mut test_engine = initialize();
+        let first_badge = admin_badge_of(&mut test_engine, "Treasury");
+        let second_badge = admin_badge_of(&mut test_engine, "exchange package::Treasury");
+
+        assert_ne!(first_badge, second_badge);
+        assert_eq!(test_engine.get_resource("Admin Badge"), first_badge);
+        assert_eq!(
+            test_engine.get_resource("exchange package::Treasury::Admin Badge"),
+            second_badge
+        );
+    }
+
+    #[test]
+    fn test_get_resource_of() {
+        let mut test_engine = initialize();
+        let first_badge = admin_badge_of(&mut test_engine, "Treasury");
+        let second_badge = admin_badge_of(&mut test_engine, "exchange package::Treasury");
+
+        assert_eq!(
+            test_engine.get_resource_of("Treasury", "Admin Badge"),
+            first_badge
+        );
+        assert_eq!(
+            test_engine.get_resource_of("exchange package::Treasury", "Admin Badge"),
+            second_badge
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "has no resource named Unknown Badge")]
+    fn test_get_resource_of_unknown_resource() {
+        let test_engine = initialize();
+        test_engine.get_resource_of("Treasury", "Unknown Badge");
+    }
+
+    #[test]
+    fn test_new_component_with_colliding_resource_name() {
+        let mut test_engine = initialize();
+        test_engine.set_current_package("lending package");
+        test_engine.new_component(
+            "second lending treasury",
+            "Treasury",
+            "instantiate",
+            env_args!(),
+        );
+
+        let badge = admin_badge_of(&mut test_engine, "second lending treasury");
+        assert_eq!(
+            test_engine.get_resource("second lending treasury::Admin Badge"),
+            badge
+        );
+        assert_eq!(
+            test_engine.get_resource_of("second lending treasury", "Admin Badge"),
+            badge
+        );
+    }
+}
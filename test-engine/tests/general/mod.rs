@@ -1,3 +1,4 @@
+use radix_engine_interface::blueprints::component::Global;
 use test_engine::prelude::*;
 
 #[test]
@@ -34,3 +35,3949 @@ fn test_transfer() {
         .assert_is_success();
     assert_eq!(test_engine.balance_of("Recipient", "Test token"), dec!(10));
 }
+
+#[test]
+fn test_new_token_with_metadata() {
+    let mut test_engine = TestEngine::new();
+
+    test_engine.new_token_with_metadata(
+        "usd token",
+        1000,
+        2,
+        vec![
+            ("name", MetadataValue::String("US Dollar".to_string())),
+            ("symbol", MetadataValue::String("USD".to_string())),
+        ],
+    );
+
+    let usd = test_engine.get_resource("usd token");
+    assert_eq!(test_engine.get_resource("US Dollar"), usd);
+    assert_eq!(test_engine.get_resource("usd"), usd);
+    assert_eq!(test_engine.total_supply("usd"), Some(dec!(1000)));
+
+    test_engine.new_account("Recipient");
+    test_engine.set_divisibility_check(false);
+    test_engine
+        .transfer("Recipient", "usd", dec!("0.001"))
+        .assert_failed_with("InvalidAmount");
+}
+
+#[test]
+fn test_new_mintable_token() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token_with_metadata("minter badge", 1, 0, vec![]);
+    test_engine.new_mintable_token("usd", 1000, 6, "minter badge");
+    let usd = test_engine.get_resource("usd");
+
+    test_engine
+        .build_call()
+        .call_from_component(usd, "mint", env_args!(dec!(500)))
+        .execute()
+        .assert_failed_with_auth_error();
+
+    test_engine
+        .build_call()
+        .with_badge("minter badge")
+        .call_from_component(usd, "mint", env_args!(dec!(500)))
+        .execute()
+        .assert_is_success();
+    assert_eq!(test_engine.total_supply("usd"), Some(dec!(1500)));
+    assert_eq!(test_engine.current_balance("usd"), dec!(1500));
+}
+
+#[test]
+fn test_advance_time_does_not_drift() {
+    let mut test_engine = TestEngine::new();
+
+    let initial_minutes = test_engine.current_time_rounded_to_minutes();
+    let initial_seconds = test_engine.current_time_seconds();
+
+    for _ in 0..60 {
+        test_engine.advance_time(1);
+    }
+
+    let minutes = test_engine.current_time_rounded_to_minutes();
+    let seconds = test_engine.current_time_seconds();
+    assert_eq!(
+        minutes.seconds_since_unix_epoch - initial_minutes.seconds_since_unix_epoch,
+        60
+    );
+    assert_eq!(
+        seconds.seconds_since_unix_epoch - initial_seconds.seconds_since_unix_epoch,
+        60
+    );
+}
+
+#[test]
+fn test_advance_time_by_milliseconds() {
+    let mut test_engine = TestEngine::new();
+
+    let initial_time = test_engine.get_current_time();
+    test_engine.advance_time_by(std::time::Duration::from_millis(600));
+    assert_eq!(test_engine.get_current_time(), initial_time);
+
+    test_engine.advance_time_by(std::time::Duration::from_millis(600));
+    assert_eq!(
+        test_engine.get_current_time(),
+        initial_time.add_seconds(1).unwrap()
+    );
+}
+
+#[test]
+fn test_advance_days_and_hours() {
+    let mut test_engine = TestEngine::new();
+
+    let initial_time = test_engine.get_current_time();
+    test_engine
+        .advance_days(3)
+        .advance_hours(4)
+        .advance_minutes(5);
+    assert_eq!(
+        test_engine.get_current_time(),
+        initial_time
+            .add_seconds(3 * 24 * 3600 + 4 * 3600 + 5 * 60)
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_set_time() {
+    let mut test_engine = TestEngine::new();
+
+    let time = test_engine.get_current_time().add_days(365).unwrap();
+    test_engine.set_time(time);
+    assert_eq!(test_engine.get_current_time(), time);
+    assert_eq!(test_engine.current_time_rounded_to_minutes(), time);
+}
+
+#[test]
+#[should_panic(expected = "Cannot set the time before the current time")]
+fn test_set_time_in_the_past() {
+    let mut test_engine = TestEngine::new();
+
+    let time = test_engine.get_current_time();
+    test_engine.advance_days(1);
+    test_engine.set_time(time);
+}
+
+#[test]
+fn test_fund_account() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Rich");
+
+    test_engine.fund_account("Rich", dec!(100000));
+
+    assert_eq!(test_engine.balance_of("Rich", "XRD"), dec!(110000));
+    assert_eq!(test_engine.current_balance("XRD"), dec!(10000));
+    assert_eq!(
+        test_engine.current_account_address(),
+        test_engine.get_account("default")
+    );
+}
+
+#[test]
+fn test_fund_current_account() {
+    let mut test_engine = TestEngine::new();
+
+    test_engine.fund_account("default", dec!("12.5"));
+
+    assert_eq!(test_engine.current_balance("XRD"), dec!("10012.5"));
+}
+
+#[test]
+#[should_panic(expected = "Cannot fund an account with a non-positive amount of XRD: -1")]
+fn test_fund_account_with_negative_amount() {
+    let mut test_engine = TestEngine::new();
+    test_engine.fund_account("default", dec!(-1));
+}
+
+#[test]
+fn test_advance_time_under_a_minute() {
+    let mut test_engine = TestEngine::new();
+
+    let initial_minutes = test_engine.current_time_rounded_to_minutes();
+    let initial_seconds = test_engine.current_time_seconds();
+    test_engine.advance_time(59);
+
+    // The genesis time is aligned on a minute, so blueprints still see the same minute
+    assert_eq!(
+        test_engine.current_time_rounded_to_minutes(),
+        initial_minutes
+    );
+    let seconds = test_engine.current_time_seconds();
+    assert_eq!(
+        seconds.seconds_since_unix_epoch - initial_seconds.seconds_since_unix_epoch,
+        59
+    );
+}
+
+#[test]
+fn test_reference_collision_warning() {
+    let mut test_engine = TestEngine::new();
+
+    let logger = MemoryLogger::new();
+    test_engine.set_logger(logger.clone());
+
+    test_engine.new_account("treasury");
+    assert!(test_engine.reference_warnings().is_empty());
+
+    test_engine.new_token("treasury", 1000);
+    assert_eq!(test_engine.reference_warnings().len(), 1);
+    assert!(test_engine.reference_warnings()[0].contains("\"resource:treasury\""));
+    assert_eq!(&logger.warnings(), test_engine.reference_warnings());
+
+    test_engine.set_logger(NullLogger);
+    test_engine.new_account("vault");
+    test_engine.new_token("vault", 1000);
+    assert_eq!(test_engine.reference_warnings().len(), 2);
+    assert_eq!(logger.warnings().len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Reference name treasury is already used by account")]
+fn test_reference_collision_strict() {
+    let mut test_engine = TestEngine::new();
+    test_engine.set_strict_references(true);
+
+    test_engine.new_account("treasury");
+    test_engine.new_token("treasury", 1000);
+}
+
+#[test]
+#[should_panic(
+    expected = "There is no component with name dutchh auction. Did you mean dutch auction?"
+)]
+fn test_get_component_suggests_close_name() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_validator("dutch auction");
+
+    test_engine.get_component("dutchh auction");
+}
+
+#[test]
+fn test_try_get_resource() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_token("usdc", 1000);
+
+    assert_eq!(
+        test_engine.try_get_resource("usd"),
+        Ok(test_engine.get_resource("usd"))
+    );
+    assert_eq!(
+        test_engine.try_get_resource("usdx"),
+        Err(ReferenceError::NotFound {
+            kind: Some(EntityKind::Resource),
+            name: "usdx".to_string(),
+            suggestions: vec!["usd".to_string(), "usdc".to_string()],
+        })
+    );
+    assert_eq!(
+        test_engine.try_get_resource("account:usd"),
+        Err(ReferenceError::WrongKind {
+            name: "account:usd".to_string(),
+            expected: EntityKind::Resource,
+            found: EntityKind::Account,
+        })
+    );
+    assert!(test_engine.try_get_account("bob").is_err());
+}
+
+#[test]
+#[should_panic(expected = "Reference name treasury is ambiguous, it matches: account")]
+fn test_ambiguous_global_reference() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("treasury");
+    test_engine.new_token("treasury", 1000);
+
+    test_engine.transfer("treasury", "resource:treasury", 10);
+}
+
+#[test]
+fn test_disambiguated_references() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("treasury");
+    test_engine.new_token("treasury", 1000);
+
+    test_engine
+        .transfer("account:treasury", "resource:treasury", 10)
+        .assert_is_success();
+    assert_eq!(
+        test_engine.balance_of("account:treasury", "resource:treasury"),
+        dec!(10)
+    );
+    assert_eq!(
+        test_engine.get_resource("resource:treasury"),
+        test_engine.get_resource("treasury")
+    );
+    assert_eq!(
+        test_engine.get_account("account:treasury"),
+        test_engine.get_account("treasury")
+    );
+}
+
+#[test]
+fn test_wallet_compatible_layout() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("badge", 1);
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+
+    let xrd_before = test_engine.current_balance("xrd");
+    test_engine
+        .build_call()
+        .transfer("Recipient", "Test token", dec!(10))
+        .with_badge("badge")
+        .wallet_compatible_layout(true)
+        .execute()
+        .assert_is_success();
+
+    assert_eq!(test_engine.balance_of("Recipient", "Test token"), dec!(10));
+    // Fees are locked from the caller account instead of the faucet.
+    assert!(test_engine.current_balance("xrd") < xrd_before);
+}
+
+#[test]
+fn test_compare_layouts() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("badge", 1);
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+
+    let diff = test_engine
+        .build_call()
+        .transfer("Recipient", "Test token", dec!(10))
+        .with_badge("badge")
+        .compare_layouts();
+
+    assert!(!diff.is_empty());
+    assert!(diff.legacy[1].contains("create_proof_of_amount"));
+    assert!(diff.legacy[2].contains("\"withdraw\""));
+
+    // In the wallet layout, the proof is created right before the deposit to the recipient
+    assert!(diff.wallet[1].contains("\"withdraw\""));
+    let proof_index = diff
+        .wallet
+        .iter()
+        .position(|instruction| instruction.contains("create_proof_of_amount"))
+        .unwrap();
+    assert!(diff.wallet[proof_index + 1].contains("try_deposit_or_abort"));
+
+    assert!(diff
+        .moved
+        .iter()
+        .any(|instruction| instruction.contains("create_proof_of_amount")));
+    assert_eq!(diff.legacy_only.len(), 1);
+    assert!(diff.legacy_only[0].contains("lock_fee"));
+    assert_eq!(diff.wallet_only.len(), 1);
+    assert!(diff.wallet_only[0].contains("lock_fee"));
+}
+
+#[test]
+fn test_seed_from_toml() {
+    let mut test_engine = TestEngine::new();
+    let xrd_before = test_engine.current_balance("xrd");
+
+    let summary = test_engine.seed_from_file("tests/general/seed/scenario.toml");
+
+    assert_eq!(summary.accounts, vec!["alice", "bob"]);
+    assert_eq!(summary.tokens, vec!["usd", "governance"]);
+    assert_eq!(summary.transfers.len(), 3);
+    assert!(summary.packages.is_empty());
+    assert!(summary.components.is_empty());
+
+    assert_eq!(test_engine.balance_of("alice", "usd"), dec!("250.5"));
+    assert_eq!(test_engine.balance_of("bob", "usd"), dec!("0.000001"));
+    assert_eq!(test_engine.current_balance("usd"), dec!("999749.499999"));
+    assert_eq!(test_engine.current_balance("governance"), dec!(21000000));
+    assert_eq!(test_engine.current_balance("xrd"), xrd_before - dec!(100));
+}
+
+#[test]
+fn test_seed_from_json() {
+    let mut test_engine = TestEngine::new();
+    test_engine.seed_from_file("tests/general/seed/scenario.json");
+
+    assert_eq!(test_engine.balance_of("carol", "eur"), dec!(12));
+}
+
+#[test]
+#[should_panic(expected = "Invalid seed entry accounts[alice]: unknown resource gbp")]
+fn test_seed_unknown_reference() {
+    let mut test_engine = TestEngine::new();
+    test_engine.seed_from_file("tests/general/seed/unknown_reference.toml");
+}
+
+#[test]
+#[should_panic(expected = "Invalid seed entry tokens[usd]: malformed decimal \"1.000.000\"")]
+fn test_seed_malformed_decimal() {
+    let mut test_engine = TestEngine::new();
+    test_engine.seed_from_file("tests/general/seed/malformed_decimal.toml");
+}
+
+#[test]
+fn test_cost_trend_stable() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+    let recipient = *test_engine.get_account("Recipient");
+
+    let trend = test_engine.cost_trend(
+        recipient,
+        "try_deposit_or_abort",
+        |_| env_args!(Fungible::Bucket("Test token", 1), None::<u64>),
+        10,
+    );
+
+    assert_eq!(trend.costs().len(), 10);
+    trend.assert_cost_stable(10.0);
+    assert_eq!(test_engine.balance_of("Recipient", "Test token"), dec!(10));
+}
+
+#[test]
+#[should_panic(expected = "Execution cost grows by")]
+fn test_cost_trend_growing() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+    let recipient = *test_engine.get_account("Recipient");
+
+    // Each call deposits one more bucket than the previous one.
+    let trend = test_engine.cost_trend(
+        recipient,
+        "try_deposit_batch_or_abort",
+        |iteration| {
+            let buckets: Vec<Box<dyn ToEncode>> = (0..=iteration)
+                .map(|_| Box::new(Fungible::Bucket("Test token", 1)) as Box<dyn ToEncode>)
+                .collect();
+            env_args!(EnvVec::from_vec(buckets), None::<u64>)
+        },
+        5,
+    );
+
+    assert!(trend.slope() > 1000.0);
+    trend.assert_cost_stable(10.0);
+}
+
+#[test]
+fn test_metadata_policy() {
+    let policy = MetadataPolicy::default();
+    let mut metadata = HashMap::new();
+    metadata.insert(
+        "symbol".to_string(),
+        MetadataValue::String("usd".to_string()),
+    );
+    metadata.insert(
+        "icon_url".to_string(),
+        MetadataValue::Url(UncheckedUrl::of("https://example.com/usd.png")),
+    );
+
+    let violations = policy.check(&metadata, &["USD".to_string()]);
+    assert_eq!(violations.len(), 2);
+    assert_eq!(violations[0].key, "name");
+    assert_eq!(violations[0].value, None);
+    assert_eq!(
+        violations[1].to_string(),
+        "symbol = \"usd\": symbol is not uppercase"
+    );
+
+    metadata.insert(
+        "name".to_string(),
+        MetadataValue::String("Dollar".to_string()),
+    );
+    metadata.insert(
+        "symbol".to_string(),
+        MetadataValue::String("USD".to_string()),
+    );
+    let violations = policy.check(&metadata, &["USD".to_string()]);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(
+        violations[0].rule,
+        "symbol is already used by another resource"
+    );
+    assert!(policy.check(&metadata, &[]).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "- name is not set: key is required")]
+fn test_resource_without_metadata_is_not_compliant() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Test token", 1000);
+    test_engine.assert_resource_metadata_compliant("Test token", &MetadataPolicy::default());
+}
+
+#[test]
+fn test_raw_call() {
+    let mut raw_engine = TestEngine::new();
+    let account = *raw_engine.current_account_address();
+    let xrd_before = raw_engine.current_balance("xrd");
+    raw_engine
+        .build_call()
+        .call_from_component("faucet", "lock_fee", env_args!(dec!(10)))
+        .call_from_component("faucet", "free", env_args!())
+        .call_from_component(
+            account,
+            "deposit_batch",
+            env_args!(ManifestExpression::EntireWorktop),
+        )
+        .raw()
+        .execute()
+        .assert_is_success();
+    assert!(raw_engine.last_injection_summary().is_empty());
+
+    let mut engine = TestEngine::new();
+    engine.call_faucet();
+
+    assert_eq!(
+        raw_engine.current_balance("xrd"),
+        engine.current_balance("xrd")
+    );
+    assert_eq!(raw_engine.current_balance("xrd"), xrd_before + dec!(10000));
+}
+
+#[test]
+fn test_raw_call_without_fee_lock_is_rejected() {
+    let mut test_engine = TestEngine::new();
+    let receipt = test_engine
+        .build_call()
+        .call_from_component("faucet", "free", env_args!())
+        .raw()
+        .execute();
+    assert!(receipt.is_rejection());
+    receipt.assert_rejected();
+}
+
+#[test]
+fn test_assert_rejected_with_insufficient_fee_lock() {
+    let mut test_engine = TestEngine::new();
+    test_engine
+        .build_call()
+        .lock_fee("faucet", dec!("0.0000001"))
+        .transfer("default", "XRD", dec!(1))
+        .execute()
+        .assert_rejected_with("CostingError");
+}
+
+#[test]
+#[should_panic(expected = "Expected Reason: IntentHashPreviouslyCommitted")]
+fn test_assert_rejected_with_other_reason() {
+    let mut test_engine = TestEngine::new();
+    test_engine
+        .build_call()
+        .lock_fee("faucet", dec!("0.0000001"))
+        .transfer("default", "XRD", dec!(1))
+        .execute()
+        .assert_rejected_with("IntentHashPreviouslyCommitted");
+}
+
+#[test]
+#[should_panic(expected = "Fee summary: ")]
+fn test_fee_related_rejection_shows_fee_summary() {
+    let mut test_engine = TestEngine::new();
+    test_engine
+        .build_call()
+        .lock_fee("faucet", dec!("0.0000001"))
+        .transfer("default", "XRD", dec!(1))
+        .execute()
+        .assert_failed_with("InsufficientBalance");
+}
+
+#[test]
+#[should_panic(expected = "Transaction was rejected without being committed")]
+fn test_assert_is_success_on_rejection() {
+    let mut test_engine = TestEngine::new();
+    test_engine
+        .build_call()
+        .call_from_component("faucet", "free", env_args!())
+        .raw()
+        .execute()
+        .assert_is_success();
+}
+
+#[test]
+fn test_injection_summary() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("badge", 1);
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+
+    test_engine.transfer("Recipient", "Test token", 1);
+    let summary = test_engine.last_injection_summary();
+    assert_eq!(summary.injected.len(), 2);
+    assert_eq!(summary.injected[0].0, 0);
+    assert!(summary.injected[0].1.contains("lock_fee"));
+    assert!(summary.injected[1].1.contains("deposit_batch"));
+
+    test_engine
+        .build_call()
+        .transfer("Recipient", "Test token", 1)
+        .with_badge("badge")
+        .execute()
+        .assert_is_success();
+    let summary = test_engine.last_injection_summary();
+    assert_eq!(summary.injected.len(), 3);
+    assert_eq!(summary.injected[1].0, 1);
+    assert!(summary.injected[1].1.contains("create_proof_of_amount"));
+}
+
+#[test]
+fn test_get_native_events() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    let receipt = test_engine.transfer("Recipient", "XRD", dec!(10));
+
+    receipt.assert_event_emitted(|event: &radix_engine::blueprints::account::DepositEvent| {
+        *event == radix_engine::blueprints::account::DepositEvent::Fungible(XRD, dec!(10))
+    });
+    assert!(receipt
+        .get_events::<fungible_vault::DepositEvent>()
+        .iter()
+        .any(|event| event.amount == dec!(10)));
+}
+
+#[test]
+#[should_panic(expected = "Transaction failed with")]
+fn test_get_events_of_failed_transaction() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    test_engine
+        .transfer("Recipient", "XRD", dec!(100000))
+        .get_events::<fungible_vault::DepositEvent>();
+}
+
+#[test]
+fn test_ed25519_account() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account_ed25519("Ed25519 account");
+    test_engine.set_current_account("Ed25519 account");
+    assert!(matches!(
+        test_engine.current_account_public_key(),
+        PublicKey::Ed25519(_)
+    ));
+
+    test_engine
+        .transfer("default", "XRD", dec!(10))
+        .assert_is_success();
+    assert_eq!(test_engine.current_balance("XRD"), dec!(9990));
+}
+
+#[test]
+fn test_account_with_keys() {
+    let private_key = PrivateKey::Ed25519(Ed25519PrivateKey::from_u64(42).unwrap());
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account_with_keys("Keyed account", &private_key);
+    test_engine.set_current_account("Keyed account");
+
+    assert_eq!(
+        test_engine.current_account_public_key(),
+        private_key.public_key()
+    );
+    test_engine
+        .transfer("default", "XRD", dec!(10))
+        .assert_is_success();
+}
+
+#[test]
+fn test_additional_signer() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Co-signer");
+
+    test_engine
+        .build_call()
+        .call_from_component("Co-signer", "withdraw", env_args!(XRD, dec!(10)))
+        .execute()
+        .assert_failed_with("Unauthorized");
+
+    test_engine
+        .build_call()
+        .call_from_component("Co-signer", "withdraw", env_args!(XRD, dec!(10)))
+        .with_additional_signer("Co-signer")
+        .execute()
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("Co-signer", "XRD"), dec!(9990));
+    assert_eq!(test_engine.current_balance("XRD"), dec!(10010));
+}
+
+#[test]
+fn test_with_caller() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Other caller");
+
+    test_engine
+        .build_call()
+        .with_caller("Other caller")
+        .transfer("default", "XRD", dec!(10))
+        .execute()
+        .assert_is_success();
+
+    assert_eq!(test_engine.balance_of("Other caller", "XRD"), dec!(9990));
+    assert_eq!(test_engine.current_balance("XRD"), dec!(10010));
+    assert_eq!(
+        test_engine.current_account_address(),
+        test_engine.get_account("default")
+    );
+}
+
+#[test]
+#[should_panic(expected = "The caller must be set before adding instructions to the call")]
+fn test_with_caller_after_instructions() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Other caller");
+    test_engine
+        .build_call()
+        .transfer("Other caller", "XRD", dec!(10))
+        .with_caller("Other caller");
+}
+
+#[derive(ScryptoSbor, ManifestSbor)]
+struct CollectionItem {
+    name: String,
+}
+
+impl NonFungibleData for CollectionItem {
+    const MUTABLE_FIELDS: &'static [&'static str] = &[];
+}
+
+#[test]
+fn test_mint_nfts() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("recipient");
+    test_engine.new_nft_collection::<CollectionItem, _>("collection", NonFungibleIdType::Integer);
+
+    let first = test_engine.mint_nft(
+        "collection",
+        1,
+        CollectionItem {
+            name: "first".to_string(),
+        },
+    );
+    let others = test_engine.mint_nfts(
+        "collection",
+        vec![
+            (
+                2,
+                CollectionItem {
+                    name: "second".to_string(),
+                },
+            ),
+            (
+                3,
+                CollectionItem {
+                    name: "third".to_string(),
+                },
+            ),
+        ],
+    );
+    assert_eq!(first, NonFungibleLocalId::integer(1));
+    assert_eq!(others, nf_ids![2, 3]);
+    let mut balance = test_engine.current_ids_balance("collection");
+    balance.sort();
+    assert_eq!(balance, nf_ids![1, 2, 3]);
+
+    test_engine
+        .transfer_non_fungibles("recipient", "collection", vec![2])
+        .assert_is_success();
+    let mut balance = test_engine.current_ids_balance("collection");
+    balance.sort();
+    assert_eq!(balance, nf_ids![1, 3]);
+    assert_eq!(
+        test_engine.ids_balance_of("recipient", "collection"),
+        nf_ids![2]
+    );
+    assert_eq!(test_engine.total_supply("collection"), Some(dec!(3)));
+    assert_eq!(
+        test_engine.vaults_of("recipient", "collection"),
+        vec![dec!(1)]
+    );
+}
+
+#[test]
+fn test_mint_ruid_nfts() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_nft_collection::<CollectionItem, _>("tickets", NonFungibleIdType::RUID);
+
+    let ids = test_engine.mint_ruid_nfts(
+        "tickets",
+        vec![
+            CollectionItem {
+                name: "first".to_string(),
+            },
+            CollectionItem {
+                name: "second".to_string(),
+            },
+        ],
+    );
+    assert_eq!(ids.len(), 2);
+    assert!(ids.iter().all(|id| id.id_type() == NonFungibleIdType::RUID));
+
+    let mut balance = test_engine.current_ids_balance("tickets");
+    let mut expected = ids.clone();
+    balance.sort();
+    expected.sort();
+    assert_eq!(balance, expected);
+}
+
+#[test]
+fn test_nft_fixture() {
+    let mut test_engine = TestEngine::new();
+    let ids = test_engine
+        .new_nft_fixture::<Ticket, _>("tickets")
+        .with_sequence(1..=150, |i| Ticket {
+            seat: i as u32,
+            holder: format!("holder {}", i),
+        })
+        .chunk_size(40)
+        .mint_to("default");
+
+    assert_eq!(ids.len(), 150);
+    assert_eq!(ids[76], NonFungibleLocalId::integer(77));
+    assert_eq!(test_engine.current_ids_balance("tickets").len(), 150);
+    assert_eq!(test_engine.total_supply("tickets"), Some(dec!(150)));
+    let ticket: Ticket = test_engine.get_non_fungible_data("tickets", 77);
+    assert_eq!(
+        ticket,
+        Ticket {
+            seat: 77,
+            holder: "holder 77".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_nft_fixture_random_ruids() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("recipient");
+    let ids = test_engine
+        .new_nft_fixture::<Ticket, _>("tickets")
+        .random_ruids(120, |i| Ticket {
+            seat: i as u32,
+            holder: "recipient".to_string(),
+        })
+        .mint_to("recipient");
+
+    assert_eq!(ids.len(), 120);
+    assert!(ids.iter().all(|id| id.id_type() == NonFungibleIdType::RUID));
+    let mut balance = test_engine.ids_balance_of("recipient", "tickets");
+    let mut expected = ids.clone();
+    balance.sort();
+    expected.sort();
+    assert_eq!(balance, expected);
+    let ticket: Ticket = test_engine.get_non_fungible_data("tickets", ids[0].clone());
+    assert_eq!(ticket.holder, "recipient");
+}
+
+#[test]
+#[should_panic(expected = "Id 3 was added twice to tickets")]
+fn test_nft_fixture_duplicate_id() {
+    let mut test_engine = TestEngine::new();
+    test_engine
+        .new_nft_fixture::<Ticket, _>("tickets")
+        .with_sequence(1..=5, |i| Ticket {
+            seat: i as u32,
+            holder: String::new(),
+        })
+        .with_sequence(vec![3], |_| Ticket {
+            seat: 0,
+            holder: String::new(),
+        })
+        .mint_to("default");
+}
+
+#[test]
+#[should_panic(expected = "InvalidNonFungibleIdType")]
+fn test_mint_nft_in_ruid_collection() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_nft_collection::<CollectionItem, _>("tickets", NonFungibleIdType::RUID);
+
+    test_engine.mint_nft(
+        "tickets",
+        1,
+        CollectionItem {
+            name: "first".to_string(),
+        },
+    );
+}
+
+#[test]
+fn test_pre_allocated_non_fungible() {
+    let mut test_engine = TestEngine::new();
+
+    let mut node_id = [7u8; 30];
+    node_id[0] = EntityType::GlobalNonFungibleResourceManager as u8;
+    let address = ResourceAddress::new_or_panic(node_id);
+    let encoder = AddressBech32Encoder::new(&NetworkDefinition::stokenet());
+
+    test_engine.new_non_fungible_with_address(
+        "collection",
+        &encoder.encode(address.as_ref()).unwrap(),
+        NetworkDefinition::stokenet(),
+        vec![
+            (
+                NonFungibleLocalId::integer(1),
+                CollectionItem {
+                    name: "First".to_string(),
+                },
+            ),
+            (
+                NonFungibleLocalId::integer(2),
+                CollectionItem {
+                    name: "Second".to_string(),
+                },
+            ),
+        ],
+    );
+
+    assert_eq!(test_engine.get_resource("collection"), address);
+    assert_eq!(test_engine.current_balance("collection"), dec!(2));
+    // Pre-allocated resources do not track their total supply.
+    assert_eq!(test_engine.total_supply("collection"), None);
+}
+
+#[test]
+#[should_panic(expected = "is not a non-fungible resource address")]
+fn test_pre_allocated_non_fungible_with_fungible_address() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_non_fungible_with_address(
+        "collection",
+        "resource_tdx_2_1t5937l2ydfe8rxjsh060r6dlxdwqscjc9dzq2ssrhfv7act63say5g",
+        NetworkDefinition::stokenet(),
+        Vec::<(NonFungibleLocalId, CollectionItem)>::new(),
+    );
+}
+
+#[test]
+fn test_get_return_resources() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    let returned = test_engine
+        .build_call()
+        .call_from_component("faucet", "free", env_args!())
+        .with_execution_trace()
+        .execute()
+        .assert_is_success()
+        .get_return_resources();
+    assert_eq!(returned.fungibles.get(&XRD), Some(&dec!(10000)));
+    assert!(returned.non_fungibles.is_empty());
+
+    test_engine.set_execution_trace(true);
+    let returned = test_engine
+        .transfer("Recipient", "XRD", dec!(10))
+        .get_return_resources();
+    assert!(returned.fungibles.is_empty());
+}
+
+#[test]
+fn test_get_return_resources_of_simple_call() {
+    let mut test_engine = TestEngine::new();
+    let returned = test_engine
+        .call_method_from("faucet", "free", env_args!())
+        .assert_is_success()
+        .get_return_resources();
+    assert_eq!(returned.fungibles.get(&XRD), Some(&dec!(10000)));
+}
+
+#[test]
+#[should_panic(expected = "The transaction was executed without execution trace")]
+fn test_get_return_resources_without_trace() {
+    let mut test_engine = TestEngine::new();
+    test_engine
+        .build_call()
+        .call_from_component("faucet", "free", env_args!())
+        .execute()
+        .get_return_resources();
+}
+
+#[test]
+fn test_assert_failed_with_auth_error() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Other account");
+    test_engine
+        .build_call()
+        .call_from_component("Other account", "withdraw", env_args!(XRD, dec!(10)))
+        .execute()
+        .assert_failed_with_auth_error();
+}
+
+#[test]
+#[should_panic(expected = "Transaction was not rejected but failed with")]
+fn test_assert_rejected_on_failure() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Other account");
+    test_engine
+        .build_call()
+        .call_from_component("Other account", "withdraw", env_args!(XRD, dec!(10)))
+        .execute()
+        .assert_rejected();
+}
+
+#[test]
+#[should_panic(expected = "Transaction did not fail with a panic")]
+fn test_assert_panicked_with_on_auth_error() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Other account");
+    test_engine
+        .build_call()
+        .call_from_component("Other account", "withdraw", env_args!(XRD, dec!(10)))
+        .execute()
+        .assert_panicked_with("Unauthorized");
+}
+
+fn encode_environment(test_engine: &mut TestEngine, argument: &dyn EnvironmentEncode) -> Vec<u8> {
+    let caller = *test_engine.current_account_address();
+    let mut buf = Vec::new();
+    let mut encoder = ManifestEncoder::new(&mut buf, MANIFEST_SBOR_V1_MAX_DEPTH);
+    encoder
+        .write_payload_prefix(MANIFEST_SBOR_V1_PAYLOAD_PREFIX)
+        .unwrap();
+    argument.encode(test_engine, ManifestBuilder::new(), &mut encoder, caller);
+    buf
+}
+
+#[test]
+fn test_env_map() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_token("btc", 10);
+    let usd = test_engine.get_resource("usd");
+    let btc = test_engine.get_resource("btc");
+
+    let encoded = encode_environment(
+        &mut test_engine,
+        &env_map!(
+            Environment::Resource("usd") => dec!("0.6"),
+            Environment::Resource("btc") => dec!("0.4")
+        ),
+    );
+
+    let mut expected: IndexMap<ResourceAddress, Decimal> = IndexMap::new();
+    expected.insert(usd, dec!("0.6"));
+    expected.insert(btc, dec!("0.4"));
+    assert_eq!(encoded, manifest_encode(&expected).unwrap());
+}
+
+#[test]
+fn test_empty_env_map() {
+    let mut test_engine = TestEngine::new();
+    let encoded = encode_environment(
+        &mut test_engine,
+        &EnvMap::empty::<ResourceAddress, Decimal>(),
+    );
+
+    let expected: IndexMap<ResourceAddress, Decimal> = IndexMap::new();
+    assert_eq!(encoded, manifest_encode(&expected).unwrap());
+}
+
+#[test]
+#[should_panic(expected = "Cannot infer the key and value kinds of an empty map")]
+fn test_untyped_empty_env_map() {
+    let mut test_engine = TestEngine::new();
+    encode_environment(&mut test_engine, &EnvMap::from_vec(vec![]));
+}
+
+#[test]
+fn test_env_tuple() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    let usd = test_engine.get_resource("usd");
+
+    let encoded = encode_environment(
+        &mut test_engine,
+        &env_tuple!(Environment::Resource("usd"), dec!(5), "label".to_string()),
+    );
+    assert_eq!(
+        encoded,
+        manifest_encode(&(usd, dec!(5), "label".to_string())).unwrap()
+    );
+}
+
+#[test]
+fn test_env_tuple_in_env_vec() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_token("btc", 10);
+    let usd = test_engine.get_resource("usd");
+    let btc = test_engine.get_resource("btc");
+
+    let encoded = encode_environment(
+        &mut test_engine,
+        &env_vec!(
+            env_tuple!(Environment::Resource("usd"), dec!(1)),
+            env_tuple!(Environment::Resource("btc"), dec!(2))
+        ),
+    );
+    assert_eq!(
+        encoded,
+        manifest_encode(&vec![(usd, dec!(1)), (btc, dec!(2))]).unwrap()
+    );
+}
+
+#[test]
+fn test_environment_buckets_in_env_vec() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    let usd = test_engine.get_resource("usd");
+
+    let encoded = encode_environment(
+        &mut test_engine,
+        &env_vec!(
+            env_tuple!(
+                Environment::Resource("usd"),
+                Environment::FungibleBucket("usd", dec!(10)),
+                dec!(1)
+            ),
+            env_tuple!(
+                Environment::Resource("usd"),
+                Fungible::Bucket("usd", dec!(5)),
+                dec!(2)
+            )
+        ),
+    );
+    assert_eq!(
+        encoded,
+        manifest_encode(&vec![
+            (usd, ManifestBucket(0), dec!(1)),
+            (usd, ManifestBucket(1), dec!(2))
+        ])
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_environment_fungible_bucket() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    test_engine
+        .call_method_from(
+            "Recipient",
+            "try_deposit_batch_or_abort",
+            env_args!(
+                env_vec!(
+                    Environment::FungibleBucket("XRD", dec!(10)),
+                    Fungible::Bucket("XRD", dec!(5))
+                ),
+                none!()
+            ),
+        )
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("Recipient", "XRD"), dec!(10015));
+}
+
+#[test]
+fn test_fee_breakdown() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    let receipt = test_engine.transfer("Recipient", "XRD", dec!(10));
+    let breakdown = receipt.fee_breakdown();
+    assert!(breakdown.execution.is_positive());
+    assert!(!breakdown.execution_cost_units.is_empty());
+    assert_eq!(breakdown.total(), receipt.execution_cost());
+}
+
+#[test]
+fn test_fee_budget() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    test_engine
+        .build_call()
+        .transfer("Recipient", "XRD", dec!(10))
+        .with_fee_budget(dec!(10))
+        .execute()
+        .assert_is_success();
+}
+
+#[test]
+fn test_fee_budget_exceeded() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        test_engine
+            .build_call()
+            .transfer("Recipient", "XRD", dec!(10))
+            .with_fee_budget(dec!("0.001"))
+            .execute();
+    }));
+    let error = result.unwrap_err();
+    let message = error.downcast_ref::<String>().unwrap();
+
+    assert!(message.contains("exceeds the fee budget of 0.001 XRD"));
+    let cost = message
+        .strip_prefix("Transaction cost ")
+        .and_then(|rest| rest.split(' ').next())
+        .unwrap();
+    assert!(Decimal::try_from(cost).unwrap() > dec!("0.001"));
+}
+
+#[test]
+fn test_fixed_owner_role_cannot_be_updated() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Badge", 1);
+
+    let rule = test_engine.require_badge("Badge");
+    test_engine
+        .build_call()
+        .set_owner_role("default", rule)
+        .execute()
+        .assert_failed_with_auth_error();
+}
+
+#[test]
+fn test_new_validator() {
+    let mut test_engine = TestEngine::new();
+
+    let validator = test_engine.new_validator("validator");
+    assert_eq!(test_engine.get_component("validator"), validator);
+    assert_eq!(
+        test_engine.current_balance("validator owner badge"),
+        Decimal::ONE
+    );
+    assert_eq!(
+        test_engine.current_balance("XRD"),
+        dec!(10000) - *DEFAULT_VALIDATOR_XRD_COST
+    );
+    assert_ne!(
+        test_engine.get_resource("validator lsu"),
+        test_engine.get_resource("validator claim nft")
+    );
+}
+
+#[test]
+fn test_stake_unstake_and_claim() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_validator("validator");
+    test_engine.new_account("Staker");
+    test_engine.set_current_account("Staker");
+
+    let lsu = test_engine.stake("validator", dec!(1000));
+    assert_eq!(lsu, test_engine.get_resource("validator lsu"));
+    assert_eq!(test_engine.current_balance("validator lsu"), dec!(1000));
+
+    test_engine
+        .unstake("validator", dec!(1000))
+        .assert_is_success();
+    assert_eq!(test_engine.current_balance("validator lsu"), Decimal::ZERO);
+    assert_eq!(
+        test_engine.current_balance("validator claim nft"),
+        Decimal::ONE
+    );
+
+    test_engine.jump_epochs(1);
+    test_engine.claim_xrd("validator").assert_is_success();
+    assert_eq!(test_engine.current_balance("XRD"), dec!(10000));
+}
+
+#[test]
+fn test_staking_emissions() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_validator("validator");
+    test_engine.stake("validator", dec!(1000));
+
+    let redemption_value = |test_engine: &mut TestEngine| -> Decimal {
+        test_engine
+            .call_method_from("validator", "get_redemption_value", env_args!(dec!(1000)))
+            .get_return()
+    };
+    assert_eq!(redemption_value(&mut test_engine), dec!(1000));
+
+    test_engine.advance_epochs(5);
+    assert!(redemption_value(&mut test_engine) > dec!(1000));
+}
+
+#[test]
+fn test_two_resource_pool() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_token("btc", 100);
+    test_engine.new_token("pool badge", 1);
+
+    let pool = test_engine.new_two_resource_pool("pool", "usd", "btc", "pool badge");
+    assert_eq!(test_engine.get_component("pool"), pool);
+
+    test_engine
+        .contribute(
+            "pool",
+            Fungible::Bucket("usd", dec!(100)),
+            Fungible::Bucket("btc", dec!(10)),
+        )
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("pool", "usd"), dec!(100));
+    assert_eq!(test_engine.balance_of("pool", "btc"), dec!(10));
+    let units = test_engine.current_balance("pool unit");
+    assert!(units > Decimal::ZERO);
+
+    test_engine
+        .redeem("pool", Fungible::Bucket("pool unit", units / 2))
+        .assert_is_success();
+    assert_eq!(test_engine.current_balance("usd"), dec!(950));
+    assert_eq!(test_engine.current_balance("btc"), dec!(95));
+    assert_eq!(test_engine.balance_of("pool", "usd"), dec!(50));
+}
+
+#[test]
+#[should_panic(expected = "is not a pool created by the engine")]
+fn test_contribute_to_unknown_pool() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.contribute(
+        "default",
+        Fungible::Bucket("usd", dec!(100)),
+        Fungible::Bucket("XRD", dec!(10)),
+    );
+}
+
+#[test]
+fn test_account_locker() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_token("locker badge", 1);
+    test_engine.new_account("claimant");
+
+    let locker = test_engine.new_account_locker("locker", "locker badge");
+    assert_eq!(test_engine.get_component("locker"), locker);
+
+    let claimant = *test_engine.get_account("claimant");
+    test_engine
+        .build_call()
+        .call_from_component(
+            "locker",
+            ACCOUNT_LOCKER_STORE_IDENT,
+            env_args!(claimant, Fungible::Bucket("usd", dec!(100)), false),
+        )
+        .with_badge("locker badge")
+        .execute()
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("locker", "usd"), dec!(100));
+}
+
+#[test]
+fn test_balance_changes() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    let receipt = test_engine
+        .build_call()
+        .transfer("Recipient", "XRD", dec!(10))
+        .execute_expect_balance_change("Recipient", "XRD", dec!(10));
+    test_engine.assert_balance_change(
+        &receipt,
+        "default",
+        "XRD",
+        BalanceChange::Fungible(dec!(-10)),
+    );
+
+    let changes = test_engine.balance_changes_of(&receipt, "Recipient");
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes.get(&XRD), Some(&BalanceChange::Fungible(dec!(10))));
+
+    let recipient = *test_engine.get_account("Recipient");
+    assert_eq!(receipt.balance_changes_of(recipient), changes);
+}
+
+#[test]
+#[should_panic(expected = "The transaction was executed without execution trace")]
+fn test_balance_changes_without_trace() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    let receipt = test_engine
+        .build_call()
+        .transfer("Recipient", "XRD", dec!(10))
+        .execute();
+    test_engine.balance_changes_of(&receipt, "Recipient");
+}
+
+#[test]
+fn test_non_fungible_balance_changes() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    let mut node_id = [8u8; 30];
+    node_id[0] = EntityType::GlobalNonFungibleResourceManager as u8;
+    let encoder = AddressBech32Encoder::new(&NetworkDefinition::simulator());
+    test_engine.new_non_fungible_with_address(
+        "collection",
+        &encoder
+            .encode(ResourceAddress::new_or_panic(node_id).as_ref())
+            .unwrap(),
+        NetworkDefinition::simulator(),
+        vec![
+            (
+                NonFungibleLocalId::integer(1),
+                CollectionItem {
+                    name: "First".to_string(),
+                },
+            ),
+            (
+                NonFungibleLocalId::integer(2),
+                CollectionItem {
+                    name: "Second".to_string(),
+                },
+            ),
+        ],
+    );
+
+    let receipt = test_engine
+        .build_call()
+        .transfer_non_fungibles("Recipient", "collection", vec![1])
+        .execute_expect_non_fungible_change("default", "collection", vec![], vec![1]);
+    test_engine.assert_balance_change(
+        &receipt,
+        "Recipient",
+        "collection",
+        BalanceChange::NonFungible {
+            added: [NonFungibleLocalId::integer(1)].into(),
+            removed: [].into(),
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "Expected Change: 11")]
+fn test_unexpected_balance_change() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    test_engine
+        .build_call()
+        .transfer("Recipient", "XRD", dec!(10))
+        .execute_expect_balance_change("Recipient", "XRD", dec!(11));
+}
+
+fn new_package_sources(name: &str) -> std::path::PathBuf {
+    let package_dir = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&package_dir);
+    std::fs::create_dir_all(package_dir.join("src")).unwrap();
+    std::fs::write(
+        package_dir.join("Cargo.toml"),
+        "[package]\nname = \"cached\"",
+    )
+    .unwrap();
+    std::fs::write(package_dir.join("src/lib.rs"), "// blueprint").unwrap();
+    package_dir
+}
+
+#[test]
+fn test_package_source_hash() {
+    let package_dir = new_package_sources("source-hash");
+    let initial_hash = CompiledPackages::source_hash(&package_dir);
+
+    std::fs::write(package_dir.join("README.md"), "not a source").unwrap();
+    assert_eq!(CompiledPackages::source_hash(&package_dir), initial_hash);
+
+    std::fs::write(package_dir.join("src/lib.rs"), "// updated blueprint").unwrap();
+    let src_hash = CompiledPackages::source_hash(&package_dir);
+    assert_ne!(src_hash, initial_hash);
+
+    std::fs::write(
+        package_dir.join("Cargo.toml"),
+        "[package]\nname = \"renamed\"",
+    )
+    .unwrap();
+    assert_ne!(CompiledPackages::source_hash(&package_dir), src_hash);
+
+    std::fs::remove_dir_all(package_dir).unwrap();
+}
+
+#[test]
+fn test_compiled_package_loaded_from_cache() {
+    let package_dir = new_package_sources("cached-package");
+    let cache_path = CompiledPackages::cache_path(&package_dir);
+    let definition = PackageDefinition {
+        blueprints: index_map_new(),
+    };
+    std::fs::create_dir_all(&cache_path).unwrap();
+    std::fs::write(cache_path.join("code.wasm"), [0, 97, 115, 109]).unwrap();
+    std::fs::write(
+        cache_path.join("definition.rpd"),
+        manifest_encode(&definition).unwrap(),
+    )
+    .unwrap();
+
+    // The sources are not a valid package, so this only succeeds if compilation is skipped.
+    let (code, cached_definition) = CompiledPackages::get(&package_dir);
+    assert_eq!(code, vec![0, 97, 115, 109]);
+    assert_eq!(cached_definition, definition);
+
+    std::fs::remove_dir_all(cache_path).unwrap();
+    std::fs::remove_dir_all(package_dir).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Could not decode the state of component")]
+fn test_set_component_state_with_wrong_type() {
+    let mut test_engine = TestEngine::new();
+    test_engine.set_component_state("default", |_: &mut Decimal| {});
+}
+
+fn output_transfer_manifest(seed: u64, directory: &str, with_reference_names: bool) -> String {
+    let mut test_engine = TestEngine::new_deterministic(seed);
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+
+    let output_dir = std::env::temp_dir().join(format!("{}-{}", directory, std::process::id()));
+    let output_dir = output_dir.to_str().unwrap();
+    let call = test_engine
+        .build_call()
+        .transfer("Recipient", "Test token", dec!(10));
+    let call = if with_reference_names {
+        call.output_with_reference_names(output_dir, "transfer")
+    } else {
+        call.output(output_dir, "transfer")
+    };
+    call.execute().assert_is_success();
+
+    let manifest = std::fs::read_to_string(format!("{}/transfer.rtm", output_dir)).unwrap();
+    std::fs::remove_dir_all(output_dir).unwrap();
+    manifest
+}
+
+#[test]
+fn test_deterministic_engines_output_identical_manifests() {
+    let first_manifest = output_transfer_manifest(42, "deterministic-1", false);
+    let second_manifest = output_transfer_manifest(42, "deterministic-2", false);
+    assert_eq!(first_manifest, second_manifest);
+
+    let other_seed_manifest = output_transfer_manifest(7, "deterministic-3", false);
+    assert_ne!(first_manifest, other_seed_manifest);
+}
+
+#[test]
+fn test_output_with_reference_names() {
+    let manifest = output_transfer_manifest(42, "reference-names", true);
+    assert!(manifest.contains("Address(\"{account:default}\")"));
+    assert!(manifest.contains("Address(\"{account:recipient}\")"));
+    assert!(manifest.contains("Address(\"{resource:testtoken}\")"));
+    assert!(!manifest.contains("account_sim"));
+    assert_eq!(
+        manifest,
+        output_transfer_manifest(7, "reference-names-other-seed", true)
+    );
+}
+
+#[test]
+fn test_assert_worktop_contains() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    test_engine
+        .build_call()
+        .withdraw("XRD", dec!(10))
+        .assert_worktop_contains("XRD", 10)
+        .take_from_worktop_named("XRD", 10, "xrd bucket")
+        .call_from_component(
+            "Recipient",
+            "try_deposit_or_abort",
+            env_args!(Environment::Bucket("xrd bucket"), none!()),
+        )
+        .execute()
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("Recipient", "XRD"), dec!(10010));
+}
+
+#[test]
+fn test_assert_worktop_contains_too_much() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    test_engine
+        .build_call()
+        .withdraw("XRD", dec!(10))
+        .assert_worktop_contains("XRD", 11)
+        .take_from_worktop_named("XRD", 10, "xrd bucket")
+        .call_from_component(
+            "Recipient",
+            "try_deposit_or_abort",
+            env_args!(Environment::Bucket("xrd bucket"), none!()),
+        )
+        .execute()
+        .assert_failed_with("AssertionFailed");
+    assert_eq!(test_engine.balance_of("Recipient", "XRD"), dec!(10000));
+}
+
+#[test]
+fn test_assert_worktop_contains_non_fungibles() {
+    let mut test_engine = TestEngine::new();
+    let mut node_id = [9u8; 30];
+    node_id[0] = EntityType::GlobalNonFungibleResourceManager as u8;
+    let encoder = AddressBech32Encoder::new(&NetworkDefinition::simulator());
+    test_engine.new_non_fungible_with_address(
+        "collection",
+        &encoder
+            .encode(ResourceAddress::new_or_panic(node_id).as_ref())
+            .unwrap(),
+        NetworkDefinition::simulator(),
+        vec![(
+            NonFungibleLocalId::integer(1),
+            CollectionItem {
+                name: "First".to_string(),
+            },
+        )],
+    );
+
+    test_engine
+        .build_call()
+        .call_from_component(
+            "default",
+            "withdraw_non_fungibles",
+            env_args!(Environment::Resource("collection"), nf_ids![1]),
+        )
+        .assert_worktop_contains_non_fungibles("collection", vec![1])
+        .execute()
+        .assert_is_success();
+    test_engine
+        .build_call()
+        .call_from_component(
+            "default",
+            "withdraw_non_fungibles",
+            env_args!(Environment::Resource("collection"), nf_ids![1]),
+        )
+        .assert_worktop_contains_non_fungibles("collection", vec![2])
+        .execute()
+        .assert_failed_with("AssertionFailed");
+}
+
+fn record_validator_scenario(name: &str) -> std::path::PathBuf {
+    let recording_dir = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&recording_dir);
+
+    let mut test_engine = TestEngine::new();
+    test_engine.enable_recording(&recording_dir);
+    test_engine.new_validator("validator");
+    test_engine.stake("validator", dec!(1000));
+    test_engine
+        .transfer("default", "XRD", dec!(100000))
+        .assert_failed_with("InsufficientBalance");
+    recording_dir
+}
+
+#[test]
+fn test_recording_index() {
+    let recording_dir = record_validator_scenario("recording-index");
+
+    let index = std::fs::read_to_string(recording_dir.join("index.json")).unwrap();
+    for step in ["0001.rtm", "0002.rtm", "0003.rtm", "0004.rtm"] {
+        assert!(index.contains(step));
+        assert!(recording_dir.join(step).exists());
+    }
+    assert!(!recording_dir.join("0005.rtm").exists());
+    assert!(index.contains("\"status\": \"Failure\""));
+    assert!(index.contains("{account:default}"));
+
+    std::fs::remove_dir_all(recording_dir).unwrap();
+}
+
+#[test]
+fn test_replay() {
+    let recording_dir = record_validator_scenario("replay");
+
+    let mut replayed = TestEngine::replay(&recording_dir).unwrap();
+    assert_eq!(replayed.current_balance("Liquid Stake Units"), dec!(1000));
+
+    // Creating an account first changes the addresses of the entities created by the replay.
+    let mut shifted = TestEngine::new();
+    shifted.new_account("other");
+    shifted.replay_recording(&recording_dir).unwrap();
+    assert_eq!(shifted.current_balance("Liquid Stake Units"), dec!(1000));
+    assert_ne!(
+        shifted.get_resource("Liquid Stake Units"),
+        replayed.get_resource("Liquid Stake Units")
+    );
+
+    std::fs::remove_dir_all(recording_dir).unwrap();
+}
+
+#[test]
+fn test_replay_divergence() {
+    let recording_dir = record_validator_scenario("replay-divergence");
+
+    let mut test_engine = TestEngine::new();
+    test_engine.fund_account("default", dec!(200000));
+    let divergence = test_engine.replay_recording(&recording_dir).unwrap_err();
+    assert_eq!(divergence.step, 4);
+    assert_eq!(divergence.expected, RecordedStatus::Failure);
+    assert_eq!(divergence.actual, RecordedStatus::Success);
+
+    std::fs::remove_dir_all(recording_dir).unwrap();
+}
+
+#[test]
+fn test_builder_starting_epoch() {
+    let start = Epoch::of(u32::MAX as u64 - 1);
+    let mut test_engine = TestEngineBuilder::new().starting_epoch(start).build();
+    assert_eq!(test_engine.current_epoch(), start);
+
+    test_engine.advance_epochs(2);
+    assert_eq!(test_engine.current_epoch(), start.after(2).unwrap());
+
+    test_engine.new_account("Recipient");
+    test_engine
+        .transfer("Recipient", "XRD", dec!(10))
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("Recipient", "XRD"), dec!(10010));
+}
+
+#[test]
+fn test_builder_validator_count() {
+    let mut test_engine = TestEngineBuilder::new().validator_count(3).build();
+    assert_eq!(test_engine.active_validators().len(), 3);
+    assert_eq!(
+        test_engine.current_epoch(),
+        TestEngine::new().current_epoch()
+    );
+
+    test_engine.advance_epochs(1);
+    assert_eq!(test_engine.active_validators().len(), 3);
+}
+
+#[test]
+fn test_self_funded_engine() {
+    let mut test_engine = TestEngineBuilder::new().self_funded().build();
+    assert!(test_engine.is_self_funded());
+    assert_eq!(
+        test_engine.current_balance("XRD"),
+        TestEngine::new().current_balance("XRD") + SELF_FUNDED_XRD
+    );
+
+    test_engine.new_account("Recipient");
+    let faucet_balance = test_engine.balance_of("faucet", "XRD");
+    let balance = test_engine.current_balance("XRD");
+    let receipt = test_engine.transfer("Recipient", "XRD", dec!(10));
+    let fee = receipt.fee_summary.total_cost();
+    receipt.assert_is_success();
+
+    assert_eq!(test_engine.current_balance("XRD"), balance - dec!(10) - fee);
+    assert_eq!(test_engine.balance_of("faucet", "XRD"), faucet_balance);
+}
+
+#[test]
+fn test_memory_logger() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("receiver");
+    let logger = MemoryLogger::new();
+    test_engine.set_logger(logger.clone());
+
+    test_engine.transfer("receiver", "XRD", dec!(10));
+    assert!(logger.titles().is_empty());
+    assert!(logger.fee_summaries().is_empty());
+
+    test_engine
+        .build_call()
+        .call_from_component(
+            "receiver",
+            "try_deposit_or_abort",
+            env_args!(Fungible::Bucket("XRD", dec!(10)), none!()),
+        )
+        .with_log_title("Deposit")
+        .with_log_tx_fee()
+        .execute()
+        .assert_is_success();
+    assert_eq!(logger.titles(), vec!["Deposit".to_string()]);
+    assert_eq!(logger.fee_summaries().len(), 1);
+    assert!(logger.fee_summaries()[0].total_cost() > Decimal::ZERO);
+    assert!(logger.application_logs().is_empty());
+
+    logger.clear();
+    assert!(logger.titles().is_empty());
+
+    test_engine.set_logger(NullLogger);
+    test_engine
+        .build_call()
+        .call_from_component(
+            "receiver",
+            "try_deposit_or_abort",
+            env_args!(Fungible::Bucket("XRD", dec!(10)), none!()),
+        )
+        .with_log_title("Ignored")
+        .execute()
+        .assert_is_success();
+    assert!(logger.titles().is_empty());
+}
+
+#[test]
+fn test_fees_paid_by() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("user");
+    test_engine.set_current_account("user");
+
+    let receipt = test_engine
+        .build_call()
+        .lock_fee("user", 10)
+        .transfer("default", "XRD", dec!(1))
+        .execute();
+    let fee = receipt.fee_summary.total_cost();
+    receipt.assert_is_success();
+
+    assert_eq!(test_engine.fees_paid_by("user"), fee);
+    assert_eq!(test_engine.fees_paid_by("faucet"), dec!(0));
+    assert_eq!(test_engine.total_fees_paid(), fee);
+
+    let receipt = test_engine.transfer("default", "XRD", dec!(1));
+    let faucet_fee = receipt.fee_summary.total_cost();
+    assert_eq!(test_engine.fees_paid_by("user"), fee);
+    assert_eq!(test_engine.fees_paid_by("faucet"), faucet_fee);
+    assert_eq!(test_engine.total_fees_paid(), fee + faucet_fee);
+
+    test_engine.reset_fee_tracking();
+    assert_eq!(test_engine.fees_paid_by("user"), dec!(0));
+    assert_eq!(test_engine.total_fees_paid(), dec!(0));
+}
+
+#[test]
+fn test_default_engine_pays_fees_with_faucet() {
+    let mut test_engine = TestEngine::new();
+    assert!(!test_engine.is_self_funded());
+
+    test_engine.new_account("Recipient");
+    let balance = test_engine.current_balance("XRD");
+    test_engine
+        .transfer("Recipient", "XRD", dec!(10))
+        .assert_is_success();
+    assert_eq!(test_engine.current_balance("XRD"), balance - dec!(10));
+}
+
+#[test]
+fn test_new_with_genesis() {
+    let mut config = CustomGenesis::default_consensus_manager_config();
+    config.epoch_change_condition.min_round_count = 10;
+    config.epoch_change_condition.max_round_count = 10;
+    let mut test_engine =
+        TestEngine::new_with_genesis(CustomGenesis::default(Epoch::of(42), config));
+    assert_eq!(test_engine.current_epoch(), Epoch::of(43));
+    assert_eq!(test_engine.active_validators().len(), 1);
+
+    test_engine.advance_epochs(1);
+    assert_eq!(test_engine.current_epoch(), Epoch::of(44));
+}
+
+#[test]
+#[should_panic(expected = "The genesis needs at least one validator")]
+fn test_builder_without_validators() {
+    TestEngineBuilder::new().validator_count(0);
+}
+
+#[test]
+#[should_panic(expected = "The engine cannot start before epoch 1")]
+fn test_builder_starting_at_epoch_zero() {
+    TestEngineBuilder::new().starting_epoch(Epoch::zero());
+}
+
+#[test]
+fn test_snapshot_and_restore() {
+    let mut test_engine = TestEngine::new();
+    let validator = test_engine.new_validator("validator 1");
+    test_engine.new_account("alice");
+    let snapshot = test_engine.snapshot();
+    let epoch = test_engine.current_epoch();
+    let time = test_engine.get_current_time();
+    let xrd_balance = test_engine.current_balance("xrd");
+    let alice_balance = test_engine.balance_of("alice", "xrd");
+    let total_fees_paid = test_engine.total_fees_paid();
+    let last_created_components = test_engine.last_created_components().clone();
+
+    test_engine
+        .transfer("alice", "xrd", 100)
+        .assert_is_success();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_token("badge", 1);
+    test_engine.new_two_resource_pool("pool", "xrd", "usd", "badge");
+    test_engine
+        .build_call()
+        .with_caller("alice")
+        .lock_fee("alice", 10)
+        .transfer("default", "xrd", 1)
+        .execute()
+        .assert_is_success();
+    assert!(test_engine.fees_paid_by("alice") > Decimal::ZERO);
+    test_engine.new_validator("validator 2");
+    test_engine.set_current_account("alice");
+    test_engine.next_epoch();
+    test_engine.advance_time(3600);
+
+    test_engine.restore(&snapshot);
+
+    assert_eq!(test_engine.get_component("validator 1"), validator);
+    assert!(test_engine.try_get_component("validator 2").is_err());
+    assert!(test_engine.try_get_resource("usd").is_err());
+    assert_eq!(test_engine.current_balance("xrd"), xrd_balance);
+    assert_eq!(test_engine.balance_of("alice", "xrd"), alice_balance);
+    assert_eq!(test_engine.current_epoch(), epoch);
+    assert_eq!(test_engine.get_current_time(), time);
+    assert!(test_engine.try_get_component("pool").is_err());
+    assert_eq!(test_engine.total_fees_paid(), total_fees_paid);
+    assert_eq!(test_engine.fees_paid_by("alice"), Decimal::ZERO);
+    assert_eq!(
+        test_engine.last_created_components(),
+        &last_created_components
+    );
+
+    // The snapshot can be restored again after new entities have been created.
+    test_engine.new_validator("validator 2");
+    test_engine.restore(&snapshot);
+    assert!(test_engine.try_get_component("validator 2").is_err());
+}
+
+#[test]
+fn test_fork() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_account("alice");
+    let mut fork = test_engine.fork();
+
+    fork.transfer("alice", "usd", 100).assert_is_success();
+    fork.new_token("btc", 10);
+
+    assert_eq!(fork.balance_of("alice", "usd"), dec!(100));
+    assert_eq!(test_engine.balance_of("alice", "usd"), dec!(0));
+    assert_eq!(test_engine.current_balance("usd"), dec!(1000));
+    assert!(test_engine.try_get_resource("btc").is_err());
+
+    // The original engine keeps working independently of its fork.
+    test_engine.transfer("alice", "usd", 5).assert_is_success();
+    assert_eq!(test_engine.balance_of("alice", "usd"), dec!(5));
+    assert_eq!(fork.balance_of("alice", "usd"), dec!(100));
+}
+
+#[cfg(feature = "proptest")]
+#[test]
+fn test_strategies() {
+    use proptest::strategy::{Strategy, ValueTree};
+    use proptest::test_runner::TestRunner;
+
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    let mut runner = TestRunner::deterministic();
+
+    let decimals = decimal_in(dec!("0.5")..=dec!(2));
+    for _ in 0..100 {
+        let value = decimals.new_tree(&mut runner).unwrap().current();
+        assert!(value >= dec!("0.5") && value <= dec!(2));
+    }
+
+    let resource = resource_among(&test_engine, vec!["usd"])
+        .new_tree(&mut runner)
+        .unwrap()
+        .current();
+    assert_eq!(resource, test_engine.get_resource("usd"));
+
+    for id_type in [
+        NonFungibleIdType::Integer,
+        NonFungibleIdType::String,
+        NonFungibleIdType::Bytes,
+        NonFungibleIdType::RUID,
+    ] {
+        let id = non_fungible_local_id(id_type)
+            .new_tree(&mut runner)
+            .unwrap()
+            .current();
+        assert_eq!(id.id_type(), id_type);
+    }
+
+    let calls =
+        MethodCallStrategy::new().method("free", decimal_in(dec!(1)..=dec!(2)), |_| env_args!());
+    let call = calls.new_tree(&mut runner).unwrap().current();
+    assert_eq!(call.method_name(), "free");
+    let balance = test_engine.current_balance("xrd");
+    call.call_from(&mut test_engine, "faucet")
+        .assert_is_success();
+    assert_eq!(test_engine.current_balance("xrd"), balance + dec!(10000));
+}
+
+#[test]
+fn test_nf_ids_from_strings() {
+    assert_eq!(
+        nf_ids!["#1#", "<SomeId>", "[0x0a]", "[0a]"],
+        vec![
+            NonFungibleLocalId::integer(1),
+            NonFungibleLocalId::string("SomeId").unwrap(),
+            NonFungibleLocalId::bytes(vec![10]).unwrap(),
+            NonFungibleLocalId::bytes(vec![10]).unwrap(),
+        ]
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "Invalid RUID non-fungible id {0a1b}: 4 hex characters instead of 64. Expected 64 hex characters between {}"
+)]
+fn test_nf_ids_with_malformed_ruid() {
+    let _ids: Vec<NonFungibleLocalId> = nf_ids![1, "{0a1b}"];
+}
+
+#[test]
+fn test_vault_ids_of_account() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_account("alice");
+    test_engine
+        .transfer("alice", "usd", 250)
+        .assert_is_success();
+
+    let vaults = test_engine.vault_ids_of("alice", "usd");
+    assert_eq!(vaults.len(), 1);
+    assert_eq!(test_engine.fungible_vault_balance(vaults[0]), dec!(250));
+    assert_eq!(
+        test_engine.internal_vault_balance("alice", "usd"),
+        dec!(250)
+    );
+    assert!(test_engine.vault_ids_of("alice", "xrd")[0] != vaults[0]);
+}
+
+#[test]
+#[should_panic(expected = "There is no non-fungible vault with id")]
+fn test_non_fungible_balance_of_fungible_vault() {
+    let mut test_engine = TestEngine::new();
+    let vault = test_engine.vault_ids_of("default", "xrd")[0];
+    test_engine.non_fungible_vault_balance(vault);
+}
+
+#[test]
+fn test_env_option_arguments() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    test_engine
+        .call_method_from(
+            "Recipient",
+            "try_deposit_or_abort",
+            env_args!(
+                Fungible::Bucket("XRD", dec!(10)),
+                env_some!(ResourceOrNonFungible::Resource(XRD))
+            ),
+        )
+        .assert_is_success();
+    test_engine
+        .call_method_from(
+            "Recipient",
+            "try_deposit_or_abort",
+            env_args!(
+                Fungible::Bucket("XRD", dec!(5)),
+                EnvOption::from(None::<Environment<&str>>)
+            ),
+        )
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("Recipient", "XRD"), dec!(10015));
+}
+
+fn method_names(instructions: &[InstructionV1]) -> Vec<String> {
+    instructions
+        .iter()
+        .map(|instruction| match instruction {
+            InstructionV1::CallMethod { method_name, .. } => method_name.clone(),
+            other => format!("{:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn test_preview_call() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("badge", 1);
+    let balance = test_engine.current_balance("xrd");
+    let mut call = test_engine
+        .build_call()
+        .call_from_component("faucet", "free", env_args!())
+        .with_badge("badge");
+
+    assert_eq!(
+        method_names(&call.instructions()),
+        vec![
+            "lock_fee",
+            "create_proof_of_amount",
+            "free",
+            "deposit_batch"
+        ]
+    );
+    let preview = call.preview();
+    let lock_fee = preview.find("\"lock_fee\"").unwrap();
+    let proof = preview.find("\"create_proof_of_amount\"").unwrap();
+    let free = preview.find("\"free\"").unwrap();
+    assert!(lock_fee < proof && proof < free);
+
+    // The previewed call can still be executed, with the same instructions.
+    call.execute().assert_is_success();
+    assert_eq!(test_engine.current_balance("xrd"), balance + dec!(10000));
+}
+
+#[test]
+#[should_panic(expected = "Instructions cannot be added to a call after its preview")]
+fn test_add_instructions_after_preview() {
+    let mut test_engine = TestEngine::new();
+    let mut call = test_engine
+        .build_call()
+        .call_from_component("faucet", "free", env_args!());
+    call.preview();
+    call.call_from_component("faucet", "free", env_args!())
+        .execute();
+}
+
+#[test]
+fn test_get_return_of_call() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", dec!(1000));
+    let receipt = test_engine
+        .build_call()
+        .call_from_component("usd", "get_total_supply", env_args!())
+        .call_from_component("usd", "get_resource_type", env_args!())
+        .execute();
+
+    let resource_type: ResourceType = receipt.get_return();
+    assert_eq!(resource_type, ResourceType::Fungible { divisibility: 18 });
+    let total_supply: Option<Decimal> = receipt.get_return_of_call(0);
+    assert_eq!(total_supply, Some(dec!(1000)));
+}
+
+#[test]
+#[should_panic(expected = "Could not decode the return of the method into u64")]
+fn test_get_return_with_wrong_type() {
+    let mut test_engine = TestEngine::new();
+    let _total_supply: u64 = test_engine
+        .call_method_from("xrd", "get_total_supply", env_args!())
+        .get_return();
+}
+
+#[test]
+fn test_call_function_from() {
+    let mut test_engine = TestEngine::new();
+    let receipt =
+        test_engine.call_function_from(ACCOUNT_PACKAGE, ACCOUNT_BLUEPRINT, "create", env_args!());
+    let (account, _owner_badge): (ComponentAddress, Bucket) = receipt.get_return();
+    assert!(receipt
+        .expect_commit_success()
+        .new_component_addresses()
+        .contains(&account));
+}
+
+#[test]
+fn test_named_bucket_through_chained_calls() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_validator("validator");
+    test_engine.new_account("Staker");
+    test_engine.set_current_account("Staker");
+
+    test_engine
+        .build_call()
+        .take_bucket("XRD", dec!(1000), "xrd bucket")
+        .call_from_component(
+            "validator",
+            "stake",
+            env_args!(Environment::Bucket("xrd bucket")),
+        )
+        .take_from_worktop_named("validator lsu", dec!(1000), "lsu bucket")
+        .call_from_component(
+            "validator",
+            "unstake",
+            env_args!(Environment::Bucket("lsu bucket")),
+        )
+        .execute()
+        .assert_is_success();
+
+    assert_eq!(test_engine.current_balance("XRD"), dec!(9000));
+    assert_eq!(test_engine.current_balance("validator lsu"), Decimal::ZERO);
+    assert_eq!(
+        test_engine.current_balance("validator claim nft"),
+        Decimal::ONE
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "Bucket \"xrdbucket\" has already been consumed by a previous instruction"
+)]
+fn test_named_bucket_consumed_twice() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    test_engine
+        .build_call()
+        .take_bucket("XRD", dec!(10), "xrd bucket")
+        .call_from_component(
+            "Recipient",
+            "try_deposit_or_abort",
+            env_args!(Environment::Bucket("xrd bucket"), none!()),
+        )
+        .call_from_component(
+            "Recipient",
+            "try_deposit_or_abort",
+            env_args!(Environment::Bucket("xrd bucket"), none!()),
+        );
+}
+
+#[test]
+#[should_panic(
+    expected = "You cannot use a bucket with name \"xrdbucket\" before it has been created"
+)]
+fn test_named_bucket_used_before_creation() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    test_engine.build_call().call_from_component(
+        "Recipient",
+        "try_deposit_or_abort",
+        env_args!(Environment::Bucket("xrd bucket"), none!()),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Proof \"xrdproof\" has already been consumed by a previous instruction")]
+fn test_named_proof_consumed_twice() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_validator("validator");
+
+    test_engine
+        .build_call()
+        .create_proof_named("XRD", dec!(10), "xrd proof")
+        .call_from_component(
+            "validator",
+            "get_redemption_value",
+            env_args!(Environment::Proof("xrd proof")),
+        )
+        .call_from_component(
+            "validator",
+            "get_redemption_value",
+            env_args!(Environment::Proof("xrd proof")),
+        );
+}
+
+#[test]
+fn test_set_string_metadata_renames_component() {
+    let mut test_engine = TestEngine::new();
+    let validator = test_engine.new_validator("validator");
+
+    test_engine
+        .set_metadata(
+            "validator",
+            "name",
+            MetadataValue::String("Best Validator".to_string()),
+            "validator owner badge",
+        )
+        .assert_is_success();
+
+    assert_eq!(
+        test_engine.get_metadata("validator", "name"),
+        Some(MetadataValue::String("Best Validator".to_string()))
+    );
+    assert_eq!(test_engine.get_component("best validator"), validator);
+    assert_eq!(test_engine.get_component("validator"), validator);
+}
+
+#[test]
+fn test_set_url_and_array_metadata() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_validator("validator");
+
+    let url = MetadataValue::Url(UncheckedUrl::of("https://example.com/validator"));
+    let tags = MetadataValue::StringArray(vec!["staking".to_string(), "test".to_string()]);
+    test_engine
+        .build_call()
+        .set_metadata("validator", "info_url", url.clone())
+        .set_metadata("validator", "tags", tags.clone())
+        .with_badge("validator owner badge")
+        .execute()
+        .assert_is_success();
+
+    assert_eq!(test_engine.get_metadata("validator", "info_url"), Some(url));
+    assert_eq!(test_engine.get_metadata("validator", "tags"), Some(tags));
+}
+
+#[test]
+fn test_set_metadata_without_badge() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_validator("validator");
+
+    test_engine
+        .build_call()
+        .set_metadata(
+            "validator",
+            "name",
+            MetadataValue::String("Best Validator".to_string()),
+        )
+        .execute()
+        .assert_failed_with_auth_error();
+    assert_eq!(test_engine.get_metadata("validator", "name"), None);
+    assert!(test_engine.try_get_component("best validator").is_err());
+}
+
+#[test]
+fn test_recall() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("admin badge", 1);
+    test_engine.new_recallable_token("usd", 1000, 18, "admin badge");
+    test_engine.new_account("Holder");
+    test_engine
+        .transfer("Holder", "usd", dec!(100))
+        .assert_is_success();
+
+    test_engine
+        .recall("Holder", "usd", dec!(40), "admin badge")
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("Holder", "usd"), dec!(60));
+    assert_eq!(test_engine.current_balance("usd"), dec!(940));
+}
+
+#[test]
+#[should_panic(expected = "Cannot recall 150 of the resource: the component only holds 100")]
+fn test_recall_more_than_held() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("admin badge", 1);
+    test_engine.new_recallable_token("usd", 1000, 18, "admin badge");
+    test_engine.new_account("Holder");
+    test_engine
+        .transfer("Holder", "usd", dec!(100))
+        .assert_is_success();
+
+    test_engine.recall("Holder", "usd", dec!(150), "admin badge");
+}
+
+#[test]
+fn test_recall_without_badge() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("admin badge", 1);
+    test_engine.new_recallable_token("usd", 1000, 18, "admin badge");
+    test_engine.new_account("Holder");
+    test_engine
+        .transfer("Holder", "usd", dec!(100))
+        .assert_is_success();
+
+    test_engine
+        .build_call()
+        .recall("Holder", "usd", dec!(40))
+        .execute()
+        .assert_failed_with_auth_error();
+    assert_eq!(test_engine.balance_of("Holder", "usd"), dec!(100));
+}
+
+#[test]
+fn test_freeze_vault() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("admin badge", 1);
+    test_engine.new_recallable_token("usd", 1000, 18, "admin badge");
+    test_engine.new_account("Holder");
+    test_engine
+        .transfer("Holder", "usd", dec!(100))
+        .assert_is_success();
+
+    test_engine
+        .freeze_vault("Holder", "usd", "admin badge")
+        .assert_is_success();
+    test_engine
+        .build_call()
+        .with_caller("Holder")
+        .transfer("default", "usd", dec!(10))
+        .execute()
+        .assert_failed_with("VaultIsFrozen");
+    assert_eq!(test_engine.balance_of("Holder", "usd"), dec!(100));
+
+    test_engine
+        .unfreeze_vault("Holder", "usd", "admin badge")
+        .assert_is_success();
+    test_engine
+        .build_call()
+        .with_caller("Holder")
+        .transfer("default", "usd", dec!(10))
+        .execute()
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("Holder", "usd"), dec!(90));
+}
+
+#[test]
+fn test_cost_unit_limit() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+
+    let mut call = test_engine.build_call().with_cost_unit_limit(5_000_000);
+    for _ in 0..20 {
+        call = call.transfer("Recipient", "XRD", dec!(1));
+    }
+    let receipt = call.execute();
+    assert!(receipt.fee_summary.total_execution_cost_units_consumed <= 5_000_000);
+    receipt.assert_failed_with("LimitExceeded");
+
+    let mut call = test_engine.build_call();
+    for _ in 0..20 {
+        call = call.transfer("Recipient", "XRD", dec!(1));
+    }
+    call.execute().assert_is_success();
+}
+
+#[test]
+#[should_panic(expected = "which exceeds the execution timeout of 0ns")]
+fn test_execution_timeout_exceeded() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    test_engine
+        .build_call()
+        .transfer("Recipient", "XRD", dec!(1))
+        .with_execution_timeout(std::time::Duration::ZERO)
+        .execute();
+}
+
+#[test]
+fn test_execution_timeout() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    test_engine
+        .build_call()
+        .transfer("Recipient", "XRD", dec!(1))
+        .with_execution_timeout(std::time::Duration::from_secs(60))
+        .execute()
+        .assert_is_success();
+}
+
+#[test]
+fn test_scenario() {
+    let mut test_engine = TestEngine::new();
+    let start_epoch = test_engine.current_epoch();
+
+    Scenario::new()
+        .account("A")
+        .account("B")
+        .step(Step::NewToken {
+            name: "usd",
+            supply: dec!(1000),
+        })
+        .step(Step::Transfer {
+            from: "default",
+            to: "A",
+            resource: "usd",
+            amount: dec!(100),
+        })
+        .step(Step::Transfer {
+            from: "A",
+            to: "B",
+            resource: "usd",
+            amount: dec!(40),
+        })
+        .assert(Assert::Balance {
+            account: "A",
+            resource: "usd",
+            op: Comparison::Eq,
+            value: dec!(60),
+        })
+        .assert(Assert::Balance {
+            account: "B",
+            resource: "usd",
+            op: Comparison::Gte,
+            value: dec!(40),
+        })
+        .step(Step::AdvanceEpochs(2))
+        .assert(Assert::Custom {
+            name: "two epochs later",
+            check: Box::new(move |test_engine| {
+                let epoch = test_engine.current_epoch();
+                if epoch == start_epoch.after(2).unwrap() {
+                    Ok(())
+                } else {
+                    Err(format!("epoch is {:?}", epoch))
+                }
+            }),
+        })
+        .assert_run(&mut test_engine);
+}
+
+#[test]
+fn test_scenario_with_badge() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_validator("validator");
+
+    let failure = Scenario::new()
+        .step(Step::Call {
+            caller: "default",
+            component: "validator",
+            method: "register",
+            args: env_args!(),
+            badge: None,
+        })
+        .run(&mut test_engine)
+        .unwrap_err();
+    assert_eq!(failure.step, 1);
+    assert_eq!(failure.description, "default calls register on validator");
+    assert!(failure.reason.contains("AuthError"));
+
+    Scenario::new()
+        .step(Step::Call {
+            caller: "default",
+            component: "validator",
+            method: "register",
+            args: env_args!(),
+            badge: Some("validator owner badge"),
+        })
+        .assert_run(&mut test_engine);
+}
+
+#[test]
+fn test_scenario_failure() {
+    let mut test_engine = TestEngine::new();
+
+    let failure = Scenario::new()
+        .account("A")
+        .step(Step::NewToken {
+            name: "usd",
+            supply: dec!(10),
+        })
+        .step(Step::Transfer {
+            from: "A",
+            to: "default",
+            resource: "usd",
+            amount: dec!(1),
+        })
+        .run(&mut test_engine)
+        .unwrap_err();
+    assert_eq!(failure.step, 3);
+    assert_eq!(failure.description, "A transfers 1 usd to default");
+    assert!(failure.reason.starts_with("Transaction failed with:"));
+
+    let failure = Scenario::new()
+        .assert(Assert::Balance {
+            account: "A",
+            resource: "usd",
+            op: Comparison::Gt,
+            value: dec!(0),
+        })
+        .run(&mut test_engine)
+        .unwrap_err();
+    assert_eq!(
+        failure.to_string(),
+        "Step 1 (assert balance of A in usd > 0) failed: balance of A in usd is 0, expected > 0"
+    );
+}
+
+#[test]
+#[should_panic(expected = "Step 1 (C transfers 1 usd to default) failed:")]
+fn test_scenario_with_unknown_account() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", dec!(10));
+
+    Scenario::new()
+        .step(Step::Transfer {
+            from: "C",
+            to: "default",
+            resource: "usd",
+            amount: dec!(1),
+        })
+        .assert_run(&mut test_engine);
+}
+
+#[test]
+fn test_bucket_from_faucet() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Buyer");
+    test_engine.new_account("Recipient");
+    test_engine
+        .build_call()
+        .with_caller("Buyer")
+        .transfer("default", "XRD", dec!(10000))
+        .execute()
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("Buyer", "XRD"), dec!(0));
+
+    test_engine
+        .build_call()
+        .with_caller("Buyer")
+        .call_from_component(
+            "Recipient",
+            "try_deposit_or_abort",
+            env_args!(Fungible::from_faucet(dec!(10)), none!()),
+        )
+        .execute()
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("Recipient", "XRD"), dec!(10010));
+    assert_eq!(test_engine.balance_of("Buyer", "XRD"), dec!(9990));
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot take 10001 XRD from the faucet, which gives 10000 XRD per transaction"
+)]
+fn test_bucket_from_faucet_above_limit() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    test_engine.call_method_builder_from(
+        "Recipient",
+        "try_deposit_or_abort",
+        env_args!(Fungible::from_faucet(dec!(10001)), none!()),
+    );
+}
+
+#[test]
+fn test_empty_buckets() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", dec!(1000));
+    test_engine.new_nft_collection::<CollectionItem, _>("collection", NonFungibleIdType::Integer);
+    test_engine.new_account("Recipient");
+    assert_eq!(test_engine.balance_of("Recipient", "usd"), dec!(0));
+
+    test_engine
+        .build_call()
+        .with_caller("Recipient")
+        .call_from_component(
+            "default",
+            "try_deposit_or_abort",
+            env_args!(Fungible::empty("usd"), none!()),
+        )
+        .call_from_component(
+            "default",
+            "try_deposit_or_abort",
+            env_args!(NonFungible::Empty("collection"), none!()),
+        )
+        .execute()
+        .assert_is_success();
+    assert_eq!(test_engine.current_balance("usd"), dec!(1000));
+    assert!(test_engine.current_ids_balance("collection").is_empty());
+}
+
+#[test]
+fn test_explain_transfer() {
+    let mut test_engine = TestEngine::new();
+    test_engine.set_execution_trace(true);
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+
+    let receipt = test_engine.transfer("Recipient", "Test token", dec!(10));
+    let summary = test_engine.explain(&receipt);
+    println!("{}", summary);
+
+    assert!(summary.starts_with("Outcome: success"));
+    assert!(summary.contains("Recipient: +10 Test token"));
+    assert!(summary.contains("default: -10 Test token"));
+    assert!(!summary.contains("_sim1"));
+}
+
+#[test]
+fn test_assert_failed_containing_amount() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+
+    test_engine
+        .transfer("Recipient", "Test token", dec!("2000.5"))
+        .assert_failed_containing_amount(dec!("2000.50"))
+        .assert_failed_containing_amount(1000);
+}
+
+#[test]
+#[should_panic(expected = "Transaction did not fail with an error containing the amount")]
+fn test_assert_failed_containing_other_amount() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+
+    test_engine
+        .transfer("Recipient", "Test token", dec!("2000.5"))
+        .assert_failed_containing_amount(dec!(2000));
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn test_assert_failed_with_regex() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+
+    test_engine
+        .transfer("Recipient", "Test token", dec!("2000.5"))
+        .assert_failed_with_regex(r"InsufficientBalance.*2000\.50*\b");
+}
+
+#[test]
+fn test_round_to_divisibility() {
+    let mut test_engine = TestEngine::new();
+    test_engine
+        .new_resource("usd")
+        .divisibility(6)
+        .supply(1000)
+        .create();
+    test_engine.new_token("btc", 10);
+
+    assert_eq!(test_engine.divisibility("usd"), 6);
+    let amount = dec!("1.23456789");
+    for (mode, expected) in [
+        (RoundingMode::ToZero, dec!("1.234567")),
+        (RoundingMode::AwayFromZero, dec!("1.234568")),
+        (RoundingMode::ToNegativeInfinity, dec!("1.234567")),
+        (RoundingMode::ToPositiveInfinity, dec!("1.234568")),
+        (RoundingMode::ToNearestMidpointToEven, dec!("1.234568")),
+    ] {
+        assert_eq!(
+            test_engine.round_to_divisibility("usd", amount, mode),
+            expected
+        );
+    }
+    assert_eq!(
+        test_engine.round_to_divisibility(
+            "usd",
+            dec!("-1.2345675"),
+            RoundingMode::ToNearestMidpointAwayFromZero
+        ),
+        dec!("-1.234568")
+    );
+    assert_eq!(
+        test_engine.round_to_divisibility("btc", amount, RoundingMode::ToZero),
+        amount
+    );
+}
+
+#[test]
+#[should_panic(expected = "Cannot withdraw 0.0000001 usd: the resource has a divisibility of 6")]
+fn test_transfer_below_divisibility() {
+    let mut test_engine = TestEngine::new();
+    test_engine
+        .new_resource("usd")
+        .divisibility(6)
+        .supply(1000)
+        .create();
+    test_engine.new_account("Recipient");
+
+    test_engine.transfer("Recipient", "usd", dec!("0.0000001"));
+}
+
+#[test]
+fn test_transfer_below_divisibility_without_check() {
+    let mut test_engine = TestEngine::new();
+    test_engine
+        .new_resource("usd")
+        .divisibility(6)
+        .supply(1000)
+        .create();
+    test_engine.new_account("Recipient");
+    test_engine.set_divisibility_check(false);
+
+    test_engine
+        .transfer("Recipient", "usd", dec!("0.0000001"))
+        .assert_failed_with("InvalidAmount");
+    let amount =
+        test_engine.round_to_divisibility("usd", dec!("0.0000001"), RoundingMode::AwayFromZero);
+    test_engine
+        .transfer("Recipient", "usd", amount)
+        .assert_is_success();
+}
+
+#[test]
+fn test_explain_failure() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+
+    let receipt = test_engine.transfer("Recipient", "Test token", dec!(2000));
+    let summary = test_engine.explain(&receipt);
+    println!("{}", summary);
+
+    assert!(summary.starts_with("Outcome: failed with"));
+    assert!(summary.contains("InsufficientBalance"));
+    assert!(!summary.contains("Test token"));
+}
+
+fn snapshot_transfer(amount: Decimal) -> ReceiptSnapshot {
+    let mut test_engine = TestEngine::new();
+    test_engine.set_execution_trace(true);
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+    test_engine
+        .transfer("Recipient", "Test token", amount)
+        .to_snapshot(&test_engine)
+}
+
+#[test]
+fn test_receipt_snapshot() {
+    let snapshot = snapshot_transfer(dec!(10));
+    println!("{:#?}", snapshot);
+
+    assert_eq!(snapshot.outcome, "success");
+    assert!(snapshot.fee.split_once('.').unwrap().1.len() <= DEFAULT_FEE_DECIMALS as usize);
+    assert!(snapshot
+        .balance_changes
+        .contains(&"Recipient: +10 Test token".to_string()));
+    assert!(snapshot.events.iter().any(|event| {
+        event.name == "DepositEvent"
+            && event.emitter.as_deref() == Some("Recipient")
+            && event.data.contains("Test token")
+    }));
+    assert!(!snapshot
+        .events
+        .iter()
+        .any(|event| event.name == "LockFeeEvent" || event.name == "PayFeeEvent"));
+    let serialized = serde_json::to_string(&snapshot).unwrap();
+    assert!(!serialized.contains("account_sim1"));
+    assert!(!serialized.contains("resource_sim1"));
+}
+
+#[test]
+fn test_assert_matches_snapshot() {
+    let path = std::env::temp_dir()
+        .join(format!("receipt-snapshot-{}", std::process::id()))
+        .join("transfer.json");
+    let _ = std::fs::remove_file(&path);
+
+    snapshot_transfer(dec!(10)).assert_matches_snapshot(&path);
+    assert!(path.exists());
+    snapshot_transfer(dec!(10)).assert_matches_snapshot(&path);
+
+    let mismatch = std::panic::catch_unwind(|| {
+        snapshot_transfer(dec!(20)).assert_matches_snapshot(&path);
+    })
+    .unwrap_err();
+    let message = mismatch.downcast_ref::<String>().unwrap();
+    println!("{}", message);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+    assert!(message.contains("Receipt does not match snapshot"));
+    assert!(message.contains("-     \"Recipient: +10 Test token\""));
+    assert!(message.contains("+     \"Recipient: +20 Test token\""));
+}
+
+#[test]
+fn test_explain_unknown_entities() {
+    let mut test_engine = TestEngine::new();
+    let receipt =
+        test_engine.call_function_from(ACCOUNT_PACKAGE, ACCOUNT_BLUEPRINT, "create", env_args!());
+    let summary = test_engine.explain(&receipt);
+    println!("{}", summary);
+
+    let (account, _owner_badge): (ComponentAddress, Bucket) = receipt.get_return();
+    let address = AddressBech32Encoder::for_simulator()
+        .encode(account.as_node_id().as_bytes())
+        .unwrap();
+    assert!(summary.contains(&format!("component {}", address)));
+}
+
+/// Package without blueprints: its code only exports a memory.
+fn empty_package() -> (Vec<u8>, PackageDefinition) {
+    let code = vec![
+        0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+        0x05, 0x03, 0x01, 0x00, 0x01, // memory of one page
+        0x07, 0x0a, 0x01, 0x06, b'm', b'e', b'm', b'o', b'r', b'y', 0x02, 0x00, // export
+    ];
+    (code, PackageDefinition::default())
+}
+
+#[test]
+fn test_set_package_metadata_with_owner_badge() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("owner badge", 1);
+    test_engine.new_package_with_owner("empty package", &empty_package(), "owner badge");
+
+    test_engine
+        .set_package_metadata(
+            "empty package",
+            "description",
+            MetadataValue::String("Version 1".to_string()),
+            "owner badge",
+        )
+        .assert_is_success();
+    assert_eq!(
+        test_engine.get_metadata("empty package", "description"),
+        Some(MetadataValue::String("Version 1".to_string()))
+    );
+}
+
+#[test]
+fn test_set_package_metadata_without_owner_badge() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("owner badge", 1);
+    test_engine.new_token("other badge", 1);
+    test_engine.new_package_with_owner("empty package", &empty_package(), "owner badge");
+
+    test_engine
+        .set_package_metadata(
+            "empty package",
+            "description",
+            MetadataValue::String("Version 1".to_string()),
+            "other badge",
+        )
+        .assert_failed_with_auth_error();
+    test_engine
+        .build_call()
+        .set_metadata(
+            "empty package",
+            "description",
+            MetadataValue::String("Version 1".to_string()),
+        )
+        .execute()
+        .assert_failed_with_auth_error();
+    assert_eq!(
+        test_engine.get_metadata("empty package", "description"),
+        None
+    );
+}
+
+#[test]
+fn test_package_versions_with_same_owner() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("owner badge", 1);
+    test_engine.new_package_with_owner("package v1", &empty_package(), "owner badge");
+    test_engine.new_package_with_owner("package v2", &empty_package(), "owner badge");
+
+    assert_ne!(
+        test_engine.get_package("package v1"),
+        test_engine.get_package("package v2")
+    );
+    test_engine
+        .set_package_metadata(
+            "package v2",
+            "description",
+            MetadataValue::String("Version 2".to_string()),
+            "owner badge",
+        )
+        .assert_is_success();
+    test_engine
+        .claim_package_royalties("package v1", "owner badge")
+        .assert_is_success();
+}
+
+#[test]
+#[should_panic(expected = "The package has no blueprint named Hello")]
+fn test_royalties_of_unknown_blueprint() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("owner badge", 1);
+    test_engine.new_package_with_royalties(
+        "empty package",
+        &empty_package(),
+        "owner badge",
+        HashMap::from([("Hello", vec![("free_token", RoyaltyAmount::Xrd(dec!(1)))])]),
+    );
+}
+
+#[test]
+#[should_panic(expected = "has no royalties")]
+fn test_royalty_balance_of_component_without_royalties() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    test_engine.component_royalty_balance("Recipient");
+}
+
+#[derive(ScryptoSbor, ManifestSbor, PartialEq, Debug)]
+struct Ticket {
+    seat: u32,
+    holder: String,
+}
+
+impl NonFungibleData for Ticket {
+    const MUTABLE_FIELDS: &'static [&'static str] = &["holder"];
+}
+
+fn new_ticket_collection() -> TestEngine {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_nft_collection::<Ticket, _>("tickets", NonFungibleIdType::Integer);
+    test_engine.mint_nft(
+        "tickets",
+        1,
+        Ticket {
+            seat: 42,
+            holder: "Alice".to_string(),
+        },
+    );
+    test_engine
+}
+
+#[test]
+fn test_get_nf_field() {
+    let mut test_engine = new_ticket_collection();
+    let seat: u32 = test_engine.get_nf_field("tickets", 1, "seat");
+    let holder: String = test_engine.get_nf_field("tickets", 1, "holder");
+    assert_eq!(seat, 42);
+    assert_eq!(holder, "Alice");
+}
+
+#[test]
+#[should_panic(
+    expected = "The non-fungible data has no field named owner. Its fields are: seat, holder"
+)]
+fn test_get_unknown_nf_field() {
+    let mut test_engine = new_ticket_collection();
+    test_engine.get_nf_field::<_, _, String>("tickets", 1, "owner");
+}
+
+#[test]
+fn test_assert_nf_data_eq() {
+    let mut test_engine = new_ticket_collection();
+    test_engine.assert_nf_data_eq(
+        "tickets",
+        1,
+        Ticket {
+            seat: 42,
+            holder: "Alice".to_string(),
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "The non-fungible data of NFT #1# is not the expected one")]
+fn test_assert_nf_data_eq_with_other_data() {
+    let mut test_engine = new_ticket_collection();
+    test_engine.assert_nf_data_eq(
+        "tickets",
+        1,
+        Ticket {
+            seat: 42,
+            holder: "Bob".to_string(),
+        },
+    );
+}
+
+#[test]
+fn test_new_resource() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("admin", 1);
+    test_engine
+        .new_resource("usd")
+        .divisibility(6)
+        .mintable_by("admin")
+        .burnable_by_all()
+        .supply(1000)
+        .metadata("symbol", MetadataValue::String("USD".to_string()))
+        .create();
+    assert_eq!(test_engine.current_balance("usd"), dec!(1000));
+    assert_eq!(
+        test_engine.get_resource("USD"),
+        test_engine.get_resource("usd")
+    );
+
+    test_engine
+        .build_call()
+        .call_from_component("usd", "mint", env_args!(dec!(10)))
+        .with_badge("admin")
+        .execute()
+        .assert_is_success();
+    test_engine
+        .build_call()
+        .call_from_component("usd", "burn", env_args!(Fungible::Bucket("usd", 500)))
+        .execute()
+        .assert_is_success();
+    assert_eq!(test_engine.current_balance("usd"), dec!(510));
+    test_engine
+        .build_call()
+        .call_from_component("usd", "mint", env_args!(dec!(10)))
+        .execute()
+        .assert_failed_with_auth_error();
+}
+
+#[test]
+fn test_transfer_soulbound_badge() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    test_engine
+        .new_resource("membership")
+        .divisibility(0)
+        .soulbound()
+        .supply(1)
+        .create();
+
+    test_engine
+        .transfer("Recipient", "membership", 1)
+        .assert_failed_with_auth_error();
+    assert_eq!(test_engine.current_balance("membership"), dec!(1));
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot create resource membership: the initial supply of a soulbound resource can only be deposited to the current account"
+)]
+fn test_soulbound_resource_deposited_to_other_account() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    test_engine
+        .new_resource("membership")
+        .soulbound()
+        .supply(1)
+        .deposit_to("Recipient")
+        .create();
+}
+
+#[test]
+#[should_panic(
+    expected = "Cannot create resource usd: it has no initial supply and cannot be minted"
+)]
+fn test_new_resource_without_supply() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_resource("usd").burnable_by_all().create();
+}
+
+#[test]
+fn test_new_nft_resource() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("admin", 1);
+    test_engine.new_account("Recipient");
+    test_engine
+        .new_nft_resource::<Ticket, _>("tickets", NonFungibleIdType::Integer)
+        .data_updatable_by("admin")
+        .nfts(vec![(
+            1,
+            Ticket {
+                seat: 42,
+                holder: "Alice".to_string(),
+            },
+        )])
+        .deposit_to("Recipient")
+        .create();
+    assert_eq!(
+        test_engine.ids_balance_of("Recipient", "tickets"),
+        nf_ids![1]
+    );
+
+    test_engine
+        .update_non_fungible_data("tickets", 1, "holder", env_args!("Bob"), "admin")
+        .assert_is_success();
+    let holder: String = test_engine.get_nf_field("tickets", 1, "holder");
+    assert_eq!(holder, "Bob");
+}
+
+#[test]
+#[should_panic(
+    expected = "Recipient of blueprint Account has no method named withdrw. Its methods are: "
+)]
+fn test_call_unknown_method() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    test_engine.call_method_from(
+        "Recipient",
+        "withdrw",
+        env_args!(Environment::Resource("XRD"), dec!(1)),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Recipient::withdraw takes 2 arguments, but 1 were given")]
+fn test_call_method_with_wrong_arity() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    test_engine.call_method_from("Recipient", "withdraw", env_args!(dec!(1)));
+}
+
+#[test]
+#[should_panic(expected = "Blueprint Account has no function named craete. Its functions are: ")]
+fn test_call_unknown_function() {
+    let mut test_engine = TestEngine::new();
+    test_engine.call_function_from(ACCOUNT_PACKAGE, ACCOUNT_BLUEPRINT, "craete", env_args!());
+}
+
+#[test]
+fn test_call_unknown_method_without_validation() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    let receipt = test_engine
+        .build_call()
+        .without_validation()
+        .call_from_component(
+            "Recipient",
+            "withdrw",
+            env_args!(Environment::Resource("XRD"), dec!(1)),
+        )
+        .execute();
+    receipt.assert_failed_with("withdrw");
+}
+
+/// Encodes `Some` of an address reservation, as expected by the native `create_advanced`
+/// functions.
+struct SomeReservation(&'static str);
+
+impl EnvironmentEncode for SomeReservation {
+    fn encode(
+        &self,
+        test_engine: &mut TestEngine,
+        manifest_builder: ManifestBuilder,
+        encoder: &mut ManifestEncoder,
+        caller: ComponentAddress,
+    ) -> ManifestBuilder {
+        encoder.write_value_kind(ManifestValueKind::Enum).unwrap();
+        encoder.write_discriminator(OPTION_VARIANT_SOME).unwrap();
+        encoder.write_size(1).unwrap();
+        Environment::AddressReservation(self.0).encode(
+            test_engine,
+            manifest_builder,
+            encoder,
+            caller,
+        )
+    }
+}
+
+#[test]
+fn test_allocate_address() {
+    let mut test_engine = TestEngine::new();
+    let receipt = test_engine
+        .build_call()
+        .allocate_address(ACCOUNT_PACKAGE, ACCOUNT_BLUEPRINT, "reserved account")
+        .call_from_package(
+            ACCOUNT_PACKAGE,
+            ACCOUNT_BLUEPRINT,
+            "create_advanced",
+            env_args!(OwnerRole::None, SomeReservation("reserved account")),
+        )
+        .execute()
+        .assert_is_success();
+
+    let new_account = receipt.expect_commit_success().new_component_addresses()[0];
+    assert_eq!(test_engine.get_component("reserved account"), new_account);
+}
+
+#[test]
+#[should_panic(expected = "There is no component with name reserved account")]
+fn test_allocate_address_in_raw_call_is_not_registered() {
+    let mut test_engine = TestEngine::new();
+    test_engine
+        .build_call()
+        .call_from_component("faucet", "lock_fee", env_args!(dec!(10)))
+        .allocate_address(ACCOUNT_PACKAGE, ACCOUNT_BLUEPRINT, "reserved account")
+        .call_from_package(
+            ACCOUNT_PACKAGE,
+            ACCOUNT_BLUEPRINT,
+            "create_advanced",
+            env_args!(OwnerRole::None, SomeReservation("reserved account")),
+        )
+        .raw()
+        .execute()
+        .assert_is_success();
+
+    test_engine.get_component("reserved account");
+}
+
+#[test]
+fn test_transfer_builder() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+
+    test_engine
+        .transfer_builder("Recipient", "Test token", dec!(10))
+        .transfer("Recipient", "Test token", dec!(5))
+        .execute()
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("Recipient", "Test token"), dec!(15));
+}
+
+#[test]
+fn test_airdrop() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Alice");
+    test_engine.new_account("Bob");
+    test_engine.new_account("Carol");
+
+    let faucet_balance = test_engine.balance_of("faucet", "XRD");
+    let receipt = test_engine.airdrop(
+        "Test token",
+        vec![("Alice", dec!(10)), ("Bob", dec!(20)), ("Carol", dec!(30))],
+    );
+    let fee = receipt.fee_summary.total_cost();
+    receipt.assert_is_success();
+
+    assert_eq!(test_engine.balance_of("Alice", "Test token"), dec!(10));
+    assert_eq!(test_engine.balance_of("Bob", "Test token"), dec!(20));
+    assert_eq!(test_engine.balance_of("Carol", "Test token"), dec!(30));
+    assert_eq!(test_engine.current_balance("Test token"), dec!(940));
+    // The fees of a single transaction were paid.
+    assert_eq!(
+        test_engine.balance_of("faucet", "XRD"),
+        faucet_balance - fee
+    );
+}
+
+#[test]
+fn test_airdrop_refused_by_recipient() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Alice");
+    test_engine.new_account("Bob");
+    test_engine
+        .build_call()
+        .with_caller("Bob")
+        .call_from_component(
+            "Bob",
+            "set_default_deposit_rule",
+            env_args!(DefaultDepositRule::Reject),
+        )
+        .execute()
+        .assert_is_success();
+
+    test_engine
+        .airdrop("Test token", vec![("Alice", dec!(10)), ("Bob", dec!(20))])
+        .assert_failed_with("DepositIsDisallowed");
+    assert_eq!(test_engine.balance_of("Alice", "Test token"), dec!(0));
+    assert_eq!(test_engine.current_balance("Test token"), dec!(1000));
+}
+
+#[test]
+fn test_airdrop_non_fungibles() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Alice");
+    test_engine.new_account("Bob");
+    let ticket = |seat| Ticket {
+        seat,
+        holder: "Organizer".to_string(),
+    };
+    test_engine
+        .new_nft_resource::<Ticket, _>("tickets", NonFungibleIdType::Integer)
+        .nfts(vec![(1, ticket(1)), (2, ticket(2)), (3, ticket(3))])
+        .create();
+
+    test_engine
+        .airdrop_non_fungibles("tickets", vec![("Alice", vec![1, 3]), ("Bob", vec![2])])
+        .assert_is_success();
+
+    let mut alice_tickets = test_engine.ids_balance_of("Alice", "tickets");
+    alice_tickets.sort();
+    assert_eq!(alice_tickets, nf_ids![1, 3]);
+    assert_eq!(test_engine.ids_balance_of("Bob", "tickets"), nf_ids![2]);
+    assert!(test_engine.current_ids_balance("tickets").is_empty());
+}
+
+#[test]
+#[should_panic(expected = "An airdrop needs at least one recipient")]
+fn test_airdrop_without_recipient() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Test token", 1000);
+    test_engine.airdrop("Test token", Vec::<(&str, Decimal)>::new());
+}
+
+#[test]
+fn test_jump_back_epochs() {
+    let mut test_engine = TestEngine::new();
+    let start = test_engine.current_epoch();
+    test_engine.jump_epochs(5);
+    test_engine.jump_back_epochs(2);
+    assert_eq!(test_engine.current_epoch(), start.after(3).unwrap());
+}
+
+#[test]
+fn test_jump_back_to_genesis_epoch() {
+    let mut test_engine = TestEngineBuilder::new()
+        .starting_epoch(Epoch::of(10))
+        .build();
+    test_engine.jump_back_epochs(1);
+    assert_eq!(test_engine.current_epoch(), Epoch::of(9));
+}
+
+#[test]
+#[should_panic(expected = "Cannot jump back 2 epochs from epoch 10: the genesis epoch is 9")]
+fn test_jump_back_before_genesis_epoch() {
+    let mut test_engine = TestEngineBuilder::new()
+        .starting_epoch(Epoch::of(10))
+        .build();
+    test_engine.jump_back_epochs(2);
+}
+
+#[test]
+fn test_set_epoch() {
+    let mut test_engine = TestEngine::new();
+    test_engine.set_epoch(Epoch::of(100));
+    assert_eq!(test_engine.current_epoch(), Epoch::of(100));
+    test_engine.next_epoch();
+    assert_eq!(test_engine.current_epoch(), Epoch::of(101));
+}
+
+fn output_badge_manifest(
+    directory: &str,
+    with_badge: impl FnOnce(CallBuilder) -> CallBuilder,
+) -> String {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("Recipient");
+    test_engine.new_nft_collection::<CollectionItem, _>("badge", NonFungibleIdType::Integer);
+    test_engine.mint_nfts(
+        "badge",
+        (1..=50u64)
+            .map(|id| {
+                (
+                    id,
+                    CollectionItem {
+                        name: format!("badge {}", id),
+                    },
+                )
+            })
+            .collect(),
+    );
+
+    let output_dir = std::env::temp_dir().join(format!("{}-{}", directory, std::process::id()));
+    let output_dir = output_dir.to_str().unwrap();
+    let call = test_engine
+        .build_call()
+        .transfer("Recipient", "XRD", dec!(10));
+    with_badge(call)
+        .output(output_dir, "badge")
+        .execute()
+        .assert_is_success();
+
+    let manifest = std::fs::read_to_string(format!("{}/badge.rtm", output_dir)).unwrap();
+    std::fs::remove_dir_all(output_dir).unwrap();
+    manifest
+}
+
+fn proved_badge_ids(manifest: &str) -> Vec<u64> {
+    (1..=50u64)
+        .filter(|id| manifest.contains(&format!("NonFungibleLocalId(\"#{}#\")", id)))
+        .collect()
+}
+
+#[test]
+fn test_with_badge_proves_smallest_id() {
+    let manifest = output_badge_manifest("badge-smallest-id", |call| call.with_badge("badge"));
+    assert!(manifest.contains("create_proof_of_non_fungibles"));
+    assert_eq!(proved_badge_ids(&manifest), vec![1]);
+}
+
+#[test]
+fn test_with_badge_ids() {
+    let manifest = output_badge_manifest("badge-ids", |call| {
+        call.with_badge_ids("badge", vec![12u64, 7])
+    });
+    assert_eq!(proved_badge_ids(&manifest), vec![7, 12]);
+}
+
+#[test]
+fn test_with_badge_all_ids() {
+    let manifest = output_badge_manifest("badge-all-ids", |call| call.with_badge_all_ids("badge"));
+    assert_eq!(proved_badge_ids(&manifest), (1..=50).collect::<Vec<u64>>());
+}
+
+#[test]
+#[should_panic(expected = "Cannot prove ids of a fungible badge")]
+fn test_with_badge_ids_of_fungible() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("badge", 1);
+    test_engine.build_call().with_badge_ids("badge", vec![1u64]);
+}
+
+/// Dumps a transfer manifest and turns it into a template using placeholders.
+fn transfer_manifest_template(test_engine: &mut TestEngine, directory: &str) -> String {
+    let recipient = test_engine.get_account("Recipient").to_owned();
+    let token = test_engine.get_resource("Test token");
+    let output_dir = std::env::temp_dir().join(format!("{}-{}", directory, std::process::id()));
+    let output_dir = output_dir.to_str().unwrap();
+    test_engine
+        .build_call()
+        .transfer("Recipient", "Test token", dec!(10))
+        .output(output_dir, "transfer")
+        .execute()
+        .assert_is_success();
+
+    let manifest_path = format!("{}/transfer.rtm", output_dir);
+    let manifest = std::fs::read_to_string(&manifest_path).unwrap();
+    let encoder = AddressBech32Encoder::new(&NetworkDefinition::simulator());
+    let template = manifest
+        .replace(&encoder.encode(recipient.as_ref()).unwrap(), "${Recipient}")
+        .replace(
+            &encoder.encode(token.as_ref()).unwrap(),
+            "${resource:Test token}",
+        )
+        .replace(
+            &encoder
+                .encode(test_engine.current_account_address().as_ref())
+                .unwrap(),
+            "${account}",
+        )
+        .replace("Decimal(\"10\")", "Decimal(\"${amount}\")");
+    std::fs::write(&manifest_path, template).unwrap();
+    manifest_path
+}
+
+#[test]
+fn test_execute_manifest_file() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("Test token", 1000);
+    test_engine.new_account("Recipient");
+    let manifest_path = transfer_manifest_template(&mut test_engine, "manifest-file");
+
+    test_engine
+        .execute_manifest_file(
+            &manifest_path,
+            HashMap::from([("amount", "25".to_string())]),
+        )
+        .assert_is_success();
+    std::fs::remove_file(&manifest_path).unwrap();
+
+    assert_eq!(test_engine.balance_of("Recipient", "Test token"), dec!(35));
+    assert_eq!(test_engine.current_balance("Test token"), dec!(965));
+}
+
+#[test]
+fn test_execute_manifest_file_updates_references() {
+    let mut test_engine = TestEngine::new();
+    let output_dir =
+        std::env::temp_dir().join(format!("manifest-file-token-{}", std::process::id()));
+    std::fs::create_dir_all(&output_dir).unwrap();
+    let manifest_path = output_dir.join("new_token.rtm");
+    let manifest = ManifestBuilder::new()
+        .lock_fee_from_faucet()
+        .create_fungible_resource(
+            OwnerRole::None,
+            true,
+            18,
+            FungibleResourceRoles::default(),
+            metadata!(init { "name" => "Manifest token", locked; }),
+            Some(dec!(100)),
+        )
+        .deposit_batch(*test_engine.current_account_address())
+        .build();
+    let manifest = radix_transactions::manifest::decompile(
+        &manifest.instructions,
+        &NetworkDefinition::simulator(),
+    )
+    .unwrap();
+    std::fs::write(&manifest_path, manifest).unwrap();
+
+    test_engine
+        .execute_manifest_file(&manifest_path, HashMap::new())
+        .assert_is_success();
+    std::fs::remove_dir_all(&output_dir).unwrap();
+
+    assert_eq!(test_engine.current_balance("Manifest token"), dec!(100));
+}
+
+#[test]
+#[should_panic(expected = "at line 4, column 5")]
+fn test_execute_manifest_file_with_invalid_manifest() {
+    let mut test_engine = TestEngine::new();
+    let manifest_path =
+        std::env::temp_dir().join(format!("invalid-manifest-{}.rtm", std::process::id()));
+    std::fs::write(
+        &manifest_path,
+        "CALL_METHOD\n    Address(\"${account}\")\n    \"withdraw\"\n    Unknown(\"XRD\");\n",
+    )
+    .unwrap();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        test_engine.execute_manifest_file(&manifest_path, HashMap::new())
+    }));
+    std::fs::remove_file(&manifest_path).unwrap();
+    std::panic::resume_unwind(result.unwrap_err());
+}
+
+#[test]
+fn test_assert_balance() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_account("receiver");
+    test_engine.transfer("receiver", "usd", dec!("12.5"));
+
+    assert_balance!(test_engine, "receiver", "usd", dec!("12.5"));
+    assert_balance!(test_engine, "default", "usd", dec!("987.5"));
+    assert_dec_approx_eq!(test_engine.balance_of("receiver", "usd"), 12, dec!("0.5"));
+}
+
+#[test]
+#[should_panic(expected = "Balance of receiver in usd is 12.5, expected 10")]
+fn test_assert_balance_mismatch() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_account("receiver");
+    test_engine.transfer("receiver", "usd", dec!("12.5"));
+
+    assert_balance!(test_engine, "receiver", "usd", 10);
+}
+
+#[test]
+#[should_panic(expected = "difference: 0.5 (5.0000% of expected)")]
+fn test_assert_dec_approx_eq_mismatch() {
+    assert_dec_approx_eq!(dec!("10.5"), 10, dec!("0.1"));
+}
+
+#[test]
+fn test_state_update_summary() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("receiver");
+
+    let receipt = test_engine.transfer("receiver", "XRD", dec!(10));
+    let stats = receipt.state_update_summary();
+    assert_eq!(stats.user, SubstateWrites::default());
+    assert!(stats.user_writes_by_node.is_empty());
+    assert!(stats.system.updated > 0);
+    assert!(stats.system.bytes_written > 0);
+    assert_eq!(stats.total(), stats.system);
+    test_engine.assert_max_writes(&receipt, 0);
+
+    let receipt = test_engine
+        .build_call()
+        .lock_fee("faucet", dec!("0.0000001"))
+        .transfer("receiver", "XRD", dec!(1))
+        .execute()
+        .assert_rejected();
+    assert_eq!(receipt.state_update_summary(), StateUpdateStats::default());
+}
+
+#[test]
+fn test_state_update_summary_of_new_vault() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_account("receiver");
+
+    // The first transfer of a resource creates a vault in the receiving account
+    let first = test_engine.transfer("receiver", "usd", dec!(10));
+    assert!(first.state_update_summary().system.created > 0);
+
+    let second = test_engine.transfer("receiver", "usd", dec!(10));
+    assert_eq!(second.state_update_summary().system.created, 0);
+}
+
+#[test]
+fn test_with_simulator_tracked() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_account("receiver");
+    let account = *test_engine.current_account_address();
+
+    let registered = test_engine.with_simulator_tracked(|simulator| {
+        let manifest = ManifestBuilder::new()
+            .lock_fee_from_faucet()
+            .create_fungible_resource(
+                OwnerRole::None,
+                true,
+                18,
+                FungibleResourceRoles {
+                    burn_roles: burn_roles! {
+                        burner => rule!(allow_all);
+                        burner_updater => rule!(deny_all);
+                    },
+                    ..Default::default()
+                },
+                metadata!(init {
+                    "name" => "Exotic token", locked;
+                }),
+                Some(dec!(1000)),
+            )
+            .try_deposit_entire_worktop_or_abort(account, None)
+            .build();
+        vec![simulator.execute_manifest(manifest, vec![])]
+    });
+
+    let exotic = test_engine.get_resource("Exotic token");
+    assert_eq!(
+        registered.resources,
+        vec![("Exotic token".to_string(), exotic)]
+    );
+    assert!(registered.components.is_empty());
+
+    test_engine
+        .transfer("receiver", "Exotic token", dec!(10))
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("receiver", "Exotic token"), dec!(10));
+}
+
+fn reject_all_deposits() -> TestEngine {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_token("depositor badge", 1);
+    test_engine.new_account("receiver");
+    test_engine.set_current_account("receiver");
+    test_engine
+        .set_default_deposit_rule(DefaultDepositRule::Reject)
+        .assert_is_success();
+    test_engine.set_current_account("default");
+    test_engine
+}
+
+#[test]
+fn test_transfer_to_rejecting_account_aborts() {
+    let mut test_engine = reject_all_deposits();
+    test_engine
+        .transfer("receiver", "usd", dec!(10))
+        .assert_failed_with("DepositIsDisallowed");
+    assert_eq!(test_engine.balance_of("receiver", "usd"), Decimal::ZERO);
+}
+
+#[test]
+fn test_transfer_or_refund() {
+    let mut test_engine = reject_all_deposits();
+    let usd = test_engine.get_resource("usd");
+    test_engine
+        .transfer_or_refund("receiver", "usd", dec!(10))
+        .assert_refunded(usd, dec!(10));
+    assert_eq!(test_engine.current_balance("usd"), dec!(1000));
+    assert_eq!(test_engine.balance_of("receiver", "usd"), Decimal::ZERO);
+}
+
+#[test]
+#[should_panic(expected = "but 0 were rejected")]
+fn test_assert_refunded_on_accepted_deposit() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_account("receiver");
+    let usd = test_engine.get_resource("usd");
+    test_engine
+        .transfer_or_refund("receiver", "usd", dec!(10))
+        .assert_refunded(usd, dec!(10));
+}
+
+#[test]
+fn test_transfer_with_authorized_badge() {
+    let mut test_engine = reject_all_deposits();
+    test_engine.set_current_account("receiver");
+    test_engine
+        .add_authorized_depositor("depositor badge")
+        .assert_is_success();
+    test_engine.set_current_account("default");
+
+    test_engine
+        .transfer_with_authorized_badge("receiver", "usd", dec!(10), "depositor badge")
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("receiver", "usd"), dec!(10));
+}
+
+#[test]
+fn test_securify_account() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_account("receiver");
+
+    let owner_badge = test_engine.securify_account("default");
+    assert_eq!(owner_badge.resource_address(), ACCOUNT_OWNER_BADGE);
+    assert_eq!(
+        test_engine.get_resource("default owner badge"),
+        ACCOUNT_OWNER_BADGE
+    );
+    assert_eq!(
+        test_engine.current_ids_balance("default owner badge"),
+        vec![owner_badge.local_id().clone()]
+    );
+
+    // Withdrawing requires the owner of the account, proven by the badge
+    test_engine
+        .transfer("receiver", "usd", dec!(10))
+        .assert_is_success();
+
+    test_engine
+        .transfer_non_fungibles(
+            "receiver",
+            "default owner badge",
+            vec![owner_badge.local_id().clone()],
+        )
+        .assert_is_success();
+    test_engine
+        .transfer("receiver", "usd", dec!(10))
+        .assert_failed_with_auth_error();
+    assert_eq!(test_engine.balance_of("receiver", "usd"), dec!(10));
+}
+
+#[test]
+#[should_panic(expected = "Account default is already securified")]
+fn test_securify_account_twice() {
+    let mut test_engine = TestEngine::new();
+    test_engine.securify_account("default");
+    test_engine.securify_account("default");
+}
+
+#[test]
+fn test_from_database() {
+    let mut source_engine = TestEngine::new();
+    source_engine.new_token("usd", 1000);
+    source_engine.new_account("treasury");
+    source_engine
+        .transfer("treasury", "usd", dec!(100))
+        .assert_is_success();
+    let usd = source_engine.get_resource("usd");
+    let treasury = *source_engine.get_account("treasury");
+
+    let mut test_engine = TestEngine::from_database(source_engine.substate_db());
+    test_engine.register_token("usd", usd);
+    test_engine.register_component("treasury", treasury);
+    assert_eq!(test_engine.balance_of("treasury", "usd"), dec!(100));
+    assert_ne!(*test_engine.get_account("default"), treasury);
+
+    test_engine.new_account("receiver");
+    test_engine
+        .transfer("receiver", "XRD", dec!(10))
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("receiver", "XRD"), dec!(10010));
+
+    // The source database is not written to
+    assert!(source_engine.try_get_account("receiver").is_err());
+    assert_eq!(source_engine.balance_of("treasury", "usd"), dec!(100));
+}
+
+#[test]
+fn test_from_database_without_faucet() {
+    let source_engine = TestEngine::new();
+    let faucet_key = SpreadPrefixKeyMapper::to_db_node_key(FAUCET.as_node_id());
+    let mut database = InMemorySubstateDatabase::standard();
+    for partition_key in source_engine.substate_db().list_partition_keys() {
+        if partition_key.node_key == faucet_key {
+            continue;
+        }
+        let entries = source_engine
+            .substate_db()
+            .list_entries(&partition_key)
+            .map(|(sort_key, value)| (sort_key, DatabaseUpdate::Set(value)))
+            .collect();
+        database.commit(&DatabaseUpdates::from_delta_maps(indexmap!(
+            partition_key => entries
+        )));
+    }
+
+    let mut test_engine = TestEngine::from_database(&database);
+    assert!(test_engine.is_self_funded());
+    assert!(test_engine.try_get_component("faucet").is_err());
+    assert_eq!(
+        test_engine.current_balance("XRD"),
+        SELF_FUNDED_XRD + dec!(10000)
+    );
+
+    test_engine.new_account("receiver");
+    test_engine
+        .transfer("receiver", "XRD", dec!(10))
+        .assert_is_success();
+    assert_eq!(test_engine.balance_of("receiver", "XRD"), dec!(10010));
+    assert!(test_engine.current_balance("XRD") < SELF_FUNDED_XRD + dec!(9990));
+}
+
+#[test]
+fn test_address_references() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    test_engine.new_account("receiver");
+    test_engine
+        .transfer("receiver", "usd", dec!(10))
+        .assert_is_success();
+
+    let receiver = *test_engine.get_account("receiver");
+    let usd = test_engine.get_resource("usd");
+    let global_receiver = GlobalAddress::from(receiver);
+    let global_usd = GlobalAddress::from(usd);
+    let typed_receiver: Global<AccountMarker> = Global::new(receiver);
+    let reference_receiver = Reference(receiver.into_node_id());
+    let reference_usd = Reference(usd.into_node_id());
+
+    assert_eq!(
+        test_engine.balance_of(global_receiver, global_usd),
+        dec!(10)
+    );
+    assert_eq!(test_engine.balance_of(typed_receiver, usd), dec!(10));
+    let (receiver_ref, usd_ref, typed_ref) = (&global_receiver, &global_usd, &typed_receiver);
+    assert_eq!(test_engine.balance_of(receiver_ref, usd_ref), dec!(10));
+    assert_eq!(test_engine.balance_of(typed_ref, usd), dec!(10));
+    assert_eq!(
+        test_engine.balance_of(reference_receiver, reference_usd),
+        dec!(10)
+    );
+
+    test_engine
+        .call_method_from(
+            global_receiver,
+            "try_deposit_or_abort",
+            env_args!(Fungible::Bucket("usd", 1), None::<u64>),
+        )
+        .assert_is_success();
+    test_engine
+        .call_method_from(
+            typed_receiver,
+            "try_deposit_or_abort",
+            env_args!(Fungible::Bucket("usd", 1), None::<u64>),
+        )
+        .assert_is_success();
+    test_engine
+        .call_method_from(
+            reference_receiver,
+            "try_deposit_or_abort",
+            env_args!(Fungible::Bucket("usd", 1), None::<u64>),
+        )
+        .assert_is_success();
+
+    assert_eq!(test_engine.balance_of("receiver", "usd"), dec!(13));
+
+    test_engine.register_component("typed receiver", typed_receiver);
+    test_engine.register_component("global receiver", global_receiver);
+    assert_eq!(test_engine.get_component("typed receiver"), receiver);
+    assert_eq!(test_engine.get_component("global receiver"), receiver);
+}
+
+#[test]
+#[should_panic(expected = "is not the address of a component")]
+fn test_resource_global_address_as_component() {
+    let mut test_engine = TestEngine::new();
+    test_engine.new_token("usd", 1000);
+    let usd = GlobalAddress::from(test_engine.get_resource("usd"));
+    test_engine.balance_of(usd, "usd");
+}
+
+fn new_bridge() -> MultiEngine {
+    let mut left = TestEngine::new();
+    left.new_resource("usd")
+        .burnable_by_all()
+        .supply(1000)
+        .create();
+    let mut right = TestEngine::new();
+    right.new_token("bridge badge", 1);
+    right
+        .new_resource("wrapped usd")
+        .mintable_by("bridge badge")
+        .create();
+
+    let mut engines = MultiEngine::new();
+    engines.add_engine("left", left).add_engine("right", right);
+    engines
+}
+
+#[test]
+fn test_multi_engine_bridge() {
+    let mut engines = new_bridge();
+
+    let (_, relayed) = engines.relay_events(
+        "left",
+        |engine| {
+            engine
+                .build_call()
+                .call_from_component("usd", "burn", env_args!(Fungible::Bucket("usd", 100)))
+                .execute()
+        },
+        "right",
+        |engine, event: BurnFungibleResourceEvent| {
+            engine
+                .build_call()
+                .call_from_component("wrapped usd", "mint", env_args!(event.amount))
+                .with_badge("bridge badge")
+                .execute()
+        },
+    );
+
+    assert_eq!(relayed.len(), 1);
+    assert_eq!(engines.engine_mut("left").current_balance("usd"), dec!(900));
+    assert_eq!(
+        engines.engine_mut("right").current_balance("wrapped usd"),
+        dec!(100)
+    );
+    assert!(engines.engine("right").try_get_resource("usd").is_err());
+}
+
+#[test]
+#[should_panic(expected = "There is no engine named middle")]
+fn test_multi_engine_unknown_engine() {
+    let engines = new_bridge();
+    engines.engine("middle");
+}
+
+#[test]
+fn test_multi_engine_shared_clock() {
+    let mut engines = new_bridge();
+    engines.engine_mut("left").advance_time(3600);
+    let mut late_engine = TestEngine::new();
+    late_engine.advance_time(60);
+    engines.add_engine("late", late_engine);
+
+    engines.advance_time(60);
+    let left_time = engines.engine_mut("left").get_current_time();
+    assert_eq!(engines.engine_mut("late").get_current_time(), left_time);
+    let right_time = engines.engine_mut("right").get_current_time();
+    assert_eq!(
+        left_time.seconds_since_unix_epoch - right_time.seconds_since_unix_epoch,
+        3600
+    );
+}
+
+#[test]
+fn test_state_field() {
+    let mut test_engine = TestEngine::new();
+
+    let vault: Own = test_engine.state_field("faucet", "vault");
+    assert!(test_engine.fungible_vault_balance(vault.0) > dec!(0));
+
+    let transactions = test_engine.kvs_of_field("faucet", "transactions");
+    assert!(transactions.0.is_internal_kv_store());
+    assert_eq!(
+        test_engine.get_kvs_value_at::<Hash, Epoch>(transactions, &Hash([0; 32])),
+        None
+    );
+}
+
+#[test]
+#[should_panic(
+    expected = "The state has no field named vaults. Its fields are: vault, transactions"
+)]
+fn test_state_field_unknown() {
+    let test_engine = TestEngine::new();
+    test_engine.kvs_of_field("faucet", "vaults");
+}
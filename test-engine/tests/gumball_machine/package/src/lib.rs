@@ -1,6 +1,13 @@
 use scrypto::prelude::*;
 
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct GumballBoughtEvent {
+    pub price: Decimal,
+    pub change: Decimal,
+}
+
 #[blueprint]
+#[events(GumballBoughtEvent)]
 mod gumball_machine {
     struct GumballMachine {
         gumballs: Vault,
@@ -33,6 +40,11 @@ mod gumball_machine {
             .globalize()
         }
 
+        // returns the price of a given number of gumballs, without needing a gumball machine
+        pub fn quote(price: Decimal, quantity: u64) -> Decimal {
+            price * quantity
+        }
+
         pub fn get_price(&self) -> Decimal {
             self.price
         }
@@ -40,6 +52,12 @@ mod gumball_machine {
         pub fn buy_gumball(&mut self, mut payment: Bucket) -> (Bucket, Bucket) {
             // take our price in XRD out of the payment
             // if the caller has sent too few, or sent something other than XRD, they'll get a runtime error
+            assert!(
+                payment.amount() >= self.price,
+                "Not enough XRD to buy a gumball: {} < {}",
+                payment.amount(),
+                self.price
+            );
             let our_share = payment.take(self.price);
             self.collected_xrd.put(our_share);
 
@@ -48,6 +66,11 @@ mod gumball_machine {
 
             // return a tuple containing a gumball, plus whatever change is left on the input payment (if any)
             // if we're out of gumballs to give, we'll see a runtime error when we try to grab one
+            Runtime::emit_event(GumballBoughtEvent {
+                price: self.price,
+                change: payment.amount(),
+            });
+
             (self.gumballs.take(1), payment)
         }
     }
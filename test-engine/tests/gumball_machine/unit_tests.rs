@@ -1,6 +1,19 @@
 mod gumball_machine_tests {
     use test_engine::prelude::*;
 
+    #[derive(ScryptoSbor, ScryptoEvent)]
+    struct GumballBoughtEvent {
+        price: Decimal,
+        change: Decimal,
+    }
+
+    #[derive(ScryptoSbor)]
+    struct GumballMachineState {
+        gumballs: Own,
+        collected_xrd: Own,
+        price: Decimal,
+    }
+
     global_package!(GUMBALL_PACKAGE, "tests/gumball_machine/package");
 
     fn instantiate_gumball() -> TestEngine {
@@ -24,6 +37,31 @@ mod gumball_machine_tests {
         assert_eq!(amount_owned, Decimal::one())
     }
 
+    #[test]
+    fn test_buy_gumball_with_faucet_xrd() {
+        let mut test_engine = instantiate_gumball();
+        test_engine.new_account("Buyer");
+        test_engine
+            .build_call()
+            .with_caller("Buyer")
+            .transfer("default", "XRD", dec!(10000))
+            .execute()
+            .assert_is_success();
+
+        test_engine
+            .build_call()
+            .with_caller("Buyer")
+            .call_from_component(
+                "gumball comp",
+                "buy_gumball",
+                env_args!(Fungible::from_faucet(dec!(5))),
+            )
+            .execute()
+            .assert_is_success();
+        assert_eq!(test_engine.balance_of("Buyer", "GUM"), Decimal::one());
+        assert_eq!(test_engine.balance_of("Buyer", "XRD"), dec!(9995));
+    }
+
     #[test]
     fn test_buy_gumball_fail() {
         let mut test_engine = instantiate_gumball();
@@ -34,6 +72,14 @@ mod gumball_machine_tests {
         assert_eq!(amount_owned, Decimal::zero())
     }
 
+    #[test]
+    fn test_buy_gumball_panics_with_message() {
+        let mut test_engine = instantiate_gumball();
+        test_engine
+            .call_method("buy_gumball", env_args!(Fungible::Bucket("XRD", 1)))
+            .assert_panicked_with("Not enough XRD to buy a gumball: 1 < 5");
+    }
+
     #[test]
     fn test_get_price() {
         let mut test_engine = instantiate_gumball();
@@ -41,4 +87,47 @@ mod gumball_machine_tests {
         let price: Decimal = receipt.get_return();
         assert_eq!(price, dec!(5));
     }
+
+    #[test]
+    fn test_call_function() {
+        let mut test_engine = TestEngine::with_package("gumball package", &GUMBALL_PACKAGE);
+        let price: Decimal = test_engine
+            .call_function("GumballMachine", "quote", env_args!(dec!(5), 3u64))
+            .get_return();
+        assert_eq!(price, dec!(15));
+
+        let price: Decimal = test_engine
+            .call_function_from(
+                "gumball package",
+                "GumballMachine",
+                "quote",
+                env_args!(dec!("2.5"), 2u64),
+            )
+            .get_return();
+        assert_eq!(price, dec!(5));
+    }
+
+    #[test]
+    fn test_buy_gumball_emits_event() {
+        let mut test_engine = instantiate_gumball();
+        let receipt =
+            test_engine.call_method("buy_gumball", env_args!(Fungible::Bucket("XRD", 10)));
+        receipt.assert_event_emitted(|event: &GumballBoughtEvent| {
+            event.price == dec!(5) && event.change == dec!(5)
+        });
+        assert_eq!(receipt.get_events::<GumballBoughtEvent>().len(), 1);
+    }
+
+    #[test]
+    fn test_set_price_in_state() {
+        let mut test_engine = instantiate_gumball();
+        test_engine.set_component_state("gumball comp", |state: &mut GumballMachineState| {
+            state.price = dec!(20)
+        });
+        test_engine
+            .call_method("buy_gumball", env_args!(Fungible::Bucket("XRD", 10)))
+            .assert_panicked_with("Not enough XRD to buy a gumball: 10 < 20");
+        let state: GumballMachineState = test_engine.get_component_state("gumball comp");
+        assert_eq!(state.price, dec!(20));
+    }
 }
@@ -40,5 +40,14 @@ mod hello {
             // In this case, a bucket containing 1 HelloToken is returned
             self.sample_vault.take(1)
         }
+
+        // This method reads the balance of the vault many times, which makes it expensive to run
+        pub fn count_tokens(&self, iterations: u64) -> Decimal {
+            let mut total = Decimal::zero();
+            for _ in 0..iterations {
+                total += self.sample_vault.amount();
+            }
+            total
+        }
     }
 }
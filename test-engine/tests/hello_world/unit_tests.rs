@@ -1,6 +1,10 @@
 mod hello_word_tests {
     use test_engine::prelude::*;
 
+    global_packages! {
+        HELLO_WORLD_PACKAGE => "tests/hello_world/package",
+    }
+
     #[test]
     fn test_free_token() {
         let mut test_engine = TestEngine::new();
@@ -11,4 +15,68 @@ mod hello_word_tests {
         let amount_owned = test_engine.current_balance("Hello Token");
         assert_eq!(amount_owned, Decimal::one())
     }
+
+    #[test]
+    fn test_free_token_from_compiled_package() {
+        let mut test_engine = TestEngine::new();
+        test_engine.new_package("hello world", &HELLO_WORLD_PACKAGE);
+        test_engine.new_component("hello_comp", "Hello", "instantiate_hello", env_args!());
+        test_engine
+            .call_method("free_token", env_args!())
+            .assert_is_success();
+        assert_eq!(test_engine.current_balance("Hello Token"), Decimal::one())
+    }
+
+    #[test]
+    fn test_memory_logger_records_application_logs() {
+        let mut test_engine = TestEngine::new();
+        test_engine.new_package("hello world", &HELLO_WORLD_PACKAGE);
+        test_engine.new_component("hello_comp", "Hello", "instantiate_hello", env_args!());
+        let logger = MemoryLogger::new();
+        test_engine.set_logger(logger.clone());
+
+        test_engine
+            .call_method_builder("free_token", env_args!())
+            .with_log_title("Free token")
+            .execute()
+            .assert_is_success();
+
+        assert!(logger.contains_log(Level::Info, "Now giving away a token!"));
+        assert!(!logger.contains_log(Level::Warn, "Now giving away a token!"));
+        assert_eq!(logger.titles(), vec!["Free token".to_string()]);
+    }
+
+    #[test]
+    fn test_expensive_loop_fails_with_cost_unit_limit() {
+        let mut test_engine = TestEngine::new();
+        test_engine.new_package("hello world", &HELLO_WORLD_PACKAGE);
+        test_engine.new_component("hello_comp", "Hello", "instantiate_hello", env_args!());
+        test_engine
+            .call_method_builder("count_tokens", env_args!(1_000_000u64))
+            .with_cost_unit_limit(10_000_000)
+            .execute()
+            .assert_failed_with("LimitExceeded");
+    }
+
+    #[test]
+    fn test_claim_package_royalties() {
+        let mut test_engine = TestEngine::new();
+        test_engine.new_token("owner badge", 1);
+        test_engine.new_package_with_royalties(
+            "hello world",
+            &HELLO_WORLD_PACKAGE,
+            "owner badge",
+            HashMap::from([("Hello", vec![("free_token", RoyaltyAmount::Xrd(dec!(1)))])]),
+        );
+        test_engine.new_component("hello_comp", "Hello", "instantiate_hello", env_args!());
+        test_engine
+            .call_method("free_token", env_args!())
+            .assert_is_success();
+
+        let balance = test_engine.current_balance("XRD");
+        test_engine
+            .claim_package_royalties("hello world", "owner badge")
+            .assert_is_success();
+        assert_eq!(test_engine.current_balance("XRD"), balance + dec!(1));
+    }
 }
@@ -1,5 +1,12 @@
+mod address_reservation;
+mod factory;
 mod general;
 mod gumball_machine;
 mod hello_world;
+mod minter;
 mod nft_marketplace;
+mod options;
+mod proofs;
 mod radiswap;
+mod roles;
+mod split_vaults;
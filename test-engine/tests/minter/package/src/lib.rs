@@ -0,0 +1,30 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod minter {
+    struct Minter {
+        minter_badge: Vault,
+        resource: ResourceAddress,
+    }
+
+    impl Minter {
+        pub fn instantiate_minter(minter_badge: Bucket, resource: ResourceAddress) -> Global<Minter> {
+            Self {
+                minter_badge: Vault::with_bucket(minter_badge),
+                resource,
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        // Mints the given amount of a resource created outside of the package
+        pub fn mint(&mut self, amount: Decimal) -> Bucket {
+            let resource_manager = FungibleResourceManager::from(self.resource);
+            self.minter_badge
+                .as_fungible()
+                .authorize_with_amount(dec!(1), || resource_manager.mint(amount))
+                .into()
+        }
+    }
+}
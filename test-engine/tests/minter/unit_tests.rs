@@ -0,0 +1,47 @@
+mod minter_tests {
+    use test_engine::prelude::*;
+
+    global_package!(MINTER_PACKAGE, "tests/minter/package");
+
+    fn instantiate_minter() -> TestEngine {
+        let mut test_engine = TestEngine::with_package("minter package", &MINTER_PACKAGE);
+        test_engine.new_token_with_metadata(
+            "minter badge",
+            1,
+            0,
+            vec![("name", MetadataValue::String("Minter badge".to_string()))],
+        );
+        test_engine.new_mintable_token("usd", 1000, 6, "minter badge");
+        let usd = test_engine.get_resource("usd");
+        test_engine.new_component(
+            "minter comp",
+            "Minter",
+            "instantiate_minter",
+            env_args!(Fungible::Bucket("minter badge", 1), usd),
+        );
+        test_engine
+    }
+
+    #[test]
+    fn test_mint_external_resource() {
+        let mut test_engine = instantiate_minter();
+
+        test_engine
+            .call_method("mint", env_args!(dec!(500)))
+            .assert_is_success();
+
+        assert_eq!(test_engine.total_supply("usd"), Some(dec!(1500)));
+        assert_eq!(test_engine.current_balance("usd"), dec!(1500));
+    }
+
+    #[test]
+    fn test_mint_respects_divisibility() {
+        let mut test_engine = instantiate_minter();
+
+        test_engine
+            .call_method("mint", env_args!(dec!("0.0000001")))
+            .assert_failed_with("InvalidAmount");
+
+        assert_eq!(test_engine.total_supply("usd"), Some(dec!(1000)));
+    }
+}
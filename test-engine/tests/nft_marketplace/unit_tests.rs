@@ -89,6 +89,16 @@ mod nft_marketplace_tests {
         ]).assert_failed_with("[Buy]: Invalid quantity was provided. This sale can only go through when 8.5 tokens are provided.");
     }
 
+    #[test]
+    fn test_buy_not_enough_fails_with_amount_dutch_auction() {
+        let mut test_engine = init_dutch_auction();
+        new_buyer(&mut test_engine, "buyer");
+        test_engine.jump_epochs(3);
+        test_engine
+            .call_method("buy", env_args![Fungible::Bucket("xrd", 5)])
+            .assert_failed_containing_amount(dec!("8.50"));
+    }
+
     #[test]
     fn test_cancel_sale() {
         let mut test_engine = init_dutch_auction();
@@ -96,4 +106,47 @@ mod nft_marketplace_tests {
         let nfts_owned = test_engine.current_balance("cars nft");
         assert_eq!(nfts_owned, dec!(4));
     }
+
+    #[test]
+    fn test_increase_bid_english_auction() {
+        let mut test_engine = bootstrap();
+        let car_id = test_engine.current_ids_balance("cars nft").pop();
+        test_engine.new_component(
+            "english_auction",
+            "EnglishAuction",
+            "instantiate_english_auction",
+            env_args![
+                env_vec![NonFungible::Bucket("cars nft", vec![car_id.unwrap()])],
+                Environment::Resource("xrd"),
+                10u64
+            ],
+        );
+        test_engine.set_current_component("english auction");
+        new_buyer(&mut test_engine, "bidder");
+        test_engine
+            .call_method("bid", env_args![Fungible::Bucket("xrd", 10)])
+            .assert_is_success();
+        let badge_id = test_engine
+            .current_ids_balance("Bidder Badge")
+            .pop()
+            .unwrap();
+        let bid_amount: Decimal =
+            test_engine.get_nf_field("Bidder Badge", badge_id.clone(), "bid_amount");
+        assert_eq!(bid_amount, dec!(10));
+
+        test_engine
+            .call_method(
+                "increase_bid",
+                env_args![
+                    Fungible::Bucket("xrd", 5),
+                    NonFungible::Proof("Bidder Badge", vec![badge_id.clone()])
+                ],
+            )
+            .assert_is_success();
+        let bid_amount: Decimal =
+            test_engine.get_nf_field("Bidder Badge", badge_id.clone(), "bid_amount");
+        let is_winner: bool = test_engine.get_nf_field("Bidder Badge", badge_id, "is_winner");
+        assert_eq!(bid_amount, dec!(15));
+        assert!(!is_winner);
+    }
 }
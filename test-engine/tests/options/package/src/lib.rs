@@ -0,0 +1,59 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod options {
+    struct Options {
+        vaults: KeyValueStore<ResourceAddress, Vault>,
+    }
+
+    impl Options {
+        pub fn instantiate() -> Global<Options> {
+            Self {
+                vaults: KeyValueStore::new(),
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        // Returns the amount deposited, zero if there is no bucket
+        pub fn deposit(&mut self, bucket: Option<Bucket>) -> Decimal {
+            match bucket {
+                Some(bucket) => self.store(bucket),
+                None => Decimal::ZERO,
+            }
+        }
+
+        pub fn deposit_all(&mut self, buckets: Option<Vec<Bucket>>) -> Decimal {
+            buckets
+                .unwrap_or_default()
+                .into_iter()
+                .map(|bucket| self.store(bucket))
+                .fold(Decimal::ZERO, |total, amount| total + amount)
+        }
+
+        pub fn resource_or_xrd(&self, resource: Option<ResourceAddress>) -> ResourceAddress {
+            resource.unwrap_or(XRD)
+        }
+
+        // Deposits the bucket of an Ok, or panics with the message of an Err
+        pub fn deposit_result(&mut self, result: Result<Bucket, String>) -> Decimal {
+            match result {
+                Ok(bucket) => self.store(bucket),
+                Err(message) => panic!("{}", message),
+            }
+        }
+
+        fn store(&mut self, bucket: Bucket) -> Decimal {
+            let amount = bucket.amount();
+            let resource = bucket.resource_address();
+            let existing = self.vaults.get_mut(&resource).is_some();
+            if existing {
+                self.vaults.get_mut(&resource).unwrap().put(bucket);
+            } else {
+                self.vaults.insert(resource, Vault::with_bucket(bucket));
+            }
+            amount
+        }
+    }
+}
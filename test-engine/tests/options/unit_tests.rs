@@ -0,0 +1,156 @@
+mod options_tests {
+    use test_engine::prelude::*;
+
+    global_package!(OPTIONS_PACKAGE, "tests/options/package");
+
+    fn initialize() -> TestEngine {
+        let mut test_engine = TestEngine::with_package("options package", &OPTIONS_PACKAGE);
+        test_engine.new_token("usd", 1000);
+        test_engine.new_component("options", "Options", "instantiate", env_args!());
+        test_engine
+    }
+
+    #[test]
+    fn test_some_bucket() {
+        let mut test_engine = initialize();
+        let amount: Decimal = test_engine
+            .call_method("deposit", env_args!(env_some!(Fungible::Bucket("usd", 10))))
+            .assert_is_success()
+            .get_return();
+        assert_eq!(amount, dec!(10));
+        assert_eq!(test_engine.balance_of("options", "usd"), dec!(10));
+    }
+
+    #[test]
+    fn test_vaults_field() {
+        let mut test_engine = initialize();
+        test_engine
+            .call_method("deposit", env_args!(env_some!(Fungible::Bucket("usd", 10))))
+            .assert_is_success();
+
+        let vaults = test_engine.kvs_of_field("options", "vaults");
+        let usd = test_engine.get_resource("usd");
+        let vault: Own = test_engine
+            .get_kvs_value_at(vaults, &usd)
+            .expect("No vault of usd");
+        assert_eq!(test_engine.fungible_vault_balance(vault.0), dec!(10));
+    }
+
+    #[test]
+    fn test_none_bucket() {
+        let mut test_engine = initialize();
+        let amount: Decimal = test_engine
+            .call_method("deposit", env_args!(none!()))
+            .assert_is_success()
+            .get_return();
+        assert_eq!(amount, dec!(0));
+    }
+
+    #[test]
+    fn test_option_from_some() {
+        let mut test_engine = initialize();
+        let amount: Decimal = test_engine
+            .call_method(
+                "deposit",
+                env_args!(EnvOption::from(Some(Fungible::Bucket("usd", 5)))),
+            )
+            .assert_is_success()
+            .get_return();
+        assert_eq!(amount, dec!(5));
+    }
+
+    #[test]
+    fn test_option_of_resource_address() {
+        let mut test_engine = initialize();
+        let resource: ResourceAddress = test_engine
+            .call_method(
+                "resource_or_xrd",
+                env_args!(env_some!(Environment::Resource("usd"))),
+            )
+            .get_return();
+        assert_eq!(resource, test_engine.get_resource("usd"));
+
+        let resource: ResourceAddress = test_engine
+            .call_method("resource_or_xrd", env_args!(none!()))
+            .get_return();
+        assert_eq!(resource, XRD);
+    }
+
+    #[test]
+    fn test_option_of_bucket_vec() {
+        let mut test_engine = initialize();
+        let amount: Decimal = test_engine
+            .call_method(
+                "deposit_all",
+                env_args!(env_some!(env_vec![
+                    Fungible::Bucket("usd", 10),
+                    Fungible::Bucket("xrd", 20)
+                ])),
+            )
+            .assert_is_success()
+            .get_return();
+        assert_eq!(amount, dec!(30));
+
+        let amount: Decimal = test_engine
+            .call_method("deposit_all", env_args!(none!()))
+            .get_return();
+        assert_eq!(amount, dec!(0));
+    }
+
+    #[test]
+    fn test_result_arguments() {
+        let mut test_engine = initialize();
+        let amount: Decimal = test_engine
+            .call_method(
+                "deposit_result",
+                env_args!(EnvResult::Ok(Box::new(Fungible::Bucket("usd", 10)))),
+            )
+            .assert_is_success()
+            .get_return();
+        assert_eq!(amount, dec!(10));
+
+        test_engine
+            .call_method(
+                "deposit_result",
+                env_args!(EnvResult::Err(Box::new("No bucket".to_string()))),
+            )
+            .assert_panicked_with("No bucket");
+    }
+
+    #[test]
+    fn test_writes_of_new_and_existing_entries() {
+        let mut test_engine = initialize();
+
+        // The first deposit of a resource inserts an entry and creates its vault
+        let first = test_engine
+            .call_method("deposit", env_args!(env_some!(Fungible::Bucket("usd", 10))))
+            .assert_is_success();
+        let first_stats = first.state_update_summary();
+        assert!(first_stats.user.writes() >= 1);
+        assert!(first_stats.system.created > 0);
+
+        // The next deposits only rewrite the existing entry
+        let second = test_engine
+            .call_method("deposit", env_args!(env_some!(Fungible::Bucket("usd", 10))))
+            .assert_is_success();
+        let second_stats = second.state_update_summary();
+        assert_eq!(second_stats.user.writes(), first_stats.user.writes());
+        assert_eq!(second_stats.total().created, 0);
+        test_engine.assert_max_writes(&second, second_stats.user.writes());
+
+        let read_only = test_engine
+            .call_method("resource_or_xrd", env_args!(none!()))
+            .assert_is_success();
+        test_engine.assert_max_writes(&read_only, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "user substates, which exceeds the maximum of 0")]
+    fn test_max_writes_exceeded() {
+        let mut test_engine = initialize();
+        let receipt = test_engine
+            .call_method("deposit", env_args!(env_some!(Fungible::Bucket("usd", 10))))
+            .assert_is_success();
+        test_engine.assert_max_writes(&receipt, 0);
+    }
+}
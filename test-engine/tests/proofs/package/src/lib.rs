@@ -0,0 +1,51 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod gate {
+    struct Gate {
+        member_badge: ResourceAddress,
+        first_badge: ResourceAddress,
+        second_badge: ResourceAddress,
+        entries: u64,
+    }
+
+    impl Gate {
+        pub fn instantiate(
+            member_badge: ResourceAddress,
+            first_badge: ResourceAddress,
+            second_badge: ResourceAddress,
+        ) -> Global<Gate> {
+            Self {
+                member_badge,
+                first_badge,
+                second_badge,
+                entries: 0,
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        // Checks every proof and returns the number of member proofs
+        pub fn enter(
+            &mut self,
+            member_proofs: Vec<Proof>,
+            first_proof: Proof,
+            second_proof: Proof,
+        ) -> usize {
+            let members = member_proofs.len();
+            for proof in member_proofs {
+                proof.check_with_message(self.member_badge, "Invalid member proof");
+            }
+            first_proof.check_with_message(self.first_badge, "Invalid first proof");
+            second_proof.check_with_message(self.second_badge, "Invalid second proof");
+
+            self.entries += 1;
+            members
+        }
+
+        pub fn entries(&self) -> u64 {
+            self.entries
+        }
+    }
+}
@@ -0,0 +1,79 @@
+mod proofs_tests {
+    use test_engine::prelude::*;
+
+    global_package!(PROOFS_PACKAGE, "tests/proofs/package");
+
+    fn initialize() -> TestEngine {
+        let mut test_engine = TestEngine::with_package("proofs package", &PROOFS_PACKAGE);
+        test_engine.new_token("member badge", 2);
+        test_engine.new_token("first badge", 1);
+        test_engine.new_token("second badge", 1);
+        test_engine.new_component(
+            "gate",
+            "Gate",
+            "instantiate",
+            env_args!(
+                Environment::Resource("member badge"),
+                Environment::Resource("first badge"),
+                Environment::Resource("second badge")
+            ),
+        );
+        test_engine
+    }
+
+    #[test]
+    fn test_proofs_in_vec_and_separate_arguments() {
+        let mut test_engine = initialize();
+        let members: usize = test_engine
+            .call_method(
+                "enter",
+                env_args!(
+                    env_vec!(
+                        Fungible::Proof("member badge", 1),
+                        Fungible::Proof("member badge", 1)
+                    ),
+                    Fungible::Proof("first badge", 1),
+                    Fungible::Proof("second badge", 1)
+                ),
+            )
+            .assert_is_success()
+            .get_return();
+        assert_eq!(members, 2);
+
+        let entries: u64 = test_engine.call_method("entries", env_args!()).get_return();
+        assert_eq!(entries, 1);
+    }
+
+    #[test]
+    fn test_proof_arguments_keep_their_order() {
+        let mut test_engine = initialize();
+        test_engine
+            .call_method(
+                "enter",
+                env_args!(
+                    env_vec!(Fungible::Proof("member badge", 1)),
+                    Fungible::Proof("second badge", 1),
+                    Fungible::Proof("first badge", 1)
+                ),
+            )
+            .assert_panicked_with("Invalid first proof");
+    }
+
+    #[test]
+    fn test_wrong_proof_in_vec() {
+        let mut test_engine = initialize();
+        test_engine
+            .call_method(
+                "enter",
+                env_args!(
+                    env_vec!(
+                        Fungible::Proof("member badge", 1),
+                        Fungible::Proof("first badge", 1)
+                    ),
+                    Fungible::Proof("first badge", 1),
+                    Fungible::Proof("second badge", 1)
+                ),
+            )
+            .assert_panicked_with("Invalid member proof");
+    }
+}
@@ -3,6 +3,19 @@ mod radiswap_tests {
 
     global_package!(RADISWAP_PACKAGE, "tests/radiswap/package");
 
+    component_proxy!(Radiswap {
+        fn add_liquidity(
+            resource1: Fungible<&'static str, Decimal>,
+            resource2: Fungible<&'static str, Decimal>,
+        );
+        fn remove_liquidity(pool_units: impl EnvironmentEncode + 'static);
+        fn swap(input_bucket: Fungible<&'static str, Decimal>);
+    });
+
+    component_proxy!(RenamedRadiswap for Radiswap {
+        fn swap_tokens(input_bucket: Fungible<&'static str, Decimal>);
+    });
+
     fn initialize() -> TestEngine {
         let mut test_engine = TestEngine::with_package("radiswap package", &RADISWAP_PACKAGE);
         test_engine.new_token("usd", dec!(100000));
@@ -36,6 +49,49 @@ mod radiswap_tests {
         assert_eq!(btc_amount, dec!(99));
     }
 
+    #[test]
+    fn test_pool_balances_after_add_liquidity() {
+        let mut test_engine = TestEngine::with_package("radiswap package", &RADISWAP_PACKAGE);
+        test_engine.new_token("usd", dec!(100000));
+        test_engine.new_token("btc", dec!(100));
+        let receipt = test_engine.new_component(
+            "radiswap",
+            "Radiswap",
+            "new",
+            env_args!(
+                OwnerRole::None,
+                Environment::Resource("usd"),
+                Environment::Resource("btc")
+            ),
+        );
+        // The reserves are held by the pool component instantiated by Radiswap.
+        let radiswap = test_engine.get_component("radiswap");
+        let commit = receipt.expect_commit_success();
+        let pool = *commit
+            .new_component_addresses()
+            .iter()
+            .find(|component| **component != radiswap)
+            .unwrap();
+        let pool_unit = commit.new_resource_addresses()[0];
+
+        test_engine.call_method(
+            "add_liquidity",
+            env_args!(
+                Fungible::Bucket("usd", dec!(1000)),
+                Fungible::Bucket("btc", dec!(1))
+            ),
+        );
+
+        assert_eq!(test_engine.balance_of(pool, "usd"), dec!(1000));
+        assert_eq!(test_engine.balance_of(pool, "btc"), dec!(1));
+        assert_eq!(test_engine.vaults_of(pool, "usd"), vec![dec!(1000)]);
+        assert_eq!(test_engine.balance_of(radiswap, "usd"), dec!(0));
+        assert_eq!(
+            test_engine.total_supply(pool_unit),
+            Some(test_engine.current_balance(pool_unit))
+        );
+    }
+
     #[test]
     fn test_swap() {
         let mut test_engine = initialize();
@@ -52,4 +108,247 @@ mod radiswap_tests {
         assert_eq!(usd_amount, dec!(98000));
         assert_eq!(btc_amount, dec!("99.5"));
     }
+
+    fn snapshot_swap(usd_amount: Decimal) -> ReceiptSnapshot {
+        let mut test_engine = initialize();
+        test_engine.call_method(
+            "add_liquidity",
+            env_args!(
+                Fungible::Bucket("usd", dec!(1000)),
+                Fungible::Bucket("btc", dec!(1))
+            ),
+        );
+        test_engine.set_execution_trace(true);
+        test_engine
+            .call_method("swap", env_args!(Fungible::Bucket("usd", usd_amount)))
+            .to_snapshot(&test_engine)
+    }
+
+    #[test]
+    fn test_swap_snapshot() {
+        let path = std::env::temp_dir()
+            .join(format!("radiswap-snapshot-{}", std::process::id()))
+            .join("swap.json");
+        let _ = std::fs::remove_file(&path);
+
+        snapshot_swap(dec!(1000)).assert_matches_snapshot(&path);
+        snapshot_swap(dec!(1000)).assert_matches_snapshot(&path);
+
+        let mismatch = std::panic::catch_unwind(|| {
+            snapshot_swap(dec!(500)).assert_matches_snapshot(&path);
+        })
+        .unwrap_err();
+        let message = mismatch.downcast_ref::<String>().unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert!(message.contains("-     \"default: -1000 usd\""));
+        assert!(message.contains("+     \"default: -500 usd\""));
+    }
+
+    #[test]
+    fn test_swap_scenario() {
+        let mut test_engine = TestEngine::with_package("radiswap package", &RADISWAP_PACKAGE);
+
+        Scenario::new()
+            .account("A")
+            .account("C")
+            .step(Step::NewToken {
+                name: "usd",
+                supply: dec!(100000),
+            })
+            .step(Step::NewToken {
+                name: "btc",
+                supply: dec!(100),
+            })
+            .step(Step::Transfer {
+                from: "default",
+                to: "A",
+                resource: "usd",
+                amount: dec!(1000),
+            })
+            .step(Step::Transfer {
+                from: "default",
+                to: "A",
+                resource: "btc",
+                amount: dec!(1),
+            })
+            .step(Step::Transfer {
+                from: "default",
+                to: "C",
+                resource: "usd",
+                amount: dec!(1000),
+            })
+            .step(Step::NewComponent {
+                caller: "A",
+                name: "radiswap",
+                blueprint: "Radiswap",
+                function: "new",
+                args: env_args!(
+                    OwnerRole::None,
+                    Environment::Resource("usd"),
+                    Environment::Resource("btc")
+                ),
+                badge: None,
+            })
+            .step(Step::Call {
+                caller: "A",
+                component: "radiswap",
+                method: "add_liquidity",
+                args: env_args!(
+                    Fungible::Bucket("usd", dec!(1000)),
+                    Fungible::Bucket("btc", dec!(1))
+                ),
+                badge: None,
+            })
+            .step(Step::Call {
+                caller: "C",
+                component: "radiswap",
+                method: "swap",
+                args: env_args!(Fungible::Bucket("usd", dec!(1000))),
+                badge: None,
+            })
+            .assert(Assert::Balance {
+                account: "C",
+                resource: "btc",
+                op: Comparison::Eq,
+                value: dec!("0.5"),
+            })
+            .assert(Assert::Balance {
+                account: "C",
+                resource: "usd",
+                op: Comparison::Eq,
+                value: dec!(0),
+            })
+            .assert_run(&mut test_engine);
+    }
+
+    #[test]
+    fn test_swap_returned_resources() {
+        let mut test_engine = initialize();
+        test_engine.call_method(
+            "add_liquidity",
+            env_args!(
+                Fungible::Bucket("usd", dec!(1000)),
+                Fungible::Bucket("btc", dec!(1))
+            ),
+        );
+        let returned = test_engine
+            .call_method_builder("swap", env_args!(Fungible::Bucket("usd", dec!(1000))))
+            .with_execution_trace()
+            .execute()
+            .assert_is_success()
+            .get_return_resources();
+
+        let btc = test_engine.get_resource("btc");
+        assert_eq!(returned.fungibles.get(&btc), Some(&dec!("0.5")));
+        assert_eq!(returned.fungibles.len(), 1);
+    }
+
+    #[test]
+    fn test_swap_with_proxy() {
+        let mut test_engine = initialize();
+        let radiswap: Radiswap = test_engine.proxy("radiswap", &*RADISWAP_PACKAGE);
+
+        radiswap
+            .add_liquidity(
+                &mut test_engine,
+                Fungible::Bucket("usd", dec!(1000)),
+                Fungible::Bucket("btc", dec!(1)),
+            )
+            .assert_is_success();
+        radiswap
+            .swap(&mut test_engine, Fungible::Bucket("usd", dec!(1000)))
+            .assert_is_success();
+
+        assert_eq!(test_engine.current_balance("usd"), dec!(98000));
+        assert_eq!(test_engine.current_balance("btc"), dec!("99.5"));
+    }
+
+    #[test]
+    #[should_panic(expected = "- swap_tokens: no such method")]
+    fn test_proxy_with_renamed_method() {
+        let test_engine = initialize();
+        let _radiswap: RenamedRadiswap = test_engine.proxy("radiswap", &*RADISWAP_PACKAGE);
+    }
+
+    #[cfg(feature = "proptest")]
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        #[derive(ScryptoSbor)]
+        struct RadiswapState {
+            pool_component: ComponentAddress,
+        }
+
+        fn fixture() -> TestEngine {
+            let mut test_engine = TestEngine::with_package("radiswap package", &RADISWAP_PACKAGE);
+            test_engine.new_token("usd", dec!(10000000));
+            test_engine.new_token("btc", dec!(10000));
+            test_engine.new_component(
+                "radiswap",
+                "Radiswap",
+                "new",
+                env_args!(
+                    OwnerRole::None,
+                    Environment::Resource("usd"),
+                    Environment::Resource("btc")
+                ),
+            );
+            test_engine
+                .call_method(
+                    "add_liquidity",
+                    env_args!(
+                        Fungible::Bucket("usd", dec!(10000)),
+                        Fungible::Bucket("btc", dec!(10))
+                    ),
+                )
+                .assert_is_success();
+            test_engine
+        }
+
+        fn operations() -> MethodCallStrategy {
+            MethodCallStrategy::new()
+                .method("swap", decimal_in(dec!(1)..=dec!(500)), |amount| {
+                    env_args!(Fungible::Bucket("usd", amount))
+                })
+                .method("swap", decimal_in(dec!("0.001")..=dec!("0.5")), |amount| {
+                    env_args!(Fungible::Bucket("btc", amount))
+                })
+                .method(
+                    "add_liquidity",
+                    (
+                        decimal_in(dec!(1)..=dec!(1000)),
+                        decimal_in(dec!("0.001")..=dec!(1)),
+                    ),
+                    |(usd, btc)| {
+                        env_args!(Fungible::Bucket("usd", usd), Fungible::Bucket("btc", btc))
+                    },
+                )
+        }
+
+        fn k(test_engine: &mut TestEngine, pool: ComponentAddress) -> PreciseDecimal {
+            PreciseDecimal::from(test_engine.balance_of(pool, "usd"))
+                * PreciseDecimal::from(test_engine.balance_of(pool, "btc"))
+        }
+
+        #[test]
+        fn test_k_never_decreases() {
+            let fixture = fixture();
+            let state: RadiswapState = fixture.get_component_state("radiswap");
+            let pool = state.pool_component;
+
+            proptest!(ProptestConfig::with_cases(10), |(calls in prop::collection::vec(operations(), 100))| {
+                // Every case starts from the same fixture without republishing the package.
+                let mut test_engine = fixture.fork();
+                let mut previous_k = k(&mut test_engine, pool);
+                for call in calls {
+                    call.call(&mut test_engine).assert_is_success();
+                    let new_k = k(&mut test_engine, pool);
+                    prop_assert!(new_k >= previous_k, "k decreased after {:?}", call);
+                    previous_k = new_k;
+                }
+            });
+        }
+    }
 }
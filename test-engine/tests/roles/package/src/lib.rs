@@ -0,0 +1,62 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod roles {
+    enable_method_auth! {
+        roles {
+            admin => updatable_by: [OWNER];
+        },
+        methods {
+            protected => restrict_to: [admin];
+            calls => PUBLIC;
+            premium => PUBLIC;
+        }
+    }
+
+    struct Roles {
+        calls: u64,
+    }
+
+    impl Roles {
+        pub fn instantiate_roles() -> (Global<Roles>, Bucket) {
+            let owner_badge = ResourceBuilder::new_fungible(OwnerRole::None)
+                .divisibility(DIVISIBILITY_NONE)
+                .metadata(metadata!(
+                    init {
+                        "name" => "Owner badge", locked;
+                    }
+                ))
+                .mint_initial_supply(1);
+            let owner_rule = rule!(require(owner_badge.resource_address()));
+
+            let component = Self { calls: 0 }
+                .instantiate()
+                .prepare_to_globalize(OwnerRole::Fixed(owner_rule.clone()))
+                .roles(roles!(
+                    admin => owner_rule;
+                ))
+                .enable_component_royalties(component_royalties! {
+                    init {
+                        protected => Free, locked;
+                        calls => Free, locked;
+                        premium => Xrd(1.into()), updatable;
+                    }
+                })
+                .globalize();
+
+            (component, owner_badge.into())
+        }
+
+        pub fn protected(&mut self) {
+            self.calls += 1;
+        }
+
+        pub fn premium(&mut self) {
+            self.calls += 1;
+        }
+
+        pub fn calls(&self) -> u64 {
+            self.calls
+        }
+    }
+}
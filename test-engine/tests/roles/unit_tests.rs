@@ -0,0 +1,102 @@
+mod roles_tests {
+    use test_engine::prelude::*;
+
+    global_package!(ROLES_PACKAGE, "tests/roles/package");
+
+    fn instantiate_roles() -> TestEngine {
+        let mut test_engine = TestEngine::with_package("roles package", &ROLES_PACKAGE);
+        test_engine.new_component("roles comp", "Roles", "instantiate_roles", env_args!());
+        test_engine
+    }
+
+    #[test]
+    fn test_set_role_to_allow_all() {
+        let mut test_engine = instantiate_roles();
+
+        test_engine
+            .call_method("protected", env_args!())
+            .assert_failed_with_auth_error();
+
+        test_engine
+            .set_role("roles comp", "admin", rule!(allow_all), "Owner badge")
+            .assert_is_success();
+        test_engine
+            .call_method("protected", env_args!())
+            .assert_is_success();
+    }
+
+    #[test]
+    fn test_set_role_to_another_badge() {
+        let mut test_engine = instantiate_roles();
+        test_engine.new_token("Admin badge", 1);
+
+        let rule = test_engine.require_badge("Admin badge");
+        test_engine
+            .set_role("roles comp", "admin", rule, "Owner badge")
+            .assert_is_success();
+        test_engine
+            .call_method_with_badge("protected", "Owner badge", env_args!())
+            .assert_failed_with_auth_error();
+        test_engine
+            .call_method_with_badge("protected", "Admin badge", env_args!())
+            .assert_is_success();
+    }
+
+    #[test]
+    fn test_set_role_without_badge() {
+        let mut test_engine = instantiate_roles();
+
+        test_engine
+            .build_call()
+            .set_role("roles comp", "admin", rule!(allow_all))
+            .execute()
+            .assert_failed_with_auth_error();
+    }
+
+    #[test]
+    fn test_claim_component_royalties() {
+        let mut test_engine = instantiate_roles();
+        test_engine.new_account("customer");
+        test_engine.set_current_account("customer");
+        test_engine
+            .call_method("premium", env_args!())
+            .assert_is_success();
+        assert_eq!(test_engine.component_royalty_balance("roles comp"), dec!(1));
+
+        test_engine
+            .claim_component_royalties("roles comp", "Owner badge")
+            .assert_failed_with_auth_error();
+
+        test_engine.set_current_account("default");
+        let balance = test_engine.current_balance("XRD");
+        test_engine
+            .claim_component_royalties("roles comp", "Owner badge")
+            .assert_is_success();
+        assert_eq!(test_engine.current_balance("XRD"), balance + dec!(1));
+        assert_eq!(test_engine.component_royalty_balance("roles comp"), dec!(0));
+    }
+
+    #[test]
+    fn test_set_component_royalty() {
+        let mut test_engine = instantiate_roles();
+
+        test_engine
+            .set_component_royalty(
+                "roles comp",
+                "premium",
+                RoyaltyAmount::Xrd(dec!(3)),
+                "Owner badge",
+            )
+            .assert_is_success();
+        test_engine
+            .call_method("premium", env_args!())
+            .assert_is_success();
+        assert_eq!(test_engine.component_royalty_balance("roles comp"), dec!(3));
+
+        test_engine
+            .build_call()
+            .set_component_royalty("roles comp", "premium", RoyaltyAmount::Free)
+            .execute()
+            .assert_failed_with_auth_error();
+    }
+}
@@ -0,0 +1,26 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod split_vaults {
+    struct SplitVaults {
+        first_vault: Vault,
+        second_vault: Vault,
+    }
+
+    impl SplitVaults {
+        // Puts the given amount of the bucket in a first vault and the rest in a second one
+        pub fn instantiate_split_vaults(
+            mut bucket: Bucket,
+            first_amount: Decimal,
+        ) -> Global<SplitVaults> {
+            let first_bucket = bucket.take(first_amount);
+            Self {
+                first_vault: Vault::with_bucket(first_bucket),
+                second_vault: Vault::with_bucket(bucket),
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+    }
+}
@@ -0,0 +1,129 @@
+mod split_vaults_tests {
+    use test_engine::prelude::*;
+
+    global_package!(SPLIT_VAULTS_PACKAGE, "tests/split_vaults/package");
+
+    #[derive(ScryptoSbor, ManifestSbor)]
+    struct Ticket {
+        seat: u64,
+    }
+
+    impl NonFungibleData for Ticket {
+        const MUTABLE_FIELDS: &'static [&'static str] = &[];
+    }
+
+    #[derive(ScryptoSbor)]
+    struct SplitVaultsState {
+        first_vault: Own,
+        second_vault: Own,
+    }
+
+    #[test]
+    fn test_fungible_balance_over_several_vaults() {
+        let mut test_engine =
+            TestEngine::with_package("split vaults package", &SPLIT_VAULTS_PACKAGE);
+        test_engine.new_token("usd", 1000);
+        test_engine.new_component(
+            "split vaults",
+            "SplitVaults",
+            "instantiate_split_vaults",
+            env_args!(Fungible::Bucket("usd", 300), dec!(100)),
+        );
+
+        assert_eq!(test_engine.balance_of("split vaults", "usd"), dec!(300));
+        let mut vaults = test_engine.vaults_of("split vaults", "usd");
+        vaults.sort();
+        assert_eq!(vaults, vec![dec!(100), dec!(200)]);
+    }
+
+    #[test]
+    fn test_non_fungible_ids_over_several_vaults() {
+        let mut test_engine =
+            TestEngine::with_package("split vaults package", &SPLIT_VAULTS_PACKAGE);
+        test_engine.new_nft_collection::<Ticket, _>("tickets", NonFungibleIdType::Integer);
+        test_engine.mint_nfts(
+            "tickets",
+            vec![
+                (1, Ticket { seat: 1 }),
+                (2, Ticket { seat: 2 }),
+                (3, Ticket { seat: 3 }),
+            ],
+        );
+        test_engine.new_component(
+            "split vaults",
+            "SplitVaults",
+            "instantiate_split_vaults",
+            env_args!(NonFungible::Bucket("tickets", nf_ids![1, 2, 3]), dec!(1)),
+        );
+
+        let mut ids = test_engine.ids_balance_of("split vaults", "tickets");
+        ids.sort();
+        assert_eq!(ids, nf_ids![1, 2, 3]);
+        let mut vaults = test_engine.vaults_of("split vaults", "tickets");
+        vaults.sort();
+        assert_eq!(vaults, vec![dec!(1), dec!(2)]);
+        assert_eq!(test_engine.total_supply("tickets"), Some(dec!(3)));
+    }
+
+    #[test]
+    fn test_balance_of_each_vault() {
+        let mut test_engine =
+            TestEngine::with_package("split vaults package", &SPLIT_VAULTS_PACKAGE);
+        test_engine.new_token("usd", 1000);
+        test_engine.new_component(
+            "split vaults",
+            "SplitVaults",
+            "instantiate_split_vaults",
+            env_args!(Fungible::Bucket("usd", 300), dec!(100)),
+        );
+
+        let state: SplitVaultsState = test_engine.get_component_state("split vaults");
+        let first_vault = state.first_vault.0;
+        let second_vault = state.second_vault.0;
+        assert_eq!(test_engine.fungible_vault_balance(first_vault), dec!(100));
+        assert_eq!(test_engine.fungible_vault_balance(second_vault), dec!(200));
+
+        let mut vault_ids = test_engine.vault_ids_of("split vaults", "usd");
+        vault_ids.sort();
+        let mut expected = vec![first_vault, second_vault];
+        expected.sort();
+        assert_eq!(vault_ids, expected);
+        assert_eq!(
+            test_engine.internal_vault_balance("split vaults", "usd"),
+            dec!(300)
+        );
+    }
+
+    #[test]
+    fn test_ids_of_each_vault() {
+        let mut test_engine =
+            TestEngine::with_package("split vaults package", &SPLIT_VAULTS_PACKAGE);
+        test_engine.new_nft_collection::<Ticket, _>("tickets", NonFungibleIdType::Integer);
+        test_engine.mint_nfts(
+            "tickets",
+            vec![
+                (1, Ticket { seat: 1 }),
+                (2, Ticket { seat: 2 }),
+                (3, Ticket { seat: 3 }),
+            ],
+        );
+        test_engine.new_component(
+            "split vaults",
+            "SplitVaults",
+            "instantiate_split_vaults",
+            env_args!(NonFungible::Bucket("tickets", nf_ids![1, 2, 3]), dec!(1)),
+        );
+
+        let state: SplitVaultsState = test_engine.get_component_state("split vaults");
+        let first_ids = test_engine.non_fungible_vault_balance(state.first_vault.0);
+        let mut second_ids = test_engine.non_fungible_vault_balance(state.second_vault.0);
+        second_ids.sort();
+        assert_eq!(first_ids.len(), 1);
+        assert_eq!(second_ids.len(), 2);
+        assert!(!second_ids.contains(&first_ids[0]));
+        assert_eq!(
+            test_engine.internal_vault_balance("split vaults", "tickets"),
+            dec!(3)
+        );
+    }
+}